@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use comrak::{canonicalize, Options};
+
+fuzz_target!(|s: &str| {
+    let options = Options::default();
+    let once = canonicalize(s, &options);
+    let twice = canonicalize(&once, &options);
+    assert_eq!(once, twice, "canonicalize is not idempotent for {:?}", s);
+});
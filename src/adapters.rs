@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use std::io::{self, Write};
 
-use crate::nodes::Sourcepos;
+use crate::nodes::{AstNode, Sourcepos};
 
 /// Implement this adapter for creating a plugin for custom syntax highlighting of codefence blocks.
 pub trait SyntaxHighlighterAdapter: Send + Sync {
@@ -20,10 +20,31 @@ pub trait SyntaxHighlighterAdapter: Send + Sync {
         code: &str,
     ) -> io::Result<()>;
 
+    /// Generates a syntax highlighted HTML output, given the code block's whole info string
+    /// rather than just the language, for highlighters that make use of the rest, such as
+    /// `rust hl_lines=1-3`.  The formatter always calls this method; its first word is the
+    /// language that [`write_highlighted`](Self::write_highlighted) would be given, after
+    /// `normalize_code_lang` and the like, and a title taken by `code_block_title_from_info` is
+    /// left out.
+    ///
+    /// The default implementation passes that first word on to
+    /// [`write_highlighted`](Self::write_highlighted).
+    fn write_highlighted_with_info(
+        &self,
+        output: &mut dyn Write,
+        info: Option<&str>,
+        code: &str,
+    ) -> io::Result<()> {
+        let lang = info.map(|info| info.split_whitespace().next().unwrap_or(""));
+        self.write_highlighted(output, lang, code)
+    }
+
     /// Generates the opening `<pre>` tag. Some syntax highlighter libraries might include their own
     /// `<pre>` tag possibly with some HTML attribute pre-filled.
     ///
-    /// `attributes`: A map of HTML attributes provided by comrak.
+    /// `attributes`: A map of HTML attributes provided by comrak.  Values come straight from the
+    /// document's info string and are not yet escaped; [`crate::html::write_opening_tag`] escapes
+    /// them as it writes the tag.
     fn write_pre_tag(
         &self,
         output: &mut dyn Write,
@@ -33,7 +54,8 @@ pub trait SyntaxHighlighterAdapter: Send + Sync {
     /// Generates the opening `<code>` tag. Some syntax highlighter libraries might include their own
     /// `<code>` tag possibly with some HTML attribute pre-filled.
     ///
-    /// `attributes`: A map of HTML attributes provided by comrak.
+    /// `attributes`: A map of HTML attributes provided by comrak, unescaped as for
+    /// [`write_pre_tag`](Self::write_pre_tag).
     fn write_code_tag(
         &self,
         output: &mut dyn Write,
@@ -45,12 +67,18 @@ pub trait SyntaxHighlighterAdapter: Send + Sync {
 #[derive(Clone, Debug)]
 pub struct HeadingMeta {
     /// The level of the heading; from 1 to 6 for ATX headings, 1 or 2 for setext headings.
+    /// It is demoted by `render.heading_offset`, up to 6.
     pub level: u8,
 
     /// The content of the heading as a "flattened" string&mdash;flattened in the sense that any
     /// `<strong>` or other tags are removed. In the Markdown heading `## This is **bold**`, for
     /// example, the would be the string `"This is bold"`.
     pub content: String,
+
+    /// The heading's id under `extension.header_ids`, prefix included, or
+    /// `None` without it.  It is made unique among the document's ids just as
+    /// when no adapter is used.  It is not escaped.
+    pub id: Option<String>,
 }
 
 /// Implement this adapter for creating a plugin for custom headings (`h1`, `h2`, etc.). The `enter`
@@ -69,3 +97,59 @@ pub trait HeadingAdapter: Send + Sync {
     /// Render the closing tag.
     fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()>;
 }
+
+/// What a [`NodeRenderer`] did with the node it was given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderDecision {
+    /// Nothing; the formatter renders the node as it normally would.
+    Default,
+
+    /// The node and everything under it have been written; the formatter skips them.
+    Handled,
+}
+
+/// Implement this adapter to take over the HTML rendering of particular nodes while leaving the
+/// rest to comrak.
+///
+/// ```
+/// # use comrak::{markdown_to_html_with_plugins, Options, Plugins, RenderPlugins};
+/// # use comrak::adapters::{NodeRenderer, RenderDecision};
+/// # use comrak::html::{dangerous_url, escape_href};
+/// # use comrak::nodes::{AstNode, NodeValue};
+/// # use std::io::{self, Write};
+/// struct Figures;
+/// impl NodeRenderer for Figures {
+///     fn render<'a>(&self, output: &mut dyn Write, node: &'a AstNode<'a>) -> io::Result<RenderDecision> {
+///         match node.data.borrow().value {
+///             NodeValue::Image(ref nl) => {
+///                 output.write_all(b"<figure><img src=\"")?;
+///                 if !dangerous_url(nl.url.as_bytes()) {
+///                     escape_href(output, nl.url.as_bytes())?;
+///                 }
+///                 output.write_all(b"\"></figure>")?;
+///                 Ok(RenderDecision::Handled)
+///             }
+///             _ => Ok(RenderDecision::Default),
+///         }
+///     }
+/// }
+///
+/// let mut plugins = Plugins::default();
+/// plugins.render.node_renderer = Some(&Figures);
+/// assert_eq!(markdown_to_html_with_plugins("*Look:* ![a cat](cat.png?a&b)", &Options::default(), &plugins),
+///            "<p><em>Look:</em> <figure><img src=\"cat.png?a&amp;b\"></figure></p>\n");
+/// assert_eq!(markdown_to_html_with_plugins("![x](javascript:alert(1))", &Options::default(), &plugins),
+///            "<p><figure><img src=\"\"></figure></p>\n");
+/// ```
+pub trait NodeRenderer: Send + Sync {
+    /// Called as the formatter reaches each node, before anything is written for it.  Nodes
+    /// rendered as plain text, such as those in an image's alt text, are not offered.
+    ///
+    /// Returning [`RenderDecision::Handled`] means this call wrote the node and its children
+    /// itself, so nothing further is written for them.  Text written here is not escaped.
+    fn render<'a>(
+        &self,
+        output: &mut dyn Write,
+        node: &'a AstNode<'a>,
+    ) -> io::Result<RenderDecision>;
+}
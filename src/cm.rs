@@ -1,8 +1,10 @@
+use crate::arena_tree::Node;
 use crate::ctype::{isalpha, isdigit, ispunct, isspace};
+use crate::html::LineEndingWriter;
 use crate::nodes::TableAlignment;
 use crate::nodes::{
-    AstNode, ListDelimType, ListType, NodeCodeBlock, NodeHeading, NodeHtmlBlock, NodeLink,
-    NodeMath, NodeTable, NodeValue, NodeWikiLink,
+    Ast, AstNode, ListDelimType, ListType, NodeAlert, NodeCodeBlock, NodeDetails, NodeFencedDiv,
+    NodeHeading, NodeHtmlBlock, NodeLink, NodeList, NodeMath, NodeTable, NodeValue, NodeWikiLink,
 };
 #[cfg(feature = "shortcodes")]
 use crate::parser::shortcodes::NodeShortCode;
@@ -11,10 +13,15 @@ use crate::scanners;
 use crate::strings::trim_start_match;
 use crate::{nodes, Plugins};
 
+use std::cell::RefCell;
 use std::cmp::max;
 use std::io::{self, Write};
+use std::mem;
+use typed_arena::Arena;
 
 /// Formats an AST as CommonMark, modified by the given options.
+///
+/// Any node may be passed as `root` to render just that subtree.
 pub fn format_document<'a>(
     root: &'a AstNode<'a>,
     options: &Options,
@@ -35,10 +42,41 @@ pub fn format_document_with_plugins<'a>(
     if !f.v.is_empty() && f.v[f.v.len() - 1] != b'\n' {
         f.v.push(b'\n');
     }
-    output.write_all(&f.v)?;
+    LineEndingWriter::new(output, options.render.line_ending).write_all(&f.v)?;
     Ok(())
 }
 
+/// Wraps `text` to `width` columns the way the CommonMark formatter wraps
+/// paragraphs when [`RenderOptions::width`](crate::RenderOptions) is set.
+///
+/// Lines are only broken at spaces, and runs of spaces are collapsed to one.
+/// A word longer than `width` is left whole on a line of its own, and a line
+/// is never broken before a word starting with a digit, which could be read
+/// back as an ordered list marker.  Newlines in `text` are kept.  A `width`
+/// of zero disables wrapping.
+///
+/// ```
+/// # use comrak::wrap_text;
+/// assert_eq!(wrap_text("The quick brown fox jumps over the lazy dog", 15),
+///            "The quick brown\nfox jumps over\nthe lazy dog");
+/// assert_eq!(wrap_text("a https://example.com/a/long/path b", 10),
+///            "a\nhttps://example.com/a/long/path\nb");
+/// ```
+pub fn wrap_text(text: &str, width: usize) -> String {
+    let arena = Arena::new();
+    let root = arena.alloc(Node::new(RefCell::new(Ast::new(
+        NodeValue::Document,
+        (1, 1).into(),
+    ))));
+
+    let mut options = Options::default();
+    options.render.width = width;
+
+    let mut f = CommonMarkFormatter::new(root, &options);
+    f.output(text.as_bytes(), true, Escaping::Literal);
+    String::from_utf8(f.v).unwrap()
+}
+
 struct CommonMarkFormatter<'a, 'o> {
     node: &'a AstNode<'a>,
     options: &'o Options,
@@ -50,9 +88,24 @@ struct CommonMarkFormatter<'a, 'o> {
     begin_line: bool,
     begin_content: bool,
     no_linebreaks: bool,
+    // Nesting depth of links and images being written; we never wrap inside
+    // one, so that `[text](url)` stays on a single line.
+    link_depth: usize,
     in_tight_list_item: bool,
     custom_escape: Option<fn(&'a AstNode<'a>, u8) -> bool>,
     footnote_ix: u32,
+    // Abbreviations written so far with their expansions, to be defined at
+    // the end of the document.
+    abbreviations: Vec<(String, String)>,
+    // Where each cell of the table being written lies in `v`, with its
+    // column, and its alignment if it's in the delimiter row.
+    table_cells: Vec<(usize, usize, usize, Option<TableAlignment>)>,
+    // Where the table row being written starts in `v`, and the index of its
+    // first cell in `table_cells`.
+    table_row: (usize, usize),
+    // With `ordered_list_pad_markers`, the width of the widest marker number
+    // of each ordered list being written, innermost last.
+    list_number_widths: Vec<usize>,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -87,14 +140,19 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             begin_line: true,
             begin_content: true,
             no_linebreaks: false,
+            link_depth: 0,
             in_tight_list_item: false,
             custom_escape: None,
+            table_cells: vec![],
+            table_row: (0, 0),
+            list_number_widths: vec![],
             footnote_ix: 0,
+            abbreviations: vec![],
         }
     }
 
     fn output(&mut self, buf: &[u8], wrap: bool, escaping: Escaping) {
-        let wrap = wrap && !self.no_linebreaks;
+        let wrap = wrap && !self.no_linebreaks && self.link_depth == 0;
 
         if self.in_tight_list_item && self.need_cr > 1 {
             self.need_cr = 1;
@@ -154,12 +212,12 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
                     self.v.push(buf[i]);
                     self.column += 1;
                     self.begin_line = false;
-                    self.begin_content = self.begin_content && isdigit(buf[i]);
+                    self.begin_content = self.begin_content && self.is_marker_char(buf[i]);
                 }
             } else {
                 self.outc(buf[i], escaping, nextc);
                 self.begin_line = false;
-                self.begin_content = self.begin_content && isdigit(buf[i]);
+                self.begin_content = self.begin_content && self.is_marker_char(buf[i]);
             }
 
             if self.options.render.width > 0
@@ -182,8 +240,14 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         }
     }
 
+    // Whether `c` can be part of an ordered list marker's number.
+    fn is_marker_char(&self, c: u8) -> bool {
+        isdigit(c) || (self.options.extension.alpha_lists && isalpha(c))
+    }
+
     fn outc(&mut self, c: u8, escaping: Escaping, nextc: Option<&u8>) {
-        let follows_digit = !self.v.is_empty() && isdigit(self.v[self.v.len() - 1]);
+        let follows_marker_char =
+            !self.v.is_empty() && self.is_marker_char(self.v[self.v.len() - 1]);
 
         let nextc = nextc.map_or(0, |&c| c);
 
@@ -205,10 +269,15 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
                     || (c == b'!' && nextc == 0x5b)
                     || (self.begin_content
                         && (c == b'-' || c == b'+' || c == b'=')
-                        && !follows_digit)
+                        && !follows_marker_char)
+                    || (self.begin_content
+                        && c == b':'
+                        && (self.options.extension.details
+                            || self.options.extension.fenced_divs)
+                        && !follows_marker_char)
                     || (self.begin_content
                         && (c == b'.' || c == b')')
-                        && follows_digit
+                        && follows_marker_char
                         && (nextc == 0 || isspace(nextc)))))
                 || (escaping == Escaping::Url
                     && (c == b'`'
@@ -331,14 +400,30 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             .map_or(true, |next| next.data.borrow().value.block());
 
         match node.data.borrow().value {
-            NodeValue::Document => (),
+            NodeValue::Document => {
+                if !entering {
+                    self.format_abbreviation_definitions();
+                }
+            }
             NodeValue::FrontMatter(ref fm) => self.format_front_matter(fm.as_bytes(), entering),
             NodeValue::BlockQuote => self.format_block_quote(entering),
+            NodeValue::Alert(ref na) => self.format_alert(na, entering),
+            NodeValue::Details(ref nd) => self.format_details(node, nd, entering),
+            NodeValue::FencedDiv(ref nfd) => self.format_fenced_div(node, nfd, entering),
             NodeValue::List(..) => self.format_list(node, entering),
             NodeValue::Item(..) => self.format_item(node, entering),
             NodeValue::DescriptionList => (),
             NodeValue::DescriptionItem(..) => (),
-            NodeValue::DescriptionTerm => (),
+            NodeValue::DescriptionTerm => {
+                // Grouped terms are written on consecutive lines.
+                if !entering
+                    && node.next_sibling().map_or(false, |n| {
+                        matches!(n.data.borrow().value, NodeValue::DescriptionTerm)
+                    })
+                {
+                    self.need_cr = 1;
+                }
+            }
             NodeValue::DescriptionDetails => self.format_description_details(entering),
             NodeValue::Heading(ref nch) => self.format_heading(nch, entering),
             NodeValue::CodeBlock(ref ncb) => self.format_code_block(node, ncb, entering),
@@ -348,10 +433,13 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             NodeValue::Text(ref literal) => {
                 self.format_text(literal.as_bytes(), allow_wrap, entering)
             }
-            NodeValue::LineBreak => self.format_line_break(entering, next_is_block),
+            NodeValue::LineBreak => self.format_line_break(node, entering, next_is_block),
             NodeValue::SoftBreak => self.format_soft_break(allow_wrap, entering),
             NodeValue::Code(ref code) => {
-                self.format_code(code.literal.as_bytes(), allow_wrap, entering)
+                self.format_code(code.literal.as_bytes(), entering);
+                if let (true, Some(lang)) = (entering, &code.lang) {
+                    write!(self, "{{.{}}}", lang).unwrap();
+                }
             }
             NodeValue::HtmlInline(ref literal) => {
                 self.format_html_inline(literal.as_bytes(), entering)
@@ -367,9 +455,12 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             NodeValue::TaskItem(symbol) => self.format_task_item(symbol, node, entering),
             NodeValue::Strikethrough => self.format_strikethrough(),
             NodeValue::Superscript => self.format_superscript(),
+            NodeValue::Highlight => self.format_highlight(),
+            NodeValue::Abbreviation(ref na) => self.format_abbreviation(node, &na.title, entering),
             NodeValue::Subscript => self.format_subscript(),
             NodeValue::SpoileredText => self.format_spoiler(),
             NodeValue::Underline => self.format_underline(),
+            NodeValue::Quoted => self.format_quoted(),
             NodeValue::ImageMention(ref nl) => self.format_image_mention(nl),
             NodeValue::EscapedTag(ref net) => self.format_escaped_tag(net),
             NodeValue::Link(ref nl) => return self.format_link(node, nl, entering),
@@ -377,8 +468,9 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             #[cfg(feature = "shortcodes")]
             NodeValue::ShortCode(ref ne) => self.format_shortcode(ne, entering),
             NodeValue::Table(..) => self.format_table(entering),
-            NodeValue::TableRow(..) => self.format_table_row(entering),
+            NodeValue::TableRow(header) => self.format_table_row(header, entering),
             NodeValue::TableCell => self.format_table_cell(node, entering),
+            NodeValue::TableCaption => self.format_table_caption(entering),
             NodeValue::FootnoteDefinition(ref nfd) => {
                 self.format_footnote_definition(&nfd.name, entering)
             }
@@ -413,7 +505,99 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         }
     }
 
+    fn format_alert(&mut self, na: &NodeAlert, entering: bool) {
+        if entering {
+            write!(
+                self,
+                "> [!{}]",
+                na.alert_type.css_class().to_ascii_uppercase()
+            )
+            .unwrap();
+            if let Some(ref title) = na.title {
+                write!(self, " {}", title).unwrap();
+            }
+            write!(self.prefix, "> ").unwrap();
+            self.cr();
+        } else {
+            let new_len = self.prefix.len() - 2;
+            self.prefix.truncate(new_len);
+            self.blankline();
+        }
+    }
+
+    fn format_details(&mut self, node: &'a AstNode<'a>, nd: &NodeDetails, entering: bool) {
+        let fence = ":".repeat(colon_fence_length(node, nd.fence_length));
+        if entering {
+            write!(self, "{}", fence).unwrap();
+            if nd.open {
+                write!(self, "+").unwrap();
+            }
+            write!(self, " details").unwrap();
+            if !nd.summary.is_empty() {
+                write!(self, " {}", nd.summary).unwrap();
+            }
+            self.cr();
+        } else {
+            self.cr();
+            write!(self, "{}", fence).unwrap();
+            self.blankline();
+        }
+    }
+
+    fn format_fenced_div(&mut self, node: &'a AstNode<'a>, nfd: &NodeFencedDiv, entering: bool) {
+        let fence = ":".repeat(colon_fence_length(node, nfd.fence_length));
+        if entering {
+            write!(self, "{} ", fence).unwrap();
+            if nfd.attrs.is_empty() && is_bare_fenced_div_class(&nfd.class) {
+                write!(self, "{}", nfd.class).unwrap();
+            } else {
+                let mut attrs = nfd
+                    .class
+                    .split(' ')
+                    .filter(|class| !class.is_empty())
+                    .map(|class| format!(".{}", class))
+                    .collect::<Vec<_>>();
+                for (key, value) in &nfd.attrs {
+                    if key == "id" && !value.is_empty() && !value.contains([' ', '\t']) {
+                        attrs.push(format!("#{}", value));
+                    } else if key == "class" {
+                        attrs.extend(value.split_whitespace().map(|class| format!(".{}", class)));
+                    } else {
+                        attrs.push(format!(
+                            "{}=\"{}\"",
+                            key,
+                            value.replace('\\', "\\\\").replace('"', "\\\"")
+                        ));
+                    }
+                }
+                write!(self, "{{{}}}", attrs.join(" ")).unwrap();
+            }
+            self.cr();
+        } else {
+            self.cr();
+            write!(self, "{}", fence).unwrap();
+            self.blankline();
+        }
+    }
+
     fn format_list(&mut self, node: &'a AstNode<'a>, entering: bool) {
+        if self.options.render.ordered_list_pad_markers {
+            if let NodeValue::List(ref nl) = node.data.borrow().value {
+                if nl.list_type == ListType::Ordered {
+                    if entering {
+                        let widest = node
+                            .children()
+                            .map(|item| nl.numbering.format(item_number(item, nl)).len())
+                            .max()
+                            .unwrap_or(0);
+                        self.list_number_widths.push(widest);
+                    } else {
+                        self.list_number_widths.pop();
+                    }
+                }
+            }
+        }
+
         if !entering
             && match node.next_sibling() {
                 Some(next_sibling) => matches!(
@@ -438,24 +622,41 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         let mut listmarker = vec![];
 
         let marker_width = if parent.list_type == ListType::Bullet {
-            2
+            if self.options.render.bullet_list_double_space {
+                3
+            } else {
+                2
+            }
         } else {
-            let list_number = match node.data.borrow().value {
-                NodeValue::Item(ref ni) => ni.start,
-                NodeValue::TaskItem(_) => parent.start,
-                _ => unreachable!(),
-            };
+            let list_number = parent.numbering.format(item_number(node, &parent));
             let list_delim = parent.delimiter;
+            let padding = if self.options.render.ordered_list_pad_markers {
+                let widest = *self.list_number_widths.last().unwrap();
+                let padding = (widest - list_number.len() + 1).min(4);
+                // `A. ` would be read back as text.
+                if list_number.len() == 1
+                    && list_delim == ListDelimType::Period
+                    && list_number.as_bytes()[0].is_ascii_uppercase()
+                {
+                    padding.max(2)
+                } else {
+                    padding
+                }
+            } else if list_number.len() < 2 {
+                2
+            } else {
+                1
+            };
             write!(
                 listmarker,
-                "{}{}{}",
+                "{}{}{:padding$}",
                 list_number,
                 if list_delim == ListDelimType::Paren {
                     ")"
                 } else {
                     "."
                 },
-                if list_number < 10 { "  " } else { " " }
+                "",
             )
             .unwrap();
             listmarker.len()
@@ -464,7 +665,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         if entering {
             if parent.list_type == ListType::Bullet {
                 let bullet = char::from(self.options.render.list_style as u8);
-                write!(self, "{} ", bullet).unwrap();
+                write!(self, "{:width$}", bullet, width = marker_width).unwrap();
             } else {
                 self.write_all(&listmarker).unwrap();
             }
@@ -481,7 +682,12 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
 
     fn format_description_details(&mut self, entering: bool) {
         if entering {
-            write!(self, ": ").unwrap()
+            let marker = self
+                .options
+                .extension
+                .description_list_marker
+                .unwrap_or(':');
+            write!(self, "{} ", marker).unwrap()
         }
     }
 
@@ -580,9 +786,17 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         }
     }
 
-    fn format_line_break(&mut self, entering: bool, next_is_block: bool) {
-        if entering {
-            if !self.options.render.hardbreaks && !next_is_block {
+    fn format_line_break(&mut self, node: &'a AstNode<'a>, entering: bool, next_is_block: bool) {
+        if entering && in_table_cell(node) {
+            // Marks where split_continued_row is to break the cell.
+            self.v.push(b'\n');
+        } else if entering {
+            // A break kept at the end of a list item's last paragraph is
+            // written out too, since it's parsed back the same way.
+            let kept = self.options.parse.list_item_trailing_hard_break
+                && node.next_sibling().is_none()
+                && node.parent().map_or(false, nodes::ends_list_item);
+            if !self.options.render.hardbreaks && (!next_is_block || kept) {
                 // If the next element is a block, a backslash means a
                 // literal backslash instead of a line break. In this case
                 // we can just skip the line break since it's meaningless
@@ -608,7 +822,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         }
     }
 
-    fn format_code(&mut self, literal: &[u8], allow_wrap: bool, entering: bool) {
+    fn format_code(&mut self, literal: &[u8], entering: bool) {
         if entering {
             let numticks = shortest_unused_sequence(literal, b'`');
             for _ in 0..numticks {
@@ -625,7 +839,9 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             if pad {
                 write!(self, " ").unwrap();
             }
-            self.output(literal, allow_wrap, Escaping::Literal);
+            // A code span is never wrapped: the line is broken around it
+            // instead, and its spaces are kept as they are.
+            self.output(literal, false, Escaping::Literal);
             if pad {
                 write!(self, " ").unwrap();
             }
@@ -675,6 +891,41 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         write!(self, "^").unwrap();
     }
 
+    fn format_highlight(&mut self) {
+        write!(self, "==").unwrap();
+    }
+
+    fn format_abbreviation(&mut self, node: &'a AstNode<'a>, title: &str, entering: bool) {
+        if !entering {
+            return;
+        }
+
+        let mut label = String::new();
+        for child in node.children() {
+            if let NodeValue::Text(ref literal) = child.data.borrow().value {
+                label.push_str(literal);
+            }
+        }
+        if !self.abbreviations.iter().any(|(l, _)| *l == label) {
+            self.abbreviations.push((label, title.to_string()));
+        }
+    }
+
+    fn format_abbreviation_definitions(&mut self) {
+        if self.abbreviations.is_empty() {
+            return;
+        }
+
+        self.blankline();
+        for (label, title) in mem::take(&mut self.abbreviations) {
+            write!(self, "*[{}]:", label).unwrap();
+            if !title.is_empty() {
+                write!(self, " {}", title).unwrap();
+            }
+            self.cr();
+        }
+    }
+
     fn format_subscript(&mut self) {
         write!(self, "%").unwrap();
     }
@@ -687,6 +938,10 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
         write!(self, "__").unwrap();
     }
 
+    fn format_quoted(&mut self) {
+        write!(self, "\"").unwrap();
+    }
+
     fn format_image_mention(&mut self, nl: &str) {
         write!(self, ">>{}", nl).unwrap();
     }
@@ -703,6 +958,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
             }
         } else if entering {
             write!(self, "[").unwrap();
+            self.link_depth += 1;
         } else {
             write!(self, "](").unwrap();
             self.output(nl.url.as_bytes(), false, Escaping::Url);
@@ -712,6 +968,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
                 write!(self, "\"").unwrap();
             }
             write!(self, ")").unwrap();
+            self.link_depth -= 1;
         }
 
         true
@@ -720,6 +977,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
     fn format_wikilink(&mut self, nl: &NodeWikiLink, entering: bool) -> bool {
         if entering {
             write!(self, "[[").unwrap();
+            self.link_depth += 1;
             if self.options.extension.wikilinks_title_after_pipe {
                 self.output(nl.url.as_bytes(), false, Escaping::Url);
                 write!(self, "|").unwrap();
@@ -730,6 +988,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
                 self.output(nl.url.as_bytes(), false, Escaping::Url);
             }
             write!(self, "]]").unwrap();
+            self.link_depth -= 1;
         }
 
         true
@@ -738,8 +997,21 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
     fn format_image(&mut self, nl: &NodeLink, allow_wrap: bool, entering: bool) {
         if entering {
             write!(self, "![").unwrap();
+            self.link_depth += 1;
         } else {
             write!(self, "](").unwrap();
+            if !nl.srcset.is_empty() {
+                for (i, candidate) in nl.srcset.iter().enumerate() {
+                    if i > 0 {
+                        self.output(b", ", allow_wrap, Escaping::Literal);
+                    }
+                    self.output(candidate.url.as_bytes(), false, Escaping::Url);
+                    write!(self, " {}", candidate.descriptor).unwrap();
+                }
+                write!(self, ")").unwrap();
+                self.link_depth -= 1;
+                return;
+            }
             self.output(nl.url.as_bytes(), false, Escaping::Url);
             if !nl.title.is_empty() {
                 self.output(&[b' ', b'"'], allow_wrap, Escaping::Literal);
@@ -747,6 +1019,7 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
                 write!(self, "\"").unwrap();
             }
             write!(self, ")").unwrap();
+            self.link_depth -= 1;
         }
     }
 
@@ -762,23 +1035,120 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
     fn format_table(&mut self, entering: bool) {
         if entering {
             self.custom_escape = Some(table_escape);
+            self.table_cells.clear();
         } else {
             self.custom_escape = None;
+            if self.options.render.width == 0 {
+                self.pad_table_cells();
+            }
         }
         self.blankline();
     }
 
-    fn format_table_row(&mut self, entering: bool) {
+    // Pads each cell of the table just written to the width of the widest in
+    // its column, lengthening the delimiter row to match.
+    fn pad_table_cells(&mut self) {
+        let cells = mem::take(&mut self.table_cells);
+        let mut widths = vec![];
+        for &(start, end, column, _) in &cells {
+            if widths.len() <= column {
+                widths.resize(column + 1, 3);
+            }
+            let width = String::from_utf8_lossy(&self.v[start..end]).chars().count();
+            widths[column] = max(widths[column], width);
+        }
+
+        for &(start, end, column, alignment) in cells.iter().rev() {
+            let width = widths[column];
+            match alignment {
+                Some(alignment) => {
+                    let (left, right) = match alignment {
+                        TableAlignment::Left => (":", "-"),
+                        TableAlignment::Center => (":", ":"),
+                        TableAlignment::Right => ("-", ":"),
+                        TableAlignment::None => ("-", "-"),
+                    };
+                    let delimiter = format!("{}{}{}", left, "-".repeat(width - 2), right);
+                    self.v.splice(start..end, delimiter.into_bytes());
+                }
+                None => {
+                    let len = String::from_utf8_lossy(&self.v[start..end]).chars().count();
+                    self.v
+                        .splice(end..end, std::iter::repeat(b' ').take(width - len));
+                }
+            }
+        }
+    }
+
+    fn format_table_row(&mut self, header: bool, entering: bool) {
         if entering {
             self.cr();
             write!(self, "|").unwrap();
+            self.table_row = (self.v.len() - 1, self.table_cells.len());
+        } else if !header {
+            self.split_continued_row();
+        }
+    }
+
+    // Rewrites the body row just written, if any of its cells holds a line
+    // break, as one line of source per line of its cells.  Each line but the
+    // last ends in a backslash, for `table_cell_line_breaks` to join them.
+    fn split_continued_row(&mut self) {
+        let (row_start, first_cell) = self.table_row;
+        if !self.table_cells[first_cell..]
+            .iter()
+            .any(|&(start, end, ..)| self.v[start..end].contains(&b'\n'))
+        {
+            return;
+        }
+
+        let cells = self.table_cells.split_off(first_cell);
+        let lines = cells
+            .iter()
+            .map(|&(start, end, column, _)| {
+                let lines = self.v[start..end]
+                    .split(|&c| c == b'\n')
+                    .map(<[u8]>::to_vec)
+                    .collect::<Vec<_>>();
+                (column, lines)
+            })
+            .collect::<Vec<_>>();
+        let count = lines
+            .iter()
+            .map(|(_, lines)| lines.len())
+            .max()
+            .unwrap_or(0);
+
+        self.v.truncate(row_start);
+        for k in 0..count {
+            if k > 0 {
+                self.v.extend_from_slice(b"\\\n");
+                self.v.extend_from_slice(&self.prefix);
+            }
+            self.v.push(b'|');
+            for (column, lines) in &lines {
+                self.v.push(b' ');
+                let start = self.v.len();
+                if let Some(line) = lines.get(k) {
+                    self.v.extend_from_slice(line);
+                }
+                self.table_cells.push((start, self.v.len(), *column, None));
+                self.v.extend_from_slice(b" |");
+            }
         }
+        self.column = self.v.len() - row_start;
     }
 
     fn format_table_cell(&mut self, node: &'a AstNode<'a>, entering: bool) {
         if entering {
             write!(self, " ").unwrap();
+            let column = node.preceding_siblings().count() - 1;
+            self.table_cells
+                .push((self.v.len(), self.v.len(), column, None));
         } else {
+            if let Some(cell) = self.table_cells.last_mut() {
+                cell.1 = self.v.len();
+            }
             write!(self, " |").unwrap();
 
             let row = &node.parent().unwrap().data.borrow().value;
@@ -796,10 +1166,12 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
 
                 self.cr();
                 write!(self, "|").unwrap();
-                for a in alignments {
+                for (column, a) in alignments.iter().enumerate() {
+                    write!(self, " ").unwrap();
+                    let start = self.v.len();
                     write!(
                         self,
-                        " {} |",
+                        "{}",
                         match *a {
                             TableAlignment::Left => ":--",
                             TableAlignment::Center => ":-:",
@@ -808,11 +1180,23 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
                         }
                     )
                     .unwrap();
+                    self.table_cells
+                        .push((start, self.v.len(), column, Some(*a)));
+                    write!(self, " |").unwrap();
                 }
                 self.cr();
             }
         }
     }
+
+    fn format_table_caption(&mut self, entering: bool) {
+        if entering {
+            write!(self, "Table: ").unwrap();
+        } else {
+            self.cr();
+        }
+    }
+
     fn format_footnote_definition(&mut self, name: &str, entering: bool) {
         if entering {
             self.footnote_ix += 1;
@@ -858,6 +1242,42 @@ impl<'a, 'o> CommonMarkFormatter<'a, 'o> {
     }
 }
 
+// The length of fence to write for a details block or fenced div: that of its
+// own fence, but longer than any within it, as their closing fences would
+// otherwise close it too.
+fn colon_fence_length<'a>(node: &'a AstNode<'a>, fence_length: usize) -> usize {
+    fn nested<'a>(node: &'a AstNode<'a>) -> usize {
+        node.children()
+            .map(|child| match child.data.borrow().value {
+                NodeValue::Details(ref nd) => colon_fence_length(child, nd.fence_length),
+                NodeValue::FencedDiv(ref nfd) => colon_fence_length(child, nfd.fence_length),
+                _ => nested(child),
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    max(fence_length, nested(node) + 1)
+}
+
+// Whether a fenced div's class can follow the fence as a bare word, as in
+// `::: warning`.  `details` can't, as it would open a details block.
+fn is_bare_fenced_div_class(class: &str) -> bool {
+    !class.is_empty()
+        && !class.eq_ignore_ascii_case("details")
+        && class
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+}
+
+fn item_number<'a>(item: &'a AstNode<'a>, list: &NodeList) -> usize {
+    match item.data.borrow().value {
+        NodeValue::Item(ref ni) => ni.start,
+        NodeValue::TaskItem(_) => list.start,
+        _ => unreachable!(),
+    }
+}
+
 fn longest_char_sequence(literal: &[u8], ch: u8) -> usize {
     let mut longest = 0;
     let mut current = 0;
@@ -923,9 +1343,19 @@ fn is_autolink<'a>(node: &'a AstNode<'a>, nl: &NodeLink) -> bool {
     trim_start_match(&nl.url, "mailto:") == link_text
 }
 
+// Whether `node` is an inline within a table cell.
+fn in_table_cell<'a>(node: &'a AstNode<'a>) -> bool {
+    node.ancestors()
+        .skip(1)
+        .any(|ancestor| matches!(ancestor.data.borrow().value, NodeValue::TableCell))
+}
+
 fn table_escape<'a>(node: &'a AstNode<'a>, c: u8) -> bool {
     match node.data.borrow().value {
-        NodeValue::Table(..) | NodeValue::TableRow(..) | NodeValue::TableCell => false,
+        NodeValue::Table(..)
+        | NodeValue::TableRow(..)
+        | NodeValue::TableCell
+        | NodeValue::TableCaption => false,
         _ => c == b'|',
     }
 }
@@ -0,0 +1,320 @@
+use crate::html::LineEndingWriter;
+use crate::nodes::{AstNode, NodeValue};
+use crate::parser::{Options, Plugins};
+use crate::plain::{flatten_inline, flatten_inlines, table_rows};
+use std::cmp;
+use std::io::{self, Write};
+
+/// Formats an AST as [Gemtext](https://geminiprotocol.net/docs/gemtext.gmi)
+/// (`text/gemini`), modified by the given options.
+///
+/// Gemtext is line-oriented and has no inline markup, so:
+///
+/// * headings become `#`, `##` or `###` lines, deeper levels sharing `###`;
+/// * paragraphs are written on one line each, with emphasis and the like
+///   reduced to their text;
+/// * links and images keep their text in place, and are listed as `=> url
+///   text` lines after the block they appear in.  A paragraph holding nothing
+///   but links is written as those lines alone;
+/// * list items of any kind and depth become `* item` lines;
+/// * block quotes and alerts become `>` lines, and code blocks and tables are
+///   fenced with ```` ``` ````, a code block's info string being used as the
+///   alt text;
+/// * raw HTML, front matter and thematic breaks are dropped.
+///
+/// A text line that would otherwise read as a heading, link, list item, quote
+/// or preformatting toggle is written with a leading space, as is a line of
+/// code starting with ```` ``` ````.
+///
+/// ```
+/// # use comrak::{format_gemtext, parse_document, Arena, Options};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(
+///     &arena,
+///     "# Hello\n\nSee [the docs](/docs) and\n*more*.\n\n- one\n- two\n",
+///     &options,
+/// );
+///
+/// let mut gmi = vec![];
+/// format_gemtext(root, &options, &mut gmi).unwrap();
+/// assert_eq!(
+///     String::from_utf8(gmi).unwrap(),
+///     "# Hello\n\nSee the docs and more.\n=> /docs the docs\n\n* one\n* two\n"
+/// );
+/// ```
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as Gemtext, modified by the given options. Accepts custom plugins,
+/// though none of them affect Gemtext.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mut f = GemtextFormatter::new(options);
+    f.format_block(root);
+    f.flush_links();
+    LineEndingWriter::new(output, options.render.line_ending).write_all(f.out.as_bytes())
+}
+
+struct GemtextFormatter<'o> {
+    options: &'o Options,
+    out: String,
+    links: Vec<(String, String)>,
+    defer_links: usize,
+    footnote_ix: u32,
+}
+
+impl<'o> GemtextFormatter<'o> {
+    fn new(options: &'o Options) -> Self {
+        GemtextFormatter {
+            options,
+            out: String::new(),
+            links: vec![],
+            defer_links: 0,
+            footnote_ix: 0,
+        }
+    }
+
+    // Separates the block about to be written from the one before it.
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    // Writes `text` after `prefix`.  Without a prefix, each line of the
+    // text is guarded so it reads as text.
+    fn line(&mut self, prefix: &str, text: &str) {
+        if prefix.is_empty() {
+            for line in text.split('\n') {
+                self.text_line(line, LINE_TYPE_PREFIXES);
+            }
+        } else {
+            self.out.push_str(prefix);
+            self.out.push_str(text);
+            self.out.push('\n');
+        }
+    }
+
+    // Writes `line` as it is, unless it starts with one of `prefixes`, in
+    // which case it's written after a space.
+    fn text_line(&mut self, line: &str, prefixes: &[&str]) {
+        if prefixes.iter().any(|p| line.starts_with(p)) {
+            self.out.push(' ');
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn flush_links(&mut self) {
+        if self.defer_links > 0 {
+            return;
+        }
+        for (url, text) in std::mem::take(&mut self.links) {
+            self.out.push_str("=> ");
+            self.out.push_str(&url);
+            if !text.is_empty() && text != url {
+                self.out.push(' ');
+                self.out.push_str(&text.replace('\n', " "));
+            }
+            self.out.push('\n');
+        }
+    }
+
+    fn format_children<'a>(&mut self, node: &'a AstNode<'a>) {
+        for child in node.children() {
+            self.format_block(child);
+        }
+    }
+
+    fn format_block<'a>(&mut self, node: &'a AstNode<'a>) {
+        match node.data.borrow().value {
+            NodeValue::Document
+            | NodeValue::DescriptionList
+            | NodeValue::DescriptionItem(..)
+            | NodeValue::DescriptionTerm
+            | NodeValue::DescriptionDetails
+            | NodeValue::FencedDiv(..) => self.format_children(node),
+            NodeValue::Paragraph | NodeValue::TableCaption => {
+                self.blank_line();
+                if only_links(node) {
+                    self.inline_text(node);
+                } else {
+                    let text = self.inline_text(node);
+                    self.line("", &text);
+                }
+                self.flush_links();
+            }
+            NodeValue::Heading(ref nh) => {
+                self.blank_line();
+                let prefix = format!("{} ", "#".repeat(cmp::min(nh.level, 3) as usize));
+                let text = self.inline_text(node);
+                self.line(&prefix, &text.replace('\n', " "));
+                self.flush_links();
+            }
+            NodeValue::List(..) => {
+                self.blank_line();
+                self.defer_links += 1;
+                self.format_list(node);
+                self.defer_links -= 1;
+                self.flush_links();
+            }
+            NodeValue::Details(ref nd) => {
+                if !nd.summary.is_empty() {
+                    self.blank_line();
+                    self.line("", &nd.summary);
+                }
+                self.format_children(node);
+            }
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(..) | NodeValue::Alert(..) => {
+                self.blank_line();
+                let start = self.out.len();
+                if let NodeValue::Alert(ref na) = node.data.borrow().value {
+                    let title = na.title.as_deref();
+                    self.line("", title.unwrap_or_else(|| na.alert_type.default_title()));
+                    self.out.push('\n');
+                }
+                self.defer_links += 1;
+                self.format_children(node);
+                self.defer_links -= 1;
+                let quoted = self.out.split_off(start);
+                for line in quoted.trim_end_matches('\n').lines() {
+                    if line.is_empty() {
+                        self.out.push_str(">\n");
+                    } else {
+                        self.line("> ", line);
+                    }
+                }
+                self.flush_links();
+            }
+            NodeValue::CodeBlock(ref ncb) => {
+                self.blank_line();
+                self.line("```", &ncb.info);
+                if !ncb.literal.is_empty() {
+                    for line in ncb
+                        .literal
+                        .strip_suffix('\n')
+                        .unwrap_or(&ncb.literal)
+                        .split('\n')
+                    {
+                        self.text_line(line, &["```"]);
+                    }
+                }
+                self.out.push_str("```\n");
+            }
+            NodeValue::Table(..) => {
+                self.blank_line();
+                for child in node.children() {
+                    if let NodeValue::TableCaption = child.data.borrow().value {
+                        self.format_block(child);
+                        self.blank_line();
+                    }
+                }
+                self.out.push_str("```\n");
+                self.defer_links += 1;
+                for cells in table_rows(node, |cell| self.inline_text(cell)) {
+                    self.text_line(&cells.join(" | "), &["```"]);
+                }
+                self.defer_links -= 1;
+                self.out.push_str("```\n");
+                self.flush_links();
+            }
+            NodeValue::FootnoteDefinition(..) => {
+                self.footnote_ix += 1;
+                let start = self.out.len();
+                self.blank_line();
+                let number = self
+                    .options
+                    .extension
+                    .footnote_base()
+                    .saturating_add(self.footnote_ix);
+                let marker = format!("[{}] ", number);
+                self.defer_links += 1;
+                self.format_children(node);
+                self.defer_links -= 1;
+                let body_start = self.out[start..]
+                    .find(|c| c != '\n')
+                    .map_or(self.out.len(), |i| start + i);
+                self.out.insert_str(body_start, &marker);
+                self.flush_links();
+            }
+            NodeValue::Item(..) | NodeValue::TaskItem(..) => self.format_list(node),
+            NodeValue::FrontMatter(..) | NodeValue::HtmlBlock(..) | NodeValue::ThematicBreak => {}
+            _ => {
+                let links = &mut self.links;
+                let text = flatten_inline(node, ' ', &mut |url, label, _| {
+                    links.push((url.to_string(), label.clone()));
+                    label
+                });
+                self.out.push_str(&text);
+            }
+        }
+    }
+
+    // Writes each item of a list, and of any list nested in it, as a `*` line.
+    fn format_list<'a>(&mut self, node: &'a AstNode<'a>) {
+        for item in node.children() {
+            let checkbox = match item.data.borrow().value {
+                NodeValue::TaskItem(Some(_)) => "[x] ",
+                NodeValue::TaskItem(None) => "[ ] ",
+                _ => "",
+            };
+            let mut first = true;
+            for child in item.children() {
+                match child.data.borrow().value {
+                    NodeValue::Paragraph | NodeValue::Heading(..) => {
+                        let text = self.inline_text(child).replace('\n', " ");
+                        let prefix = format!("* {}", if first { checkbox } else { "" });
+                        self.line(&prefix, &text);
+                    }
+                    NodeValue::List(..) => self.format_list(child),
+                    _ => {
+                        self.format_block(child);
+                        self.blank_line();
+                    }
+                }
+                first = false;
+            }
+            if first {
+                self.line("* ", checkbox.trim_end());
+            }
+        }
+    }
+
+    // Flattens the inlines under `node` to plain text, queueing up the links
+    // and images found along the way.
+    fn inline_text<'a>(&mut self, node: &'a AstNode<'a>) -> String {
+        let links = &mut self.links;
+        flatten_inlines(node, ' ', &mut |url, label, _| {
+            links.push((url.to_string(), label.clone()));
+            label
+        })
+    }
+}
+
+// The starts of lines which Gemtext doesn't take as text.
+const LINE_TYPE_PREFIXES: &[&str] = &["#", "=>", "*", ">", "```"];
+
+// Whether a paragraph consists of links and images alone, save for the
+// whitespace between them.
+fn only_links<'a>(node: &'a AstNode<'a>) -> bool {
+    let mut any = false;
+    for child in node.children() {
+        match child.data.borrow().value {
+            NodeValue::Link(..) | NodeValue::Image(..) | NodeValue::WikiLink(..) => any = true,
+            NodeValue::SoftBreak | NodeValue::LineBreak => {}
+            NodeValue::Text(ref literal) if literal.trim().is_empty() => {}
+            _ => return false,
+        }
+    }
+    any
+}
@@ -1,10 +1,10 @@
 //! The HTML renderer for the CommonMark AST, as well as helper functions.
 use crate::ctype::isspace;
 use crate::nodes::{
-    AstNode, ListType, NodeCode, NodeFootnoteDefinition, NodeMath, NodeTable, NodeValue,
-    TableAlignment,
+    AstNode, AutolinkType, ListType, NodeCode, NodeFootnoteDefinition, NodeLink, NodeList,
+    NodeMath, NodeTable, NodeValue, TableAlignment,
 };
-use crate::parser::{Options, Plugins};
+use crate::parser::{HeaderIdStyle, LineEnding, Options, Plugins};
 use crate::scanners;
 use http::Uri;
 use once_cell::sync::Lazy;
@@ -15,9 +15,12 @@ use std::collections::{HashMap, HashSet};
 use std::io::{self, Write};
 use std::str;
 
-use crate::adapters::HeadingMeta;
+use crate::adapters::{HeadingMeta, RenderDecision};
 
 /// Formats an AST as HTML, modified by the given options.
+///
+/// `root` needn't be the document node: any node can be passed, in which
+/// case only it and its descendants are rendered.  See [`format_fragment`].
 pub fn format_document<'a>(
     root: &'a AstNode<'a>,
     options: &Options,
@@ -33,9 +36,12 @@ pub fn format_document_with_plugins<'a>(
     output: &mut dyn Write,
     plugins: &Plugins,
 ) -> io::Result<()> {
+    let mut output = LineEndingWriter::new(output, options.render.line_ending);
     let mut writer = WriteWithLast {
-        output,
+        output: &mut output,
         last_was_lf: Cell::new(true),
+        ascii_only: options.render.ascii_only,
+        partial: Vec::new(),
     };
     let mut f = HtmlFormatter::new(options, &mut writer, plugins);
     f.format(root, false)?;
@@ -45,9 +51,161 @@ pub fn format_document_with_plugins<'a>(
     Ok(())
 }
 
+/// Formats a single node and its descendants as HTML, modified by the given
+/// options.
+///
+/// This is the same as [`format_document`], named for when the node is
+/// something other than the document root, such as one table to be sent
+/// to a client on its own.  Footnote references in the fragment link to
+/// their definitions' usual IDs; definitions outside the fragment are not
+/// rendered.
+///
+/// ```
+/// # use comrak::{Arena, parse_document, format_html_fragment, Options};
+/// # use comrak::nodes::NodeValue;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.table = true;
+///
+/// let root = parse_document(&arena, "Intro.\n\n| a |\n|---|\n| b |\n", &options);
+/// let table = root
+///     .children()
+///     .find(|n| matches!(n.data.borrow().value, NodeValue::Table(..)))
+///     .unwrap();
+///
+/// let mut html = vec![];
+/// format_html_fragment(table, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<table>\n<thead>\n<tr>\n<th>a</th>\n</tr>\n</thead>\n\
+///      <tbody>\n<tr>\n<td>b</td>\n</tr>\n</tbody>\n</table>\n"
+/// );
+/// ```
+pub fn format_fragment<'a>(
+    node: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document(node, options, output)
+}
+
+/// A heading in a document, as listed by [`table_of_contents`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// The level the heading is written at, after `render.heading_offset`.
+    pub level: u8,
+
+    /// The heading's text content.
+    pub text: String,
+
+    /// The heading's id, unescaped and without the `header_ids` prefix: link
+    /// to it as `#` followed by the id, or by the prefix and the id when the
+    /// id is written on the heading itself.
+    pub id: String,
+}
+
+/// Lists the headings in a document in order, with the ids the HTML
+/// formatter gives them under `extension.header_ids`.
+///
+/// The ids are made exactly as when formatting, so they take the
+/// `header_id_slugifier` into account, and stay valid whether or not
+/// `header_ids` is set.
+///
+/// ```
+/// # use comrak::{Arena, parse_document, table_of_contents, Options, TocEntry};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(&arena, "# Intro\n\n## Usage\n\n## Usage\n", &options);
+///
+/// let toc = table_of_contents(root, &options);
+/// assert_eq!(
+///     toc.iter().map(|e| (e.level, e.id.as_str())).collect::<Vec<_>>(),
+///     vec![(1, "intro"), (2, "usage"), (2, "usage-1")]
+/// );
+/// assert_eq!(toc[0].text, "Intro");
+/// ```
+pub fn table_of_contents<'a>(root: &'a AstNode<'a>, options: &Options) -> Vec<TocEntry> {
+    let mut options = options.clone();
+    if options.extension.header_ids.is_none() {
+        options.extension.header_ids = Some(String::new());
+    }
+    options.render.line_ending = LineEnding::Lf;
+
+    let plugins = Plugins::default();
+    let mut sink = io::sink();
+    let mut writer = WriteWithLast {
+        output: &mut sink,
+        last_was_lf: Cell::new(true),
+        ascii_only: false,
+        partial: Vec::new(),
+    };
+    let mut f = HtmlFormatter::new(&options, &mut writer, &plugins);
+    f.toc = Some(vec![]);
+    // Writing to a sink can't fail.
+    f.format(root, false).unwrap();
+    f.toc.take().unwrap_or_default()
+}
+
+type Attributes = Vec<(&'static str, Option<String>)>;
+
+fn escaped(buffer: &[u8]) -> String {
+    let mut v = vec![];
+    escape(&mut v, buffer).unwrap();
+    String::from_utf8(v).unwrap()
+}
+
+fn escaped_href(buffer: &[u8]) -> String {
+    let mut v = vec![];
+    escape_href(&mut v, buffer).unwrap();
+    String::from_utf8(v).unwrap()
+}
+
 struct WriteWithLast<'w> {
     output: &'w mut dyn Write,
     last_was_lf: Cell<bool>,
+    ascii_only: bool,
+    // The start of a UTF-8 sequence split between writes, when `ascii_only`.
+    partial: Vec<u8>,
+}
+
+impl<'w> WriteWithLast<'w> {
+    fn write_ascii_only(&mut self, buf: &[u8]) -> io::Result<()> {
+        let mut pending = std::mem::take(&mut self.partial);
+        pending.extend_from_slice(buf);
+
+        let mut rest = &pending[..];
+        loop {
+            match str::from_utf8(rest) {
+                Ok(s) => return self.write_char_refs(s),
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    self.write_char_refs(str::from_utf8(valid).unwrap())?;
+                    match e.error_len() {
+                        Some(len) => {
+                            self.output.write_all(&after[..len])?;
+                            rest = &after[len..];
+                        }
+                        None => {
+                            self.partial = after.to_vec();
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn write_char_refs(&mut self, s: &str) -> io::Result<()> {
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            if !c.is_ascii() {
+                self.output.write_all(&s.as_bytes()[start..i])?;
+                write!(self.output, "&#x{:X};", c as u32)?;
+                start = i + c.len_utf8();
+            }
+        }
+        self.output.write_all(&s.as_bytes()[start..])
+    }
 }
 
 impl<'w> Write for WriteWithLast<'w> {
@@ -60,10 +218,57 @@ impl<'w> Write for WriteWithLast<'w> {
         if l > 0 {
             self.last_was_lf.set(buf[l - 1] == 10);
         }
+        if self.ascii_only {
+            self.write_ascii_only(buf)?;
+            return Ok(l);
+        }
         self.output.write(buf)
     }
 }
 
+// Passes output through, with each `\n` not already preceded by `\r`
+// turned into `\r\n` when the line ending is `CrLf`.  Shared by all the
+// formatters.
+pub(crate) struct LineEndingWriter<'w> {
+    output: &'w mut dyn Write,
+    crlf: bool,
+    last_was_cr: bool,
+}
+
+impl<'w> LineEndingWriter<'w> {
+    pub(crate) fn new(output: &'w mut dyn Write, line_ending: LineEnding) -> Self {
+        LineEndingWriter {
+            output,
+            crlf: line_ending == LineEnding::CrLf,
+            last_was_cr: false,
+        }
+    }
+}
+
+impl<'w> Write for LineEndingWriter<'w> {
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.crlf {
+            return self.output.write(buf);
+        }
+
+        let mut start = 0;
+        for (i, &c) in buf.iter().enumerate() {
+            if c == b'\n' && !self.last_was_cr {
+                self.output.write_all(&buf[start..i])?;
+                self.output.write_all(b"\r")?;
+                start = i;
+            }
+            self.last_was_cr = c == b'\r';
+        }
+        self.output.write_all(&buf[start..])?;
+        Ok(buf.len())
+    }
+}
+
 /// Converts header Strings to canonical, unique, but still human-readable, anchors.
 ///
 /// To guarantee uniqueness, an anchorizer keeps track of the anchors
@@ -109,9 +314,23 @@ impl Anchorizer {
         static REJECTED_CHARS: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"[^\p{L}\p{M}\p{N}\p{Pc} -]").unwrap());
 
-        let mut id = header.to_lowercase();
-        id = REJECTED_CHARS.replace_all(&id, "").replace(' ', "-");
+        let id = header.to_lowercase();
+        self.uniquify(REJECTED_CHARS.replace_all(&id, "").replace(' ', "-"))
+    }
 
+    /// Returns `id` as is if it hasn't been returned before, or with the
+    /// first numeric suffix that makes it unique otherwise.
+    ///
+    /// ```
+    /// use comrak::Anchorizer;
+    ///
+    /// let mut anchorizer = Anchorizer::new();
+    ///
+    /// assert_eq!("Stuff".to_string(), anchorizer.uniquify("Stuff".to_string()));
+    /// assert_eq!("Stuff-1".to_string(), anchorizer.uniquify("Stuff".to_string()));
+    /// assert_eq!("stuff".to_string(), anchorizer.anchorize("Stuff".to_string()));
+    /// ```
+    pub fn uniquify(&mut self, mut id: String) -> String {
         let mut uniq = 0;
         id = loop {
             let anchor = if uniq == 0 {
@@ -131,6 +350,15 @@ impl Anchorizer {
     }
 }
 
+// The unprefixed id `header_ids` gives a heading with `text`, made unique
+// among those `anchorizer` has given out.
+fn heading_slug(options: &Options, anchorizer: &mut Anchorizer, text: String) -> String {
+    match options.extension.header_id_slugifier {
+        Some(slugify) => anchorizer.uniquify(slugify(&text)),
+        None => anchorizer.anchorize(text),
+    }
+}
+
 struct HtmlFormatter<'o> {
     output: &'o mut WriteWithLast<'o>,
     options: &'o Options,
@@ -138,6 +366,12 @@ struct HtmlFormatter<'o> {
     footnote_ix: u32,
     written_footnote_ix: u32,
     plugins: &'o Plugins<'o>,
+    heading_anchor_after: Option<(String, String)>,
+    heading_adapter_id: Option<String>,
+    block_id_counts: HashMap<&'static str, usize>,
+    // The full ids of the headings to be written, under `block_ids`.
+    heading_ids: HashSet<String>,
+    toc: Option<Vec<TocEntry>>,
 }
 
 #[rustfmt::skip]
@@ -211,6 +445,139 @@ fn tagfilter(literal: &[u8]) -> bool {
     false
 }
 
+fn allowed_inline_html(literal: &[u8], allowed: &[String]) -> bool {
+    if allowed.is_empty() || literal.len() < 3 || literal[0] != b'<' {
+        return false;
+    }
+
+    let mut i = 1;
+    if literal[i] == b'/' {
+        i += 1;
+    }
+
+    let start = i;
+    while i < literal.len() && (literal[i].is_ascii_alphanumeric() || literal[i] == b'-') {
+        i += 1;
+    }
+    if i == start {
+        return false;
+    }
+
+    let name = &literal[start..i];
+
+    // Attributes could carry event handlers, `style` and the like, so only
+    // bare tags are let through.
+    while i < literal.len() && isspace(literal[i]) {
+        i += 1;
+    }
+    if i < literal.len() && literal[i] == b'/' && literal[1] != b'/' {
+        i += 1;
+    }
+    if i + 1 != literal.len() || literal[i] != b'>' {
+        return false;
+    }
+
+    allowed
+        .iter()
+        .any(|t| t.as_bytes().eq_ignore_ascii_case(name))
+}
+
+// Outside unsafe mode a fenced div only gets ids and `data-*` attributes
+// from its attribute list; anything else, like `style` or an event handler,
+// is dropped.  Names that couldn't have been parsed are never written.
+fn fenced_div_attribute_allowed(key: &str, unsafe_: bool) -> bool {
+    if key.is_empty()
+        || !key
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_' || c == b':')
+    {
+        return false;
+    }
+    unsafe_
+        || key.eq_ignore_ascii_case("id")
+        || (key.len() > 5 && key[..5].eq_ignore_ascii_case("data-"))
+}
+
+// Attribute names given by a broken link callback are written only if they
+// are well-formed, and outside unsafe mode never as event handlers.
+fn link_attribute_allowed(key: &str, unsafe_: bool) -> bool {
+    let mut bytes = key.bytes();
+    match bytes.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == b'_' || c == b':' => {}
+        _ => return false,
+    }
+    if !bytes.all(|c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b':' | b'.')) {
+        return false;
+    }
+    unsafe_ || !(key.len() > 2 && key[..2].eq_ignore_ascii_case("on"))
+}
+
+// Find a `title=...` token in a code block's info string, returning the
+// title and the rest of the info string with that token removed.  Any
+// `key=value` token's value may be double- or single-quoted to include
+// spaces.
+fn split_info_title(info: &str) -> Option<(String, String)> {
+    let bytes = info.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        while i < bytes.len() && isspace(bytes[i]) {
+            i += 1;
+        }
+        let start = i;
+
+        while i < bytes.len() && !isspace(bytes[i]) {
+            let c = bytes[i];
+            i += 1;
+            if c == b'=' && i < bytes.len() && (bytes[i] == b'"' || bytes[i] == b'\'') {
+                let quote = bytes[i];
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                if i < bytes.len() {
+                    i += 1;
+                }
+            }
+        }
+
+        if let Some(value) = info[start..i].strip_prefix("title=") {
+            let value = match value.as_bytes().first() {
+                Some(&q) if q == b'"' || q == b'\'' => {
+                    let inner = &value[1..];
+                    inner.strip_suffix(q as char).unwrap_or(inner)
+                }
+                _ => value,
+            };
+            let rest = format!("{} {}", info[..start].trim(), info[i..].trim());
+            return Some((value.to_string(), rest.trim().to_string()));
+        }
+    }
+
+    None
+}
+
+// Writes every character as a numeric character reference.
+fn obfuscated(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() * 5);
+    for c in text.chars() {
+        result.push_str(&format!("&#{};", c as u32));
+    }
+    result
+}
+
+// Removes spaces and tabs from the end of each line of a code block's
+// literal, keeping the line endings.
+fn trim_trailing_whitespace(literal: &str) -> String {
+    let mut result = String::with_capacity(literal.len());
+    for line in literal.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        result.push_str(content.trim_end_matches([' ', '\t']));
+        result.push_str(&line[content.len()..]);
+    }
+    result
+}
+
 fn tagfilter_block(input: &[u8], o: &mut dyn Write) -> io::Result<()> {
     let size = input.len();
     let mut i = 0;
@@ -241,10 +608,41 @@ fn tagfilter_block(input: &[u8], o: &mut dyn Write) -> io::Result<()> {
     Ok(())
 }
 
-fn dangerous_url(input: &[u8]) -> bool {
+/// Whether `input` is a URL the formatter leaves out of links and images
+/// unless [`RenderOptions::unsafe_`](crate::RenderOptions::unsafe_) is set,
+/// such as a `javascript:` one.
+pub fn dangerous_url(input: &[u8]) -> bool {
     scanners::dangerous_url(input).is_some()
 }
 
+// Whether `url` points to another host: it has an authority, as in
+// `https://host/` or `//host/`, and its host doesn't start with one of
+// `internal`.  Matching ignores case, and a prefix only matches whole labels
+// or path segments, so `example.com` doesn't match `example.com.evil.org`.
+fn external_url(url: &str, internal: Option<&[String]>) -> bool {
+    let scheme_end = url
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.'))
+        .unwrap_or(url.len());
+    let rest = match url[scheme_end..].strip_prefix("://") {
+        Some(rest) if scheme_end > 0 => rest,
+        _ => match url.strip_prefix("//") {
+            Some(rest) => rest,
+            None => return false,
+        },
+    };
+
+    !internal.unwrap_or_default().iter().any(|prefix| {
+        rest.len() >= prefix.len()
+            && rest.is_char_boundary(prefix.len())
+            && rest[..prefix.len()].eq_ignore_ascii_case(prefix)
+            && (prefix.ends_with('/')
+                || rest[prefix.len()..]
+                    .chars()
+                    .next()
+                    .map_or(true, |c| matches!(c, '/' | ':' | '?' | '#')))
+    })
+}
+
 /// Writes buffer to output, escaping anything that could be interpreted as an
 /// HTML tag.
 ///
@@ -375,6 +773,11 @@ impl<'o> HtmlFormatter<'o> {
             footnote_ix: 0,
             written_footnote_ix: 0,
             plugins,
+            heading_anchor_after: None,
+            heading_adapter_id: None,
+            block_id_counts: HashMap::new(),
+            heading_ids: HashSet::new(),
+            toc: None,
         }
     }
 
@@ -389,8 +792,64 @@ impl<'o> HtmlFormatter<'o> {
         escape(&mut self.output, buffer)
     }
 
-    fn escape_href(&mut self, buffer: &[u8]) -> io::Result<()> {
-        escape_href(&mut self.output, buffer)
+    // Insert a <wbr> after every n characters of a run of non-whitespace,
+    // provided the run continues past that point.
+    fn escape_with_wbr(&mut self, literal: &str, n: usize) -> io::Result<()> {
+        let mut run = 0;
+        let mut last = 0;
+
+        for (i, c) in literal.char_indices() {
+            if c.is_whitespace() {
+                run = 0;
+                continue;
+            }
+
+            if run == n {
+                self.escape(&literal.as_bytes()[last..i])?;
+                self.output.write_all(b"<wbr>")?;
+                last = i;
+                run = 0;
+            }
+
+            run += 1;
+        }
+
+        self.escape(&literal.as_bytes()[last..])
+    }
+
+    // Writes each attribute as ` name="value"`, or just ` name` if the value
+    // is None.  Values must already be escaped.
+    fn write_attributes(&mut self, mut attributes: Attributes) -> io::Result<()> {
+        if self.options.render.sorted_attributes {
+            attributes.sort_by(|a, b| a.0.cmp(b.0));
+        }
+
+        for (name, value) in attributes {
+            match value {
+                Some(value) => write!(self.output, " {}=\"{}\"", name, value)?,
+                None => write!(self.output, " {}", name)?,
+            }
+        }
+        Ok(())
+    }
+
+    // The extra attributes of a link or image, after all the others.
+    fn write_link_attrs(&mut self, nl: &NodeLink) -> io::Result<()> {
+        for (key, value) in &nl.attrs {
+            if !link_attribute_allowed(key, self.options.render.unsafe_) {
+                continue;
+            }
+            write!(self.output, " {}=\"", key)?;
+            self.escape(value.as_bytes())?;
+            self.output.write_all(b"\"")?;
+        }
+        Ok(())
+    }
+
+    fn sort_attributes(&self, attributes: &mut [(String, String)]) {
+        if self.options.render.sorted_attributes {
+            attributes.sort_by(|a, b| a.0.cmp(&b.0));
+        }
     }
 
     fn replace_href(&mut self, buffer: &[u8]) -> Option<Vec<u8>> {
@@ -429,11 +888,19 @@ impl<'o> HtmlFormatter<'o> {
             Pre,
             Post,
         }
+        if self.options.render.block_ids.is_some() {
+            self.heading_ids = self.collect_heading_ids(node);
+        }
+
         let mut stack = vec![(node, plain, Phase::Pre)];
 
         while let Some((node, plain, phase)) = stack.pop() {
             match phase {
                 Phase::Pre => {
+                    if !plain && self.render_with_node_renderer(node)? {
+                        continue;
+                    }
+
                     let new_plain = if plain {
                         match node.data.borrow().value {
                             NodeValue::Text(ref literal)
@@ -469,20 +936,10 @@ impl<'o> HtmlFormatter<'o> {
         Ok(())
     }
 
-    fn collect_text<'a>(node: &'a AstNode<'a>, output: &mut Vec<u8>) {
-        match node.data.borrow().value {
-            NodeValue::Text(ref literal) | NodeValue::Code(NodeCode { ref literal, .. }) => {
-                output.extend_from_slice(literal.as_bytes())
-            }
-            NodeValue::LineBreak | NodeValue::SoftBreak => output.push(b' '),
-            NodeValue::Math(NodeMath { ref literal, .. }) => {
-                output.extend_from_slice(literal.as_bytes())
-            }
-            _ => {
-                for n in node.children() {
-                    Self::collect_text(n, output);
-                }
-            }
+    fn render_with_node_renderer<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<bool> {
+        match self.plugins.render.node_renderer {
+            Some(renderer) => Ok(renderer.render(self.output, node)? == RenderDecision::Handled),
+            None => Ok(false),
         }
     }
 
@@ -501,21 +958,54 @@ impl<'o> HtmlFormatter<'o> {
                     self.output.write_all(b"</blockquote>\n")?;
                 }
             }
+            NodeValue::Alert(ref na) => {
+                if entering {
+                    self.cr()?;
+                    write!(
+                        self.output,
+                        "<div class=\"markdown-alert markdown-alert-{}\"",
+                        na.alert_type.css_class()
+                    )?;
+                    self.render_sourcepos(node)?;
+                    self.output
+                        .write_all(b">\n<p class=\"markdown-alert-title\">")?;
+                    match na.title {
+                        Some(ref title) => self.escape(title.as_bytes())?,
+                        None => self
+                            .output
+                            .write_all(na.alert_type.default_title().as_bytes())?,
+                    }
+                    self.output.write_all(b"</p>\n")?;
+                } else {
+                    self.cr()?;
+                    self.output.write_all(b"</div>\n")?;
+                }
+            }
             NodeValue::List(ref nl) => {
                 if entering {
                     self.cr()?;
                     if nl.list_type == ListType::Bullet {
                         self.output.write_all(b"<ul")?;
-                        self.render_sourcepos(node)?;
-                        self.output.write_all(b">\n")?;
-                    } else if nl.start == 1 {
-                        self.output.write_all(b"<ol")?;
-                        self.render_sourcepos(node)?;
+                        self.render_sourcepos_and_role(node, "list")?;
                         self.output.write_all(b">\n")?;
                     } else {
                         self.output.write_all(b"<ol")?;
-                        self.render_sourcepos(node)?;
-                        writeln!(self.output, " start=\"{}\">", nl.start)?;
+                        let mut attributes = vec![];
+                        self.block_id_attribute(node, &mut attributes);
+                        self.sourcepos_attribute(node, &mut attributes);
+                        self.role_attribute("list", &mut attributes);
+                        if let Some(numbering) = nl.numbering.html_type() {
+                            attributes.push(("type", Some(numbering.to_string())));
+                        }
+                        let reversed = self.counts_down(node);
+                        if reversed {
+                            attributes.push(("reversed", None));
+                        }
+                        if nl.start != 1 || reversed {
+                            attributes.push(("start", Some(nl.start.to_string())));
+                        }
+                        self.write_attributes(attributes)?;
+                        self.output.write_all(b">\n")?;
                     }
                 } else if nl.list_type == ListType::Bullet {
                     self.output.write_all(b"</ul>\n")?;
@@ -523,11 +1013,18 @@ impl<'o> HtmlFormatter<'o> {
                     self.output.write_all(b"</ol>\n")?;
                 }
             }
-            NodeValue::Item(..) => {
+            NodeValue::Item(ref nl) => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<li")?;
-                    self.render_sourcepos(node)?;
+                    let mut attributes = vec![];
+                    self.block_id_attribute(node, &mut attributes);
+                    self.sourcepos_attribute(node, &mut attributes);
+                    self.role_attribute("listitem", &mut attributes);
+                    if let Some(value) = self.item_value(node, nl) {
+                        attributes.push(("value", Some(value.to_string())));
+                    }
+                    self.write_attributes(attributes)?;
                     self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</li>\n")?;
@@ -566,38 +1063,61 @@ impl<'o> HtmlFormatter<'o> {
                 None => {
                     if entering {
                         self.cr()?;
-                        write!(self.output, "<h{}", nch.level)?;
-                        self.render_sourcepos(node)?;
-                        self.output.write_all(b">")?;
-
-                        if let Some(ref prefix) = self.options.extension.header_ids {
-                            let mut text_content = Vec::with_capacity(20);
-                            Self::collect_text(node, &mut text_content);
-
-                            let mut id = String::from_utf8(text_content).unwrap();
-                            id = self.anchorizer.anchorize(id);
-                            write!(
-                                        self.output,
-                                        "<a href=\"#{}\" aria-hidden=\"true\" class=\"anchor\" id=\"{}{}\"></a>",
-                                        id,
-                                        prefix,
-                                        id
-                                    )?;
+                        write!(self.output, "<h{}", self.heading_level(nch.level))?;
+                        let mut attributes = vec![];
+                        self.sourcepos_attribute(node, &mut attributes);
+
+                        // Custom slugs may hold characters needing escaping,
+                        // unlike the built-in ones.
+                        let header_id = self
+                            .header_id(node)
+                            .map(|(prefix, id)| (prefix, escaped(id.as_bytes())));
+
+                        match (header_id, self.options.extension.header_id_style) {
+                            (Some((prefix, id)), HeaderIdStyle::AnchorBefore) => {
+                                self.write_attributes(attributes)?;
+                                self.output.write_all(b">")?;
+                                self.write_heading_anchor(&prefix, &id)?;
+                            }
+                            (Some((prefix, id)), HeaderIdStyle::AnchorAfter) => {
+                                self.write_attributes(attributes)?;
+                                self.output.write_all(b">")?;
+                                self.heading_anchor_after = Some((prefix, id));
+                            }
+                            (Some((prefix, id)), HeaderIdStyle::OnHeading) => {
+                                attributes.push(("id", Some(format!("{}{}", prefix, id))));
+                                self.write_attributes(attributes)?;
+                                self.output.write_all(b">")?;
+                            }
+                            _ => {
+                                self.write_attributes(attributes)?;
+                                self.output.write_all(b">")?;
+                            }
                         }
                     } else {
-                        writeln!(self.output, "</h{}>", nch.level)?;
+                        if let Some((prefix, id)) = self.heading_anchor_after.take() {
+                            self.write_heading_anchor(&prefix, &id)?;
+                        }
+                        writeln!(self.output, "</h{}>", self.heading_level(nch.level))?;
                     }
                 }
                 Some(adapter) => {
-                    let mut text_content = Vec::with_capacity(20);
-                    Self::collect_text(node, &mut text_content);
-                    let content = String::from_utf8(text_content).unwrap();
+                    // The id is made on entering, and kept for exit so the
+                    // anchorizer isn't consulted twice.
+                    let id = if entering {
+                        self.header_id(node)
+                            .map(|(prefix, id)| format!("{}{}", prefix, id))
+                    } else {
+                        self.heading_adapter_id.take()
+                    };
                     let heading = HeadingMeta {
-                        level: nch.level,
-                        content,
+                        level: self.heading_level(nch.level),
+                        content: node.text_content(),
+                        id,
                     };
 
                     if entering {
+                        self.heading_adapter_id = heading.id.clone();
                         self.cr()?;
                         adapter.enter(
                             self.output,
@@ -615,51 +1135,102 @@ impl<'o> HtmlFormatter<'o> {
             },
             NodeValue::CodeBlock(ref ncb) => {
                 if entering {
+                    let literal: Cow<str> = if self.options.render.trim_code_trailing_whitespace {
+                        Cow::Owned(trim_trailing_whitespace(&ncb.literal))
+                    } else {
+                        Cow::Borrowed(&ncb.literal)
+                    };
+
                     if ncb.info.eq("math") {
-                        self.render_math_code_block(node, &ncb.literal)?;
+                        self.render_math_code_block(node, &literal)?;
                     } else {
                         self.cr()?;
 
                         let mut first_tag = 0;
-                        let mut pre_attributes: HashMap<String, String> = HashMap::new();
-                        let mut code_attributes: HashMap<String, String> = HashMap::new();
+                        let mut pre_attributes: Vec<(String, String)> = Vec::new();
+                        let mut code_attributes: Vec<(String, String)> = Vec::new();
                         let code_attr: String;
 
-                        let literal = &ncb.literal.as_bytes();
                         let info = &ncb.info.as_bytes();
 
+                        while first_tag < info.len() && !isspace(info[first_tag]) {
+                            first_tag += 1;
+                        }
+
+                        let lang = match ncb.raw_info.split_whitespace().next() {
+                            Some(raw) if self.options.render.raw_info_string_class => raw,
+                            _ => str::from_utf8(&info[..first_tag]).unwrap(),
+                        };
+                        let lang_str: Cow<str> = match self.options.render.normalize_code_lang {
+                            Some(normalize) if !lang.is_empty() => Cow::Owned(normalize(lang)),
+                            _ => Cow::Borrowed(lang),
+                        };
+
+                        let mut title = None;
+                        let mut meta = Cow::Borrowed("");
+
                         if !info.is_empty() {
-                            while first_tag < info.len() && !isspace(info[first_tag]) {
-                                first_tag += 1;
+                            let mut info_str: Cow<str> =
+                                Cow::Borrowed(str::from_utf8(&info[first_tag..]).unwrap().trim());
+
+                            if self.options.render.full_info_string
+                                && self.options.render.code_block_title_from_info
+                            {
+                                if let Some((t, rest)) = split_info_title(&info_str) {
+                                    title = Some(t);
+                                    info_str = Cow::Owned(rest);
+                                }
                             }
 
-                            let lang_str = str::from_utf8(&info[..first_tag]).unwrap();
-                            let info_str = str::from_utf8(&info[first_tag..]).unwrap().trim();
-
                             if self.options.render.github_pre_lang {
-                                pre_attributes.insert(String::from("lang"), lang_str.to_string());
+                                pre_attributes.push((String::from("lang"), lang_str.to_string()));
 
                                 if self.options.render.full_info_string && !info_str.is_empty() {
-                                    pre_attributes.insert(
-                                        String::from("data-meta"),
-                                        info_str.trim().to_string(),
-                                    );
+                                    pre_attributes
+                                        .push((String::from("data-meta"), info_str.to_string()));
                                 }
                             } else {
-                                code_attr = format!("language-{}", lang_str);
-                                code_attributes.insert(String::from("class"), code_attr);
+                                if !(self.options.render.code_lang_as_data_attr
+                                    && self.options.render.code_lang_data_attr_only)
+                                {
+                                    code_attr = format!("language-{}", lang_str);
+                                    code_attributes.push((String::from("class"), code_attr));
+                                }
 
                                 if self.options.render.full_info_string && !info_str.is_empty() {
                                     code_attributes
-                                        .insert(String::from("data-meta"), info_str.to_string());
+                                        .push((String::from("data-meta"), info_str.to_string()));
                                 }
                             }
+
+                            if self.options.render.code_lang_as_data_attr {
+                                code_attributes
+                                    .push((String::from("data-language"), lang_str.to_string()));
+                            }
+
+                            meta = info_str;
                         }
 
+                        if let Some(id) = self.block_id(node) {
+                            pre_attributes.push(("id".to_string(), id));
+                        }
                         if self.options.render.sourcepos {
                             let ast = node.data.borrow();
                             pre_attributes
-                                .insert("data-sourcepos".to_string(), ast.sourcepos.to_string());
+                                .push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
+                        }
+                        if let Some(line) = self.data_line(node) {
+                            pre_attributes.push(("data-line".to_string(), line.to_string()));
+                        }
+
+                        self.sort_attributes(&mut pre_attributes);
+                        self.sort_attributes(&mut code_attributes);
+
+                        if let Some(ref title) = title {
+                            self.output
+                                .write_all(b"<figure class=\"code-block\">\n<figcaption>")?;
+                            self.escape(title.as_bytes())?;
+                            self.output.write_all(b"</figcaption>\n")?;
                         }
 
                         match self.plugins.render.codefence_syntax_highlighter {
@@ -667,26 +1238,38 @@ impl<'o> HtmlFormatter<'o> {
                                 write_opening_tag(self.output, "pre", pre_attributes)?;
                                 write_opening_tag(self.output, "code", code_attributes)?;
 
-                                self.escape(literal)?;
+                                self.escape(literal.as_bytes())?;
 
                                 self.output.write_all(b"</code></pre>\n")?
                             }
                             Some(highlighter) => {
-                                highlighter.write_pre_tag(self.output, pre_attributes)?;
-                                highlighter.write_code_tag(self.output, code_attributes)?;
+                                highlighter.write_pre_tag(
+                                    self.output,
+                                    pre_attributes.into_iter().collect(),
+                                )?;
+                                highlighter.write_code_tag(
+                                    self.output,
+                                    code_attributes.into_iter().collect(),
+                                )?;
 
-                                highlighter.write_highlighted(
+                                let info = if meta.is_empty() {
+                                    lang_str.to_string()
+                                } else {
+                                    format!("{} {}", lang_str, meta)
+                                };
+                                highlighter.write_highlighted_with_info(
                                     self.output,
-                                    match str::from_utf8(&info[..first_tag]) {
-                                        Ok(lang) => Some(lang),
-                                        Err(_) => None,
-                                    },
-                                    &ncb.literal,
+                                    Some(&info),
+                                    &literal,
                                 )?;
 
                                 self.output.write_all(b"</code></pre>\n")?
                             }
                         }
+
+                        if title.is_some() {
+                            self.output.write_all(b"</figure>\n")?;
+                        }
                     }
                 }
             }
@@ -711,7 +1294,13 @@ impl<'o> HtmlFormatter<'o> {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<hr")?;
-                    self.render_sourcepos(node)?;
+                    let mut attributes = vec![];
+                    self.block_id_attribute(node, &mut attributes);
+                    if let Some(ref class) = self.options.render.thematic_break_class {
+                        attributes.push(("class", Some(escaped(class.as_bytes()))));
+                    }
+                    self.sourcepos_attribute(node, &mut attributes);
+                    self.write_attributes(attributes)?;
                     self.output.write_all(b" />\n")?;
                 }
             }
@@ -731,6 +1320,19 @@ impl<'o> HtmlFormatter<'o> {
                         Some(NodeValue::DescriptionTerm)
                     );
 
+                let tight = tight
+                    || match node
+                        .parent()
+                        .filter(|n| matches!(n.data.borrow().value, NodeValue::DescriptionDetails))
+                        .and_then(|n| n.parent())
+                        .map(|n| n.data.borrow().value.clone())
+                    {
+                        Some(NodeValue::DescriptionItem(di)) => {
+                            di.tight && !self.options.render.description_list_loose
+                        }
+                        _ => false,
+                    };
+
                 if !tight {
                     if entering {
                         self.cr()?;
@@ -745,7 +1347,7 @@ impl<'o> HtmlFormatter<'o> {
                         if let NodeValue::FootnoteDefinition(nfd) =
                             &node.parent().unwrap().data.borrow().value
                         {
-                            if node.next_sibling().is_none() {
+                            if node.next_sibling().is_none() && nfd.total_references > 0 {
                                 self.output.write_all(b" ")?;
                                 self.put_footnote_backref(nfd)?;
                             }
@@ -760,7 +1362,14 @@ impl<'o> HtmlFormatter<'o> {
             }
             NodeValue::Text(ref literal) => {
                 if entering {
-                    self.escape(literal.as_bytes())?;
+                    if node.parent().map_or(false, |p| self.obfuscates_email(p)) {
+                        self.output.write_all(obfuscated(literal).as_bytes())?;
+                    } else {
+                        match self.options.render.insert_wbr_in_long_words {
+                            Some(n) if n > 0 => self.escape_with_wbr(literal, n)?,
+                            _ => self.escape(literal.as_bytes())?,
+                        }
+                    }
                 }
             }
             NodeValue::LineBreak => {
@@ -776,15 +1385,26 @@ impl<'o> HtmlFormatter<'o> {
                         self.output.write_all(b"<br")?;
                         self.render_sourcepos(node)?;
                         self.output.write_all(b" />\n")?;
+                    } else if self.options.render.collapse_soft_breaks {
+                        self.output.write_all(b" ")?;
                     } else {
                         self.output.write_all(b"\n")?;
                     }
                 }
             }
-            NodeValue::Code(NodeCode { ref literal, .. }) => {
+            NodeValue::Code(NodeCode {
+                ref literal,
+                ref lang,
+                ..
+            }) => {
                 if entering {
                     self.output.write_all(b"<code")?;
                     self.render_sourcepos(node)?;
+                    if let Some(ref lang) = lang {
+                        self.output.write_all(b" class=\"language-")?;
+                        self.escape(lang.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
                     self.output.write_all(b">")?;
                     self.escape(literal.as_bytes())?;
                     self.output.write_all(b"</code>")?;
@@ -797,7 +1417,14 @@ impl<'o> HtmlFormatter<'o> {
                     if self.options.render.escape {
                         self.escape(literal)?;
                     } else if !self.options.render.unsafe_ {
-                        self.output.write_all(b"<!-- raw HTML omitted -->")?;
+                        if allowed_inline_html(
+                            literal,
+                            &self.options.render.allowed_inline_html_tags,
+                        ) {
+                            self.output.write_all(literal)?;
+                        } else {
+                            self.output.write_all(b"<!-- raw HTML omitted -->")?;
+                        }
                     } else if self.options.extension.tagfilter && tagfilter(literal) {
                         self.output.write_all(b"&lt;")?;
                         self.output.write_all(&literal[1..])?;
@@ -847,6 +1474,29 @@ impl<'o> HtmlFormatter<'o> {
                     self.output.write_all(b"</sup>")?;
                 }
             }
+            NodeValue::Highlight => {
+                if entering {
+                    self.output.write_all(b"<mark")?;
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">")?;
+                } else {
+                    self.output.write_all(b"</mark>")?;
+                }
+            }
+            NodeValue::Abbreviation(ref na) => {
+                if entering {
+                    self.output.write_all(b"<abbr")?;
+                    if !na.title.is_empty() {
+                        self.output.write_all(b" title=\"")?;
+                        self.escape(na.title.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">")?;
+                } else {
+                    self.output.write_all(b"</abbr>")?;
+                }
+            }
             NodeValue::Subscript => {
                 if entering {
                     self.output.write_all(b"<sub>")?;
@@ -861,21 +1511,48 @@ impl<'o> HtmlFormatter<'o> {
                     self.output.write_all(b"</ins>")?;
                 }
             }
+            NodeValue::Quoted => {
+                if entering {
+                    self.output.write_all(b"<q")?;
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">")?;
+                } else {
+                    self.output.write_all(b"</q>")?;
+                }
+            }
             NodeValue::Link(ref nl) => {
                 if entering {
                     self.output.write_all(b"<a")?;
-                    self.render_sourcepos(node)?;
-                    self.output.write_all(b" href=\"")?;
+                    let mut attributes = vec![];
+                    self.sourcepos_attribute(node, &mut attributes);
                     let url = nl.url.as_bytes();
-                    if self.options.render.unsafe_ || !dangerous_url(url) {
+                    let mut external = false;
+                    let href = if self.obfuscates_email(node) {
+                        obfuscated(&nl.url)
+                    } else if self.options.render.unsafe_ || !dangerous_url(url) {
                         let new_href = self.replace_href(url).unwrap_or_else(|| url.to_vec());
-                        self.escape_href(&new_href)?;
+                        // A link relativized under philomena_domains is internal.
+                        external = self.options.render.external_links_new_tab
+                            && new_href == url
+                            && external_url(
+                                &nl.url,
+                                self.options.render.internal_link_hosts.as_deref(),
+                            );
+                        escaped_href(&new_href)
+                    } else {
+                        String::new()
+                    };
+                    attributes.push(("href", Some(href)));
+                    if external {
+                        attributes.push(("target", Some("_blank".to_string())));
+                        attributes.push(("rel", Some("noopener noreferrer".to_string())));
                     }
                     if !nl.title.is_empty() {
-                        self.output.write_all(b"\" title=\"")?;
-                        self.escape(nl.title.as_bytes())?;
+                        attributes.push(("title", Some(escaped(nl.title.as_bytes()))));
                     }
-                    self.output.write_all(b"\">")?;
+                    self.write_attributes(attributes)?;
+                    self.write_link_attrs(nl)?;
+                    self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</a>")?;
                 }
@@ -886,25 +1563,22 @@ impl<'o> HtmlFormatter<'o> {
                         self.output.write_all(b"<span class=\"imgspoiler\">")?;
                     }
 
+                    // The alt text is written by rendering the children as
+                    // plain text, so when sorting it comes first and the
+                    // other attributes follow on exit.
                     self.output.write_all(b"<img")?;
-                    self.render_sourcepos(node)?;
-                    self.output.write_all(b" src=\"")?;
-                    let url = nl.url.as_bytes();
-                    if self.options.render.unsafe_ || !dangerous_url(url) {
-                        if let Some(c) = self.options.extension.camoifier {
-                            self.escape_href(c(nl.url.clone()).as_ref())?
-                        } else {
-                            self.escape_href(url)?;
-                        }
+                    if !self.options.render.sorted_attributes {
+                        let attributes = self.image_attributes(node, nl, false);
+                        self.write_attributes(attributes)?;
                     }
-                    self.output.write_all(b"\" alt=\"")?;
+                    self.output.write_all(b" alt=\"")?;
                     return Ok(true);
                 } else {
-                    if !nl.title.is_empty() {
-                        self.output.write_all(b"\" title=\"")?;
-                        self.escape(nl.title.as_bytes())?;
-                    }
-                    self.output.write_all(b"\" />")?;
+                    self.output.write_all(b"\"")?;
+                    let attributes = self.image_attributes(node, nl, true);
+                    self.write_attributes(attributes)?;
+                    self.write_link_attrs(nl)?;
+                    self.output.write_all(b" />")?;
 
                     if self.options.extension.philomena {
                         self.output.write_all(b"</span>")?;
@@ -914,20 +1588,38 @@ impl<'o> HtmlFormatter<'o> {
             #[cfg(feature = "shortcodes")]
             NodeValue::ShortCode(ref nsc) => {
                 if entering {
-                    self.output.write_all(nsc.emoji().as_bytes())?;
+                    match self
+                        .options
+                        .render
+                        .emoji_image_url
+                        .and_then(|url| url(nsc.shortcode()))
+                    {
+                        Some(url) => {
+                            self.output.write_all(b"<img")?;
+                            let mut attributes = vec![];
+                            self.sourcepos_attribute(node, &mut attributes);
+                            attributes.push(("class", Some("emoji".to_string())));
+                            attributes.push(("src", Some(escaped_href(url.as_bytes()))));
+                            attributes.push((
+                                "alt",
+                                Some(escaped(format!(":{}:", nsc.shortcode()).as_bytes())),
+                            ));
+                            self.write_attributes(attributes)?;
+                            self.output.write_all(b" />")?;
+                        }
+                        None => self.output.write_all(nsc.emoji().as_bytes())?,
+                    }
                 }
             }
             NodeValue::Table(..) => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<table")?;
-                    self.render_sourcepos(node)?;
+                    self.render_sourcepos_and_role(node, "table")?;
                     self.output.write_all(b">\n")?;
                 } else {
-                    if !node
-                        .last_child()
-                        .unwrap()
-                        .same_node(node.first_child().unwrap())
+                    if let NodeValue::TableRow(false) =
+                        node.last_child().unwrap().data.borrow().value
                     {
                         self.cr()?;
                         self.output.write_all(b"</tbody>\n")?;
@@ -940,14 +1632,18 @@ impl<'o> HtmlFormatter<'o> {
                 if entering {
                     self.cr()?;
                     if header {
-                        self.output.write_all(b"<thead>\n")?;
-                    } else if let Some(n) = node.previous_sibling() {
-                        if let NodeValue::TableRow(true) = n.data.borrow().value {
-                            self.output.write_all(b"<tbody>\n")?;
-                        }
+                        self.output.write_all(b"<thead")?;
+                        self.render_role("rowgroup")?;
+                        self.output.write_all(b">\n")?;
+                    } else if !node.previous_sibling().map_or(false, |n| {
+                        matches!(n.data.borrow().value, NodeValue::TableRow(false))
+                    }) {
+                        self.output.write_all(b"<tbody")?;
+                        self.render_role("rowgroup")?;
+                        self.output.write_all(b">\n")?;
                     }
                     self.output.write_all(b"<tr")?;
-                    self.render_sourcepos(node)?;
+                    self.render_sourcepos_and_role(node, "row")?;
                     self.output.write_all(b">")?;
                 } else {
                     self.cr()?;
@@ -958,6 +1654,16 @@ impl<'o> HtmlFormatter<'o> {
                     }
                 }
             }
+            NodeValue::TableCaption => {
+                if entering {
+                    self.cr()?;
+                    self.output.write_all(b"<caption")?;
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">")?;
+                } else {
+                    self.output.write_all(b"</caption>\n")?;
+                }
+            }
             NodeValue::TableCell => {
                 let row = &node.parent().unwrap().data.borrow().value;
                 let in_header = match *row {
@@ -975,11 +1681,15 @@ impl<'o> HtmlFormatter<'o> {
                     self.cr()?;
                     if in_header {
                         self.output.write_all(b"<th")?;
-                        self.render_sourcepos(node)?;
                     } else {
                         self.output.write_all(b"<td")?;
-                        self.render_sourcepos(node)?;
                     }
+                    let mut attributes = vec![];
+                    self.sourcepos_attribute(node, &mut attributes);
+                    self.role_attribute(
+                        if in_header { "columnheader" } else { "cell" },
+                        &mut attributes,
+                    );
 
                     let mut start = node.parent().unwrap().first_child().unwrap();
                     let mut i = 0;
@@ -988,19 +1698,29 @@ impl<'o> HtmlFormatter<'o> {
                         start = start.next_sibling().unwrap();
                     }
 
-                    match alignments[i] {
-                        TableAlignment::Left => {
-                            self.output.write_all(b" align=\"left\"")?;
+                    if let Some(ref classes) = self.options.render.table_alignment_classes {
+                        let class = match alignments[i] {
+                            TableAlignment::Left => Some(&classes[0]),
+                            TableAlignment::Center => Some(&classes[1]),
+                            TableAlignment::Right => Some(&classes[2]),
+                            TableAlignment::None => None,
+                        };
+                        if let Some(class) = class {
+                            attributes.push(("class", Some(escaped(class.as_bytes()))));
                         }
-                        TableAlignment::Right => {
-                            self.output.write_all(b" align=\"right\"")?;
-                        }
-                        TableAlignment::Center => {
-                            self.output.write_all(b" align=\"center\"")?;
+                    } else {
+                        let align = match alignments[i] {
+                            TableAlignment::Left => Some("left"),
+                            TableAlignment::Right => Some("right"),
+                            TableAlignment::Center => Some("center"),
+                            TableAlignment::None => None,
+                        };
+                        if let Some(align) = align {
+                            attributes.push(("align", Some(align.to_string())));
                         }
-                        TableAlignment::None => (),
                     }
 
+                    self.write_attributes(attributes)?;
                     self.output.write_all(b">")?;
                 } else if in_header {
                     self.output.write_all(b"</th>")?;
@@ -1012,16 +1732,30 @@ impl<'o> HtmlFormatter<'o> {
                 if entering {
                     if self.footnote_ix == 0 {
                         self.output.write_all(b"<section")?;
-                        self.render_sourcepos(node)?;
-                        self.output
-                            .write_all(b" class=\"footnotes\" data-footnotes>\n<ol>\n")?;
+                        let mut attributes = vec![];
+                        self.sourcepos_attribute(node, &mut attributes);
+                        attributes.push(("class", Some("footnotes".to_string())));
+                        attributes.push(("data-footnotes", None));
+                        self.write_attributes(attributes)?;
+                        self.output.write_all(b">\n<ol")?;
+                        let base = self.options.extension.footnote_base();
+                        if base > 0 {
+                            write!(self.output, " start=\"{}\"", base.saturating_add(1))?;
+                        }
+                        self.output.write_all(b">\n")?;
                     }
                     self.footnote_ix += 1;
                     self.output.write_all(b"<li")?;
-                    self.render_sourcepos(node)?;
-                    self.output.write_all(b" id=\"fn-")?;
-                    self.escape_href(nfd.name.as_bytes())?;
-                    self.output.write_all(b"\">")?;
+                    let mut attributes = vec![];
+                    self.sourcepos_attribute(node, &mut attributes);
+                    attributes.push((
+                        "id",
+                        Some(escaped_href(
+                            format!("{}{}", self.footnote_id_prefix(), nfd.name).as_bytes(),
+                        )),
+                    ));
+                    self.write_attributes(attributes)?;
+                    self.output.write_all(b">")?;
                 } else {
                     if self.put_footnote_backref(nfd)? {
                         self.output.write_all(b"\n")?;
@@ -1031,38 +1765,63 @@ impl<'o> HtmlFormatter<'o> {
             }
             NodeValue::FootnoteReference(ref nfr) => {
                 if entering {
-                    let mut ref_id = format!("fnref-{}", nfr.name);
+                    let mut ref_id = format!("{}{}", self.footnote_ref_id_prefix(), nfr.name);
+
+                    // Adjacent references share a single <sup> when grouped.
+                    let group = self.options.render.group_footnote_refs;
+                    let is_ref = |n: Option<&'a AstNode<'a>>| {
+                        n.map_or(false, |n| {
+                            matches!(n.data.borrow().value, NodeValue::FootnoteReference(..))
+                        })
+                    };
+                    let continues_group = group && is_ref(node.previous_sibling());
+                    let ends_group = !group || !is_ref(node.next_sibling());
 
-                    self.output.write_all(b"<sup")?;
-                    self.render_sourcepos(node)?;
+                    if continues_group {
+                        self.output.write_all(b", ")?;
+                    } else {
+                        self.output.write_all(b"<sup")?;
+                        let mut attributes = vec![];
+                        self.sourcepos_attribute(node, &mut attributes);
+                        attributes.push(("class", Some("footnote-ref".to_string())));
+                        self.write_attributes(attributes)?;
+                        self.output.write_all(b">")?;
+                    }
 
                     if nfr.ref_num > 1 {
                         ref_id = format!("{}-{}", ref_id, nfr.ref_num);
                     }
 
-                    self.output
-                        .write_all(b" class=\"footnote-ref\"><a href=\"#fn-")?;
-                    self.escape_href(nfr.name.as_bytes())?;
-                    self.output.write_all(b"\" id=\"")?;
-                    self.escape_href(ref_id.as_bytes())?;
-                    write!(self.output, "\" data-footnote-ref>{}</a></sup>", nfr.ix)?;
+                    self.output.write_all(b"<a")?;
+                    self.write_attributes(vec![
+                        (
+                            "href",
+                            Some(escaped_href(
+                                format!("#{}{}", self.footnote_id_prefix(), nfr.name).as_bytes(),
+                            )),
+                        ),
+                        ("id", Some(escaped_href(ref_id.as_bytes()))),
+                        ("data-footnote-ref", None),
+                    ])?;
+                    write!(self.output, ">{}</a>", nfr.ix)?;
+                    if ends_group {
+                        self.output.write_all(b"</sup>")?;
+                    }
                 }
             }
             NodeValue::TaskItem(symbol) => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<li")?;
-                    self.render_sourcepos(node)?;
-                    self.output.write_all(b">")?;
-                    write!(
-                        self.output,
-                        "<input type=\"checkbox\" {}disabled=\"\" /> ",
-                        if symbol.is_some() {
-                            "checked=\"\" "
-                        } else {
-                            ""
-                        }
-                    )?;
+                    self.render_sourcepos_and_role(node, "listitem")?;
+                    self.output.write_all(b"><input")?;
+                    let mut attributes = vec![("type", Some("checkbox".to_string()))];
+                    if symbol.is_some() {
+                        attributes.push(("checked", Some(String::new())));
+                    }
+                    attributes.push(("disabled", Some(String::new())));
+                    self.write_attributes(attributes)?;
+                    self.output.write_all(b" /> ")?;
                 } else {
                     self.output.write_all(b"</li>\n")?;
                 }
@@ -1078,11 +1837,56 @@ impl<'o> HtmlFormatter<'o> {
                     self.output.write_all(b"</blockquote>\n")?;
                 }
             }
+            NodeValue::Details(ref nd) => {
+                if entering {
+                    self.cr()?;
+                    self.output.write_all(b"<details")?;
+                    if nd.open {
+                        self.output.write_all(b" open")?;
+                    }
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">\n")?;
+                    if !nd.summary.is_empty() {
+                        self.output.write_all(b"<summary>")?;
+                        self.escape(nd.summary.as_bytes())?;
+                        self.output.write_all(b"</summary>\n")?;
+                    }
+                } else {
+                    self.cr()?;
+                    self.output.write_all(b"</details>\n")?;
+                }
+            }
+            NodeValue::FencedDiv(ref nfd) => {
+                if entering {
+                    self.cr()?;
+                    self.output.write_all(b"<div")?;
+                    if !nfd.class.is_empty() {
+                        self.output.write_all(b" class=\"")?;
+                        self.escape(nfd.class.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                    for (key, value) in &nfd.attrs {
+                        if !fenced_div_attribute_allowed(key, self.options.render.unsafe_) {
+                            continue;
+                        }
+                        write!(self.output, " {}=\"", key)?;
+                        self.escape(value.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                    self.render_sourcepos(node)?;
+                    self.output.write_all(b">\n")?;
+                } else {
+                    self.cr()?;
+                    self.output.write_all(b"</div>\n")?;
+                }
+            }
             NodeValue::Escaped => {
                 if self.options.render.escaped_char_spans {
                     if entering {
-                        self.output.write_all(b"<span data-escaped-char")?;
-                        self.render_sourcepos(node)?;
+                        self.output.write_all(b"<span")?;
+                        let mut attributes = vec![("data-escaped-char", None)];
+                        self.sourcepos_attribute(node, &mut attributes);
+                        self.write_attributes(attributes)?;
                         self.output.write_all(b">")?;
                     } else {
                         self.output.write_all(b"</span>")?;
@@ -1102,14 +1906,29 @@ impl<'o> HtmlFormatter<'o> {
             NodeValue::WikiLink(ref nl) => {
                 if entering {
                     self.output.write_all(b"<a")?;
-                    self.render_sourcepos(node)?;
-                    self.output.write_all(b" href=\"")?;
-                    let url = nl.url.as_bytes();
-                    if self.options.render.unsafe_ || !dangerous_url(url) {
-                        self.escape_href(url)?;
+                    let mut attributes = vec![];
+                    self.sourcepos_attribute(node, &mut attributes);
+                    let mut url = Cow::from(nl.url.as_str());
+                    if let Some(separator) = self.options.extension.wikilinks_slug_separator {
+                        let slug = url
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join(separator.encode_utf8(&mut [0; 4]));
+                        url = Cow::from(slug);
                     }
-                    self.output.write_all(b"\" data-wikilink=\"true")?;
-                    self.output.write_all(b"\">")?;
+                    if let Some(resolve) = self.options.extension.wikilinks_url_resolver {
+                        url = Cow::from(resolve(&url));
+                    }
+                    let url = url.as_bytes();
+                    let href = if self.options.render.unsafe_ || !dangerous_url(url) {
+                        escaped_href(url)
+                    } else {
+                        String::new()
+                    };
+                    attributes.push(("href", Some(href)));
+                    attributes.push(("data-wikilink", Some("true".to_string())));
+                    self.write_attributes(attributes)?;
+                    self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</a>")?;
                 }
@@ -1133,23 +1952,352 @@ impl<'o> HtmlFormatter<'o> {
         Ok(false)
     }
 
-    fn render_sourcepos<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<()> {
+    // The attributes of an <img> other than alt; either those before it
+    // (`after_alt` false) or those after it.
+    fn image_attributes<'a>(
+        &self,
+        node: &'a AstNode<'a>,
+        nl: &NodeLink,
+        after_alt: bool,
+    ) -> Attributes {
+        let mut attributes = vec![];
+        let sorted = self.options.render.sorted_attributes;
+
+        if sorted == after_alt {
+            self.sourcepos_attribute(node, &mut attributes);
+            let url = nl.url.as_bytes();
+            let src = if self.options.render.unsafe_ || !dangerous_url(url) {
+                match self.options.extension.camoifier {
+                    Some(c) => escaped_href(c(nl.url.clone()).as_ref()),
+                    None => escaped_href(url),
+                }
+            } else {
+                String::new()
+            };
+            attributes.push(("src", Some(src)));
+
+            if !nl.srcset.is_empty() {
+                let srcset = nl
+                    .srcset
+                    .iter()
+                    .filter(|c| self.options.render.unsafe_ || !dangerous_url(c.url.as_bytes()))
+                    .map(|c| {
+                        let url = match self.options.extension.camoifier {
+                            Some(camo) => escaped_href(camo(c.url.clone()).as_ref()),
+                            None => escaped_href(c.url.as_bytes()),
+                        };
+                        format!("{} {}", url, c.descriptor)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                attributes.push(("srcset", Some(srcset)));
+            }
+        }
+
+        if after_alt && !nl.title.is_empty() {
+            attributes.push(("title", Some(escaped(nl.title.as_bytes()))));
+        }
+
+        attributes
+    }
+
+    // The level a heading is written at, after `render.heading_offset`.
+    fn heading_level(&self, level: u8) -> u8 {
+        level
+            .saturating_add(self.options.render.heading_offset)
+            .min(6)
+    }
+
+    // The prefix and unescaped id to give a heading under `header_ids`, made
+    // unique among the ids given out so far.
+    fn header_id<'a>(&mut self, node: &'a AstNode<'a>) -> Option<(String, String)> {
+        let prefix = self.options.extension.header_ids.as_ref()?;
+        let text = node.text_content();
+        let id = heading_slug(self.options, &mut self.anchorizer, text.clone());
+        if let NodeValue::Heading(ref nch) = node.data.borrow().value {
+            let level = self.heading_level(nch.level);
+            if let Some(ref mut toc) = self.toc {
+                toc.push(TocEntry {
+                    level,
+                    text,
+                    id: id.clone(),
+                });
+            }
+        }
+        Some((prefix.clone(), id))
+    }
+
+    fn write_heading_anchor(&mut self, prefix: &str, id: &str) -> io::Result<()> {
+        self.output.write_all(b"<a")?;
+        self.write_attributes(vec![
+            ("href", Some(format!("#{}", id))),
+            ("aria-hidden", Some("true".to_string())),
+            ("class", Some("anchor".to_string())),
+            ("id", Some(format!("{}{}", prefix, id))),
+        ])?;
+        self.output.write_all(b">")?;
+        if let Some(ref content) = self.options.extension.header_anchor_content {
+            self.output.write_all(content.as_bytes())?;
+        }
+        self.output.write_all(b"</a>")
+    }
+
+    fn sourcepos_attribute<'a>(&self, node: &'a AstNode<'a>, attributes: &mut Attributes) {
         if self.options.render.sourcepos {
             let ast = node.data.borrow();
             if ast.sourcepos.start.line > 0 {
-                write!(self.output, " data-sourcepos=\"{}\"", ast.sourcepos)?;
+                attributes.push(("data-sourcepos", Some(ast.sourcepos.to_string())));
             }
         }
-        Ok(())
+        if let Some(line) = self.data_line(node) {
+            attributes.push(("data-line", Some(line.to_string())));
+        }
+    }
+
+    fn block_id_attribute<'a>(&mut self, node: &'a AstNode<'a>, attributes: &mut Attributes) {
+        if let Some(id) = self.block_id(node) {
+            attributes.push(("id", Some(escaped(id.as_bytes()))));
+        }
+    }
+
+    // The id to give `node` under `block_ids`, counting it among the elements
+    // sharing its tag.
+    fn block_id<'a>(&mut self, node: &'a AstNode<'a>) -> Option<String> {
+        let prefix = self.options.render.block_ids.as_ref()?;
+        let tag = match node.data.borrow().value {
+            NodeValue::Paragraph => "p",
+            NodeValue::BlockQuote | NodeValue::MultilineBlockQuote(..) => "blockquote",
+            NodeValue::List(ref nl) if nl.list_type == ListType::Bullet => "ul",
+            NodeValue::List(..) => "ol",
+            NodeValue::Item(..) | NodeValue::TaskItem(..) => "li",
+            NodeValue::CodeBlock(..) => "pre",
+            NodeValue::Table(..) => "table",
+            NodeValue::DescriptionList => "dl",
+            NodeValue::ThematicBreak => "hr",
+            _ => return None,
+        };
+        let count = self.block_id_counts.entry(tag).or_insert(0);
+        *count += 1;
+
+        // Headings keep their ids; a block id that would clash is suffixed.
+        let id = format!("{}{}-{}", prefix, tag, count);
+        let mut uniq = 0;
+        loop {
+            let candidate = if uniq == 0 {
+                Cow::from(&id)
+            } else {
+                Cow::from(format!("{}-{}", id, uniq))
+            };
+            if !self.heading_ids.contains(&*candidate) {
+                return Some(candidate.into_owned());
+            }
+            uniq += 1;
+        }
+    }
+
+    // The ids `header_ids` will give the headings under `root`, worked out
+    // just as `header_id` does as it comes to each.
+    fn collect_heading_ids<'a>(&self, root: &'a AstNode<'a>) -> HashSet<String> {
+        let prefix = match self.options.extension.header_ids {
+            Some(ref prefix) => prefix,
+            None => return HashSet::new(),
+        };
+        let mut anchorizer = Anchorizer::new();
+        root.descendants()
+            .filter(|node| matches!(node.data.borrow().value, NodeValue::Heading(..)))
+            .map(|node| {
+                let id = heading_slug(self.options, &mut anchorizer, node.text_content());
+                format!("{}{}", prefix, id)
+            })
+            .collect()
+    }
+
+    // The line to give as `data-line`, if `node` is a block and they're
+    // wanted.
+    fn data_line<'a>(&self, node: &'a AstNode<'a>) -> Option<usize> {
+        if !self.options.render.data_line_start {
+            return None;
+        }
+        let ast = node.data.borrow();
+        if ast.value.block() && ast.sourcepos.start.line > 0 {
+            Some(ast.sourcepos.start.line)
+        } else {
+            None
+        }
+    }
+
+    fn role_attribute(&self, role: &'static str, attributes: &mut Attributes) {
+        if self.options.render.aria_roles {
+            attributes.push(("role", Some(role.to_string())));
+        }
+    }
+
+    fn render_role(&mut self, role: &'static str) -> io::Result<()> {
+        let mut attributes = vec![];
+        self.role_attribute(role, &mut attributes);
+        self.write_attributes(attributes)
+    }
+
+    fn render_sourcepos_and_role<'a>(
+        &mut self,
+        node: &'a AstNode<'a>,
+        role: &'static str,
+    ) -> io::Result<()> {
+        let mut attributes = vec![];
+        self.block_id_attribute(node, &mut attributes);
+        self.sourcepos_attribute(node, &mut attributes);
+        self.role_attribute(role, &mut attributes);
+        self.write_attributes(attributes)
+    }
+
+    // The number written before an ordered list item, if the option to
+    // preserve them is set and the browser wouldn't number it the same.
+    fn item_value<'a>(&self, node: &'a AstNode<'a>, nl: &NodeList) -> Option<usize> {
+        if !self.options.render.preserve_ordered_numbers || nl.list_type != ListType::Ordered {
+            return None;
+        }
+
+        let reversed = self.counts_down(node.parent()?);
+        let step = |number: usize, by: usize| {
+            if reversed {
+                number.checked_sub(by)
+            } else {
+                number.checked_add(by)
+            }
+        };
+
+        // Task items don't keep their number, so are numbered in sequence.
+        let mut skipped = 0;
+        let mut prev = node.previous_sibling();
+        let expected = loop {
+            match prev {
+                Some(p) => match p.data.borrow().value {
+                    NodeValue::Item(ref prev_nl) => break step(prev_nl.start, skipped + 1),
+                    _ => {
+                        skipped += 1;
+                        prev = p.previous_sibling();
+                    }
+                },
+                None => match node.parent()?.data.borrow().value {
+                    NodeValue::List(ref list_nl) => break step(list_nl.start, skipped),
+                    _ => return None,
+                },
+            }
+        };
+
+        if expected != Some(nl.start) {
+            Some(nl.start)
+        } else {
+            None
+        }
+    }
+
+    // Whether the browser should count `list` down: the parser found it
+    // reversed, or, keeping the numbers as written, its second item is
+    // numbered one less than its first.
+    fn counts_down<'a>(&self, list: &'a AstNode<'a>) -> bool {
+        match list.data.borrow().value {
+            NodeValue::List(ref nl) if nl.reversed => return true,
+            NodeValue::List(ref nl)
+                if nl.list_type == ListType::Ordered
+                    && self.options.render.preserve_ordered_numbers => {}
+            _ => return false,
+        }
+
+        let mut numbers = list
+            .children()
+            .take(2)
+            .map(|item| match item.data.borrow().value {
+                NodeValue::Item(ref nl) => Some(nl.start),
+                _ => None,
+            });
+        match (numbers.next(), numbers.next()) {
+            (Some(Some(first)), Some(Some(second))) => second.checked_add(1) == Some(first),
+            _ => false,
+        }
+    }
+
+    fn obfuscates_email<'a>(&self, node: &'a AstNode<'a>) -> bool {
+        self.options.render.obfuscate_email
+            && matches!(
+                node.data.borrow().value,
+                NodeValue::Link(NodeLink {
+                    autolink: Some(AutolinkType::Email),
+                    ..
+                })
+            )
+    }
+
+    fn render_sourcepos<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<()> {
+        let mut attributes = vec![];
+        self.block_id_attribute(node, &mut attributes);
+        self.sourcepos_attribute(node, &mut attributes);
+        self.write_attributes(attributes)
+    }
+
+    fn footnote_id_prefix(&self) -> &'o str {
+        self.options
+            .render
+            .footnote_id_prefix
+            .as_deref()
+            .unwrap_or("fn-")
+    }
+
+    fn footnote_ref_id_prefix(&self) -> &'o str {
+        self.options
+            .render
+            .footnote_ref_id_prefix
+            .as_deref()
+            .unwrap_or("fnref-")
+    }
+
+    fn footnote_backref_symbol(&self) -> &'o str {
+        self.options
+            .render
+            .footnote_backref_symbol
+            .as_deref()
+            .unwrap_or("↩")
     }
 
     fn put_footnote_backref(&mut self, nfd: &NodeFootnoteDefinition) -> io::Result<bool> {
-        if self.written_footnote_ix >= self.footnote_ix {
+        if self.written_footnote_ix >= self.footnote_ix || nfd.total_references == 0 {
             return Ok(false);
         }
 
         self.written_footnote_ix = self.footnote_ix;
 
+        if nfd.total_references == 1 && self.options.render.footnote_single_backref_simple {
+            self.output.write_all(b"<a")?;
+            self.write_attributes(vec![
+                (
+                    "href",
+                    Some(escaped_href(
+                        format!("#{}{}", self.footnote_ref_id_prefix(), nfd.name).as_bytes(),
+                    )),
+                ),
+                ("class", Some("footnote-backref".to_string())),
+                ("data-footnote-backref", None),
+                (
+                    "aria-label",
+                    Some(escaped(
+                        self.options
+                            .render
+                            .footnote_backref_simple_label
+                            .as_deref()
+                            .unwrap_or("Back to content")
+                            .as_bytes(),
+                    )),
+                ),
+            ])?;
+            write!(self.output, ">{}</a>", self.footnote_backref_symbol())?;
+            return Ok(true);
+        }
+
+        let footnote_number = self
+            .options
+            .extension
+            .footnote_base()
+            .saturating_add(self.footnote_ix);
         let mut ref_suffix = String::new();
         let mut superscript = String::new();
 
@@ -1160,12 +2308,44 @@ impl<'o> HtmlFormatter<'o> {
                 write!(self.output, " ")?;
             }
 
-            self.output.write_all(b"<a href=\"#fnref-")?;
-            self.escape_href(nfd.name.as_bytes())?;
+            self.output.write_all(b"<a")?;
+            self.write_attributes(vec![
+                (
+                    "href",
+                    Some(escaped_href(
+                        format!(
+                            "#{}{}{}",
+                            self.footnote_ref_id_prefix(),
+                            nfd.name,
+                            ref_suffix
+                        )
+                        .as_bytes(),
+                    )),
+                ),
+                ("class", Some("footnote-backref".to_string())),
+                ("data-footnote-backref", None),
+                (
+                    "data-footnote-backref-idx",
+                    Some(format!("{}{}", footnote_number, ref_suffix)),
+                ),
+                (
+                    "aria-label",
+                    Some(escaped(
+                        self.options
+                            .render
+                            .footnote_backref_label
+                            .as_deref()
+                            .unwrap_or("Back to reference {}")
+                            .replace("{}", &format!("{}{}", footnote_number, ref_suffix))
+                            .as_bytes(),
+                    )),
+                ),
+            ])?;
             write!(
                 self.output,
-                "{}\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"{}{}\" aria-label=\"Back to reference {}{}\">↩{}</a>",
-                ref_suffix, self.footnote_ix, ref_suffix, self.footnote_ix, ref_suffix, superscript
+                ">{}{}</a>",
+                self.footnote_backref_symbol(),
+                superscript
             )?;
         }
         Ok(true)
@@ -1182,7 +2362,12 @@ impl<'o> HtmlFormatter<'o> {
     ) -> io::Result<()> {
         let mut tag_attributes: Vec<(String, String)> = Vec::new();
         let style_attr = if display_math { "display" } else { "inline" };
-        let tag: &str = if dollar_math { "span" } else { "code" };
+        let mathjax = self.options.render.math_mathjax_delimiters;
+        let tag: &str = if dollar_math || mathjax {
+            "span"
+        } else {
+            "code"
+        };
 
         tag_attributes.push((String::from("data-math-style"), String::from(style_attr)));
 
@@ -1191,18 +2376,32 @@ impl<'o> HtmlFormatter<'o> {
             tag_attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
         }
 
+        self.sort_attributes(&mut tag_attributes);
         write_opening_tag(self.output, tag, tag_attributes)?;
-        self.escape(literal.as_bytes())?;
+        if mathjax {
+            self.render_mathjax(literal, display_math)?;
+        } else {
+            self.escape(literal.as_bytes())?;
+        }
         write!(self.output, "</{}>", tag)?;
 
         Ok(())
     }
 
+    // Writes TeX between the delimiters MathJax looks for by default.
+    fn render_mathjax(&mut self, literal: &str, display_math: bool) -> io::Result<()> {
+        self.output
+            .write_all(if display_math { b"\\[" } else { b"\\(" })?;
+        self.escape(literal.as_bytes())?;
+        self.output
+            .write_all(if display_math { b"\\]" } else { b"\\)" })
+    }
+
     // Renders a math code block, ```` ```math ```` using `<pre><code>`
     fn render_math_code_block<'a>(
         &mut self,
         node: &'a AstNode<'a>,
-        literal: &String,
+        literal: &str,
     ) -> io::Result<()> {
         self.cr()?;
 
@@ -1212,6 +2411,16 @@ impl<'o> HtmlFormatter<'o> {
         let mut code_attributes: Vec<(String, String)> = Vec::new();
         let lang_str = "math";
 
+        if self.options.render.math_mathjax_delimiters {
+            let mut attributes = vec![("data-math-style", Some("display".to_string()))];
+            self.sourcepos_attribute(node, &mut attributes);
+            self.output.write_all(b"<span")?;
+            self.write_attributes(attributes)?;
+            self.output.write_all(b">")?;
+            self.render_mathjax(literal, true)?;
+            return self.output.write_all(b"</span>\n");
+        }
+
         if self.options.render.github_pre_lang {
             pre_attributes.push((String::from("lang"), lang_str.to_string()));
             pre_attributes.push((String::from("data-math-style"), String::from("display")));
@@ -1225,7 +2434,12 @@ impl<'o> HtmlFormatter<'o> {
             let ast = node.data.borrow();
             pre_attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
         }
+        if let Some(line) = self.data_line(node) {
+            pre_attributes.push(("data-line".to_string(), line.to_string()));
+        }
 
+        self.sort_attributes(&mut pre_attributes);
+        self.sort_attributes(&mut code_attributes);
         write_opening_tag(self.output, "pre", pre_attributes)?;
         write_opening_tag(self.output, "code", code_attributes)?;
 
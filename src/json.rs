@@ -0,0 +1,153 @@
+use crate::arena_tree::{Node, NodeEdge};
+use crate::nodes::{Ast, AstNode, LineColumn, NodeValue, Sourcepos};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fmt::{self, Formatter};
+use typed_arena::Arena;
+
+/// A node of an AST, owned rather than allocated in an arena, as serialized
+/// by [`to_json`].
+///
+/// A tree is kept as a list of nodes in document order, each naming its
+/// parent by index, so that however deeply the tree nests, neither the JSON
+/// nor the code handling it does.  Only each node's value and source
+/// position are kept; the parser's working state is not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonNode {
+    /// The index of the node's parent in the list, which must come before
+    /// it; only the root, first in the list, has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+
+    /// The node's value.
+    pub value: NodeValue,
+
+    /// Where in the input the node came from.
+    pub sourcepos: Sourcepos,
+}
+
+impl JsonNode {
+    /// Copies `root` and its descendants out of their arena, in document
+    /// order.
+    pub fn from_ast<'a>(root: &'a AstNode<'a>) -> Vec<Self> {
+        let mut nodes = vec![];
+        let mut open: Vec<usize> = vec![];
+        for edge in root.traverse() {
+            match edge {
+                NodeEdge::Start(node) => {
+                    let ast = node.data.borrow();
+                    open.push(nodes.len());
+                    nodes.push(JsonNode {
+                        parent: open.len().checked_sub(2).map(|ix| open[ix]),
+                        value: ast.value.clone(),
+                        sourcepos: ast.sourcepos,
+                    });
+                }
+                NodeEdge::End(_) => {
+                    open.pop();
+                }
+            }
+        }
+        nodes
+    }
+
+    /// Allocates the tree described by `nodes` in `arena`, returning its
+    /// root.
+    ///
+    /// Fails with [`JsonError::Tree`] unless `nodes` is a root followed by
+    /// its descendants, as given by [`from_ast`](JsonNode::from_ast).
+    pub fn to_ast<'a>(
+        nodes: &[JsonNode],
+        arena: &'a Arena<AstNode<'a>>,
+    ) -> Result<&'a AstNode<'a>, JsonError> {
+        let mut allocated: Vec<&'a AstNode<'a>> = Vec::with_capacity(nodes.len());
+        for (ix, json) in nodes.iter().enumerate() {
+            let parent = match (ix, json.parent) {
+                (0, None) => None,
+                (_, Some(parent)) if parent < ix => Some(allocated[parent]),
+                _ => return Err(JsonError::Tree(ix)),
+            };
+            let mut ast = Ast::new(json.value.clone(), json.sourcepos.start);
+            ast.sourcepos = json.sourcepos;
+            ast.open = false;
+            let node = arena.alloc(Node::new(RefCell::new(ast)));
+            if let Some(parent) = parent {
+                parent.append(node);
+            }
+            allocated.push(node);
+        }
+        allocated.first().copied().ok_or(JsonError::Tree(0))
+    }
+}
+
+/// Serializes an AST to JSON.
+///
+/// The tree is written as an array of nodes in document order, each an
+/// object with its `value`, `sourcepos`, and the index of its `parent` but
+/// for the root, such that [`from_json`] gives back an equivalent tree.
+///
+/// ```
+/// # use comrak::{format_html, from_json, parse_document, to_json, Arena, Options};
+/// let options = Options::default();
+/// let arena = Arena::new();
+/// let root = parse_document(&arena, "``` rust\nfn main() {}\n```\n\n3. three\n", &options);
+/// let json = to_json(root);
+///
+/// // Later, or in another process:
+/// let arena = Arena::new();
+/// let root = from_json(&arena, &json).unwrap();
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n\
+///      <ol start=\"3\">\n<li>three</li>\n</ol>\n"
+/// );
+/// ```
+pub fn to_json<'a>(root: &'a AstNode<'a>) -> String {
+    serde_json::to_string(&JsonNode::from_ast(root)).unwrap()
+}
+
+/// Deserializes an AST written by [`to_json`], allocating it in `arena`.
+///
+/// Fails if `json` is malformed or doesn't describe a tree of nodes.
+pub fn from_json<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    json: &str,
+) -> Result<&'a AstNode<'a>, JsonError> {
+    let nodes: Vec<JsonNode> = serde_json::from_str(json).map_err(|err| {
+        JsonError::Syntax(
+            LineColumn {
+                line: err.line(),
+                column: err.column(),
+            },
+            err.to_string(),
+        )
+    })?;
+    JsonNode::to_ast(&nodes, arena)
+}
+
+/// An error returned by [`from_json`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonError {
+    /// The input isn't JSON, or doesn't describe a list of nodes, as found at
+    /// the given position and described by the message.
+    Syntax(LineColumn, String),
+
+    /// The node at the given index doesn't fit in a tree: it isn't the root
+    /// but names no earlier node as its parent, or it's the root but has a
+    /// parent.  An empty list gives index 0.
+    Tree(usize),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Syntax(_, message) => write!(f, "{}", message),
+            JsonError::Tree(ix) => write!(f, "node {} is not part of the tree", ix),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
@@ -77,16 +77,20 @@
     clippy::too_many_arguments
 )]
 
-use std::io::BufWriter;
+use std::io::{self, BufWriter, Write};
 
 pub mod adapters;
 pub mod arena_tree;
 mod cm;
 mod ctype;
 mod entity;
+mod gemtext;
 pub mod html;
+#[cfg(feature = "json")]
+mod json;
 pub mod nodes;
 mod parser;
+mod plain;
 pub mod plugins;
 mod scanners;
 mod strings;
@@ -96,14 +100,29 @@ mod xml;
 
 pub use cm::format_document as format_commonmark;
 pub use cm::format_document_with_plugins as format_commonmark_with_plugins;
+pub use cm::wrap_text;
+pub use gemtext::format_document as format_gemtext;
+pub use gemtext::format_document_with_plugins as format_gemtext_with_plugins;
 pub use html::format_document as format_html;
 pub use html::format_document_with_plugins as format_html_with_plugins;
+pub use html::format_fragment as format_html_fragment;
 pub use html::Anchorizer;
+pub use html::{table_of_contents, TocEntry};
+#[cfg(feature = "json")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
+pub use json::{from_json, to_json, JsonError, JsonNode};
 pub use parser::{
-    parse_document, parse_document_with_broken_link_callback, ExtensionOptions,
-    ExtensionOptionsBuilder, ListStyleType, Options, ParseOptions, ParseOptionsBuilder, Plugins,
-    PluginsBuilder, RenderOptions, RenderOptionsBuilder, RenderPlugins, RenderPluginsBuilder,
+    parse_document, parse_document_from_reader, parse_document_with_broken_link_callback,
+    parse_document_with_diagnostics, parse_document_with_refmap,
+    parse_document_with_unresolved_reference_callback, reparse, try_parse_document,
+    BrokenLinkCallback, BrokenLinkReference, BrokenLinkResolution, Diagnostic, DiagnosticKind,
+    ExtensionOptions, ExtensionOptionsBuilder, HeaderIdStyle, InlineDelimiter, InlineToken,
+    LineEnding, ListStyleType, NulPolicy, Options, ParseError, ParseOptions, ParseOptionsBuilder,
+    Plugins, PluginsBuilder, RefMap, ReferenceKind, RenderOptions, RenderOptionsBuilder,
+    RenderPlugins, RenderPluginsBuilder, StreamingParser, TextEdit,
 };
+pub use plain::format_document as format_plain;
+pub use plain::format_document_with_plugins as format_plain_with_plugins;
 pub use typed_arena::Arena;
 pub use xml::format_document as format_xml;
 pub use xml::format_document_with_plugins as format_xml_with_plugins;
@@ -139,6 +158,20 @@ pub fn markdown_to_html_with_plugins(md: &str, options: &Options, plugins: &Plug
     String::from_utf8(bw.into_inner().unwrap()).unwrap()
 }
 
+/// Render Markdown to HTML, writing it directly to `output`.
+///
+/// ```
+/// # use comrak::{markdown_to_writer, Options};
+/// let mut output = vec![];
+/// markdown_to_writer("Hello, **world**!", &Options::default(), &mut output).unwrap();
+/// assert_eq!(output, b"<p>Hello, <strong>world</strong>!</p>\n");
+/// ```
+pub fn markdown_to_writer(md: &str, options: &Options, output: &mut dyn Write) -> io::Result<()> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    format_html(root, options, output)
+}
+
 /// Return the version of the crate.
 pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
@@ -153,6 +186,43 @@ pub fn markdown_to_commonmark(md: &str, options: &Options) -> String {
     String::from_utf8(bw.into_inner().unwrap()).unwrap()
 }
 
+/// Render Markdown back to CommonMark, writing it directly to `output`.
+pub fn markdown_to_commonmark_writer(
+    md: &str,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    format_commonmark(root, options, output)
+}
+
+/// Normalize Markdown to a canonical CommonMark form.
+///
+/// The document is parsed with the extension and parse options given, then
+/// formatted with a fixed set of render options: `*` for emphasis, `-` for
+/// bullets, no wrapping, and single blank lines between blocks.  Reference
+/// links are written inline, and their definitions dropped.  Canonicalizing
+/// already canonical output gives back the same string, so this can be used
+/// to compare documents which differ only in how they were written.
+///
+/// ```
+/// # use comrak::{canonicalize, Options};
+/// let options = Options::default();
+/// let md = "Hello _world_.\n\n\n\n+ [one][]\n+ two\n\n[one]: /url\n";
+/// let canonical = canonicalize(md, &options);
+/// assert_eq!(canonical, "Hello *world*.\n\n- [one](/url)\n- two\n");
+/// assert_eq!(canonicalize(&canonical, &options), canonical);
+/// ```
+pub fn canonicalize(md: &str, options: &Options) -> String {
+    let options = Options {
+        extension: options.extension.clone(),
+        parse: options.parse.clone(),
+        render: RenderOptions::default(),
+    };
+    markdown_to_commonmark(md, &options)
+}
+
 /// Render Markdown to CommonMark XML.
 /// See https://github.com/commonmark/commonmark-spec/blob/master/CommonMark.dtd.
 pub fn markdown_to_commonmark_xml(md: &str, options: &Options) -> String {
@@ -172,3 +242,35 @@ pub fn markdown_to_commonmark_xml_with_plugins(
     format_xml_with_plugins(root, options, &mut bw, plugins).unwrap();
     String::from_utf8(bw.into_inner().unwrap()).unwrap()
 }
+
+/// Render Markdown to CommonMark XML, writing it directly to `output`.
+/// See https://github.com/commonmark/commonmark-spec/blob/master/CommonMark.dtd.
+pub fn markdown_to_commonmark_xml_writer(
+    md: &str,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    format_xml(root, options, output)
+}
+
+/// Render Markdown to Gemtext.
+/// See [`format_gemtext`] for how each node is written.
+pub fn markdown_to_gemtext(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_gemtext(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
+
+/// Render Markdown to plain text.
+/// See [`format_plain`] for how each node is written.
+pub fn markdown_to_plain(md: &str, options: &Options) -> String {
+    let arena = Arena::new();
+    let root = parse_document(&arena, md, options);
+    let mut bw = BufWriter::new(Vec::new());
+    format_plain(root, options, &mut bw).unwrap();
+    String::from_utf8(bw.into_inner().unwrap()).unwrap()
+}
@@ -2,8 +2,8 @@
 
 use comrak::{
     adapters::SyntaxHighlighterAdapter, plugins::syntect::SyntectAdapter, Arena,
-    ExtensionOptionsBuilder, ListStyleType, Options, ParseOptionsBuilder, Plugins,
-    RenderOptionsBuilder,
+    ExtensionOptionsBuilder, HeaderIdStyle, LineEnding, ListStyleType, NulPolicy, Options,
+    ParseOptionsBuilder, Plugins, RenderOptionsBuilder,
 };
 use std::boxed::Box;
 use std::env;
@@ -44,6 +44,10 @@ struct Cli {
     #[arg(long)]
     hardbreaks: bool,
 
+    /// Render soft line breaks as spaces
+    #[arg(long)]
+    collapse_soft_breaks: bool,
+
     /// Use smart punctuation
     #[arg(long)]
     smart: bool,
@@ -56,6 +60,10 @@ struct Cli {
     #[arg(long)]
     full_info_string: bool,
 
+    /// Render a title="..." token in code block info strings as a caption
+    #[arg(long)]
+    code_block_title_from_info: bool,
+
     /// Enable GitHub-flavored markdown extensions: strikethrough, tagfilter, table, autolink, and tasklist.
     /// Also enables --github-pre-lang.
     #[arg(long)]
@@ -70,6 +78,62 @@ struct Cli {
     #[arg(long)]
     relaxed_autolinks: bool,
 
+    /// Top-level domains for which bare domains are autolinked
+    ///
+    /// Multiple TLDs can be delimited with ",", e.g. --autolink-extra-tlds internal,corp
+    #[arg(long, value_name = "TLD", value_delimiter = ',')]
+    autolink_extra_tlds: Option<Vec<String>>,
+
+    /// URL schemes autolinked besides http, https and ftp
+    ///
+    /// Multiple schemes can be delimited with ",", e.g. --autolink-extra-schemes tel,matrix
+    #[arg(long, value_name = "SCHEME", value_delimiter = ',')]
+    autolink_extra_schemes: Option<Vec<String>>,
+
+    /// Only autolink www. domains and email addresses with these top-level domains
+    #[arg(long, value_name = "TLD", value_delimiter = ',')]
+    autolink_allowed_tlds: Option<Vec<String>>,
+
+    /// Leave link reference definitions as literal text
+    #[arg(long)]
+    no_reference_definitions: bool,
+
+    /// Allow block quotes with no space after '>'
+    #[arg(long)]
+    blockquote_no_space_required: bool,
+
+    /// Continue block quotes across blank and '>'-only lines
+    #[arg(long)]
+    lazy_blockquote_continuation: bool,
+
+    /// Skip byte order marks at the start of each input file
+    #[arg(long)]
+    strip_bom: bool,
+
+    /// Let emphasis open and close next to CJK punctuation
+    #[arg(long)]
+    cjk_friendly_emphasis: bool,
+
+    /// Keep a hard line break at the end of a list item
+    #[arg(long)]
+    list_item_trailing_hard_break: bool,
+
+    /// Don't use smart punctuation in link text and image alt text
+    #[arg(long)]
+    smart_skip_links: bool,
+
+    /// Parse paired smart double quotes as quotations, written as <q> elements
+    #[arg(long)]
+    quote_elements: bool,
+
+    /// How to handle NUL bytes in the input
+    #[arg(long, value_enum, default_value_t = NulHandling::Replace)]
+    nul_handling: NulHandling,
+
+    /// Depth at which further list and block quote markers on a line are left as text
+    #[arg(long, value_name = "N")]
+    max_nesting_depth: Option<usize>,
+
     /// Default value for fenced code block's info strings if none is given
     #[arg(long, value_name = "INFO")]
     default_info_string: Option<String>,
@@ -91,6 +155,126 @@ struct Cli {
     #[arg(long)]
     escaped_char_spans: bool,
 
+    /// Inline HTML tag(s) to pass through when --unsafe is not given
+    ///
+    /// Multiple tags can be delimited with ",", e.g. --allowed-inline-html-tags sub,sup
+    #[arg(long, value_name = "TAG", value_delimiter = ',')]
+    allowed_inline_html_tags: Vec<String>,
+
+    /// Insert <wbr> after every N characters of long words
+    #[arg(long, value_name = "N")]
+    insert_wbr_in_long_words: Option<usize>,
+
+    /// Write HTML attributes in alphabetical order
+    #[arg(long)]
+    sorted_attributes: bool,
+
+    /// Add ARIA roles to tables and lists
+    #[arg(long)]
+    aria_roles: bool,
+
+    /// Write non-ASCII characters as numeric character references
+    #[arg(long)]
+    ascii_only: bool,
+
+    /// Add the starting source line to block elements as data-line
+    #[arg(long)]
+    data_line_start: bool,
+
+    /// Keep ordered list item numbers as written
+    #[arg(long)]
+    preserve_ordered_numbers: bool,
+
+    /// Render math between MathJax's default delimiters
+    #[arg(long)]
+    math_mathjax_delimiters: bool,
+
+    /// Remove trailing whitespace from lines of code blocks
+    #[arg(long)]
+    trim_code_trailing_whitespace: bool,
+
+    /// Write email autolinks as character references
+    #[arg(long)]
+    obfuscate_email: bool,
+
+    /// Use the code block info string as written for the language class
+    #[arg(long)]
+    raw_info_string_class: bool,
+
+    /// Wrap description list details in paragraphs even in tight lists
+    #[arg(long)]
+    description_list_loose: bool,
+
+    /// Class to put on thematic breaks
+    #[arg(long, value_name = "CLASS")]
+    thematic_break_class: Option<String>,
+
+    /// Write a plain backreference for footnotes referenced once
+    #[arg(long)]
+    footnote_single_backref_simple: bool,
+
+    /// Pad ordered list markers to the widest in the list in CommonMark output
+    #[arg(long)]
+    ordered_list_pad_markers: bool,
+
+    /// Write two spaces after bullet list markers in CommonMark output
+    #[arg(long)]
+    bullet_list_double_space: bool,
+
+    /// Write adjacent footnote references in a single <sup>
+    #[arg(long)]
+    group_footnote_refs: bool,
+
+    /// Prefix of footnote ids (default "fn-")
+    #[arg(long, value_name = "PREFIX")]
+    footnote_id_prefix: Option<String>,
+
+    /// Prefix of footnote reference ids (default "fnref-")
+    #[arg(long, value_name = "PREFIX")]
+    footnote_ref_id_prefix: Option<String>,
+
+    /// HTML of footnote backreference links (default "↩")
+    #[arg(long, value_name = "HTML")]
+    footnote_backref_symbol: Option<String>,
+
+    /// Label of footnote backreference links, "{}" standing for the number
+    #[arg(long, value_name = "TEMPLATE")]
+    footnote_backref_label: Option<String>,
+
+    /// Label of simple footnote backreference links (default "Back to content")
+    #[arg(long, value_name = "LABEL")]
+    footnote_backref_simple_label: Option<String>,
+
+    /// Give code block languages in a data-language attribute
+    #[arg(long)]
+    code_lang_as_data_attr: bool,
+
+    /// Leave out the language- class when using --code-lang-as-data-attr
+    #[arg(long)]
+    code_lang_data_attr_only: bool,
+
+    /// Give block elements ids made from PREFIX, their tag and position
+    #[arg(long, value_name = "PREFIX")]
+    block_ids: Option<String>,
+
+    /// Follow link text with the URL in parentheses in plain text output
+    #[arg(long)]
+    plain_link_urls: bool,
+
+    /// Demote headings in HTML output by this many levels
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    heading_offset: u8,
+
+    /// Open links to other hosts in a new tab
+    #[arg(long)]
+    external_links_new_tab: bool,
+
+    /// Hosts whose links aren't opened in a new tab
+    ///
+    /// Multiple hosts can be delimited with ",", e.g. --internal-link-hosts example.com,example.org
+    #[arg(long, value_name = "HOST", value_delimiter = ',')]
+    internal_link_hosts: Option<Vec<String>>,
+
     /// Specify extension name(s) to use
     ///
     /// Multiple extensions can be delimited with ",", e.g. --extension strikethrough,table
@@ -119,6 +303,30 @@ struct Cli {
     #[arg(long, value_name = "PREFIX")]
     header_ids: Option<String>,
 
+    /// Where to place the header IDs
+    #[arg(long, value_enum, default_value_t = HeaderIdStyleArg::AnchorBefore)]
+    header_id_style: HeaderIdStyleArg,
+
+    /// HTML to put inside heading anchors
+    #[arg(long, value_name = "HTML")]
+    header_anchor_content: Option<String>,
+
+    /// Number given to the first footnote (default 1)
+    #[arg(long, value_name = "N")]
+    footnote_start_number: Option<u32>,
+
+    /// Character introducing description list details (default ':')
+    #[arg(long, value_name = "CHAR")]
+    description_list_marker: Option<char>,
+
+    /// Replace whitespace in wikilink targets with CHAR when rendering HTML
+    #[arg(long, value_name = "CHAR")]
+    wikilinks_slug_separator: Option<char>,
+
+    /// Read each line before description list details as a separate term
+    #[arg(long)]
+    description_list_grouped_terms: bool,
+
     /// Ignore front-matter that starts and ends with the given string
     #[arg(long, value_name = "DELIMITER", allow_hyphen_values = true)]
     front_matter_delimiter: Option<String>,
@@ -131,6 +339,10 @@ struct Cli {
     #[arg(long, value_enum, default_value_t = ListStyle::Dash)]
     list_style: ListStyle,
 
+    /// Line ending to write between lines of output
+    #[arg(long, value_enum, default_value_t = Eol::Lf)]
+    line_ending: Eol,
+
     /// Include source position attribute in HTML and XML output
     #[arg(long)]
     sourcepos: bool,
@@ -144,6 +356,10 @@ enum Format {
 
     #[value(name = "commonmark")]
     CommonMark,
+
+    Gemtext,
+
+    Plain,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
@@ -151,17 +367,30 @@ enum Extension {
     Strikethrough,
     Tagfilter,
     Table,
+    TablesWithoutHeader,
+    TableCaptions,
+    TableCellLineBreaks,
     Autolink,
     Tasklist,
     Superscript,
+    Highlight,
     Footnotes,
+    FootnotesRenderUnreferenced,
     DescriptionLists,
+    AlphaLists,
+    ReversedLists,
     MultilineBlockQuotes,
+    Alerts,
+    Details,
+    FencedDivs,
+    Abbreviations,
     MathDollars,
     MathCode,
     WikilinksTitleAfterPipe,
     WikilinksTitleBeforePipe,
     Philomena,
+    ImageSrcset,
+    InlineCodeLang,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -171,6 +400,21 @@ enum ListStyle {
     Star,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Eol {
+    Lf,
+    Crlf,
+}
+
+impl From<Eol> for LineEnding {
+    fn from(eol: Eol) -> Self {
+        match eol {
+            Eol::Lf => Self::Lf,
+            Eol::Crlf => Self::CrLf,
+        }
+    }
+}
+
 impl From<ListStyle> for ListStyleType {
     fn from(style: ListStyle) -> Self {
         match style {
@@ -181,6 +425,42 @@ impl From<ListStyle> for ListStyleType {
     }
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum NulHandling {
+    Replace,
+    Strip,
+    Error,
+}
+
+impl From<NulHandling> for NulPolicy {
+    fn from(handling: NulHandling) -> Self {
+        match handling {
+            NulHandling::Replace => Self::Replace,
+            NulHandling::Strip => Self::Strip,
+            NulHandling::Error => Self::Error,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum HeaderIdStyleArg {
+    AnchorBefore,
+    AnchorAfter,
+    OnHeading,
+    None,
+}
+
+impl From<HeaderIdStyleArg> for HeaderIdStyle {
+    fn from(style: HeaderIdStyleArg) -> Self {
+        match style {
+            HeaderIdStyleArg::AnchorBefore => Self::AnchorBefore,
+            HeaderIdStyleArg::AnchorAfter => Self::AnchorAfter,
+            HeaderIdStyleArg::OnHeading => Self::OnHeading,
+            HeaderIdStyleArg::None => Self::None,
+        }
+    }
+}
+
 fn cli_with_config() -> Cli {
     let cli = Cli::parse();
     let config_file_path = &cli.config_file;
@@ -232,22 +512,43 @@ fn main() -> Result<(), Box<dyn Error>> {
         .strikethrough(exts.contains(&Extension::Strikethrough) || cli.gfm)
         .tagfilter(exts.contains(&Extension::Tagfilter) || cli.gfm)
         .table(exts.contains(&Extension::Table) || cli.gfm)
+        .tables_without_header(exts.contains(&Extension::TablesWithoutHeader))
+        .table_captions(exts.contains(&Extension::TableCaptions))
+        .table_cell_line_breaks(exts.contains(&Extension::TableCellLineBreaks))
         .autolink(exts.contains(&Extension::Autolink) || cli.gfm)
         .tasklist(exts.contains(&Extension::Tasklist) || cli.gfm)
         .superscript(exts.contains(&Extension::Superscript))
+        .highlight(exts.contains(&Extension::Highlight))
         .header_ids(cli.header_ids)
+        .header_id_style(cli.header_id_style.into())
+        .header_anchor_content(cli.header_anchor_content)
+        .header_id_slugifier(None)
         .footnotes(exts.contains(&Extension::Footnotes))
+        .footnotes_render_unreferenced(exts.contains(&Extension::FootnotesRenderUnreferenced))
+        .footnote_start_number(cli.footnote_start_number)
         .description_lists(exts.contains(&Extension::DescriptionLists))
+        .description_list_marker(cli.description_list_marker)
+        .description_list_grouped_terms(cli.description_list_grouped_terms)
+        .alpha_lists(exts.contains(&Extension::AlphaLists))
+        .reversed_lists(exts.contains(&Extension::ReversedLists))
         .philomena(exts.contains(&Extension::Philomena))
         .philomena_replacements(None)
         .philomena_domains(None)
         .multiline_block_quotes(exts.contains(&Extension::MultilineBlockQuotes))
+        .alerts(exts.contains(&Extension::Alerts))
+        .details(exts.contains(&Extension::Details))
+        .fenced_divs(exts.contains(&Extension::FencedDivs))
+        .abbreviations(exts.contains(&Extension::Abbreviations))
         .math_dollars(exts.contains(&Extension::MathDollars))
         .math_code(exts.contains(&Extension::MathCode))
         .wikilinks_title_after_pipe(exts.contains(&Extension::WikilinksTitleAfterPipe))
         .wikilinks_title_before_pipe(exts.contains(&Extension::WikilinksTitleBeforePipe))
+        .wikilinks_slug_separator(cli.wikilinks_slug_separator)
+        .wikilinks_url_resolver(None)
         .front_matter_delimiter(cli.front_matter_delimiter)
-        .camoifier(None);
+        .camoifier(None)
+        .image_srcset(exts.contains(&Extension::ImageSrcset))
+        .inline_code_lang(exts.contains(&Extension::InlineCodeLang));
 
     #[cfg(feature = "shortcodes")]
     {
@@ -261,18 +562,63 @@ fn main() -> Result<(), Box<dyn Error>> {
         .default_info_string(cli.default_info_string)
         .relaxed_tasklist_matching(cli.relaxed_tasklist_character)
         .relaxed_autolinks(cli.relaxed_autolinks)
+        .autolink_extra_tlds(cli.autolink_extra_tlds)
+        .autolink_extra_schemes(cli.autolink_extra_schemes)
+        .autolink_allowed_tlds(cli.autolink_allowed_tlds)
+        .no_reference_definitions(cli.no_reference_definitions)
+        .blockquote_no_space_required(cli.blockquote_no_space_required)
+        .lazy_blockquote_continuation(cli.lazy_blockquote_continuation)
+        .strip_bom(cli.strip_bom)
+        .cjk_friendly_emphasis(cli.cjk_friendly_emphasis)
+        .list_item_trailing_hard_break(cli.list_item_trailing_hard_break)
+        .smart_skip_links(cli.smart_skip_links)
+        .quote_elements(cli.quote_elements)
+        .nul_handling(cli.nul_handling.into())
+        .max_nesting_depth(cli.max_nesting_depth)
         .build()?;
 
     let render = RenderOptionsBuilder::default()
         .hardbreaks(cli.hardbreaks)
+        .collapse_soft_breaks(cli.collapse_soft_breaks)
         .github_pre_lang(cli.github_pre_lang || cli.gfm)
         .full_info_string(cli.full_info_string)
+        .code_block_title_from_info(cli.code_block_title_from_info)
         .width(cli.width)
         .unsafe_(cli.unsafe_)
         .escape(cli.escape)
         .list_style(cli.list_style.into())
+        .line_ending(cli.line_ending.into())
         .sourcepos(cli.sourcepos)
         .escaped_char_spans(cli.escaped_char_spans)
+        .allowed_inline_html_tags(cli.allowed_inline_html_tags)
+        .insert_wbr_in_long_words(cli.insert_wbr_in_long_words)
+        .sorted_attributes(cli.sorted_attributes)
+        .aria_roles(cli.aria_roles)
+        .ascii_only(cli.ascii_only)
+        .data_line_start(cli.data_line_start)
+        .preserve_ordered_numbers(cli.preserve_ordered_numbers)
+        .math_mathjax_delimiters(cli.math_mathjax_delimiters)
+        .trim_code_trailing_whitespace(cli.trim_code_trailing_whitespace)
+        .obfuscate_email(cli.obfuscate_email)
+        .raw_info_string_class(cli.raw_info_string_class)
+        .description_list_loose(cli.description_list_loose)
+        .thematic_break_class(cli.thematic_break_class)
+        .footnote_single_backref_simple(cli.footnote_single_backref_simple)
+        .ordered_list_pad_markers(cli.ordered_list_pad_markers)
+        .bullet_list_double_space(cli.bullet_list_double_space)
+        .group_footnote_refs(cli.group_footnote_refs)
+        .footnote_id_prefix(cli.footnote_id_prefix)
+        .footnote_ref_id_prefix(cli.footnote_ref_id_prefix)
+        .footnote_backref_symbol(cli.footnote_backref_symbol)
+        .footnote_backref_label(cli.footnote_backref_label)
+        .footnote_backref_simple_label(cli.footnote_backref_simple_label)
+        .code_lang_as_data_attr(cli.code_lang_as_data_attr)
+        .code_lang_data_attr_only(cli.code_lang_data_attr_only)
+        .block_ids(cli.block_ids)
+        .plain_link_urls(cli.plain_link_urls)
+        .heading_offset(cli.heading_offset)
+        .external_links_new_tab(cli.external_links_new_tab)
+        .internal_link_hosts(cli.internal_link_hosts)
         .build()?;
 
     let options = Options {
@@ -303,7 +649,12 @@ fn main() -> Result<(), Box<dyn Error>> {
             for f in fs {
                 match fs::File::open(f) {
                     Ok(mut io) => {
+                        let start = s.len();
                         io.read_to_end(&mut s)?;
+                        let bom = "\u{feff}".as_bytes();
+                        if cli.strip_bom && start > 0 && s[start..].starts_with(bom) {
+                            s.drain(start..start + bom.len());
+                        }
                     }
                     Err(e) => {
                         eprintln!("failed to read {}: {}", f.display(), e);
@@ -315,7 +666,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     };
 
     let arena = Arena::new();
-    let root = comrak::parse_document(&arena, &String::from_utf8(s)?, &options);
+    let root = comrak::try_parse_document(&arena, &String::from_utf8(s)?, &options)?;
 
     let formatter = if cli.inplace {
         comrak::format_commonmark_with_plugins
@@ -327,6 +678,8 @@ fn main() -> Result<(), Box<dyn Error>> {
             }
             Format::Xml => comrak::format_xml_with_plugins,
             Format::CommonMark => comrak::format_commonmark_with_plugins,
+            Format::Gemtext => comrak::format_gemtext_with_plugins,
+            Format::Plain => comrak::format_plain_with_plugins,
         }
     };
 
@@ -1,22 +1,30 @@
 //! The CommonMark AST.
 
-use crate::arena_tree::Node;
+use crate::arena_tree::{Node, NodeEdge};
+use crate::ctype::isspace;
 use std::cell::RefCell;
 use std::convert::TryFrom;
+use std::fmt::Write;
 
 #[cfg(feature = "shortcodes")]
 pub use crate::parser::shortcodes::NodeShortCode;
 
+pub use crate::parser::abbreviation::NodeAbbreviation;
+pub use crate::parser::details::NodeDetails;
+pub use crate::parser::fenced_div::NodeFencedDiv;
 pub use crate::parser::math::NodeMath;
 pub use crate::parser::multiline_block_quote::NodeMultilineBlockQuote;
 
 /// The core AST node enum.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub enum NodeValue {
     /// The root of every CommonMark document.  Contains **blocks**.
     Document,
 
-    /// Non-Markdown front matter.  Treated as an opaque blob.
+    /// Non-Markdown front matter.  Treated as an opaque blob, which includes
+    /// both delimiter lines and any blank line following; see [`front_matter`]
+    /// for just what's between the delimiters.
     FrontMatter(String),
 
     /// **Block**. A [block quote](https://github.github.com/gfm/#block-quotes).  Contains other
@@ -104,6 +112,11 @@ pub enum NodeValue {
     /// **Block**.  A table cell.  Contains **inlines**.
     TableCell,
 
+    /// **Block**.  A table caption, taken from a `Table:` paragraph adjacent to the table when
+    /// the `table_captions` extension is enabled.  Always the table's first child.  Contains
+    /// **inlines**.
+    TableCaption,
+
     /// **Inline**.  [Textual content](https://github.github.com/gfm/#textual-content).  All text
     /// in a document will be contained in a `Text` node.
     Text(String),
@@ -147,6 +160,17 @@ pub enum NodeValue {
     /// **Inline**.  Underline. Enabled with `ext_philomena` option.
     Underline,
 
+    /// **Inline**.  Highlighted text, delimited by `==`.  Enabled with `ext_highlight` option.
+    Highlight,
+
+    /// **Inline**.  An abbreviation, marked up with its expansion.  Enabled with
+    /// `ext_abbreviations` option.  Contains the abbreviation as text.
+    Abbreviation(NodeAbbreviation),
+
+    /// **Inline**.  A span enclosed in a matched pair of double quotes.  Only produced
+    /// with both the `smart` and `quote_elements` options.
+    Quoted,
+
     /// **Inline**.  A [link](https://github.github.com/gfm/#links) to some URL, with possible
     /// title.
     Link(NodeLink),
@@ -193,6 +217,35 @@ pub enum NodeValue {
     /// ```
     MultilineBlockQuote(NodeMultilineBlockQuote),
 
+    /// **Block**. A block quote marked as an alert, enabled with the `alerts` extension.  Contains
+    /// other **blocks**.
+    ///
+    /// ``` md
+    /// > [!WARNING]
+    /// > Mind the gap.
+    /// ```
+    Alert(NodeAlert),
+
+    /// **Block**. A collapsible block, enabled with the `details` extension.  Contains other
+    /// **blocks**.
+    ///
+    /// ``` md
+    /// ::: details Spoilers
+    /// The butler did it.
+    /// :::
+    /// ```
+    Details(NodeDetails),
+
+    /// **Block**. A generic container, enabled with the `fenced_divs` extension.  Contains other
+    /// **blocks**.
+    ///
+    /// ``` md
+    /// ::: warning
+    /// Wet paint.
+    /// :::
+    /// ```
+    FencedDiv(NodeFencedDiv),
+
     /// **Inline**.  A character that has been [escaped](https://github.github.com/gfm/#backslash-escapes)
     Escaped,
 
@@ -206,6 +259,7 @@ pub enum NodeValue {
 
 /// Alignment of a single table cell.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub enum TableAlignment {
     /// Cell content is unaligned.
     None,
@@ -233,6 +287,7 @@ impl TableAlignment {
 
 /// The metadata of a table
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeTable {
     /// The table alignments
     pub alignments: Vec<TableAlignment>,
@@ -249,6 +304,7 @@ pub struct NodeTable {
 
 /// An inline [code span](https://github.github.com/gfm/#code-spans).
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeCode {
     /// The number of backticks
     pub num_backticks: usize,
@@ -258,10 +314,15 @@ pub struct NodeCode {
     /// they are contained within this structure,
     /// rather than inserted into a child inline of any kind.
     pub literal: String,
+
+    /// The language given by a `{.lang}` annotation directly after the
+    /// closing backticks, when the `inline_code_lang` extension is enabled.
+    pub lang: Option<String>,
 }
 
 /// The details of a link's destination, or an image's source.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeLink {
     /// The URL for the link destination or image source.
     pub url: String,
@@ -271,10 +332,51 @@ pub struct NodeLink {
     /// Note this field is used for the `title` attribute by the HTML formatter even for images;
     /// `alt` text is supplied in the image inline text.
     pub title: String,
+
+    /// Set when the link is an autolink, either an `<https://...>` style
+    /// [autolink](https://github.github.com/gfm/#autolinks) or one recognised by the
+    /// autolink extension, and `None` for all other links and images.
+    pub autolink: Option<AutolinkType>,
+
+    /// The candidate sources of an image written with a `srcset`-style destination,
+    /// such as `![alt](small.jpg 1x, large.jpg 2x)`, when the `image_srcset` extension is
+    /// enabled.  `url` is then the first candidate's URL.  Empty for all other links and
+    /// images.
+    pub srcset: Vec<NodeImageCandidate>,
+
+    /// Extra HTML attributes for the link or image, in order, as given by a
+    /// [`BrokenLinkCallback`](crate::BrokenLinkCallback).  Values are
+    /// unescaped.  The HTML formatter drops attributes whose names aren't
+    /// valid, and event handlers such as `onclick` unless `render.unsafe_`
+    /// is set.  Empty for all other links and images.
+    pub attrs: Vec<(String, String)>,
+}
+
+/// One candidate source of an image's `srcset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeImageCandidate {
+    /// The URL of the candidate image.
+    pub url: String,
+
+    /// The candidate's width (`640w`) or pixel density (`1.5x`) descriptor.
+    pub descriptor: String,
+}
+
+/// The kind of destination an autolink points to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum AutolinkType {
+    /// A URI, e.g. `https://example.com` or `www.example.com`.
+    Uri,
+
+    /// An email address, e.g. `someone@example.com`.
+    Email,
 }
 
 /// The details of a wikilink's destination.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeWikiLink {
     /// The URL for the link destination.
     pub url: String,
@@ -282,6 +384,7 @@ pub struct NodeWikiLink {
 
 /// The metadata of a list; the kind of list, the delimiter used and so on.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeList {
     /// The kind of list (bullet (unordered) or ordered).
     pub list_type: ListType,
@@ -298,26 +401,41 @@ pub struct NodeList {
     /// For ordered lists, the delimiter after each number.
     pub delimiter: ListDelimType,
 
+    /// For ordered lists, how items are numbered.  Anything but decimal
+    /// requires the `alpha_lists` extension.
+    pub numbering: ListNumbering,
+
     /// For bullet lists, the character used for each bullet.
     pub bullet_char: u8,
 
     /// Whether the list is [tight](https://github.github.com/gfm/#tight), i.e. whether the
     /// paragraphs are wrapped in `<p>` tags when formatted as HTML.
     pub tight: bool,
+
+    /// For ordered lists, whether the items count down by one each, as in
+    /// `3.`, `2.`, `1.`.  Only detected with the `reversed_lists` extension.
+    pub reversed: bool,
 }
 
 /// The metadata of a description list
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeDescriptionItem {
     /// Number of spaces before the list marker.
     pub marker_offset: usize,
 
     /// Number of characters between the start of the list marker and the item text (including the list marker(s)).
     pub padding: usize,
+
+    /// Whether the description list this item belongs to is tight.  The blank lines the syntax
+    /// requires around a term don't count; the list is loose when any details contain blank
+    /// lines between their blocks.
+    pub tight: bool,
 }
 
 /// The type of list.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListType {
     /// A bullet list, i.e. an unordered list.
     #[default]
@@ -329,6 +447,7 @@ pub enum ListType {
 
 /// The delimiter for ordered lists, i.e. the character which appears after each number.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub enum ListDelimType {
     /// A period character `.`.
     #[default]
@@ -347,8 +466,124 @@ impl ListDelimType {
     }
 }
 
+/// How the items of an ordered list are numbered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum ListNumbering {
+    /// `1.`, `2.`, `3.`
+    #[default]
+    Decimal,
+
+    /// `a.`, `b.`, `c.`
+    LowerAlpha,
+
+    /// `A.`, `B.`, `C.`
+    UpperAlpha,
+
+    /// `i.`, `ii.`, `iii.`
+    LowerRoman,
+
+    /// `I.`, `II.`, `III.`
+    UpperRoman,
+}
+
+impl ListNumbering {
+    /// The value of the HTML `type` attribute for an `<ol>`, if not
+    /// decimal.
+    pub fn html_type(&self) -> Option<&'static str> {
+        match *self {
+            ListNumbering::Decimal => None,
+            ListNumbering::LowerAlpha => Some("a"),
+            ListNumbering::UpperAlpha => Some("A"),
+            ListNumbering::LowerRoman => Some("i"),
+            ListNumbering::UpperRoman => Some("I"),
+        }
+    }
+
+    /// Formats `n` as the number of a list item, without a delimiter.
+    pub(crate) fn format(&self, n: usize) -> String {
+        match *self {
+            ListNumbering::Decimal => n.to_string(),
+            ListNumbering::LowerAlpha | ListNumbering::UpperAlpha => {
+                let mut s = String::new();
+                let mut n = n;
+                while n > 0 {
+                    n -= 1;
+                    s.insert(0, (b'a' + (n % 26) as u8) as char);
+                    n /= 26;
+                }
+                if *self == ListNumbering::UpperAlpha {
+                    s.make_ascii_uppercase();
+                }
+                s
+            }
+            ListNumbering::LowerRoman | ListNumbering::UpperRoman => {
+                let s = to_roman(n);
+                if *self == ListNumbering::UpperRoman {
+                    s.to_ascii_uppercase()
+                } else {
+                    s
+                }
+            }
+        }
+    }
+}
+
+const ROMAN_NUMERALS: [(usize, &str); 13] = [
+    (1000, "m"),
+    (900, "cm"),
+    (500, "d"),
+    (400, "cd"),
+    (100, "c"),
+    (90, "xc"),
+    (50, "l"),
+    (40, "xl"),
+    (10, "x"),
+    (9, "ix"),
+    (5, "v"),
+    (4, "iv"),
+    (1, "i"),
+];
+
+fn to_roman(mut n: usize) -> String {
+    let mut s = String::new();
+    for &(value, numeral) in ROMAN_NUMERALS.iter() {
+        while n >= value {
+            s.push_str(numeral);
+            n -= value;
+        }
+    }
+    s
+}
+
+/// Parses a lowercase roman numeral, rejecting any not in canonical form.
+pub(crate) fn parse_roman(s: &[u8]) -> Option<usize> {
+    let mut n = 0;
+    let mut rest = s;
+    for &(value, numeral) in ROMAN_NUMERALS.iter() {
+        while rest.starts_with(numeral.as_bytes()) {
+            n += value;
+            rest = &rest[numeral.len()..];
+        }
+    }
+
+    if n == 0 || !rest.is_empty() || to_roman(n).as_bytes() != s {
+        return None;
+    }
+    Some(n)
+}
+
+// Reads letters as `ListNumbering::LowerAlpha::format` writes them: `a` to
+// `z`, then `aa`, `ab` and so on.
+pub(crate) fn parse_alpha(s: &[u8]) -> Option<usize> {
+    s.iter().try_fold(0usize, |n, &c| {
+        n.checked_mul(26)?.checked_add((c - b'a' + 1) as usize)
+    })
+}
+
 /// The metadata and data of a code block (fenced or indented).
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeCodeBlock {
     /// Whether the code block is fenced.
     pub fenced: bool,
@@ -364,8 +599,17 @@ pub struct NodeCodeBlock {
 
     /// For fenced code blocks, the [info string](https://github.github.com/gfm/#info-string) after
     /// the opening fence, if any.
+    ///
+    /// This is not the text as written: entity and numeric character references are decoded
+    /// first, then surrounding whitespace is trimmed, then backslash escapes are removed.  If
+    /// that leaves nothing, [`ParseOptions::default_info_string`](crate::ParseOptions) is used
+    /// instead.  See `raw_info` for the untouched text.
     pub info: String,
 
+    /// For fenced code blocks, everything after the opening fence up to the end of the line,
+    /// exactly as written.
+    pub raw_info: String,
+
     /// The literal contents of the code block.  As the contents are not interpreted as Markdown at
     /// all, they are contained within this structure, rather than inserted into a child inline of
     /// any kind.
@@ -374,6 +618,7 @@ pub struct NodeCodeBlock {
 
 /// The metadata of a heading.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeHeading {
     /// The level of the header; from 1 to 6 for ATX headings, 1 or 2 for setext headings.
     pub level: u8,
@@ -384,6 +629,7 @@ pub struct NodeHeading {
 
 /// The metadata of an included HTML block.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeHtmlBlock {
     /// The HTML block's type
     pub block_type: u8,
@@ -395,6 +641,7 @@ pub struct NodeHtmlBlock {
 
 /// The metadata of a footnote definition.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeFootnoteDefinition {
     /// The name of the footnote.
     pub name: String,
@@ -405,6 +652,7 @@ pub struct NodeFootnoteDefinition {
 
 /// The metadata of a footnote reference.
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeFootnoteReference {
     /// The name of the footnote.
     pub name: String,
@@ -416,6 +664,63 @@ pub struct NodeFootnoteReference {
     pub ix: u32,
 }
 
+/// The kind of an alert, as given by its `[!TYPE]` marker.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlertType {
+    /// `[!NOTE]`
+    #[default]
+    Note,
+
+    /// `[!TIP]`
+    Tip,
+
+    /// `[!IMPORTANT]`
+    Important,
+
+    /// `[!WARNING]`
+    Warning,
+
+    /// `[!CAUTION]`
+    Caution,
+}
+
+impl AlertType {
+    /// The title shown when the alert doesn't give its own.
+    pub fn default_title(&self) -> &'static str {
+        match *self {
+            AlertType::Note => "Note",
+            AlertType::Tip => "Tip",
+            AlertType::Important => "Important",
+            AlertType::Warning => "Warning",
+            AlertType::Caution => "Caution",
+        }
+    }
+
+    /// The lowercase name of the type, as used in CSS classes.
+    pub fn css_class(&self) -> &'static str {
+        match *self {
+            AlertType::Note => "note",
+            AlertType::Tip => "tip",
+            AlertType::Important => "important",
+            AlertType::Warning => "warning",
+            AlertType::Caution => "caution",
+        }
+    }
+}
+
+/// The metadata of an alert.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAlert {
+    /// The kind of alert.
+    pub alert_type: AlertType,
+
+    /// The text following the marker on its line, if any, which replaces the
+    /// type's default title.
+    pub title: Option<String>,
+}
+
 impl NodeValue {
     /// Indicates whether this node is a block node or inline node.
     pub fn block(&self) -> bool {
@@ -438,8 +743,12 @@ impl NodeValue {
                 | NodeValue::Table(..)
                 | NodeValue::TableRow(..)
                 | NodeValue::TableCell
+                | NodeValue::TableCaption
                 | NodeValue::TaskItem(..)
                 | NodeValue::MultilineBlockQuote(_)
+                | NodeValue::Alert(_)
+                | NodeValue::Details(_)
+                | NodeValue::FencedDiv(_)
         )
     }
 
@@ -447,7 +756,10 @@ impl NodeValue {
     pub fn contains_inlines(&self) -> bool {
         matches!(
             *self,
-            NodeValue::Paragraph | NodeValue::Heading(..) | NodeValue::TableCell
+            NodeValue::Paragraph
+                | NodeValue::Heading(..)
+                | NodeValue::TableCell
+                | NodeValue::TableCaption
         )
     }
 
@@ -497,6 +809,7 @@ impl NodeValue {
             NodeValue::Table(..) => "table",
             NodeValue::TableRow(..) => "table_row",
             NodeValue::TableCell => "table_cell",
+            NodeValue::TableCaption => "table_caption",
             NodeValue::Text(..) => "text",
             NodeValue::SoftBreak => "softbreak",
             NodeValue::LineBreak => "linebreak",
@@ -513,12 +826,18 @@ impl NodeValue {
             NodeValue::FootnoteReference(..) => "footnote_reference",
             NodeValue::Subscript => "subscript",
             NodeValue::Underline => "underline",
+            NodeValue::Highlight => "highlight",
+            NodeValue::Abbreviation(_) => "abbreviation",
+            NodeValue::Quoted => "quoted",
             NodeValue::SpoileredText => "spoilered_text",
             NodeValue::ImageMention(_) => "image_mention",
             NodeValue::EscapedTag(_) => "escaped_tag",
             #[cfg(feature = "shortcodes")]
             NodeValue::ShortCode(_) => "shortcode",
             NodeValue::MultilineBlockQuote(_) => "multiline_block_quote",
+            NodeValue::Alert(_) => "alert",
+            NodeValue::Details(_) => "details",
+            NodeValue::FencedDiv(_) => "fenced_div",
             NodeValue::Escaped => "escaped",
             NodeValue::Math(..) => "math",
             NodeValue::WikiLink(..) => "wikilink",
@@ -540,6 +859,8 @@ pub struct Ast {
     pub(crate) internal_offset: usize,
 
     pub(crate) content: String,
+    // For a paragraph, where each line of `content` started in the input.
+    pub(crate) line_starts: Vec<LineColumn>,
     pub(crate) open: bool,
     pub(crate) last_line_blank: bool,
     pub(crate) table_visited: bool,
@@ -547,6 +868,7 @@ pub struct Ast {
 
 /// Represents the position in the source Markdown this node was rendered from.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sourcepos {
     /// The line and column of the first character of this node.
     pub start: LineColumn,
@@ -581,6 +903,7 @@ impl From<(usize, usize, usize, usize)> for Sourcepos {
 
 /// Represents the 1-based line and column positions of a given character.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineColumn {
     /// The 1-based line number of the character.
     pub line: usize,
@@ -615,6 +938,7 @@ impl Ast {
             content: String::new(),
             sourcepos: (start.line, start.column, start.line, 0).into(),
             internal_offset: 0,
+            line_starts: vec![],
             open: true,
             last_line_blank: false,
             table_visited: false,
@@ -628,6 +952,258 @@ impl Ast {
 /// Child `Ast`s are wrapped in `RefCell` for interior mutability.
 pub type AstNode<'a> = Node<'a, RefCell<Ast>>;
 
+impl<'a> Node<'a, RefCell<Ast>> {
+    /// The plain text of this node and its descendants, as used to generate
+    /// header IDs.
+    ///
+    /// Text, code and math contribute their literal content, line breaks
+    /// become spaces, and images contribute their alt text.  All other
+    /// markup is dropped.
+    ///
+    /// ```
+    /// # use comrak::{Arena, parse_document, Options};
+    /// let arena = Arena::new();
+    /// let root = parse_document(&arena, "*Hi*, `you`\n![there](/x.png)", &Options::default());
+    /// assert_eq!(root.text_content(), "Hi, you there");
+    /// ```
+    pub fn text_content(&'a self) -> String {
+        let mut output = String::with_capacity(20);
+        self.collect_text(&mut output);
+        output
+    }
+
+    fn collect_text(&'a self, output: &mut String) {
+        match self.data.borrow().value {
+            NodeValue::Text(ref literal)
+            | NodeValue::Code(NodeCode { ref literal, .. })
+            | NodeValue::Math(NodeMath { ref literal, .. }) => output.push_str(literal),
+            NodeValue::LineBreak | NodeValue::SoftBreak => output.push(' '),
+            _ => {
+                for n in self.children() {
+                    n.collect_text(output);
+                }
+            }
+        }
+    }
+}
+
+/// Describes a node and its descendants as an indented tree, for debugging.
+///
+/// Each node is written with its type, as named in the XML output, and its
+/// sourcepos.  The content of text, code, math and raw HTML nodes follows,
+/// cut down to `preview_len` characters, and then the node's children.
+///
+/// ```
+/// # use comrak::{Arena, parse_document, Options};
+/// # use comrak::nodes::dump_ast;
+/// let arena = Arena::new();
+/// let root = parse_document(&arena, "# Hi\n\nSome `code`, *and more text*.\n", &Options::default());
+/// assert_eq!(
+///     dump_ast(root, 8),
+///     concat!(
+///         "(document (1:1-3:29) [\n",
+///         "    (heading (1:1-1:4) [\n",
+///         "        (text (1:3-1:4) \"Hi\")\n",
+///         "    ])\n",
+///         "    (paragraph (3:1-3:29) [\n",
+///         "        (text (3:1-3:5) \"Some \")\n",
+///         "        (code (3:7-3:10) \"code\")\n",
+///         "        (text (3:12-3:13) \", \")\n",
+///         "        (emph (3:14-3:28) [\n",
+///         "            (text (3:15-3:27) \"and more…\")\n",
+///         "        ])\n",
+///         "        (text (3:29-3:29) \".\")\n",
+///         "    ])\n",
+///         "])\n",
+///     )
+/// );
+/// ```
+pub fn dump_ast<'a>(root: &'a AstNode<'a>, preview_len: usize) -> String {
+    let mut output = String::new();
+    let mut depth = 0;
+
+    for edge in root.traverse() {
+        match edge {
+            NodeEdge::Start(node) => {
+                let ast = node.data.borrow();
+                write!(
+                    output,
+                    "{:indent$}({} ({})",
+                    "",
+                    ast.value.xml_node_name(),
+                    ast.sourcepos,
+                    indent = depth * 4
+                )
+                .unwrap();
+
+                if let Some(literal) = dump_literal(&ast.value) {
+                    let mut preview = literal.chars().take(preview_len).collect::<String>();
+                    if preview.len() < literal.len() {
+                        preview.push('…');
+                    }
+                    write!(output, " {:?}", preview).unwrap();
+                }
+
+                if node.first_child().is_some() {
+                    output.push_str(" [\n");
+                    depth += 1;
+                } else {
+                    output.push_str(")\n");
+                }
+            }
+            NodeEdge::End(node) => {
+                if node.first_child().is_some() {
+                    depth -= 1;
+                    writeln!(output, "{:indent$}])", "", indent = depth * 4).unwrap();
+                }
+            }
+        }
+    }
+
+    output
+}
+
+/// Returns the front matter of the document at `root`, as enabled by
+/// `front_matter_delimiter` in [`ExtensionOptions`](crate::ExtensionOptions),
+/// or `None` if it has none.  The delimiter lines are excluded, while every
+/// line between them is returned in full, its line ending included.  The
+/// text is copied out, since the node is behind a [`RefCell`].
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::nodes::front_matter;
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.front_matter_delimiter = Some("---".to_string());
+///
+/// let root = parse_document(&arena, "---\ntitle: Hi\ntags: [a]\n---\n\n# Hi\n", &options);
+/// assert_eq!(front_matter(root).as_deref(), Some("title: Hi\ntags: [a]\n"));
+///
+/// let root = parse_document(&arena, "# Hi\n", &options);
+/// assert_eq!(front_matter(root), None);
+/// ```
+pub fn front_matter<'a>(root: &'a AstNode<'a>) -> Option<String> {
+    let first = root.first_child()?;
+    let ast = first.data.borrow();
+    let raw = match ast.value {
+        NodeValue::FrontMatter(ref raw) => raw,
+        _ => return None,
+    };
+
+    // The closing delimiter line is the last in the blob to repeat the
+    // opening one; only blank lines can follow it.
+    let open_end = raw.find('\n')?;
+    let delimiter = raw[..open_end].trim_end_matches('\r');
+    let body = &raw[open_end + 1..];
+    let close = body.rfind(&format!("\n{}", delimiter))?;
+    Some(body[..close + 1].to_string())
+}
+
+/// Returns true if there is raw HTML anywhere under `root`, i.e. any
+/// [`NodeValue::HtmlBlock`] or [`NodeValue::HtmlInline`] node.
+///
+/// ```
+/// # use comrak::{parse_document, Arena, Options};
+/// # use comrak::nodes::contains_raw_html;
+/// let arena = Arena::new();
+/// let options = Options::default();
+///
+/// let root = parse_document(&arena, "Some `<b>code</b>`.\n", &options);
+/// assert!(!contains_raw_html(root));
+///
+/// let root = parse_document(&arena, "Some <b>bold</b>.\n", &options);
+/// assert!(contains_raw_html(root));
+/// ```
+pub fn contains_raw_html<'a>(root: &'a AstNode<'a>) -> bool {
+    root.descendants().any(|node| {
+        matches!(
+            node.data.borrow().value,
+            NodeValue::HtmlBlock(..) | NodeValue::HtmlInline(..)
+        )
+    })
+}
+
+/// Removes empty nodes from the tree under `root`, such as those left behind
+/// after rewriting or detaching nodes by hand.  Children are pruned before
+/// their parents, so a paragraph left empty by the removal of its text goes
+/// too.  What counts as empty depends on the node:
+///
+/// * [`NodeValue::Text`] is empty when its literal is `""`, or, if
+///   `whitespace_is_empty` is set, when it consists only of spaces, tabs and
+///   line endings.
+/// * [`NodeValue::Paragraph`], [`NodeValue::Emph`], [`NodeValue::Strong`],
+///   [`NodeValue::Strikethrough`], [`NodeValue::Superscript`],
+///   [`NodeValue::Subscript`], [`NodeValue::Underline`],
+///   [`NodeValue::Highlight`] and [`NodeValue::SpoileredText`] are empty when they have no children other
+///   than [`NodeValue::SoftBreak`] and [`NodeValue::LineBreak`].
+///
+/// No other node is ever removed; `root` itself is never removed.
+///
+/// ```
+/// # use comrak::{format_html, parse_document, Arena, Options};
+/// # use comrak::nodes::{prune_empty, NodeValue};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(&arena, "*gone*\n\nKept **too**.\n", &options);
+///
+/// for node in root.descendants() {
+///     if let NodeValue::Text(ref mut text) = node.data.borrow_mut().value {
+///         if text == "gone" || text == "too" {
+///             text.clear();
+///         }
+///     }
+/// }
+/// prune_empty(root, false);
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(String::from_utf8(html).unwrap(), "<p>Kept .</p>\n");
+/// ```
+pub fn prune_empty<'a>(root: &'a AstNode<'a>, whitespace_is_empty: bool) {
+    for child in root.children().collect::<Vec<_>>() {
+        prune_empty(child, whitespace_is_empty);
+        if is_prunable(child, whitespace_is_empty) {
+            child.detach();
+        }
+    }
+}
+
+fn is_prunable<'a>(node: &'a AstNode<'a>, whitespace_is_empty: bool) -> bool {
+    match node.data.borrow().value {
+        NodeValue::Text(ref literal) => {
+            literal.is_empty() || (whitespace_is_empty && literal.bytes().all(isspace))
+        }
+        NodeValue::Paragraph
+        | NodeValue::Emph
+        | NodeValue::Strong
+        | NodeValue::Strikethrough
+        | NodeValue::Superscript
+        | NodeValue::Subscript
+        | NodeValue::Underline
+        | NodeValue::Highlight
+        | NodeValue::SpoileredText => node.children().all(|child| {
+            matches!(
+                child.data.borrow().value,
+                NodeValue::SoftBreak | NodeValue::LineBreak
+            )
+        }),
+        _ => false,
+    }
+}
+
+fn dump_literal(value: &NodeValue) -> Option<&str> {
+    match *value {
+        NodeValue::Text(ref literal)
+        | NodeValue::HtmlInline(ref literal)
+        | NodeValue::FrontMatter(ref literal)
+        | NodeValue::Code(NodeCode { ref literal, .. })
+        | NodeValue::CodeBlock(NodeCodeBlock { ref literal, .. })
+        | NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
+        | NodeValue::Math(NodeMath { ref literal, .. }) => Some(literal),
+        _ => None,
+    }
+}
+
 pub(crate) fn last_child_is_open<'a>(node: &'a AstNode<'a>) -> bool {
     node.last_child().map_or(false, |n| n.data.borrow().open)
 }
@@ -669,13 +1245,17 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
 
         NodeValue::Paragraph
         | NodeValue::Heading(..)
+        | NodeValue::TableCaption
         | NodeValue::Emph
         | NodeValue::Strong
+        | NodeValue::Quoted
         | NodeValue::Link(..)
         | NodeValue::Image(..)
         | NodeValue::WikiLink(..) => !child.block(),
 
-        NodeValue::Table(..) => matches!(*child, NodeValue::TableRow(..)),
+        NodeValue::Abbreviation(..) => matches!(*child, NodeValue::Text(..)),
+
+        NodeValue::Table(..) => matches!(*child, NodeValue::TableRow(..) | NodeValue::TableCaption),
 
         NodeValue::TableRow(..) => matches!(*child, NodeValue::TableCell),
 
@@ -686,9 +1266,12 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
                 | NodeValue::Code(..)
                 | NodeValue::Emph
                 | NodeValue::Strong
+                | NodeValue::Quoted
                 | NodeValue::Link(..)
                 | NodeValue::Image(..)
                 | NodeValue::Strikethrough
+                | NodeValue::Highlight
+                | NodeValue::Abbreviation(..)
                 | NodeValue::HtmlInline(..)
                 | NodeValue::Math(..)
                 | NodeValue::WikiLink(..)
@@ -701,16 +1284,22 @@ pub fn can_contain_type<'a>(node: &'a AstNode<'a>, child: &NodeValue) -> bool {
                 | NodeValue::Code(..)
                 | NodeValue::Emph
                 | NodeValue::Strong
+                | NodeValue::Quoted
                 | NodeValue::Link(..)
                 | NodeValue::Image(..)
                 | NodeValue::ShortCode(..)
                 | NodeValue::Strikethrough
+                | NodeValue::Highlight
+                | NodeValue::Abbreviation(..)
                 | NodeValue::HtmlInline(..)
                 | NodeValue::Math(..)
                 | NodeValue::WikiLink(..)
         ),
 
-        NodeValue::MultilineBlockQuote(_) => {
+        NodeValue::MultilineBlockQuote(_)
+        | NodeValue::Alert(_)
+        | NodeValue::Details(_)
+        | NodeValue::FencedDiv(_) => {
             child.block() && !matches!(*child, NodeValue::Item(..) | NodeValue::TaskItem(..))
         }
 
@@ -734,6 +1323,37 @@ pub(crate) fn ends_with_blank_line<'a>(node: &'a AstNode<'a>) -> bool {
     false
 }
 
+// Whether the last line of a block quote's content was blank, looking into
+// the nested block quotes and lists it ends with.
+pub(crate) fn block_quote_ends_with_blank_line<'a>(node: &'a AstNode<'a>) -> bool {
+    let mut it = node.last_child();
+    while let Some(cur) = it {
+        if cur.data.borrow().last_line_blank {
+            return true;
+        }
+        match cur.data.borrow().value {
+            NodeValue::BlockQuote
+            | NodeValue::Alert(..)
+            | NodeValue::List(..)
+            | NodeValue::Item(..)
+            | NodeValue::TaskItem(..) => it = cur.last_child(),
+            _ => it = None,
+        };
+    }
+    false
+}
+
+/// Whether `node` is the last block of a list item.
+pub(crate) fn ends_list_item<'a>(node: &'a AstNode<'a>) -> bool {
+    node.next_sibling().is_none()
+        && node.parent().map_or(false, |p| {
+            matches!(
+                p.data.borrow().value,
+                NodeValue::Item(..) | NodeValue::TaskItem(..)
+            )
+        })
+}
+
 pub(crate) fn containing_block<'a>(node: &'a AstNode<'a>) -> Option<&'a AstNode<'a>> {
     let mut ch = Some(node);
     while let Some(n) = ch {
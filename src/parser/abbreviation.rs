@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use crate::nodes::{AstNode, NodeValue, Sourcepos};
+use crate::parser::inlines::make_inline;
+use crate::strings::{is_line_end_char, trim_slice};
+use typed_arena::Arena;
+
+/// The details of an abbreviation.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAbbreviation {
+    /// The expansion given by the abbreviation's definition; it may be empty.
+    pub title: String,
+}
+
+// Matches a definition such as `*[HTML]: HyperText Markup Language` at the
+// start of `content`, which runs to the end of its line.  Returns the
+// abbreviation, its expansion and the length matched, line ending included.
+pub(crate) fn parse_definition(content: &[u8]) -> Option<(String, String, usize)> {
+    if !content.starts_with(b"*[") {
+        return None;
+    }
+
+    let close = content[2..]
+        .iter()
+        .position(|&c| c == b']' || is_line_end_char(c))?
+        + 2;
+    if content[close] != b']' || content.get(close + 1) != Some(&b':') {
+        return None;
+    }
+    let label = trim_slice(&content[2..close]);
+    if label.is_empty() {
+        return None;
+    }
+
+    let rest = &content[close + 2..];
+    let mut end = rest
+        .iter()
+        .position(|&c| is_line_end_char(c))
+        .unwrap_or(rest.len());
+    let title = trim_slice(&rest[..end]);
+    if rest[end..].starts_with(b"\r\n") {
+        end += 2;
+    } else if end < rest.len() {
+        end += 1;
+    }
+
+    Some((
+        String::from_utf8_lossy(label).into_owned(),
+        String::from_utf8_lossy(title).into_owned(),
+        close + 2 + end,
+    ))
+}
+
+// The abbreviations defined in a document, kept in a trie of their bytes so
+// that text can be scanned once for all of them.
+#[derive(Debug, Default)]
+pub(crate) struct Abbreviations {
+    // Trie nodes; the root is the first.  Each has its children by byte and,
+    // if a label ends there, the index of its definition.
+    nodes: Vec<(HashMap<u8, usize>, Option<usize>)>,
+    definitions: Vec<(String, String)>,
+}
+
+impl Abbreviations {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    // Adds a definition, unless the label is already defined.
+    pub(crate) fn insert(&mut self, label: String, title: String) {
+        if self.nodes.is_empty() {
+            self.nodes.push(Default::default());
+        }
+
+        let mut node = 0;
+        for &b in label.as_bytes() {
+            node = match self.nodes[node].0.get(&b) {
+                Some(&next) => next,
+                None => {
+                    let next = self.nodes.len();
+                    self.nodes.push(Default::default());
+                    self.nodes[node].0.insert(b, next);
+                    next
+                }
+            };
+        }
+
+        if self.nodes[node].1.is_none() {
+            self.nodes[node].1 = Some(self.definitions.len());
+            self.definitions.push((label, title));
+        }
+    }
+
+    // Finds the first whole-word occurrence of any label in `text` from
+    // `from`, the longest where several start at the same place.  Returns
+    // its offset and definition.
+    fn find(&self, text: &str, from: usize) -> Option<(usize, &(String, String))> {
+        let bytes = text.as_bytes();
+        let mut prev_is_word = text[..from].chars().next_back().map_or(false, is_word);
+
+        for (start, c) in text[from..].char_indices() {
+            let start = from + start;
+            if !prev_is_word {
+                let mut node = 0;
+                let mut found = None;
+                for (i, b) in bytes[start..].iter().enumerate() {
+                    node = match self.nodes[node].0.get(b) {
+                        Some(&next) => next,
+                        None => break,
+                    };
+                    if let Some(def) = self.nodes[node].1 {
+                        let end = start + i + 1;
+                        if !text[end..].chars().next().map_or(false, is_word) {
+                            found = Some(def);
+                        }
+                    }
+                }
+                if let Some(def) = found {
+                    return Some((start, &self.definitions[def]));
+                }
+            }
+            prev_is_word = is_word(c);
+        }
+
+        None
+    }
+}
+
+// Splits every whole-word occurrence of a defined abbreviation out of
+// `text`, the content of `node`, into abbreviation nodes after it, with new
+// text nodes between them for the rest.  `sourcepos` is that of `node`, and
+// is trimmed to what's left of it; when `literal`, each byte of `text` is
+// one column of it, and the new nodes are given positions to match.
+pub(crate) fn process_abbreviations<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    node: &'a AstNode<'a>,
+    text: &mut String,
+    sourcepos: &mut Sourcepos,
+    literal: bool,
+    abbreviations: &Abbreviations,
+) {
+    let line = sourcepos.start.line;
+    let column = sourcepos.start.column;
+    let end_column = sourcepos.end.column;
+    let position = |start: usize, end: usize| -> Sourcepos {
+        if literal {
+            (line, column + start, line, column + end - 1).into()
+        } else {
+            // Without a literal mapping from text to columns, the pieces
+            // only get the unknown position, as autolinks do.
+            (0, 1, 0, 1).into()
+        }
+    };
+
+    let mut found = match abbreviations.find(text, 0) {
+        Some(found) => found,
+        None => return,
+    };
+    let contents = std::mem::take(text);
+    let mut last = node;
+    let mut start = 0;
+
+    loop {
+        let (ix, (label, title)) = found;
+        let end = ix + label.len();
+
+        if start > 0 && ix > start {
+            let inl = make_inline(
+                arena,
+                NodeValue::Text(contents[start..ix].to_string()),
+                position(start, ix),
+            );
+            last.insert_after(inl);
+            last = inl;
+        }
+
+        let abbr = make_inline(
+            arena,
+            NodeValue::Abbreviation(NodeAbbreviation {
+                title: title.to_string(),
+            }),
+            position(ix, end),
+        );
+        abbr.append(make_inline(
+            arena,
+            NodeValue::Text(label.to_string()),
+            position(ix, end),
+        ));
+        last.insert_after(abbr);
+        last = abbr;
+
+        if start == 0 {
+            if ix == 0 {
+                // Nothing of `node` is left before the abbreviation.
+                node.detach();
+            }
+            *text = contents[..ix].to_string();
+            if literal {
+                sourcepos.end.column = column + ix - 1;
+            }
+        }
+        start = end;
+
+        found = match abbreviations.find(&contents, start) {
+            Some(found) => found,
+            None => break,
+        };
+    }
+
+    if start < contents.len() {
+        let mut rest_pos = position(start, contents.len());
+        if literal {
+            rest_pos.end.column = end_column;
+        }
+        last.insert_after(make_inline(
+            arena,
+            NodeValue::Text(contents[start..].to_string()),
+            rest_pos,
+        ));
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
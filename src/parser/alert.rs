@@ -0,0 +1,31 @@
+use crate::ctype::isspace;
+use crate::nodes::AlertType;
+use crate::strings::trim_slice;
+
+// Matches an alert marker such as `[!note]` at the start of `line`, returning
+// its type and the title following it up to the end of the line.
+pub(crate) fn scan_alert_marker(line: &[u8]) -> Option<(AlertType, Option<String>)> {
+    let rest = line.strip_prefix(b"[!")?;
+    let close = rest.iter().position(|&c| c == b']')?;
+    let alert_type = match rest[..close].to_ascii_lowercase().as_slice() {
+        b"note" => AlertType::Note,
+        b"tip" => AlertType::Tip,
+        b"important" => AlertType::Important,
+        b"warning" => AlertType::Warning,
+        b"caution" => AlertType::Caution,
+        _ => return None,
+    };
+
+    let title = &rest[close + 1..];
+    if title.first().map_or(false, |&c| !isspace(c)) {
+        return None;
+    }
+    let title = trim_slice(title);
+
+    let title = if title.is_empty() {
+        None
+    } else {
+        Some(String::from_utf8_lossy(title).into_owned())
+    };
+    Some((alert_type, title))
+}
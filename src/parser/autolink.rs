@@ -1,20 +1,96 @@
 use crate::ctype::{isalnum, isalpha, isspace};
-use crate::nodes::{AstNode, NodeLink, NodeValue};
+use crate::nodes::{AstNode, AutolinkType, NodeLink, NodeValue};
 use crate::parser::inlines::make_inline;
+use crate::parser::ParseOptions;
 use once_cell::sync::Lazy;
 use std::str;
 use typed_arena::Arena;
 use unicode_categories::UnicodeCategories;
 
+// Splits autolinks out of `contents_str`, the content of `node`.  The node
+// keeps the text before the first link, and each link is followed by a new
+// text node holding what's left before the next; these new text nodes are
+// returned.  `raw_backslashes` are the sorted offsets of backslashes that
+// stood for themselves in the source, rather than being escaped.  One of
+// those directly before a candidate suppresses the link, and is dropped.
 pub(crate) fn process_autolinks<'a>(
     arena: &'a Arena<AstNode<'a>>,
     node: &'a AstNode<'a>,
     contents_str: &mut String,
-    relaxed_autolinks: bool,
-) {
-    let contents = contents_str.as_bytes();
+    options: &ParseOptions,
+    raw_backslashes: &[usize],
+) -> Vec<&'a AstNode<'a>> {
+    let contents = std::mem::take(contents_str);
+    let contents = contents.as_bytes();
+    let mut starts_at_boundary = starts_at_boundary(node);
+    let mut start = 0;
+    let mut last = node;
+    let mut pieces = vec![];
+
+    loop {
+        let (found, escapes) = next_autolink(
+            arena,
+            contents,
+            start,
+            starts_at_boundary,
+            options,
+            raw_backslashes,
+        );
+        let end = found.map_or(contents.len(), |(_, link_start, _)| link_start);
+
+        let mut text = Vec::with_capacity(end - start);
+        let mut from = start;
+        for ix in escapes {
+            text.extend_from_slice(&contents[from..ix]);
+            from = ix + 1;
+        }
+        text.extend_from_slice(&contents[from..end]);
+        let text = String::from_utf8(text).unwrap();
+
+        if start == 0 {
+            *contents_str = text;
+        } else if !text.is_empty() {
+            let inl = make_inline(arena, NodeValue::Text(text), (0, 1, 0, 1).into());
+            last.insert_after(inl);
+            last = inl;
+            pieces.push(inl);
+        }
+
+        match found {
+            Some((post, _, link_end)) => {
+                last.insert_after(post);
+                last = post;
+                start = link_end;
+                starts_at_boundary = false;
+                if start == contents.len() {
+                    return pieces;
+                }
+            }
+            None => return pieces,
+        }
+    }
+}
+
+// Finds the first autolink in `contents` from `start`, scanning as though
+// the text began there.  Returns the link and the span of `contents` it
+// replaces, along with the offsets of the backslashes which suppressed
+// candidates before it.
+#[allow(clippy::type_complexity)]
+fn next_autolink<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    contents: &[u8],
+    start: usize,
+    starts_at_boundary: bool,
+    options: &ParseOptions,
+    raw_backslashes: &[usize],
+) -> (Option<(&'a AstNode<'a>, usize, usize)>, Vec<usize>) {
+    let relaxed_autolinks = options.relaxed_autolinks;
+    let raw_backslash_before =
+        |i: usize| i > 0 && raw_backslashes.binary_search(&(start + i - 1)).is_ok();
+    let contents = &contents[start..];
     let len = contents.len();
     let mut i = 0;
+    let mut escapes = vec![];
 
     while i < len {
         let mut post_org = None;
@@ -41,43 +117,128 @@ pub(crate) fn process_autolinks<'a>(
 
             match contents[i] {
                 b':' => {
-                    post_org = url_match(arena, contents, i, relaxed_autolinks);
+                    post_org = url_match(arena, contents, i, options);
                     if post_org.is_some() {
                         break;
                     }
                 }
                 b'w' => {
-                    post_org = www_match(arena, contents, i, relaxed_autolinks);
+                    post_org = www_match(
+                        arena,
+                        contents,
+                        i,
+                        starts_at_boundary,
+                        raw_backslash_before(i),
+                        options,
+                    );
                     if post_org.is_some() {
                         break;
                     }
                 }
                 b'@' => {
-                    post_org = email_match(arena, contents, i, relaxed_autolinks);
+                    post_org = email_match(arena, contents, i, options);
                     if post_org.is_some() {
                         break;
                     }
                 }
                 _ => (),
             }
+
+            if let Some(ref tlds) = options.autolink_extra_tlds {
+                post_org = domain_match(
+                    arena,
+                    contents,
+                    i,
+                    starts_at_boundary,
+                    raw_backslash_before(i),
+                    relaxed_autolinks,
+                    tlds,
+                );
+                if post_org.is_some() {
+                    break;
+                }
+            }
+
             i += 1;
         }
 
         if let Some((post, reverse, skip)) = post_org {
             i -= reverse;
-            node.insert_after(post);
-            if i + skip < len {
-                let remain = str::from_utf8(&contents[i + skip..]).unwrap();
-                assert!(!remain.is_empty());
-                post.insert_after(make_inline(
-                    arena,
-                    NodeValue::Text(remain.to_string()),
-                    (0, 1, 0, 1).into(),
-                ));
+
+            // A backslash directly before a candidate suppresses the link;
+            // the backslash itself is dropped from the text.
+            if raw_backslash_before(i) {
+                escapes.push(start + i - 1);
+                i += skip;
+                continue;
             }
-            contents_str.truncate(i);
-            return;
+
+            return (Some((post, start + i, start + i + skip)), escapes);
+        }
+    }
+
+    (None, escapes)
+}
+
+static WWW_DELIMS: Lazy<[bool; 256]> = Lazy::new(|| {
+    let mut sc = [false; 256];
+    for c in &[b'*', b'_', b'~', b'(', b'['] {
+        sc[*c as usize] = true;
+    }
+    sc
+});
+
+// Whether the start of a text node counts as the start of a line or the
+// end of a delimiter run: GFM only recognises a `www.` autolink there, or
+// after whitespace or one of `*`, `_`, `~` and `(`.  Text straight after a
+// code span, link or inline HTML doesn't qualify.
+fn starts_at_boundary<'a>(node: &'a AstNode<'a>) -> bool {
+    let prev = match node.previous_sibling() {
+        Some(prev) => prev,
+        None => return true,
+    };
+
+    let value = &prev.data.borrow().value;
+    match *value {
+        NodeValue::SoftBreak
+        | NodeValue::LineBreak
+        | NodeValue::Emph
+        | NodeValue::Strong
+        | NodeValue::Strikethrough
+        | NodeValue::Highlight
+        | NodeValue::Underline => true,
+        // With escaped_char_spans, the character escaped is what precedes.
+        NodeValue::Escaped => {
+            prev.first_child()
+                .map_or(false, |child| match child.data.borrow().value {
+                    NodeValue::Text(ref text) => text
+                        .bytes()
+                        .last()
+                        .map_or(false, |c| isspace(c) || WWW_DELIMS[c as usize]),
+                    _ => false,
+                })
         }
+        _ => false,
+    }
+}
+
+fn at_word_start(
+    contents: &[u8],
+    i: usize,
+    starts_at_boundary: bool,
+    raw_backslash_before: bool,
+) -> bool {
+    // Look past a literal backslash so that process_autolinks can treat the
+    // candidate as escaped.
+    let mut prev = i;
+    if raw_backslash_before {
+        prev -= 1;
+    }
+
+    if prev == 0 {
+        starts_at_boundary
+    } else {
+        isspace(contents[prev - 1]) || WWW_DELIMS[contents[prev - 1] as usize]
     }
 }
 
@@ -85,17 +246,13 @@ fn www_match<'a>(
     arena: &'a Arena<AstNode<'a>>,
     contents: &[u8],
     i: usize,
-    relaxed_autolinks: bool,
+    starts_at_boundary: bool,
+    raw_backslash_before: bool,
+    options: &ParseOptions,
 ) -> Option<(&'a AstNode<'a>, usize, usize)> {
-    static WWW_DELIMS: Lazy<[bool; 256]> = Lazy::new(|| {
-        let mut sc = [false; 256];
-        for c in &[b'*', b'_', b'~', b'(', b'['] {
-            sc[*c as usize] = true;
-        }
-        sc
-    });
+    let relaxed_autolinks = options.relaxed_autolinks;
 
-    if i > 0 && !isspace(contents[i - 1]) && !WWW_DELIMS[contents[i - 1] as usize] {
+    if !at_word_start(contents, i, starts_at_boundary, raw_backslash_before) {
         return None;
     }
 
@@ -108,6 +265,16 @@ fn www_match<'a>(
         Some(link_end) => link_end,
     };
 
+    if let Some(ref allowed) = options.autolink_allowed_tlds {
+        if !tld_listed(
+            &contents[i..i + link_end],
+            allowed,
+            &options.autolink_extra_tlds,
+        ) {
+            return None;
+        }
+    }
+
     while i + link_end < contents.len() && !isspace(contents[i + link_end]) {
         link_end += 1;
     }
@@ -122,6 +289,9 @@ fn www_match<'a>(
         NodeValue::Link(NodeLink {
             url,
             title: String::new(),
+            autolink: Some(AutolinkType::Uri),
+            srcset: vec![],
+            attrs: vec![],
         }),
         (0, 1, 0, 1).into(),
     );
@@ -138,6 +308,75 @@ fn www_match<'a>(
     Some((inl, 0, link_end))
 }
 
+fn domain_match<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    contents: &[u8],
+    i: usize,
+    starts_at_boundary: bool,
+    raw_backslash_before: bool,
+    relaxed_autolinks: bool,
+    tlds: &[String],
+) -> Option<(&'a AstNode<'a>, usize, usize)> {
+    if !isalnum(contents[i])
+        || !at_word_start(contents, i, starts_at_boundary, raw_backslash_before)
+    {
+        return None;
+    }
+
+    let mut link_end = check_domain(&contents[i..], false)?;
+
+    // Leave the local part of an email address to email_match.
+    if contents.get(i + link_end) == Some(&b'@') || !tld_in(&contents[i..i + link_end], tlds) {
+        return None;
+    }
+
+    while i + link_end < contents.len() && !isspace(contents[i + link_end]) {
+        link_end += 1;
+    }
+
+    link_end = autolink_delim(&contents[i..], link_end, relaxed_autolinks);
+
+    let text = str::from_utf8(&contents[i..link_end + i]).unwrap();
+    let inl = make_inline(
+        arena,
+        NodeValue::Link(NodeLink {
+            url: format!("http://{}", text),
+            title: String::new(),
+            autolink: Some(AutolinkType::Uri),
+            srcset: vec![],
+            attrs: vec![],
+        }),
+        (0, 1, 0, 1).into(),
+    );
+
+    inl.append(make_inline(
+        arena,
+        NodeValue::Text(text.to_string()),
+        (0, 1, 0, 1).into(),
+    ));
+    Some((inl, 0, link_end))
+}
+
+fn tld_in(domain: &[u8], tlds: &[String]) -> bool {
+    let domain = match domain.iter().rposition(|&c| c != b'.') {
+        Some(end) => &domain[..=end],
+        None => return false,
+    };
+    let tld = match domain.iter().rposition(|&c| c == b'.') {
+        Some(dot) => &domain[dot + 1..],
+        None => domain,
+    };
+
+    tlds.iter().any(|candidate| {
+        let candidate = candidate.strip_prefix('.').unwrap_or(candidate);
+        candidate.as_bytes().eq_ignore_ascii_case(tld)
+    })
+}
+
+fn tld_listed(domain: &[u8], allowed: &[String], extra: &Option<Vec<String>>) -> bool {
+    tld_in(domain, allowed) || extra.as_ref().map_or(false, |extra| tld_in(domain, extra))
+}
+
 fn check_domain(data: &[u8], allow_short: bool) -> Option<usize> {
     let mut np = 0;
     let mut uscore1 = 0;
@@ -249,31 +488,36 @@ fn url_match<'a>(
     arena: &'a Arena<AstNode<'a>>,
     contents: &[u8],
     i: usize,
-    relaxed_autolinks: bool,
+    options: &ParseOptions,
 ) -> Option<(&'a AstNode<'a>, usize, usize)> {
     const SCHEMES: [&[u8]; 3] = [b"http", b"https", b"ftp"];
 
+    let relaxed_autolinks = options.relaxed_autolinks;
     let size = contents.len();
 
-    if size - i < 4 || contents[i + 1] != b'/' || contents[i + 2] != b'/' {
-        return None;
-    }
-
     let mut rewind = 0;
     while rewind < i && isalpha(contents[i - rewind - 1]) {
         rewind += 1;
     }
 
-    if !relaxed_autolinks {
-        let cond = |s: &&[u8]| size - i + rewind >= s.len() && &&contents[i - rewind..i] == s;
-        if !SCHEMES.iter().any(cond) {
+    let scheme = &contents[i - rewind..i];
+    let extra = extra_scheme(contents, i - rewind, scheme, options);
+    let authority = size - i >= 4 && contents[i + 1] == b'/' && contents[i + 2] == b'/';
+
+    let mut link_end = if authority {
+        if !relaxed_autolinks && !extra && !SCHEMES.contains(&scheme) {
             return None;
         }
-    }
 
-    let mut link_end = match check_domain(&contents[i + 3..], true) {
-        None => return None,
-        Some(link_end) => link_end,
+        match check_domain(&contents[i + 3..], true) {
+            None => return None,
+            Some(link_end) => link_end,
+        }
+    } else if extra && i + 1 < size && (isalnum(contents[i + 1]) || contents[i + 1] == b'+') {
+        // Schemes such as `tel:` and `mailto:` have a path but no authority.
+        2
+    } else {
+        return None;
     };
 
     while link_end < size - i && !isspace(contents[i + link_end]) {
@@ -290,6 +534,9 @@ fn url_match<'a>(
         NodeValue::Link(NodeLink {
             url: url.clone(),
             title: String::new(),
+            autolink: Some(AutolinkType::Uri),
+            srcset: vec![],
+            attrs: vec![],
         }),
         (0, 1, 0, 1).into(),
     );
@@ -302,12 +549,32 @@ fn url_match<'a>(
     Some((inl, rewind, rewind + link_end))
 }
 
+// Whether `scheme`, starting at `start`, is one of
+// `autolink_extra_schemes`, and not just the end of a longer word.
+fn extra_scheme(contents: &[u8], start: usize, scheme: &[u8], options: &ParseOptions) -> bool {
+    let schemes = match options.autolink_extra_schemes {
+        Some(ref schemes) => schemes,
+        None => return false,
+    };
+
+    if start > 0 && (isalnum(contents[start - 1]) || contents[start - 1] >= 0x80) {
+        return false;
+    }
+
+    !scheme.is_empty()
+        && schemes
+            .iter()
+            .any(|s| s.as_bytes().eq_ignore_ascii_case(scheme))
+}
+
 fn email_match<'a>(
     arena: &'a Arena<AstNode<'a>>,
     contents: &[u8],
     i: usize,
-    relaxed_autolinks: bool,
+    options: &ParseOptions,
 ) -> Option<(&'a AstNode<'a>, usize, usize)> {
+    let relaxed_autolinks = options.relaxed_autolinks;
+
     static EMAIL_OK_SET: Lazy<[bool; 256]> = Lazy::new(|| {
         let mut sc = [false; 256];
         for c in &[b'.', b'+', b'-', b'_'] {
@@ -385,6 +652,16 @@ fn email_match<'a>(
         return None;
     }
 
+    if let Some(ref allowed) = options.autolink_allowed_tlds {
+        if !tld_listed(
+            &contents[i + 1..i + link_end],
+            allowed,
+            &options.autolink_extra_tlds,
+        ) {
+            return None;
+        }
+    }
+
     let mut url = if auto_mailto {
         "mailto:".to_string()
     } else {
@@ -398,6 +675,9 @@ fn email_match<'a>(
         NodeValue::Link(NodeLink {
             url,
             title: String::new(),
+            autolink: Some(AutolinkType::Email),
+            srcset: vec![],
+            attrs: vec![],
         }),
         (0, 1, 0, 1).into(),
     );
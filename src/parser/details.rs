@@ -0,0 +1,71 @@
+use crate::strings::{is_line_end_char, is_space_or_tab, trim_slice};
+
+/// The metadata of a collapsible details block.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeDetails {
+    /// The text following `details` on the opening fence, shown while the
+    /// block is collapsed; it may be empty.  This is plain text, not parsed
+    /// for inlines.
+    pub summary: String,
+
+    /// Whether the block starts out expanded, as marked by `:::+`.
+    pub open: bool,
+
+    /// The length of the fence.
+    pub fence_length: usize,
+
+    /// The indentation level of the fence marker.
+    pub fence_offset: usize,
+}
+
+// Matches an opening fence such as `::: details Summary` or `:::+ details` at
+// the start of `line`, which runs to the end of the line.  The fence offset
+// is left for the caller.
+pub(crate) fn scan_open_fence(line: &[u8]) -> Option<NodeDetails> {
+    let fence_length = line.iter().take_while(|&&c| c == b':').count();
+    if fence_length < 3 {
+        return None;
+    }
+
+    let mut rest = &line[fence_length..];
+    let open = rest.first() == Some(&b'+');
+    if open {
+        rest = &rest[1..];
+    }
+    while rest.first().map_or(false, |&c| is_space_or_tab(c)) {
+        rest = &rest[1..];
+    }
+
+    if rest.len() < 7 || !rest[..7].eq_ignore_ascii_case(b"details") {
+        return None;
+    }
+    let summary = &rest[7..];
+    if summary
+        .first()
+        .map_or(false, |&c| !is_space_or_tab(c) && !is_line_end_char(c))
+    {
+        return None;
+    }
+
+    Some(NodeDetails {
+        summary: String::from_utf8_lossy(trim_slice(summary)).into_owned(),
+        open,
+        fence_length,
+        fence_offset: 0,
+    })
+}
+
+// Matches a closing fence at the start of `line`, returning its length: a
+// run of colons with nothing but whitespace after it.
+pub(crate) fn scan_close_fence(line: &[u8]) -> Option<usize> {
+    let fence_length = line.iter().take_while(|&&c| c == b':').count();
+    if fence_length < 3
+        || !line[fence_length..]
+            .iter()
+            .all(|&c| c.is_ascii_whitespace())
+    {
+        return None;
+    }
+    Some(fence_length)
+}
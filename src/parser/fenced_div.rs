@@ -0,0 +1,170 @@
+use crate::strings::{is_line_end_char, is_space_or_tab};
+
+/// The metadata of a fenced div.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeFencedDiv {
+    /// The div's classes, separated by spaces.
+    pub class: String,
+
+    /// The div's other attributes in the order given, its id among them as
+    /// `("id", …)`.  Values are unescaped.
+    pub attrs: Vec<(String, String)>,
+
+    /// The length of the fence.
+    pub fence_length: usize,
+
+    /// The indentation level of the fence marker.
+    pub fence_offset: usize,
+}
+
+// Matches an opening fence at the start of `line`: three or more colons,
+// then either a single word, taken as a class, or attributes in braces, as
+// in `::: warning` or `::: {.note #intro lang=en}`.  More colons may follow.
+// The fence offset is left for the caller.
+pub(crate) fn scan_open_fence(line: &[u8]) -> Option<NodeFencedDiv> {
+    let fence_length = line.iter().take_while(|&&c| c == b':').count();
+    if fence_length < 3 {
+        return None;
+    }
+
+    let rest = skip_spaces(&line[fence_length..]);
+    let mut end = rest.len();
+    while end > 0 && (is_line_end_char(rest[end - 1]) || is_space_or_tab(rest[end - 1])) {
+        end -= 1;
+    }
+    while end > 0 && rest[end - 1] == b':' {
+        end -= 1;
+    }
+    let spec = trim_end_spaces(&rest[..end]);
+    if spec.is_empty() {
+        return None;
+    }
+
+    let (class, attrs) = if spec[0] == b'{' {
+        if spec[spec.len() - 1] != b'}' {
+            return None;
+        }
+        parse_attributes(&spec[1..spec.len() - 1])?
+    } else {
+        if spec.iter().any(|&c| is_space_or_tab(c) || c == b'{') {
+            return None;
+        }
+        let word = spec.strip_prefix(b".").unwrap_or(spec);
+        if word.is_empty() {
+            return None;
+        }
+        (String::from_utf8_lossy(word).into_owned(), vec![])
+    };
+
+    Some(NodeFencedDiv {
+        class,
+        attrs,
+        fence_length,
+        fence_offset: 0,
+    })
+}
+
+// Parses the inside of `{…}`: `.class`, `#id` and `key=value` or
+// `key="value"` tokens separated by whitespace.  A quoted value may escape
+// `"` and `\` with a backslash.  `class=…` adds to the classes rather than
+// making a second attribute.
+fn parse_attributes(mut s: &[u8]) -> Option<(String, Vec<(String, String)>)> {
+    let mut classes = vec![];
+    let mut attrs = vec![];
+
+    loop {
+        s = skip_spaces(s);
+        if s.is_empty() {
+            break;
+        }
+
+        if s[0] == b'.' || s[0] == b'#' {
+            let len = s
+                .iter()
+                .position(|&c| is_space_or_tab(c))
+                .unwrap_or(s.len());
+            let name = String::from_utf8_lossy(&s[1..len]).into_owned();
+            if name.is_empty() {
+                return None;
+            }
+            if s[0] == b'.' {
+                classes.push(name);
+            } else {
+                attrs.push(("id".to_string(), name));
+            }
+            s = &s[len..];
+            continue;
+        }
+
+        let eq = s
+            .iter()
+            .position(|&c| c == b'=' || is_space_or_tab(c))
+            .filter(|&i| i > 0 && s[i] == b'=')?;
+        let key = &s[..eq];
+        if !key
+            .iter()
+            .all(|&c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_' || c == b':')
+        {
+            return None;
+        }
+        s = &s[eq + 1..];
+
+        let value = if s.first() == Some(&b'"') {
+            let mut value = vec![];
+            let mut i = 1;
+            loop {
+                match *s.get(i)? {
+                    b'"' => break,
+                    b'\\' if matches!(s.get(i + 1), Some(b'"' | b'\\')) => {
+                        value.push(s[i + 1]);
+                        i += 2;
+                    }
+                    c => {
+                        value.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            s = &s[i + 1..];
+            value
+        } else {
+            let len = s
+                .iter()
+                .position(|&c| is_space_or_tab(c))
+                .unwrap_or(s.len());
+            let value = s[..len].to_vec();
+            s = &s[len..];
+            value
+        };
+        if s.first().map_or(false, |&c| !is_space_or_tab(c)) {
+            return None;
+        }
+
+        let key = String::from_utf8_lossy(key).into_owned();
+        let value = String::from_utf8_lossy(&value).into_owned();
+        if key == "class" {
+            classes.extend(value.split_whitespace().map(str::to_string));
+        } else {
+            attrs.push((key, value));
+        }
+    }
+
+    if classes.is_empty() && attrs.is_empty() {
+        return None;
+    }
+    Some((classes.join(" "), attrs))
+}
+
+fn skip_spaces(s: &[u8]) -> &[u8] {
+    let len = s.iter().take_while(|&&c| is_space_or_tab(c)).count();
+    &s[len..]
+}
+
+fn trim_end_spaces(s: &[u8]) -> &[u8] {
+    let mut end = s.len();
+    while end > 0 && is_space_or_tab(s[end - 1]) {
+        end -= 1;
+    }
+    &s[..end]
+}
@@ -2,12 +2,15 @@ use crate::arena_tree::Node;
 use crate::ctype::{isdigit, ispunct, isspace};
 use crate::entity;
 use crate::nodes::{
-    Ast, AstNode, NodeCode, NodeFootnoteReference, NodeLink, NodeMath, NodeValue, NodeWikiLink,
-    Sourcepos,
+    can_contain_type, Ast, AstNode, AutolinkType, NodeCode, NodeFootnoteReference,
+    NodeImageCandidate, NodeLink, NodeMath, NodeValue, NodeWikiLink, Sourcepos,
 };
 #[cfg(feature = "shortcodes")]
 use crate::parser::shortcodes::NodeShortCode;
-use crate::parser::{unwrap_into_2, unwrap_into_copy, AutolinkType, Callback, Options, Reference};
+use crate::parser::{
+    unwrap_into_2, unwrap_into_copy, BrokenLinkReference, BrokenLinkResolution, Callback,
+    Diagnostic, DiagnosticKind, InlineDelimiter, Options, Parser, Reference, ReferenceKind,
+};
 use crate::scanners;
 use crate::strings::{self, is_blank, Case};
 use std::cell::{Cell, RefCell};
@@ -26,12 +29,15 @@ pub struct Subject<'a: 'd, 'r, 'o, 'd, 'i, 'c: 'subj, 'subj> {
     pub arena: &'a Arena<AstNode<'a>>,
     options: &'o Options,
     pub input: &'i [u8],
+    // `input` as text, checked once, for the custom inline tokens.
+    input_str: Option<&'i str>,
     line: usize,
     pub pos: usize,
     block_offset: usize,
     column_offset: isize,
     flags: Flags,
     pub refmap: &'r mut RefMap,
+    shared_refmap: Option<&'o RefMap>,
     delimiter_arena: &'d Arena<Delimiter<'a, 'd>>,
     last_delimiter: Option<&'d Delimiter<'a, 'd>>,
     brackets: Vec<Bracket<'a>>,
@@ -42,10 +48,21 @@ pub struct Subject<'a: 'd, 'r, 'o, 'd, 'i, 'c: 'subj, 'subj> {
     special_chars: [bool; 256],
     skip_chars: [bool; 256],
     smart_chars: [bool; 256],
+    // The paired delimiters handled by `process_emphasis` besides `*` and
+    // `_`: those of enabled extensions, then the caller's.
+    delimiters: Vec<InlineDelimiter>,
     // Need to borrow the callback from the parser only for the lifetime of the Subject, 'subj, and
     // then give it back when the Subject goes out of scope. Needs to be a mutable reference so we
     // can call the FnMut and let it mutate its captured variables.
     callback: Option<&'subj mut Callback<'c>>,
+    diagnostics: Option<&'subj mut Vec<Diagnostic>>,
+    // Text nodes produced by smart punctuation, with the span of input they
+    // replace, kept so they can be reverted inside links with
+    // `smart_skip_links`.
+    smart_text: Vec<(&'a AstNode<'a>, usize, usize)>,
+    // Whether `process_emphasis` turns matched double quotes into `Quoted`
+    // nodes; not while it works on link text that will be reverted.
+    pairing_quotes: bool,
 }
 
 #[derive(Default)]
@@ -56,13 +73,26 @@ struct Flags {
     skip_html_comment: bool,
 }
 
+/// The link reference definitions of a document, by normalized label.
+///
+/// A map collected from one document with [`RefMap::from_document`] can be
+/// shared by others through
+/// [`parse_document_with_refmap`](crate::parse_document_with_refmap).
+#[derive(Debug, Clone)]
 pub struct RefMap {
-    pub map: HashMap<String, Reference>,
+    pub(crate) map: HashMap<String, Reference>,
     pub(crate) max_ref_size: usize,
     ref_size: usize,
 }
 
+impl Default for RefMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RefMap {
+    /// Creates an empty map.
     pub fn new() -> Self {
         Self {
             map: HashMap::new(),
@@ -71,8 +101,32 @@ impl RefMap {
         }
     }
 
-    fn lookup(&mut self, lab: &str) -> Option<Reference> {
-        match self.map.get(lab) {
+    /// Collects the link reference definitions in `buffer`, parsed with the
+    /// given options.  Anything else in `buffer` is discarded.
+    pub fn from_document(buffer: &str, options: &Options) -> Self {
+        let arena = Arena::new();
+        let root = arena.alloc(Node::new(RefCell::new(Ast::new(
+            NodeValue::Document,
+            (1, 1).into(),
+        ))));
+        let mut parser = Parser::new(&arena, root, options, None);
+        let mut linebuf = Vec::with_capacity(buffer.len());
+        parser.feed(&mut linebuf, buffer, true);
+        parser.finish(linebuf);
+        RefMap {
+            map: parser.refmap.map,
+            ..RefMap::new()
+        }
+    }
+
+    // Looks `lab` up here first, then in `shared`, counting what's found
+    // towards `max_ref_size` either way.
+    fn lookup(&mut self, lab: &str, shared: Option<&RefMap>) -> Option<Reference> {
+        match self
+            .map
+            .get(lab)
+            .or_else(|| shared.and_then(|shared| shared.map.get(lab)))
+        {
             Some(entry) => {
                 let size = entry.url.len() + entry.title.len();
                 if size > self.max_ref_size - self.ref_size {
@@ -119,19 +173,27 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         line: usize,
         block_offset: usize,
         refmap: &'r mut RefMap,
+        shared_refmap: Option<&'o RefMap>,
         delimiter_arena: &'d Arena<Delimiter<'a, 'd>>,
         callback: Option<&'subj mut Callback<'c>>,
+        diagnostics: Option<&'subj mut Vec<Diagnostic>>,
     ) -> Self {
         let mut s = Subject {
             arena,
             options,
             input,
+            input_str: if options.parse.inline_tokens.is_empty() {
+                None
+            } else {
+                str::from_utf8(input).ok()
+            },
             line,
             pos: 0,
             block_offset,
             column_offset: 0,
             flags: Flags::default(),
             refmap,
+            shared_refmap,
             delimiter_arena,
             last_delimiter: None,
             brackets: vec![],
@@ -142,7 +204,11 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             special_chars: [false; 256],
             skip_chars: [false; 256],
             smart_chars: [false; 256],
+            delimiters: builtin_delimiters(options),
             callback,
+            diagnostics,
+            smart_text: vec![],
+            pairing_quotes: options.parse.quote_elements,
         };
         for &c in &[
             b'\n', b'\r', b'_', b'*', b'"', b'`', b'\\', b'&', b'<', b'[', b']', b'!', b'$',
@@ -150,24 +216,36 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             s.special_chars[c as usize] = true;
         }
         if options.extension.strikethrough {
-            s.special_chars[b'~' as usize] = true;
             s.skip_chars[b'~' as usize] = true;
         }
-        if options.extension.superscript {
-            s.special_chars[b'^' as usize] = true;
+        for delimiter in &s.delimiters {
+            s.special_chars[delimiter.delim_char as usize] = true;
         }
         #[cfg(feature = "shortcodes")]
         if options.extension.shortcodes {
             s.special_chars[b':' as usize] = true;
         }
         if options.extension.philomena {
-            s.special_chars[b'%' as usize] = true;
-            s.special_chars[b'|' as usize] = true;
             s.special_chars[b'>' as usize] = true;
         }
         for &c in &[b'"', b'\'', b'.', b'-'] {
             s.smart_chars[c as usize] = true;
         }
+        // The caller's delimiters can't take over characters with a
+        // meaning of their own.
+        for delimiter in &options.parse.inline_delimiters {
+            let c = delimiter.delim_char as usize;
+            if delimiter.delim_char.is_ascii_punctuation()
+                && !s.special_chars[c]
+                && !s.smart_chars[c]
+            {
+                s.special_chars[c] = true;
+                s.delimiters.push(*delimiter);
+            }
+        }
+        for token in &options.parse.inline_tokens {
+            s.special_chars[token.prefix as usize] = true;
+        }
         s
     }
 
@@ -180,6 +258,10 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             None => return false,
             Some(ch) => *ch as char,
         };
+        if let Some(inl) = self.handle_inline_token() {
+            node.append(inl);
+            return true;
+        }
         let new_inl: Option<&'a AstNode<'a>> = match c {
             '\0' => return false,
             '\r' | '\n' => Some(self.handle_newline()),
@@ -242,13 +324,7 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                     ))
                 }
             }
-            '~' if self.options.extension.strikethrough => Some(self.handle_delim(b'~')),
-            '^' if self.options.extension.superscript && !self.within_brackets => {
-                Some(self.handle_delim(b'^'))
-            }
             '$' => Some(self.handle_dollars()),
-            '%' if self.options.extension.philomena => Some(self.handle_delim(b'%')),
-            '|' if self.options.extension.philomena => Some(self.handle_delim(b'|')),
             '>' if self.options.extension.philomena && self.peek_char_n(1) == Some(&(b'>')) => {
                 let start_column = self.pos;
                 self.pos += 1;
@@ -261,10 +337,20 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                 let value = self.handle_image_mention(id);
                 Some(self.make_inline(value, start_column, end_column))
             }
+            // A `^` in square brackets may start a footnote reference.
+            _ if self.inline_delimiter(c as u8).is_some()
+                && !(c == '^' && self.within_brackets) =>
+            {
+                Some(self.handle_delim(c as u8))
+            }
             _ => {
-                let endpos = self.find_special_char();
-                let mut contents = self.input[self.pos..endpos].to_vec();
                 let startpos = self.pos;
+                // The prefix of a custom token that didn't match is text.
+                if self.is_inline_token_prefix(c as u8) {
+                    self.pos += 1;
+                }
+                let endpos = self.find_special_char();
+                let mut contents = self.input[startpos..endpos].to_vec();
                 self.pos = endpos;
 
                 if self
@@ -358,7 +444,7 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         // This array is an important optimization that prevents searching down
         // the stack for openers we've previously searched for and know don't
         // exist, preventing exponential blowup on pathological cases.
-        let mut openers_bottom: [usize; 13] = [stack_bottom; 13];
+        let mut openers_bottom = vec![stack_bottom; 9 + self.delimiters.len()];
 
         // This is traversing the stack from the top to the bottom, setting `closer` to
         // the delimiter directly above `stack_bottom`. In the case where we are processing
@@ -382,15 +468,17 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                 let mut mod_three_rule_invoked = false;
 
                 let ix = match c.delim_char {
-                    b'%' => 0,
-                    b'|' => 1,
-                    b'~' => 2,
-                    b'^' => 3,
-                    b'"' => 4,
-                    b'\'' => 5,
-                    b'_' => 6,
-                    b'*' => 7 + (if c.can_open { 3 } else { 0 }) + (c.length % 3),
-                    _ => unreachable!(),
+                    b'"' => 0,
+                    b'\'' => 1,
+                    b'_' => 2,
+                    b'*' => 3 + (if c.can_open { 3 } else { 0 }) + (c.length % 3),
+                    ch => {
+                        9 + self
+                            .delimiters
+                            .iter()
+                            .position(|d| d.delim_char == ch)
+                            .unwrap()
+                    }
                 };
 
                 // Here's where we find the opener by searching down the stack,
@@ -436,10 +524,7 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                 // both get passed.
                 if c.delim_char == b'*'
                     || c.delim_char == b'_'
-                    || (self.options.extension.strikethrough && c.delim_char == b'~')
-                    || (self.options.extension.superscript && c.delim_char == b'^')
-                    || (self.options.extension.philomena && c.delim_char == b'%')
-                    || (self.options.extension.philomena && c.delim_char == b'|')
+                    || self.inline_delimiter(c.delim_char).is_some()
                 {
                     if opener_found {
                         // Finally, here's the happy case where the delimiters
@@ -462,6 +547,8 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                         // (below), try again.
                         closer = c.next.get();
                     }
+                } else if c.delim_char == b'"' && opener_found && self.pairing_quotes {
+                    closer = self.insert_quoted(opener.unwrap(), c);
                 } else if c.delim_char == b'\'' || c.delim_char == b'"' {
                     *c.inl.data.borrow_mut().value.text_mut().unwrap() =
                         if c.delim_char == b'\'' { "’" } else { "”" }.to_string();
@@ -667,10 +754,24 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                 let code = NodeCode {
                     num_backticks: openticks,
                     literal: String::from_utf8(buf).unwrap(),
+                    lang: None,
                 };
                 let node =
                     self.make_inline(NodeValue::Code(code), startpos, endpos - openticks - 1);
                 self.adjust_node_newlines(node, endpos - startpos, openticks);
+
+                if self.options.extension.inline_code_lang {
+                    if let Some(len) = scan_inline_code_lang(&self.input[self.pos..]) {
+                        let lang = str::from_utf8(&self.input[self.pos + 2..self.pos + len - 1])
+                            .unwrap()
+                            .to_string();
+                        if let NodeValue::Code(ref mut code) = node.data.borrow_mut().value {
+                            code.lang = Some(lang);
+                        }
+                        self.pos += len;
+                    }
+                }
+
                 node
             }
         }
@@ -857,7 +958,17 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             self.pos - 1,
         );
 
-        if (can_open || can_close) && (!(c == b'\'' || c == b'"') || self.options.parse.smart) {
+        if c == b'\'' || c == b'"' {
+            self.record_smart_text(inl, self.pos - numdelims);
+        }
+
+        let allowed_length = self.inline_delimiter(c).map_or(true, |d| {
+            numdelims >= d.min_length && d.max_length.map_or(true, |max| numdelims <= max)
+        });
+        if (can_open || can_close)
+            && (!(c == b'\'' || c == b'"') || self.options.parse.smart)
+            && allowed_length
+        {
             self.push_delimiter(c, can_open, can_close, inl);
         }
 
@@ -894,7 +1005,9 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         let mut buf = String::with_capacity(3 * (ems + ens));
         buf.push_str(&"—".repeat(ems));
         buf.push_str(&"–".repeat(ens));
-        self.make_inline(NodeValue::Text(buf), start, self.pos - 1)
+        let inl = self.make_inline(NodeValue::Text(buf), start, self.pos - 1);
+        self.record_smart_text(inl, start);
+        inl
     }
 
     pub fn handle_period(&mut self) -> &'a AstNode<'a> {
@@ -903,7 +1016,10 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             self.pos += 1;
             if self.peek_char().map_or(false, |&c| c == b'.') {
                 self.pos += 1;
-                self.make_inline(NodeValue::Text("…".to_string()), self.pos - 3, self.pos - 1)
+                let inl =
+                    self.make_inline(NodeValue::Text("…".to_string()), self.pos - 3, self.pos - 1);
+                self.record_smart_text(inl, self.pos - 3);
+                inl
             } else {
                 self.make_inline(
                     NodeValue::Text("..".to_string()),
@@ -916,6 +1032,59 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         }
     }
 
+    fn record_smart_text(&mut self, inl: &'a AstNode<'a>, start: usize) {
+        if self.options.parse.smart
+            && (self.options.parse.smart_skip_links || self.diagnostics.is_some())
+        {
+            self.smart_text.push((inl, start, self.pos));
+        }
+    }
+
+    // Reports each piece of smart punctuation left in the output, once
+    // emphasis and links have been processed.
+    pub fn report_smart_punctuation(&mut self) {
+        let diagnostics = match self.diagnostics {
+            Some(ref mut diagnostics) => diagnostics,
+            None => return,
+        };
+
+        for &(inl, from, to) in &self.smart_text {
+            if inl.parent().is_none() {
+                continue;
+            }
+
+            let ast = inl.data.borrow();
+            let original = str::from_utf8(&self.input[from..to]).unwrap();
+            if let NodeValue::Text(ref text) = ast.value {
+                if text != original {
+                    diagnostics.push(Diagnostic {
+                        sourcepos: ast.sourcepos,
+                        kind: DiagnosticKind::SmartPunctuation,
+                        message: format!(
+                            "smart punctuation replaced {:?} with {:?}",
+                            original, text
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    // Puts back the original input for smart punctuation from `start` on,
+    // once it's known to be inside a link or image description.
+    fn revert_smart_text(&mut self, start: usize) {
+        let input = self.input;
+        self.smart_text.retain(|&(inl, from, to)| {
+            if from < start {
+                return true;
+            }
+            if let NodeValue::Text(ref mut text) = inl.data.borrow_mut().value {
+                *text = String::from_utf8(input[from..to].to_vec()).unwrap();
+            }
+            false
+        });
+    }
+
     pub fn scan_delims(&mut self, c: u8) -> (usize, bool, bool) {
         let before_char = if self.pos == 0 {
             '\n'
@@ -977,16 +1146,19 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             }
         };
 
+        // With CJK-friendly emphasis, a CJK character on the other side of
+        // the run is as good a boundary as whitespace or punctuation.
+        let is_boundary = |ch: char| {
+            ch.is_whitespace()
+                || ch.is_punctuation()
+                || (self.options.parse.cjk_friendly_emphasis && is_cjk(ch))
+        };
         let left_flanking = numdelims > 0
             && !after_char.is_whitespace()
-            && !(after_char.is_punctuation()
-                && !before_char.is_whitespace()
-                && !before_char.is_punctuation());
+            && (!after_char.is_punctuation() || is_boundary(before_char));
         let right_flanking = numdelims > 0
             && !before_char.is_whitespace()
-            && !(before_char.is_punctuation()
-                && !after_char.is_whitespace()
-                && !after_char.is_punctuation());
+            && (!before_char.is_punctuation() || is_boundary(after_char));
 
         if c == b'_' {
             (
@@ -1008,6 +1180,11 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         }
     }
 
+    // The paired delimiter for `c` other than `*` and `_`, if there is one.
+    fn inline_delimiter(&self, c: u8) -> Option<InlineDelimiter> {
+        self.delimiters.iter().find(|d| d.delim_char == c).copied()
+    }
+
     pub fn push_delimiter(&mut self, c: u8, can_open: bool, can_close: bool, inl: &'a AstNode<'a>) {
         let d = self.delimiter_arena.alloc(Delimiter {
             prev: Cell::new(self.last_delimiter),
@@ -1030,6 +1207,42 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
     //
     // As a side-effect, handle long "***" and "___" nodes by truncating them in
     // place to be re-matched by `process_emphasis`.
+    // Wraps everything between a matched pair of double quotes in a `Quoted`
+    // node, dropping the quotes themselves.  Like `insert_emph`, any
+    // delimiters left between the pair can no longer match.
+    fn insert_quoted(
+        &mut self,
+        opener: &'d Delimiter<'a, 'd>,
+        closer: &'d Delimiter<'a, 'd>,
+    ) -> Option<&'d Delimiter<'a, 'd>> {
+        let mut delim = closer.prev.get();
+        while delim.is_some() && !Self::del_ref_eq(delim, Some(opener)) {
+            self.remove_delimiter(delim.unwrap());
+            delim = delim.unwrap().prev.get();
+        }
+
+        let quoted = self.make_inline(NodeValue::Quoted, self.pos, self.pos);
+        quoted.data.borrow_mut().sourcepos = Sourcepos {
+            start: opener.inl.data.borrow().sourcepos.start,
+            end: closer.inl.data.borrow().sourcepos.end,
+        };
+
+        let mut tmp = opener.inl.next_sibling().unwrap();
+        while !tmp.same_node(closer.inl) {
+            let next = tmp.next_sibling().unwrap();
+            quoted.append(tmp);
+            tmp = next;
+        }
+        opener.inl.insert_after(quoted);
+
+        let next = closer.next.get();
+        opener.inl.detach();
+        closer.inl.detach();
+        self.remove_delimiter(opener);
+        self.remove_delimiter(closer);
+        next
+    }
+
     pub fn insert_emph(
         &mut self,
         opener: &'d Delimiter<'a, 'd>,
@@ -1047,11 +1260,14 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         opener_num_chars -= use_delims;
         closer_num_chars -= use_delims;
 
-        if self.options.extension.strikethrough
-            && opener_char == b'~'
+        let delimiter = match opener_char {
+            b'*' | b'_' => None,
+            c => self.inline_delimiter(c),
+        };
+        if delimiter.map_or(false, |d| d.same_length)
             && (opener_num_chars != closer_num_chars || opener_num_chars > 0)
         {
-            return None;
+            return closer.next.get();
         }
 
         opener
@@ -1080,18 +1296,8 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         }
 
         let emph = self.make_inline(
-            if self.options.extension.strikethrough && opener_char == b'~' {
-                NodeValue::Strikethrough
-            } else if self.options.extension.superscript && opener_char == b'^' {
-                NodeValue::Superscript
-            } else if self.options.extension.philomena && opener_char == b'%' {
-                NodeValue::Subscript
-            } else if self.options.extension.philomena && opener_char == b'|' {
-                if use_delims == 2 {
-                    NodeValue::SpoileredText
-                } else {
-                    NodeValue::EscapedTag("|".to_owned())
-                }
+            if let Some(d) = delimiter {
+                (d.builder)(use_delims)
             } else if self.options.extension.philomena && opener_char == b'_' && use_delims == 2 {
                 NodeValue::Underline
             } else if use_delims == 1 {
@@ -1393,6 +1599,16 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
 
         let after_link_text_pos = self.pos;
 
+        // Try a srcset image destination before an ordinary one
+        if is_image && self.options.extension.image_srcset && self.peek_char() == Some(&b'(') {
+            if let Some((srcset, n)) = manual_scan_image_srcset(&self.input[self.pos + 1..]) {
+                self.pos += 1 + n;
+                let url = srcset[0].url.clone();
+                self.close_bracket_match(is_image, url, String::new(), srcset, vec![]);
+                return None;
+            }
+        }
+
         // Try to find a link destination within parenthesis
 
         let mut sps = 0;
@@ -1426,6 +1642,8 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                     is_image,
                     String::from_utf8(url).unwrap(),
                     String::from_utf8(title).unwrap(),
+                    vec![],
+                    vec![],
                 );
                 return None;
             } else {
@@ -1454,28 +1672,16 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         }
 
         // Need to normalize both to lookup in refmap and to call callback
-        let lab = strings::normalize_label(&lab, Case::DontPreserve);
+        let original = lab;
+        let lab = strings::normalize_label(&original, Case::DontPreserve);
         let mut reff = if found_label {
-            self.refmap.lookup(&lab)
+            self.refmap.lookup(&lab, self.shared_refmap)
         } else {
             None
         };
 
-        // Attempt to use the provided broken link callback if a reference cannot be resolved
-        if reff.is_none() {
-            if let Some(ref mut callback) = self.callback {
-                reff = callback(&lab).map(|(url, title)| Reference { url, title });
-            }
-        }
-
-        if let Some(reff) = reff {
-            self.close_bracket_match(is_image, reff.url.clone(), reff.title);
-            return None;
-        }
-
         let bracket_inl_text = self.brackets[brackets_len - 1].inl_text;
-
-        if self.options.extension.footnotes
+        let footnote_like = self.options.extension.footnotes
             && match bracket_inl_text.next_sibling() {
                 Some(n) => {
                     if n.data.borrow().value.text().is_some() {
@@ -1491,8 +1697,46 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                     }
                 }
                 _ => false,
+            };
+
+        // Attempt to use the provided broken link callback if a reference cannot be resolved
+        let mut attrs = vec![];
+        let mut keep_text = false;
+        if reff.is_none() {
+            if let Some(ref mut callback) = self.callback {
+                let kind = if is_image {
+                    ReferenceKind::Image
+                } else {
+                    ReferenceKind::Link
+                };
+                match callback(
+                    BrokenLinkReference {
+                        normalized: &lab,
+                        original: &original,
+                        kind,
+                    },
+                    footnote_like,
+                ) {
+                    BrokenLinkResolution::Link {
+                        url,
+                        title,
+                        attrs: link_attrs,
+                    } => {
+                        reff = Some(Reference { url, title });
+                        attrs = link_attrs;
+                    }
+                    BrokenLinkResolution::Text => keep_text = true,
+                    BrokenLinkResolution::Default => (),
+                }
             }
-        {
+        }
+
+        if let Some(reff) = reff {
+            self.close_bracket_match(is_image, reff.url.clone(), reff.title, vec![], attrs);
+            return None;
+        }
+
+        if !keep_text && footnote_like {
             let mut text = String::new();
             let mut sibling_iterator = bracket_inl_text.following_siblings();
 
@@ -1553,15 +1797,64 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             }
         }
 
+        if found_label && !lab.is_empty() {
+            self.report_unresolved_reference(bracket_inl_text, is_image, &lab);
+        }
+
         self.brackets.pop();
         self.pos = initial_pos;
         Some(self.make_inline(NodeValue::Text("]".to_string()), self.pos - 1, self.pos - 1))
     }
 
-    pub fn close_bracket_match(&mut self, is_image: bool, url: String, title: String) {
+    // Record a diagnostic for a reference link or image whose label has no
+    // definition; `self.pos` is just past its closing bracket or label.
+    fn report_unresolved_reference(&mut self, opener: &'a AstNode<'a>, is_image: bool, lab: &str) {
+        let end_column =
+            usize::try_from(self.pos as isize + self.column_offset + self.block_offset as isize)
+                .unwrap();
+        let sourcepos = Sourcepos {
+            start: opener.data.borrow().sourcepos.start,
+            end: (self.line, end_column).into(),
+        };
+        let (kind, name) = if is_image {
+            (ReferenceKind::Image, "image")
+        } else {
+            (ReferenceKind::Link, "link")
+        };
+
+        if let Some(ref mut diagnostics) = self.diagnostics {
+            // The label of an unresolved `[text][label]` is read again as a
+            // shortcut reference; it was already reported with the text.
+            if diagnostics
+                .last()
+                .map_or(true, |d| d.sourcepos.end != sourcepos.end)
+            {
+                diagnostics.push(Diagnostic {
+                    sourcepos,
+                    kind: DiagnosticKind::UnresolvedReference(kind),
+                    message: format!("unresolved {} reference \"{}\"", name, lab),
+                });
+            }
+        }
+    }
+
+    pub fn close_bracket_match(
+        &mut self,
+        is_image: bool,
+        url: String,
+        title: String,
+        srcset: Vec<NodeImageCandidate>,
+        attrs: Vec<(String, String)>,
+    ) {
         let brackets_len = self.brackets.len();
 
-        let nl = NodeLink { url, title };
+        let nl = NodeLink {
+            url,
+            title,
+            autolink: None,
+            srcset,
+            attrs,
+        };
         let inl = self.make_inline(
             if is_image {
                 NodeValue::Image(nl)
@@ -1590,7 +1883,14 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             inl.append(tmp);
         }
         self.brackets[brackets_len - 1].inl_text.detach();
+        if self.options.parse.smart_skip_links {
+            self.pairing_quotes = false;
+        }
         self.process_emphasis(self.brackets[brackets_len - 1].position);
+        self.pairing_quotes = self.options.parse.quote_elements;
+        if self.options.parse.smart_skip_links && !self.smart_text.is_empty() {
+            self.revert_smart_text(self.brackets[brackets_len - 1].position);
+        }
         self.brackets.pop();
 
         if !is_image {
@@ -1617,6 +1917,12 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                     self.pos += 1;
                     length += 1;
                 }
+            } else if c == b'`' {
+                // A code span is taken whole, so that any `|` or `]` in it
+                // doesn't end the component.
+                let span = self.wikilink_code_span_len();
+                self.pos += span;
+                length += span;
             } else {
                 self.pos += 1;
                 length += 1;
@@ -1644,7 +1950,7 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         let startpos = self.pos;
         let component = self.wikilink_url_link_label()?;
         let url_clean = strings::clean_url(component.url);
-        let (link_label, link_label_start_column, link_label_end_column) =
+        let (mut link_label, link_label_start_column, link_label_end_column) =
             match component.link_label {
                 Some((label, sc, ec)) => (entity::unescape_html(label), sc, ec),
                 None => (
@@ -1653,6 +1959,7 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                     self.pos - 3,
                 ),
             };
+        strings::unescape(&mut link_label);
 
         let nl = NodeWikiLink {
             url: String::from_utf8(url_clean).unwrap(),
@@ -1745,6 +2052,12 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
                     self.pos += 1;
                     length += 1;
                 }
+            } else if c == b'`' {
+                // A code span is taken whole, so that any `|` or `]` in it
+                // doesn't end the component.
+                let span = self.wikilink_code_span_len();
+                self.pos += span;
+                length += span;
             } else {
                 self.pos += 1;
                 length += 1;
@@ -1758,6 +2071,26 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         true
     }
 
+    // The length of the backtick run at self.pos, plus that of the code span it
+    // opens if a closing run of the same length follows.
+    fn wikilink_code_span_len(&self) -> usize {
+        let rest = &self.input[self.pos..];
+        let ticks = rest.iter().take_while(|&&c| c == b'`').count();
+        let mut i = ticks;
+        while i < rest.len() {
+            if rest[i] != b'`' {
+                i += 1;
+                continue;
+            }
+            let run = rest[i..].iter().take_while(|&&c| c == b'`').count();
+            if run == ticks {
+                return i + run;
+            }
+            i += run;
+        }
+        ticks
+    }
+
     pub fn scan_image_mention_id(&mut self) -> Option<Vec<u8>> {
         let input = &self.input[self.pos..];
         let len = input.len();
@@ -1784,10 +2117,60 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
         }
     }
 
+    fn is_inline_token_prefix(&self, c: u8) -> bool {
+        self.options
+            .parse
+            .inline_tokens
+            .iter()
+            .any(|token| token.prefix == c)
+    }
+
+    // Tries each of the custom inline tokens starting with the current
+    // character, returning the node built for the first that matches.
+    fn handle_inline_token(&mut self) -> Option<&'a AstNode<'a>> {
+        let c = *self.peek_char()?;
+        if !self.is_inline_token_prefix(c)
+            || self.within_brackets
+            || (self.pos > 0 && self.input[self.pos - 1].is_ascii_alphanumeric())
+        {
+            return None;
+        }
+
+        let rest = self.input_str?.get(self.pos..)?;
+        for token in &self.options.parse.inline_tokens {
+            if token.prefix != c {
+                continue;
+            }
+            let len = match (token.matcher)(rest) {
+                Some(len) if len > 0 && len <= rest.len() && rest.is_char_boundary(len) => len,
+                _ => continue,
+            };
+            let value = match (token.builder)(&rest[..len]) {
+                Some(value) => value,
+                None => continue,
+            };
+
+            let startpos = self.pos;
+            self.pos += len;
+            let inl = self.make_inline(value, startpos, self.pos - 1);
+            if can_contain_type(inl, &NodeValue::Text(String::new())) {
+                inl.append(self.make_inline(
+                    NodeValue::Text(rest[..len].to_string()),
+                    startpos,
+                    self.pos - 1,
+                ));
+            }
+            return Some(inl);
+        }
+        None
+    }
+
     pub fn handle_image_mention(&mut self, id: Vec<u8>) -> NodeValue {
         let id_str = String::from_utf8(id).unwrap_or_else(|_| String::from(""));
         let mut html = format!("&gt;&gt;{}", &id_str);
 
+        // The id was scanned in full and is looked up exactly, so a key which
+        // is only a prefix of it never applies, whatever the HashMap's order.
         if let Some(replacements) = &self.options.extension.philomena_replacements.as_ref() {
             html = replacements
                 .get(&id_str)
@@ -1826,6 +2209,7 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             )
                 .into(),
             internal_offset: 0,
+            line_starts: vec![],
             open: false,
             last_line_blank: false,
             table_visited: false,
@@ -1844,6 +2228,9 @@ impl<'a, 'r, 'o, 'd, 'i, 'c, 'subj> Subject<'a, 'r, 'o, 'd, 'i, 'c, 'subj> {
             NodeValue::Link(NodeLink {
                 url: String::from_utf8(strings::clean_autolink(url, kind)).unwrap(),
                 title: String::new(),
+                autolink: Some(kind),
+                srcset: vec![],
+                attrs: vec![],
             }),
             start_column + 1,
             end_column + 1,
@@ -1924,6 +2311,178 @@ pub fn manual_scan_link_url_2(input: &[u8]) -> Option<(&[u8], usize)> {
     }
 }
 
+// Scans a `srcset` image destination, starting just after the opening
+// parenthesis:
+//
+//   srcset     = sp* candidate (sp* "," sp* candidate)* sp* ")"
+//   candidate  = url sp+ descriptor
+//   url        = (escape | any byte but space, control, "(" or ")")+, not ending in ","
+//   descriptor = digit+ "w" | digit+ ("." digit+)? "x"
+//
+// Returns the candidates and the length scanned, including the closing
+// parenthesis.
+pub fn manual_scan_image_srcset(input: &[u8]) -> Option<(Vec<NodeImageCandidate>, usize)> {
+    let len = input.len();
+    let skip_spaces = |mut i: usize| {
+        while i < len && isspace(input[i]) {
+            i += 1;
+        }
+        i
+    };
+    let skip_digits = |mut i: usize| {
+        while i < len && isdigit(input[i]) {
+            i += 1;
+        }
+        i
+    };
+
+    let mut candidates = vec![];
+    let mut i = skip_spaces(0);
+
+    loop {
+        let start = i;
+        while i < len {
+            let b = input[i];
+            if b == b'\\' && i + 1 < len && ispunct(input[i + 1]) {
+                i += 2;
+            } else if isspace(b) || b.is_ascii_control() || b == b'(' || b == b')' {
+                break;
+            } else {
+                i += 1;
+            }
+        }
+        if i == start || input[i - 1] == b',' {
+            return None;
+        }
+        let url = strings::clean_url(&input[start..i]);
+
+        let desc_start = skip_spaces(i);
+        if desc_start == i {
+            return None;
+        }
+        i = skip_digits(desc_start);
+        if i == desc_start || i >= len {
+            return None;
+        }
+        if input[i] == b'.' {
+            let frac = i + 1;
+            i = skip_digits(frac);
+            if i == frac || i >= len || input[i] != b'x' {
+                return None;
+            }
+        } else if input[i] != b'w' && input[i] != b'x' {
+            return None;
+        }
+        i += 1;
+
+        candidates.push(NodeImageCandidate {
+            url: String::from_utf8(url).unwrap(),
+            descriptor: str::from_utf8(&input[desc_start..i]).unwrap().to_string(),
+        });
+
+        i = skip_spaces(i);
+        match input.get(i) {
+            Some(b',') => i = skip_spaces(i + 1),
+            Some(b')') => return Some((candidates, i + 1)),
+            _ => return None,
+        }
+    }
+}
+
+// Scans a language annotation directly after an inline code span:
+//
+//   annotation = "{." (alnum | "-" | "_" | "+" | "#")+ "}"
+//
+// Returns the length scanned, including the braces.
+fn scan_inline_code_lang(input: &[u8]) -> Option<usize> {
+    if !input.starts_with(b"{.") {
+        return None;
+    }
+
+    let len = input[2..]
+        .iter()
+        .take_while(|&&c| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b'+' | b'#'))
+        .count();
+
+    if len > 0 && input.get(len + 2) == Some(&b'}') {
+        Some(len + 3)
+    } else {
+        None
+    }
+}
+
+// The paired delimiters of the extensions enabled in `options`.
+fn builtin_delimiters(options: &Options) -> Vec<InlineDelimiter> {
+    let mut delimiters = vec![];
+    if options.extension.strikethrough {
+        delimiters.push(InlineDelimiter {
+            delim_char: b'~',
+            min_length: 1,
+            max_length: Some(2),
+            same_length: true,
+            builder: |_| NodeValue::Strikethrough,
+        });
+    }
+    if options.extension.superscript {
+        delimiters.push(InlineDelimiter {
+            delim_char: b'^',
+            min_length: 1,
+            max_length: None,
+            same_length: false,
+            builder: |_| NodeValue::Superscript,
+        });
+    }
+    if options.extension.highlight {
+        // Only a run of exactly two `=` delimits a highlight.
+        delimiters.push(InlineDelimiter {
+            delim_char: b'=',
+            min_length: 2,
+            max_length: Some(2),
+            same_length: false,
+            builder: |_| NodeValue::Highlight,
+        });
+    }
+    if options.extension.philomena {
+        delimiters.push(InlineDelimiter {
+            delim_char: b'%',
+            min_length: 1,
+            max_length: None,
+            same_length: false,
+            builder: |_| NodeValue::Subscript,
+        });
+        delimiters.push(InlineDelimiter {
+            delim_char: b'|',
+            min_length: 1,
+            max_length: None,
+            same_length: false,
+            builder: |used| {
+                if used == 2 {
+                    NodeValue::SpoileredText
+                } else {
+                    NodeValue::EscapedTag("|".to_owned())
+                }
+            },
+        });
+    }
+    delimiters
+}
+
+// Whether `c` is from one of the scripts, or the punctuation or symbol
+// blocks, of Chinese, Japanese or Korean.
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1100}'..='\u{11ff}'
+            | '\u{2e80}'..='\u{a4cf}'
+            | '\u{a960}'..='\u{a97f}'
+            | '\u{ac00}'..='\u{d7ff}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{fe30}'..='\u{fe4f}'
+            | '\u{ff00}'..='\u{ffef}'
+            | '\u{20000}'..='\u{3fffd}'
+    )
+}
+
 pub fn make_inline<'a>(
     arena: &'a Arena<AstNode<'a>>,
     value: NodeValue,
@@ -1934,6 +2493,7 @@ pub fn make_inline<'a>(
         content: String::new(),
         sourcepos,
         internal_offset: 0,
+        line_starts: vec![],
         open: false,
         last_line_blank: false,
         table_visited: false,
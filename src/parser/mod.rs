@@ -1,7 +1,13 @@
+pub mod abbreviation;
+pub mod alert;
 mod autolink;
+pub mod details;
+pub mod fenced_div;
 mod inlines;
+mod reparse;
 #[cfg(feature = "shortcodes")]
 pub mod shortcodes;
+mod streaming;
 mod table;
 
 pub mod math;
@@ -9,12 +15,12 @@ pub mod multiline_block_quote;
 
 use crate::adapters::SyntaxHighlighterAdapter;
 use crate::arena_tree::Node;
-use crate::ctype::{isdigit, isspace};
+use crate::ctype::{isalpha, isdigit, isspace};
 use crate::entity;
-use crate::nodes::{self, NodeFootnoteDefinition, Sourcepos};
+use crate::nodes::{self, LineColumn, NodeFootnoteDefinition, Sourcepos};
 use crate::nodes::{
-    Ast, AstNode, ListDelimType, ListType, NodeCodeBlock, NodeDescriptionItem, NodeHeading,
-    NodeHtmlBlock, NodeList, NodeValue,
+    Ast, AstNode, ListDelimType, ListNumbering, ListType, NodeAlert, NodeCodeBlock,
+    NodeDescriptionItem, NodeHeading, NodeHtmlBlock, NodeList, NodeValue,
 };
 use crate::scanners;
 use crate::strings::{self, split_off_front_matter, Case};
@@ -27,18 +33,22 @@ use std::mem;
 use std::str;
 use typed_arena::Arena;
 
-use crate::adapters::HeadingAdapter;
+use crate::adapters::{HeadingAdapter, NodeRenderer};
+use crate::parser::details::NodeDetails;
+use crate::parser::fenced_div::NodeFencedDiv;
 use crate::parser::multiline_block_quote::NodeMultilineBlockQuote;
 
-use self::inlines::RefMap;
+pub use self::inlines::RefMap;
+pub use self::reparse::{reparse, TextEdit};
+pub use self::streaming::{parse_document_from_reader, StreamingParser};
 
 const TAB_STOP: usize = 4;
 const CODE_INDENT: usize = 4;
 
 // Very deeply nested lists can cause quadratic performance issues.
 // This constant is used in open_new_blocks() to limit the nesting
-// depth. It is unlikely that a non-contrived markdown document will
-// be nested this deeply.
+// depth, unless overridden by `parse.max_nesting_depth`. It is unlikely
+// that a non-contrived markdown document will be nested this deeply.
 const MAX_LIST_DEPTH: usize = 100;
 
 macro_rules! node_matches {
@@ -58,9 +68,71 @@ pub fn parse_document<'a>(
     buffer: &str,
     options: &Options,
 ) -> &'a AstNode<'a> {
-    parse_document_with_broken_link_callback(arena, buffer, options, None)
+    parse_document_with_callback(arena, buffer, options, None)
 }
 
+/// Parse a Markdown document to an AST, failing if the input can't be
+/// parsed under the given options.
+///
+/// The only such case at present is a NUL byte in the input when
+/// `nul_handling` is [`NulPolicy::Error`]; every other entry point treats that
+/// policy as [`NulPolicy::Replace`].
+///
+/// ```
+/// use comrak::{Arena, try_parse_document, NulPolicy, Options, ParseError};
+///
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.parse.nul_handling = NulPolicy::Error;
+///
+/// assert!(try_parse_document(&arena, "Fine.\n", &options).is_ok());
+/// assert_eq!(
+///     try_parse_document(&arena, "Fine.\nNot\0 fine.\n", &options).unwrap_err(),
+///     ParseError::Nul((2, 4).into()),
+/// );
+/// ```
+pub fn try_parse_document<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+) -> Result<&'a AstNode<'a>, ParseError> {
+    if options.parse.nul_handling == NulPolicy::Error {
+        if let Some(ix) = buffer.find('\0') {
+            let before = &buffer.as_bytes()[..ix];
+            let mut line = 1;
+            let mut line_start = 0;
+            for (i, &c) in before.iter().enumerate() {
+                if c == b'\n' || (c == b'\r' && before.get(i + 1) != Some(&b'\n')) {
+                    line += 1;
+                    line_start = i + 1;
+                }
+            }
+            return Err(ParseError::Nul((line, ix - line_start + 1).into()));
+        }
+    }
+
+    Ok(parse_document(arena, buffer, options))
+}
+
+/// An error returned by [`try_parse_document`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input has a NUL byte at the given position, and `nul_handling` is
+    /// [`NulPolicy::Error`].
+    Nul(LineColumn),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Nul(lc) => write!(f, "NUL byte at {}:{}", lc.line, lc.column),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
 /// Parse a Markdown document to an AST.
 ///
 /// In case the parser encounters any potential links that have a broken reference (e.g `[foo]`
@@ -101,7 +173,100 @@ pub fn parse_document<'a>(
 /// # Ok(())
 /// # }
 /// ```
-pub fn parse_document_with_broken_link_callback<'a, 'c>(
+///
+/// For more control, the callback can be any [`BrokenLinkCallback`], which is also given the
+/// label as written and can add HTML attributes to the link or insist on text.
+pub fn parse_document_with_broken_link_callback<'a, C>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+    callback: Option<&mut C>,
+) -> &'a AstNode<'a>
+where
+    C: BrokenLinkCallback + ?Sized,
+{
+    match callback {
+        Some(callback) => {
+            let mut adapter = |reference: BrokenLinkReference, _| match reference.kind {
+                ReferenceKind::Link | ReferenceKind::Image => callback.resolve(reference),
+                ReferenceKind::Footnote => BrokenLinkResolution::Default,
+            };
+            parse_document_with_callback(arena, buffer, options, Some(&mut adapter))
+        }
+        None => parse_document_with_callback(arena, buffer, options, None),
+    }
+}
+
+/// Parse a Markdown document to an AST, reporting every unresolved reference.
+///
+/// This is like [`parse_document_with_broken_link_callback`], except the callback is also told
+/// what kind of reference could not be resolved.  Links and images are reported as they are
+/// parsed, and a returned pair is used as the destination and title as usual.  With
+/// `extension.footnotes` enabled, footnote references naming no definition are reported
+/// afterwards as [`ReferenceKind::Footnote`]; the return value is ignored for these, and they
+/// aren't offered as links beforehand.
+///
+/// ```
+/// use comrak::{Arena, parse_document_with_unresolved_reference_callback, format_html, Options};
+/// use comrak::ReferenceKind;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let arena = Arena::new();
+/// let mut options = Options::default();
+/// options.extension.footnotes = true;
+///
+/// let mut missing = vec![];
+/// let root = parse_document_with_unresolved_reference_callback(
+///     &arena,
+///     "A [link], an ![image] and a footnote.[^c]",
+///     &options,
+///     Some(&mut |label: &str, kind: ReferenceKind| {
+///         missing.push((label.to_string(), kind));
+///         None
+///     }),
+/// );
+///
+/// assert_eq!(missing, vec![
+///     ("link".to_string(), ReferenceKind::Link),
+///     ("image".to_string(), ReferenceKind::Image),
+///     ("c".to_string(), ReferenceKind::Footnote),
+/// ]);
+///
+/// let mut output = Vec::new();
+/// format_html(root, &options, &mut output)?;
+/// assert_eq!(std::str::from_utf8(&output).unwrap(),
+///            "<p>A [link], an ![image] and a footnote.[^c]</p>\n");
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_document_with_unresolved_reference_callback<'a, 'c>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+    callback: Option<UnresolvedReferenceCallback<'c>>,
+) -> &'a AstNode<'a> {
+    match callback {
+        Some(callback) => {
+            let mut adapter = |reference: BrokenLinkReference, footnote_like| {
+                if footnote_like {
+                    return BrokenLinkResolution::Default;
+                }
+                match callback(reference.normalized, reference.kind) {
+                    Some((url, title)) => BrokenLinkResolution::Link {
+                        url,
+                        title,
+                        attrs: vec![],
+                    },
+                    None => BrokenLinkResolution::Default,
+                }
+            };
+            parse_document_with_callback(arena, buffer, options, Some(&mut adapter))
+        }
+        None => parse_document_with_callback(arena, buffer, options, None),
+    }
+}
+
+fn parse_document_with_callback<'a, 'c>(
     arena: &'a Arena<AstNode<'a>>,
     buffer: &str,
     options: &Options,
@@ -112,6 +277,7 @@ pub fn parse_document_with_broken_link_callback<'a, 'c>(
         content: String::new(),
         sourcepos: (1, 1, 1, 1).into(),
         internal_offset: 0,
+        line_starts: vec![],
         open: true,
         last_line_blank: false,
         table_visited: false,
@@ -122,17 +288,281 @@ pub fn parse_document_with_broken_link_callback<'a, 'c>(
     parser.finish(linebuf)
 }
 
-type Callback<'c> = &'c mut dyn FnMut(&str) -> Option<(String, String)>;
+/// Parse a Markdown document to an AST, resolving references against a shared
+/// [`RefMap`] as well as the document's own link reference definitions.
+///
+/// This saves re-parsing a common set of definitions for each of many small
+/// documents.  Where the document defines a label also in `refmap`, the
+/// document's definition wins, just as the first of several definitions does
+/// within a single document.
+///
+/// ```
+/// use comrak::{format_html, parse_document_with_refmap, Arena, Options, RefMap};
+///
+/// let options = Options::default();
+/// let shared = RefMap::from_document(
+///     "[docs]: https://example.com/docs\n[home]: https://example.com/\n",
+///     &options,
+/// );
+///
+/// let arena = Arena::new();
+/// let root = parse_document_with_refmap(
+///     &arena,
+///     "See [docs] and [home].\n\n[home]: /\n",
+///     &options,
+///     &shared,
+/// );
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<p>See <a href=\"https://example.com/docs\">docs</a> and <a href=\"/\">home</a>.</p>\n"
+/// );
+/// ```
+pub fn parse_document_with_refmap<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+    refmap: &RefMap,
+) -> &'a AstNode<'a> {
+    let root: &'a AstNode<'a> = arena.alloc(Node::new(RefCell::new(Ast {
+        value: NodeValue::Document,
+        content: String::new(),
+        sourcepos: (1, 1, 1, 1).into(),
+        internal_offset: 0,
+        line_starts: vec![],
+        open: true,
+        last_line_blank: false,
+        table_visited: false,
+    })));
+    let mut parser = Parser::new(arena, root, options, None);
+    parser.shared_refmap = Some(refmap);
+    let mut linebuf = Vec::with_capacity(buffer.len());
+    parser.feed(&mut linebuf, buffer, true);
+    parser.finish(linebuf)
+}
+
+/// Parse a Markdown document to an AST, also returning [`Diagnostic`]s for problems in the input
+/// that the parser silently handled: unresolved link, image and footnote references, fenced code
+/// blocks closed by the end of their container rather than a closing fence, list markers
+/// ignored because lists were nested too deeply, footnotes defined more than once, and headings
+/// with more than six `#`.  With [`ParseOptions::smart`], each piece of
+/// punctuation it replaced is reported too, so the replacement can be undone in the source.
+///
+/// Diagnostics are ordered by position.  They are only collected by this function, so the other
+/// entry points pay nothing for them.
+///
+/// ```
+/// use comrak::{Arena, parse_document_with_diagnostics, DiagnosticKind, Options, ReferenceKind};
+///
+/// let arena = Arena::new();
+/// let (_root, diagnostics) =
+///     parse_document_with_diagnostics(&arena, "See [the docs][docs].\n\n```\ncode\n", &Options::default());
+///
+/// assert_eq!(diagnostics.len(), 2);
+/// assert_eq!(diagnostics[0].kind, DiagnosticKind::UnresolvedReference(ReferenceKind::Link));
+/// assert_eq!(diagnostics[0].message, "unresolved link reference \"docs\"");
+/// assert_eq!(diagnostics[0].sourcepos, (1, 5, 1, 20).into());
+/// assert_eq!(diagnostics[1].kind, DiagnosticKind::UnclosedCodeFence);
+/// ```
+pub fn parse_document_with_diagnostics<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    buffer: &str,
+    options: &Options,
+) -> (&'a AstNode<'a>, Vec<Diagnostic>) {
+    let root: &'a AstNode<'a> = arena.alloc(Node::new(RefCell::new(Ast {
+        value: NodeValue::Document,
+        content: String::new(),
+        sourcepos: (1, 1, 1, 1).into(),
+        internal_offset: 0,
+        line_starts: vec![],
+        open: true,
+        last_line_blank: false,
+        table_visited: false,
+    })));
+    let mut parser = Parser::new(arena, root, options, None);
+    parser.diagnostics = Some(vec![]);
+    let mut linebuf = Vec::with_capacity(buffer.len());
+    parser.feed(&mut linebuf, buffer, true);
+    let root = parser.finish(linebuf);
+    let mut diagnostics = parser.diagnostics.unwrap_or_default();
+    diagnostics.sort_by_key(|d| d.sourcepos);
+    (root, diagnostics)
+}
+
+type UnresolvedReferenceCallback<'c> =
+    &'c mut dyn FnMut(&str, ReferenceKind) -> Option<(String, String)>;
+
+// The flag is set for a reference that would otherwise be taken as a footnote
+// reference.
+type Callback<'c> = &'c mut dyn FnMut(BrokenLinkReference, bool) -> BrokenLinkResolution;
+
+/// Decides what becomes of a reference link or image whose label has no definition, for
+/// [`parse_document_with_broken_link_callback`].
+///
+/// Any `FnMut(&str) -> Option<(String, String)>` is one, including a `dyn` one, so an existing
+/// `&mut dyn FnMut` can be passed as it is: it's given the normalized label, and returns the
+/// destination and title of a link, or `None` for the default treatment.
+///
+/// ```
+/// use comrak::{
+///     markdown_to_html, parse_document_with_broken_link_callback, format_html, Arena,
+///     BrokenLinkCallback, BrokenLinkReference, BrokenLinkResolution, Options,
+/// };
+///
+/// struct Wiki;
+///
+/// impl BrokenLinkCallback for Wiki {
+///     fn resolve(&mut self, reference: BrokenLinkReference) -> BrokenLinkResolution {
+///         if reference.original.starts_with("TODO") {
+///             return BrokenLinkResolution::Text;
+///         }
+///         BrokenLinkResolution::Link {
+///             url: format!("/wiki/{}", reference.normalized.replace(' ', "_")),
+///             title: String::new(),
+///             attrs: vec![("class".to_string(), "missing".to_string())],
+///         }
+///     }
+/// }
+///
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document_with_broken_link_callback(
+///     &arena,
+///     "See [Main Page] or [TODO: later].",
+///     &options,
+///     Some(&mut Wiki),
+/// );
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(String::from_utf8(html).unwrap(),
+///            "<p>See <a href=\"/wiki/main_page\" class=\"missing\">Main Page</a> \
+///             or [TODO: later].</p>\n");
+/// ```
+pub trait BrokenLinkCallback {
+    /// Resolves the broken reference described by `reference`.
+    fn resolve(&mut self, reference: BrokenLinkReference) -> BrokenLinkResolution;
+}
+
+impl<F> BrokenLinkCallback for F
+where
+    F: FnMut(&str) -> Option<(String, String)>,
+{
+    fn resolve(&mut self, reference: BrokenLinkReference) -> BrokenLinkResolution {
+        let callback: &mut dyn FnMut(&str) -> Option<(String, String)> = self;
+        callback.resolve(reference)
+    }
+}
+
+impl<'f> BrokenLinkCallback for dyn FnMut(&str) -> Option<(String, String)> + 'f {
+    fn resolve(&mut self, reference: BrokenLinkReference) -> BrokenLinkResolution {
+        match self(reference.normalized) {
+            Some((url, title)) => BrokenLinkResolution::Link {
+                url,
+                title,
+                attrs: vec![],
+            },
+            None => BrokenLinkResolution::Default,
+        }
+    }
+}
+
+/// A reference whose label has no definition, as given to a [`BrokenLinkCallback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrokenLinkReference<'l> {
+    /// The label normalized as described in the
+    /// [GFM spec](https://github.github.com/gfm/#matches): case-folded, with runs of
+    /// whitespace collapsed.
+    pub normalized: &'l str,
+
+    /// The label as written in the document.
+    pub original: &'l str,
+
+    /// Whether the reference is a link or an image.
+    pub kind: ReferenceKind,
+}
+
+/// What a [`BrokenLinkCallback`] makes of a broken reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrokenLinkResolution {
+    /// Make it a link or image after all.
+    Link {
+        /// The destination of the link, or source of the image.
+        url: String,
+        /// The title of the link or image.
+        title: String,
+        /// Extra HTML attributes, names and unescaped values; see
+        /// [`NodeLink::attrs`](crate::nodes::NodeLink::attrs).
+        attrs: Vec<(String, String)>,
+    },
+    /// Leave it as text, even where it would otherwise be taken as a footnote reference.
+    Text,
+    /// Treat it as if there were no callback: it's left as text, unless it's a footnote
+    /// reference.
+    Default,
+}
+
+/// The kind of reference passed to the callback of
+/// [`parse_document_with_unresolved_reference_callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// A reference link, e.g. `[text][label]`, `[label][]` or `[label]`.
+    Link,
+    /// A reference image, e.g. `![alt][label]`.
+    Image,
+    /// A footnote reference, e.g. `[^label]`.
+    Footnote,
+}
+
+/// A problem in the input reported by [`parse_document_with_diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Where in the input the problem is.
+    pub sourcepos: Sourcepos,
+
+    /// What kind of problem it is.
+    pub kind: DiagnosticKind,
+
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+/// The kind of a [`Diagnostic`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A reference naming no definition; it is rendered as plain text.
+    UnresolvedReference(ReferenceKind),
+    /// A fenced code block with no closing fence, closed by the end of the
+    /// document or of its container.
+    UnclosedCodeFence,
+    /// A list or block quote marker ignored because blocks are already
+    /// nested too deeply.
+    NestingLimit,
+    /// Punctuation replaced by the `smart` option, such as `--` by an en
+    /// dash; the message gives the original and replacement text.
+    SmartPunctuation,
+    /// A footnote definition ignored because a later one has the same label.
+    DuplicateFootnoteDefinition,
+    /// A run of more than six `#` which would otherwise start an ATX
+    /// heading; it is read as text.
+    HeadingTooDeep,
+}
 
 pub struct Parser<'a, 'o, 'c> {
     arena: &'a Arena<AstNode<'a>>,
     refmap: RefMap,
+    // Abbreviations and their expansions, for the abbreviations extension.
+    abbreviations: abbreviation::Abbreviations,
     root: &'a AstNode<'a>,
     current: &'a AstNode<'a>,
     line_number: usize,
     offset: usize,
     column: usize,
     thematic_break_kill_pos: usize,
+    continued_table_row: Option<&'a AstNode<'a>>,
     first_nonspace: usize,
     first_nonspace_column: usize,
     indent: usize,
@@ -145,6 +575,12 @@ pub struct Parser<'a, 'o, 'c> {
     total_size: usize,
     options: &'o Options,
     callback: Option<Callback<'c>>,
+    diagnostics: Option<Vec<Diagnostic>>,
+    // Definitions shared across documents, for parse_document_with_refmap.
+    shared_refmap: Option<&'o RefMap>,
+    closing_fence: bool,
+    // Fenced code blocks without an info string, for default_info_string_fn.
+    missing_info: Vec<&'a AstNode<'a>>,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -207,6 +643,53 @@ pub struct ExtensionOptions {
     /// ```
     pub table: bool,
 
+    /// Enables tables without a header row.  Two or more consecutive lines
+    /// beginning with `|` and having the same number of cells form a table
+    /// consisting only of body rows.  Requires `table` to be enabled.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// options.extension.tables_without_header = true;
+    /// assert_eq!(markdown_to_html("| a | b |\n| c | d |\n", &options),
+    ///            "<table>\n<tbody>\n<tr>\n<td>a</td>\n<td>b</td>\n</tr>\n\
+    ///             <tr>\n<td>c</td>\n<td>d</td>\n</tr>\n</tbody>\n</table>\n");
+    /// ```
+    pub tables_without_header: bool,
+
+    /// Enables pandoc-style table captions.  A paragraph starting with `Table:`
+    /// directly above a table (with no blank line between) becomes its
+    /// caption; failing that, such a paragraph directly below the table is
+    /// used.  Requires `table` to be enabled.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// options.extension.table_captions = true;
+    /// assert_eq!(markdown_to_html("| a |\n|---|\n| b |\nTable: *Letters*\n", &options),
+    ///            "<table>\n<caption><em>Letters</em></caption>\n<thead>\n<tr>\n<th>a</th>\n</tr>\n\
+    ///             </thead>\n<tbody>\n<tr>\n<td>b</td>\n</tr>\n</tbody>\n</table>\n");
+    /// ```
+    pub table_captions: bool,
+
+    /// Allows a table cell to span several lines of source.  A body row
+    /// ending in a backslash continues on the next line, whose cells are
+    /// joined to the row's with a line break.  Requires `table` to be
+    /// enabled.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// options.extension.table_cell_line_breaks = true;
+    /// assert_eq!(markdown_to_html("| a | b |\n|---|---|\n| c | d |\\\n|   | e |\n", &options),
+    ///            "<table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n\
+    ///             <tbody>\n<tr>\n<td>c</td>\n<td>d<br />\ne</td>\n</tr>\n</tbody>\n</table>\n");
+    /// ```
+    pub table_cell_line_breaks: bool,
+
     /// Enables the [autolink extension](https://github.github.com/gfm/#autolinks-extension-)
     /// from the GFM spec.
     ///
@@ -217,6 +700,19 @@ pub struct ExtensionOptions {
     /// assert_eq!(markdown_to_html("Hello www.github.com.\n", &options),
     ///            "<p>Hello <a href=\"http://www.github.com\">www.github.com</a>.</p>\n");
     /// ```
+    ///
+    /// A backslash immediately before a candidate suppresses the link, and is
+    /// itself removed from the output.  An escaped backslash, `\\`, doesn't.  Since the character following it isn't
+    /// punctuation, this isn't a CommonMark backslash escape, and so isn't
+    /// wrapped in a span by `render.escaped_char_spans`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// assert_eq!(markdown_to_html("Hello \\www.github.com.\n", &options),
+    ///            "<p>Hello www.github.com.</p>\n");
+    /// ```
     pub autolink: bool,
 
     /// Enables the
@@ -248,6 +744,18 @@ pub struct ExtensionOptions {
     /// ```
     pub superscript: bool,
 
+    /// Enables the highlight extension, which marks text between `==`
+    /// delimiters.  Runs of more or fewer than two `=` are left as text.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.highlight = true;
+    /// assert_eq!(markdown_to_html("Some ==*marked*== text, not ===this===.\n", &options),
+    ///            "<p>Some <mark><em>marked</em></mark> text, not ===this===.</p>\n");
+    /// ```
+    pub highlight: bool,
+
     /// Enables the Philomena Comrak extension.
     ///
     /// ```
@@ -269,6 +777,11 @@ pub struct ExtensionOptions {
 
     /// Additional replacements for the Philomena Comrak extension.
     ///
+    /// The id following `>>` is scanned in full and then looked up exactly, so
+    /// with overlapping keys such as `12` and `1234p`, `>>1234p` always uses
+    /// the longer key and `>>12` the shorter; the choice never depends on the
+    /// map's iteration order.
+    ///
     /// ```
     /// # use comrak::{markdown_to_html, ComrakOptions};
     /// let mut options = ComrakOptions::default();
@@ -314,6 +827,64 @@ pub struct ExtensionOptions {
     /// ```
     pub header_ids: Option<String>,
 
+    /// How the IDs generated by `header_ids` are placed in the output.
+    ///
+    /// * `HeaderIdStyle::AnchorBefore` puts them on an empty anchor at the start of the
+    ///   heading (default)
+    /// * `HeaderIdStyle::AnchorAfter` puts the same anchor at the end of the heading
+    /// * `HeaderIdStyle::OnHeading` puts them on the heading element itself
+    /// * `HeaderIdStyle::None` omits them
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, HeaderIdStyle, Options};
+    /// let mut options = Options::default();
+    /// options.extension.header_ids = Some("user-content-".to_string());
+    /// options.extension.header_id_style = HeaderIdStyle::OnHeading;
+    /// assert_eq!(markdown_to_html("# README\n", &options),
+    ///            "<h1 id=\"user-content-readme\">README</h1>\n");
+    ///
+    /// options.extension.header_id_style = HeaderIdStyle::AnchorAfter;
+    /// assert_eq!(markdown_to_html("# README\n", &options),
+    ///            "<h1>README<a href=\"#readme\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-readme\"></a></h1>\n");
+    ///
+    /// options.extension.header_id_style = HeaderIdStyle::None;
+    /// assert_eq!(markdown_to_html("# README\n", &options),
+    ///            "<h1>README</h1>\n");
+    /// ```
+    pub header_id_style: HeaderIdStyle,
+
+    /// HTML to put inside the anchor written for `HeaderIdStyle::AnchorBefore`
+    /// or `HeaderIdStyle::AnchorAfter`, such as a link symbol, which is otherwise left empty.  It is written
+    /// as given, without escaping.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.header_ids = Some("".to_string());
+    /// options.extension.header_anchor_content = Some("#".to_string());
+    /// assert_eq!(markdown_to_html("# README\n", &options),
+    ///            "<h1><a href=\"#readme\" aria-hidden=\"true\" class=\"anchor\" id=\"readme\">#</a>README</h1>\n");
+    /// ```
+    pub header_anchor_content: Option<String>,
+
+    /// Makes header IDs from a heading's text with the given function rather
+    /// than the built-in GFM algorithm.  The prefix from `header_ids` is still
+    /// added, and an ID already in use still gets a `-1`, `-2`, ... suffix.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// fn slugify(text: &str) -> String {
+    ///     text.split_whitespace().collect::<Vec<_>>().join("_")
+    /// }
+    /// options.extension.header_ids = Some("h-".to_string());
+    /// options.extension.header_id_slugifier = Some(slugify);
+    /// assert_eq!(markdown_to_html("# Grüße, Welt\n# Grüße, Welt\n", &options),
+    ///            "<h1><a href=\"#Grüße,_Welt\" aria-hidden=\"true\" class=\"anchor\" id=\"h-Grüße,_Welt\"></a>Grüße, Welt</h1>\n\
+    ///             <h1><a href=\"#Grüße,_Welt-1\" aria-hidden=\"true\" class=\"anchor\" id=\"h-Grüße,_Welt-1\"></a>Grüße, Welt</h1>\n");
+    /// ```
+    pub header_id_slugifier: Option<fn(&str) -> String>,
+
     /// Enables the footnotes extension per `cmark-gfm`.
     ///
     /// For usage, see `src/tests.rs`.  The extension is modelled after
@@ -328,13 +899,45 @@ pub struct ExtensionOptions {
     /// ```
     pub footnotes: bool,
 
+    /// Render footnote definitions which are never referenced, numbered after
+    /// all referenced footnotes in document order.  Normally such definitions
+    /// are dropped.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.extension.footnotes_render_unreferenced = true;
+    /// assert_eq!(markdown_to_html("Hi.\n\n[^orphan]: Nobody loves me.\n", &options),
+    ///            "<p>Hi.</p>\n<section class=\"footnotes\" data-footnotes>\n<ol>\n\
+    ///             <li id=\"fn-orphan\">\n<p>Nobody loves me.</p>\n</li>\n</ol>\n</section>\n");
+    /// ```
+    pub footnotes_render_unreferenced: bool,
+
+    /// The number given to the first footnote, for fragments continuing the
+    /// footnote sequence of another document.  Reference numbers count up
+    /// from here and the footnote section's `<ol>` gets a matching `start`.
+    /// `None`, `Some(0)` and `Some(1)` all number from 1.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.extension.footnote_start_number = Some(4);
+    /// assert_eq!(markdown_to_html("Hi[^x].\n\n[^x]: A greeting.\n", &options),
+    ///            "<p>Hi<sup class=\"footnote-ref\"><a href=\"#fn-x\" id=\"fnref-x\" data-footnote-ref>4</a></sup>.</p>\n\
+    ///             <section class=\"footnotes\" data-footnotes>\n<ol start=\"4\">\n<li id=\"fn-x\">\n\
+    ///             <p>A greeting. <a href=\"#fnref-x\" class=\"footnote-backref\" data-footnote-backref \
+    ///             data-footnote-backref-idx=\"4\" aria-label=\"Back to reference 4\">↩</a></p>\n\
+    ///             </li>\n</ol>\n</section>\n");
+    /// ```
+    pub footnote_start_number: Option<u32>,
+
     /// Enables the description lists extension.
     ///
     /// Each term must be defined in one paragraph, followed by a blank line,
     /// and then by the details.  Details begins with a colon.
     ///
-    /// Not (yet) compatible with render.sourcepos.
-    ///
     /// ``` md
     /// First term
     ///
@@ -352,25 +955,87 @@ pub struct ExtensionOptions {
     /// let mut options = Options::default();
     /// options.extension.description_lists = true;
     /// assert_eq!(markdown_to_html("Term\n\n: Definition", &options),
-    ///            "<dl><dt>Term</dt>\n<dd>\n<p>Definition</p>\n</dd>\n</dl>\n");
+    ///            "<dl><dt>Term</dt>\n<dd>Definition</dd>\n</dl>\n");
     /// ```
     pub description_lists: bool,
 
-    /// Enables the front matter extension.
-    ///
-    /// Front matter, which begins with the delimiter string at the beginning of the file and ends
-    /// at the end of the next line that contains only the delimiter, is passed through unchanged
-    /// in markdown output and omitted from HTML output.
-    ///
-    /// ``` md
-    /// ---
-    /// layout: post
-    /// title: Formatting Markdown with Comrak
-    /// ---
+    /// The character which introduces the details of a description list
+    /// item.  Defaults to `:` when `None`.  Only ASCII characters are
+    /// recognised.
     ///
-    /// # Shorter Title
-    ///
-    /// etc.
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.description_lists = true;
+    /// options.extension.description_list_marker = Some('~');
+    /// assert_eq!(markdown_to_html("Term\n\n~ Definition", &options),
+    ///            "<dl><dt>Term</dt>\n<dd>Definition</dd>\n</dl>\n");
+    /// ```
+    pub description_list_marker: Option<char>,
+
+    /// With `description_lists`, read each line of a term paragraph as a
+    /// term of its own, and allow further details to follow an item's
+    /// first details, so that several terms can share several details.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.description_lists = true;
+    /// options.extension.description_list_grouped_terms = true;
+    /// assert_eq!(markdown_to_html("Colour\nColor\n\n: A hue\n\n: A tint\n", &options),
+    ///            "<dl><dt>Colour</dt>\n<dt>Color</dt>\n<dd>A hue</dd>\n<dd>A tint</dd>\n</dl>\n");
+    /// ```
+    pub description_list_grouped_terms: bool,
+
+    /// Enables ordered lists numbered with letters or roman numerals, such
+    /// as `a.`, `B)` or `iv.`; these render with the `type` attribute.
+    ///
+    /// A marker may have up to 8 letters, all in the same case.  A single
+    /// `i` starts a roman list and other single letters an alphabetical
+    /// one, though a letter continuing an existing list is read in its
+    /// style.  Past `z`, an alphabetical list continues with `aa`, `ab`
+    /// and so on.  These lists can't interrupt a paragraph, and as in
+    /// Pandoc, a single capital letter followed by a period needs two
+    /// spaces or a tab after it, so that `A. Smith` stays text.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.alpha_lists = true;
+    /// assert_eq!(markdown_to_html("c. one\nd. two\n", &options),
+    ///            "<ol type=\"a\" start=\"3\">\n<li>one</li>\n<li>two</li>\n</ol>\n");
+    /// assert_eq!(markdown_to_html("I) one\nII) two\n", &options),
+    ///            "<ol type=\"I\">\n<li>one</li>\n<li>two</li>\n</ol>\n");
+    /// ```
+    pub alpha_lists: bool,
+
+    /// Detects ordered lists whose items count down by one each, rendering
+    /// them with the `reversed` attribute.  Such lists always carry `start`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.reversed_lists = true;
+    /// assert_eq!(markdown_to_html("3. one\n2. two\n1. three\n", &options),
+    ///            "<ol reversed start=\"3\">\n<li>one</li>\n<li>two</li>\n<li>three</li>\n</ol>\n");
+    /// ```
+    pub reversed_lists: bool,
+
+    /// Enables the front matter extension.
+    ///
+    /// Front matter, which begins with the delimiter string at the beginning of the file and ends
+    /// at the end of the next line that contains only the delimiter, is passed through unchanged
+    /// in markdown output and omitted from HTML output.
+    ///
+    /// ``` md
+    /// ---
+    /// layout: post
+    /// title: Formatting Markdown with Comrak
+    /// ---
+    ///
+    /// # Shorter Title
+    ///
+    /// etc.
     /// ```
     ///
     /// ```
@@ -421,6 +1086,76 @@ pub struct ExtensionOptions {
     /// ```
     pub multiline_block_quotes: bool,
 
+    /// Enables GitHub-style alerts: block quotes whose first line is a
+    /// marker such as `[!NOTE]`, `[!TIP]`, `[!IMPORTANT]`, `[!WARNING]` or
+    /// `[!CAUTION]`.  The marker is matched case-insensitively, and any text
+    /// after it on the line replaces the default title.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.alerts = true;
+    /// assert_eq!(markdown_to_html("> [!note]\n> Something of note\n", &options),
+    ///            "<div class=\"markdown-alert markdown-alert-note\">\n\
+    ///             <p class=\"markdown-alert-title\">Note</p>\n\
+    ///             <p>Something of note</p>\n</div>\n");
+    /// ```
+    pub alerts: bool,
+
+    /// Enables collapsible details blocks, fenced by `:::` lines.  The
+    /// opening fence reads `::: details`, followed by an optional summary;
+    /// `:::+ details` starts the block expanded.  The closing fence must be
+    /// at least as long as the opening one.  The summary is plain text: it
+    /// isn't parsed for inlines, so Markdown in it is written as is.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.details = true;
+    /// assert_eq!(markdown_to_html("::: details More *info*\nHidden.\n:::\n", &options),
+    ///            "<details>\n<summary>More *info*</summary>\n<p>Hidden.</p>\n</details>\n");
+    /// assert_eq!(markdown_to_html(":::+ details\nShown.\n:::\n", &options),
+    ///            "<details open>\n<p>Shown.</p>\n</details>\n");
+    /// ```
+    pub details: bool,
+
+    /// Enables generic fenced divs, as in Pandoc.  The opening fence is
+    /// three or more colons followed by a class name, or by attributes in
+    /// braces; the closing fence is a bare run of colons at least as long,
+    /// and closes the innermost div it can.  When `details` is also enabled,
+    /// `::: details` still opens a details block.  Unless `render.unsafe_` is
+    /// set, only classes, ids and `data-*` attributes are rendered.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.fenced_divs = true;
+    /// assert_eq!(markdown_to_html("::: warning\nCareful.\n:::\n", &options),
+    ///            "<div class=\"warning\">\n<p>Careful.</p>\n</div>\n");
+    /// assert_eq!(markdown_to_html("::: {.note #intro lang=en}\nHi.\n:::\n", &options),
+    ///            "<div class=\"note\" id=\"intro\">\n<p>Hi.</p>\n</div>\n");
+    ///
+    /// options.render.unsafe_ = true;
+    /// assert_eq!(markdown_to_html("::: {.note #intro lang=en}\nHi.\n:::\n", &options),
+    ///            "<div class=\"note\" id=\"intro\" lang=\"en\">\n<p>Hi.</p>\n</div>\n");
+    /// ```
+    pub fenced_divs: bool,
+
+    /// Enables abbreviations, as in PHP Markdown Extra.  A line such as
+    /// `*[HTML]: HyperText Markup Language` defines one, and like a link
+    /// reference definition it can appear wherever a paragraph can.  Every
+    /// whole-word, case-sensitive occurrence of the abbreviation in text is
+    /// then marked up with its expansion; code and link text are left alone.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.abbreviations = true;
+    /// assert_eq!(markdown_to_html("*[HTML]: HyperText Markup Language\n\nHTML, not HTMLX.\n", &options),
+    ///            "<p><abbr title=\"HyperText Markup Language\">HTML</abbr>, not HTMLX.</p>\n");
+    /// ```
+    pub abbreviations: bool,
+
     /// Enables math using dollar syntax.
     ///
     /// ``` md
@@ -509,6 +1244,38 @@ pub struct ExtensionOptions {
     /// ```
     pub wikilinks_title_before_pipe: bool,
 
+    /// Slugifies wikilink targets when rendering to HTML, replacing each run
+    /// of whitespace with the given separator.  The target is kept as written
+    /// in the AST.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.wikilinks_title_after_pipe = true;
+    /// options.extension.wikilinks_slug_separator = Some('_');
+    /// assert_eq!(markdown_to_html("[[Main  Page|home]]", &options),
+    ///            "<p><a href=\"Main_Page\" data-wikilink=\"true\">home</a></p>\n");
+    /// ```
+    pub wikilinks_slug_separator: Option<char>,
+
+    /// Resolves the URL a wikilink points to from its target, after any
+    /// slugifying by `wikilinks_slug_separator`.  The result is still subject
+    /// to the usual check for dangerous URLs.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// fn resolve(target: &str) -> String {
+    ///     format!("/wiki/{}", target.to_lowercase())
+    /// }
+    /// options.extension.wikilinks_title_after_pipe = true;
+    /// options.extension.wikilinks_slug_separator = Some('-');
+    /// options.extension.wikilinks_url_resolver = Some(resolve);
+    /// assert_eq!(markdown_to_html("[[Getting Started]]", &options),
+    ///            "<p><a href=\"/wiki/getting-started\" data-wikilink=\"true\">Getting Started</a></p>\n");
+    /// ```
+    pub wikilinks_url_resolver: Option<fn(&str) -> String>,
+
     /// Wraps image URLs using camoifier.
     ///
     /// ```
@@ -521,6 +1288,49 @@ pub struct ExtensionOptions {
     ///            "<p><img src=\"https://safe-proxy.com?url=http://unsafe.evil/bad.png\" alt=\"\" /></p>\n");
     /// ```
     pub camoifier: Option<fn(String) -> String>,
+
+    /// Enables `srcset` image destinations: a comma-separated list of
+    /// candidates, each a URL followed by a width (`640w`) or pixel density
+    /// (`2x`) descriptor.  The image is written with a `srcset` attribute, and
+    /// the first candidate is used for `src`.
+    ///
+    /// ``` md
+    /// ![alt](small.jpg 1x, large.jpg 2x)
+    /// ```
+    ///
+    /// A destination with no descriptors, or one that doesn't follow this
+    /// form exactly, is parsed as usual.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.image_srcset = true;
+    /// assert_eq!(markdown_to_html("![alt](small.jpg 1x, large.jpg 2x)", &options),
+    ///            "<p><img src=\"small.jpg\" srcset=\"small.jpg 1x, large.jpg 2x\" alt=\"alt\" /></p>\n");
+    /// assert_eq!(markdown_to_html("![alt](small.jpg)", &options),
+    ///            "<p><img src=\"small.jpg\" alt=\"alt\" /></p>\n");
+    /// ```
+    pub image_srcset: bool,
+
+    /// Enables language annotations on inline code, written as `{.lang}`
+    /// directly after the closing backticks.  The code is given a
+    /// `language-` class, as fenced code blocks are.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.inline_code_lang = true;
+    /// assert_eq!(markdown_to_html("Call `fn main()`{.rust} first.", &options),
+    ///            "<p>Call <code class=\"language-rust\">fn main()</code> first.</p>\n");
+    /// ```
+    pub inline_code_lang: bool,
+}
+
+impl ExtensionOptions {
+    // Footnotes are numbered from one past this; see `footnote_start_number`.
+    pub(crate) fn footnote_base(&self) -> u32 {
+        self.footnote_start_number.unwrap_or(1).saturating_sub(1)
+    }
 }
 
 #[non_exhaustive]
@@ -534,214 +1344,1109 @@ pub struct ParseOptions {
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
-    ///            "<p>'Hello,' &quot;world&quot; ...</p>\n");
-    ///
-    /// options.parse.smart = true;
-    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
-    ///            "<p>‘Hello,’ “world” …</p>\n");
+    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
+    ///            "<p>'Hello,' &quot;world&quot; ...</p>\n");
+    ///
+    /// options.parse.smart = true;
+    /// assert_eq!(markdown_to_html("'Hello,' \"world\" ...", &options),
+    ///            "<p>‘Hello,’ “world” …</p>\n");
+    /// ```
+    pub smart: bool,
+
+    /// The default info string for fenced code blocks.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
+    ///            "<pre><code>fn hello();\n</code></pre>\n");
+    ///
+    /// options.parse.default_info_string = Some("rust".into());
+    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
+    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+    /// ```
+    pub default_info_string: Option<String>,
+
+    /// Choose the info string for a fenced code block that has none, given
+    /// the code block's node.  This is called once the whole document has
+    /// been parsed, so the surrounding nodes are complete, and
+    /// `default_info_string` is used when it returns `None`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// # use comrak::nodes::{AstNode, NodeValue};
+    /// // Code in block quotes is shell, everything else is Rust.
+    /// fn info<'a>(node: &'a AstNode<'a>) -> Option<String> {
+    ///     match node.parent()?.data.borrow().value {
+    ///         NodeValue::BlockQuote => Some("sh".to_string()),
+    ///         _ => None,
+    ///     }
+    /// }
+    ///
+    /// let mut options = Options::default();
+    /// options.parse.default_info_string = Some("rust".into());
+    /// options.parse.default_info_string_fn = Some(info);
+    /// assert_eq!(markdown_to_html("```\nfn x();\n```\n> ```\n> ls\n> ```\n", &options),
+    ///            "<pre><code class=\"language-rust\">fn x();\n</code></pre>\n\
+    ///             <blockquote>\n<pre><code class=\"language-sh\">ls\n</code></pre>\n</blockquote>\n");
+    /// ```
+    pub default_info_string_fn: Option<for<'a> fn(&'a AstNode<'a>) -> Option<String>>,
+
+    /// Whether or not a simple `x` or `X` is used for tasklist or any other symbol is allowed.
+    pub relaxed_tasklist_matching: bool,
+
+    /// Relax parsing of autolinks, allow links to be detected inside brackets
+    /// and allow all url schemes
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
+    ///            "<p>[https://foo.com]</p>\n");
+    ///
+    /// options.parse.relaxed_autolinks = true;
+    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
+    ///            "<p>[<a href=\"https://foo.com\">https://foo.com</a>]</p>\n");
+    /// ```
+    pub relaxed_autolinks: bool,
+
+    /// With the `autolink` extension, also link bare domains, without a
+    /// `www.` prefix or a scheme, when their top-level domain is one of
+    /// these.  Matching ignores case and a leading `.`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// assert_eq!(markdown_to_html("See wiki.corp.internal/start", &options),
+    ///            "<p>See wiki.corp.internal/start</p>\n");
+    ///
+    /// options.parse.autolink_extra_tlds = Some(vec!["internal".to_string()]);
+    /// assert_eq!(markdown_to_html("See wiki.corp.internal/start", &options),
+    ///            "<p>See <a href=\"http://wiki.corp.internal/start\">wiki.corp.internal/start</a></p>\n");
+    /// ```
+    pub autolink_extra_tlds: Option<Vec<String>>,
+
+    /// With the `autolink` extension, also link bare URLs with these
+    /// schemes, besides `http`, `https` and `ftp`.  Matching ignores case,
+    /// and the scheme must start a word.  A scheme may be followed by `//`
+    /// and a domain, as usual, or directly by a path starting with a letter,
+    /// digit or `+`, as in `tel:+123` or `matrix:u/alice:example.org`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// assert_eq!(markdown_to_html("Call tel:+1-555-0100.", &options),
+    ///            "<p>Call tel:+1-555-0100.</p>\n");
+    ///
+    /// options.parse.autolink_extra_schemes = Some(vec!["tel".to_string()]);
+    /// assert_eq!(markdown_to_html("Call tel:+1-555-0100.", &options),
+    ///            "<p>Call <a href=\"tel:+1-555-0100\">tel:+1-555-0100</a>.</p>\n");
+    /// ```
+    pub autolink_extra_schemes: Option<Vec<String>>,
+
+    /// With the `autolink` extension, only link `www.` domains and email
+    /// addresses whose top-level domain is one of these, or one of
+    /// `autolink_extra_tlds`.  Links with an explicit scheme are unaffected.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// options.parse.autolink_allowed_tlds = Some(vec!["com".to_string()]);
+    /// assert_eq!(markdown_to_html("www.example.com and www.notes.txt", &options),
+    ///            "<p><a href=\"http://www.example.com\">www.example.com</a> and www.notes.txt</p>\n");
+    /// ```
+    pub autolink_allowed_tlds: Option<Vec<String>>,
+
+    /// Don't recognise [link reference definitions](https://github.github.com/gfm/#link-reference-definitions).
+    /// Such lines are left as paragraph text and reference links never resolve.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("[a]\n\n[a]: https://example.com\n", &options),
+    ///            "<p><a href=\"https://example.com\">a</a></p>\n");
+    ///
+    /// options.parse.no_reference_definitions = true;
+    /// assert_eq!(markdown_to_html("[a]\n\n[a]: https://example.com\n", &options),
+    ///            "<p>[a]</p>\n<p>[a]: https://example.com</p>\n");
+    /// ```
+    pub no_reference_definitions: bool,
+
+    /// Start or continue a block quote at a `>` not followed by a space,
+    /// as in `>quoted`.  By default such lines are left as text, which
+    /// keeps "greentext" like `>implying` intact.  A `>` alone on a line
+    /// still doesn't continue a block quote.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html(">a\n>>b\n", &options),
+    ///            "<p>&gt;a\n&gt;&gt;b</p>\n");
+    ///
+    /// options.parse.blockquote_no_space_required = true;
+    /// assert_eq!(markdown_to_html(">a\n>>b\n", &options),
+    ///            "<blockquote>\n<p>a</p>\n<blockquote>\n<p>b</p>\n</blockquote>\n</blockquote>\n");
+    /// ```
+    pub blockquote_no_space_required: bool,
+
+    /// Keep a block quote open across blank lines.
+    ///
+    /// By default, a line holding only `>` ends a block quote and is left
+    /// as text, and an empty line ends it too.  With this option, either
+    /// kind of line continues the block quote, ending any paragraph in it,
+    /// so quote lines after it join the same block quote.  If a line which
+    /// isn't part of a quote follows instead, the block quote ends before
+    /// it as usual.  A `>` alone never starts a block quote.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("> a\n>\n> b\n", &options),
+    ///            "<blockquote>\n<p>a</p>\n</blockquote>\n<p>&gt;</p>\n\
+    ///             <blockquote>\n<p>b</p>\n</blockquote>\n");
+    ///
+    /// options.parse.lazy_blockquote_continuation = true;
+    /// assert_eq!(markdown_to_html("> a\n>\n> b\n", &options),
+    ///            "<blockquote>\n<p>a</p>\n<p>b</p>\n</blockquote>\n");
+    /// assert_eq!(markdown_to_html("> a\n\n> b\n", &options),
+    ///            "<blockquote>\n<p>a</p>\n<p>b</p>\n</blockquote>\n");
+    /// ```
+    pub lazy_blockquote_continuation: bool,
+
+    /// Skip a byte order mark at the start of each piece of input pushed to
+    /// a [`StreamingParser`], not only the first, such as when documents
+    /// that each begin with one are parsed as one.  A byte order mark
+    /// elsewhere is kept, as is usual.
+    ///
+    /// ```
+    /// # use comrak::{format_html, Arena, Options, StreamingParser};
+    /// let arena = Arena::new();
+    /// let mut options = Options::default();
+    /// options.parse.strip_bom = true;
+    ///
+    /// let mut parser = StreamingParser::new(&arena, &options);
+    /// parser.push("\u{feff}a\n\n");
+    /// parser.push("\u{feff}b \u{feff}c\n");
+    /// let root = parser.finish();
+    ///
+    /// let mut html = vec![];
+    /// format_html(root, &options, &mut html).unwrap();
+    /// assert_eq!(String::from_utf8(html).unwrap(), "<p>a</p>\n<p>b \u{feff}c</p>\n");
+    /// ```
+    pub strip_bom: bool,
+
+    /// Relax the rules for where emphasis may open and close next to CJK
+    /// text.  Delimiter runs are normally only allowed to close after
+    /// punctuation if they're followed by whitespace or punctuation, and
+    /// likewise to open; since CJK text isn't separated by spaces, that
+    /// leaves emphasis ending in a full-width bracket or comma unclosed.
+    /// With this option a CJK character counts as such a boundary too.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("**「強調」**です", &options),
+    ///            "<p>**「強調」**です</p>\n");
+    ///
+    /// options.parse.cjk_friendly_emphasis = true;
+    /// assert_eq!(markdown_to_html("**「強調」**です", &options),
+    ///            "<p><strong>「強調」</strong>です</p>\n");
+    /// ```
+    pub cjk_friendly_emphasis: bool,
+
+    /// Keep a hard line break which ends the last paragraph of a list item.
+    /// Normally, as for any other block, a hard break at the very end is
+    /// dropped (or, for a backslash, left as a literal `\`).
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("- a\\\n- b  \n- c\n", &options),
+    ///            "<ul>\n<li>a\\</li>\n<li>b</li>\n<li>c</li>\n</ul>\n");
+    ///
+    /// options.parse.list_item_trailing_hard_break = true;
+    /// assert_eq!(markdown_to_html("- a\\\n- b  \n- c\n", &options),
+    ///            "<ul>\n<li>a<br />\n</li>\n<li>b<br />\n</li>\n<li>c</li>\n</ul>\n");
+    /// ```
+    pub list_item_trailing_hard_break: bool,
+
+    /// Leave the text of link descriptions and image alt text alone when
+    /// `smart` is enabled, converting punctuation only outside of them.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.parse.smart = true;
+    /// options.parse.smart_skip_links = true;
+    /// assert_eq!(markdown_to_html("\"Hi\" -- ![\"Hi\" -- ...](a.png)", &options),
+    ///            "<p>“Hi” – <img src=\"a.png\" alt=\"&quot;Hi&quot; -- ...\" /></p>\n");
+    /// ```
+    pub smart_skip_links: bool,
+
+    /// When `smart` is enabled, parse matched pairs of double quotes into
+    /// [`NodeValue::Quoted`](crate::nodes::NodeValue::Quoted) nodes, which
+    /// HTML output writes as `<q>` elements, leaving the quote marks to the
+    /// stylesheet.  Quotes which don't pair up are still written as glyphs,
+    /// as are all quotes in link text with `smart_skip_links`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.parse.smart = true;
+    /// assert_eq!(markdown_to_html("\"Hi\", she said. 5\" tall.\n", &options),
+    ///            "<p>“Hi”, she said. 5” tall.</p>\n");
+    ///
+    /// options.parse.quote_elements = true;
+    /// assert_eq!(markdown_to_html("\"Hi\", she said. 5\" tall.\n", &options),
+    ///            "<p><q>Hi</q>, she said. 5” tall.</p>\n");
+    /// ```
+    pub quote_elements: bool,
+
+    /// What to do with NUL bytes in the input.  By default each is replaced
+    /// with U+FFFD REPLACEMENT CHARACTER, as the spec requires.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, NulPolicy, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("a\0b\n", &options),
+    ///            "<p>a\u{fffd}b</p>\n");
+    ///
+    /// options.parse.nul_handling = NulPolicy::Strip;
+    /// assert_eq!(markdown_to_html("a\0b\n", &options),
+    ///            "<p>ab</p>\n");
+    /// ```
+    pub nul_handling: NulPolicy,
+
+    /// The depth, counted in blocks opened on a single line, at which further
+    /// list markers, footnote definitions and block quote markers are left as
+    /// text, guarding against quadratic blowup on input like `- - - - …`.
+    /// Lists and footnote definitions are limited to a depth of 100 by
+    /// default; block quotes are only limited once this is set.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("> > > a\n", &options),
+    ///            "<blockquote>\n<blockquote>\n<blockquote>\n<p>a</p>\n\
+    ///             </blockquote>\n</blockquote>\n</blockquote>\n");
+    ///
+    /// options.parse.max_nesting_depth = Some(3);
+    /// assert_eq!(markdown_to_html("> > > a\n", &options),
+    ///            "<blockquote>\n<blockquote>\n<p>&gt; a</p>\n\
+    ///             </blockquote>\n</blockquote>\n");
+    /// ```
+    pub max_nesting_depth: Option<usize>,
+
+    /// Custom inline tokens, such as issue references or app-specific
+    /// mentions, to be turned into nodes of the caller's choosing.  See
+    /// [`InlineToken`].  Tokens are tried in order, before any of the built-in
+    /// syntax starting with the same character.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, InlineToken, Options};
+    /// # use comrak::nodes::{NodeLink, NodeValue};
+    /// fn digits(s: &str) -> Option<usize> {
+    ///     let len = s[1..].bytes().take_while(u8::is_ascii_digit).count();
+    ///     if len > 0 { Some(1 + len) } else { None }
+    /// }
+    ///
+    /// fn issue(token: &str) -> Option<NodeValue> {
+    ///     Some(NodeValue::Link(NodeLink {
+    ///         url: format!("/issues/{}", &token[1..]),
+    ///         title: String::new(),
+    ///         autolink: None,
+    ///         srcset: vec![],
+    ///         attrs: vec![],
+    ///     }))
+    /// }
+    ///
+    /// let mut options = Options::default();
+    /// options.parse.inline_tokens = vec![InlineToken { prefix: b'#', matcher: digits, builder: issue }];
+    /// assert_eq!(markdown_to_html("Fixes #123, not C#4 or #x.", &options),
+    ///            "<p>Fixes <a href=\"/issues/123\">#123</a>, not C#4 or #x.</p>\n");
+    /// ```
+    pub inline_tokens: Vec<InlineToken>,
+
+    /// Custom paired delimiters, such as `++` for marked text, matched up
+    /// like emphasis and turned into nodes of the caller's choosing.  See
+    /// [`InlineDelimiter`].
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, InlineDelimiter, Options};
+    /// # use comrak::nodes::NodeValue;
+    /// let mut options = Options::default();
+    /// options.parse.inline_delimiters = vec![InlineDelimiter {
+    ///     delim_char: b'+',
+    ///     min_length: 2,
+    ///     max_length: Some(2),
+    ///     same_length: true,
+    ///     builder: |_| NodeValue::Highlight,
+    /// }];
+    ///
+    /// assert_eq!(markdown_to_html("++new++ and a+b, +c+", &options),
+    ///            "<p><mark>new</mark> and a+b, +c+</p>\n");
+    /// ```
+    pub inline_delimiters: Vec<InlineDelimiter>,
+}
+
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, Builder)]
+#[builder(default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+/// Options for formatter functions.
+pub struct RenderOptions {
+    /// [Soft line breaks](http://spec.commonmark.org/0.27/#soft-line-breaks) in the input
+    /// translate into hard line breaks in the output.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
+    ///            "<p>Hello.\nWorld.</p>\n");
+    ///
+    /// options.render.hardbreaks = true;
+    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
+    ///            "<p>Hello.<br />\nWorld.</p>\n");
+    /// ```
+    pub hardbreaks: bool,
+
+    /// [Soft line breaks](http://spec.commonmark.org/0.27/#soft-line-breaks) in the input
+    /// are rendered as a single space rather than a newline.  `hardbreaks` takes precedence.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
+    ///            "<p>Hello.\nWorld.</p>\n");
+    ///
+    /// options.render.collapse_soft_breaks = true;
+    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
+    ///            "<p>Hello. World.</p>\n");
+    /// ```
+    pub collapse_soft_breaks: bool,
+
+    /// GitHub-style `<pre lang="xyz">` is used for fenced code blocks with info tags.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("``` rust\nfn hello();\n```\n", &options),
+    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+    ///
+    /// options.render.github_pre_lang = true;
+    /// assert_eq!(markdown_to_html("``` rust\nfn hello();\n```\n", &options),
+    ///            "<pre lang=\"rust\"><code>fn hello();\n</code></pre>\n");
+    /// ```
+    pub github_pre_lang: bool,
+
+    /// Enable full info strings for code blocks
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("``` rust extra info\nfn hello();\n```\n", &options),
+    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+    ///
+    /// options.render.full_info_string = true;
+    /// let html = markdown_to_html("``` rust extra info\nfn hello();\n```\n", &options);
+    /// let re = regex::Regex::new(r#"data-meta="extra info""#).unwrap();
+    /// assert!(re.is_match(&html));
+    /// ```
+    pub full_info_string: bool,
+
+    /// Take a `title="..."` token from a code block's info string and render
+    /// it as a caption, wrapping the block in a `<figure>`.  Requires
+    /// `full_info_string`; the rest of the info string is used as normal.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.full_info_string = true;
+    /// options.render.code_block_title_from_info = true;
+    /// assert_eq!(markdown_to_html("``` rust title=\"src/main.rs\"\nfn main();\n```\n", &options),
+    ///            "<figure class=\"code-block\">\n<figcaption>src/main.rs</figcaption>\n\
+    ///             <pre><code class=\"language-rust\">fn main();\n</code></pre>\n\
+    ///             </figure>\n");
+    /// ```
+    pub code_block_title_from_info: bool,
+
+    /// The wrap column when outputting CommonMark.
+    ///
+    /// Lines are only broken at spaces between words: code spans, links,
+    /// images and autolinks are never broken, and one longer than the width
+    /// goes on a line of its own.
+    ///
+    /// ```
+    /// # use comrak::{parse_document, Options, format_commonmark};
+    /// # fn main() {
+    /// # let arena = typed_arena::Arena::new();
+    /// let mut options = Options::default();
+    /// let node = parse_document(&arena, "hello hello hello hello hello hello", &options);
+    /// let mut output = vec![];
+    /// format_commonmark(node, &options, &mut output).unwrap();
+    /// assert_eq!(String::from_utf8(output).unwrap(),
+    ///            "hello hello hello hello hello hello\n");
+    ///
+    /// options.render.width = 20;
+    /// let mut output = vec![];
+    /// format_commonmark(node, &options, &mut output).unwrap();
+    /// assert_eq!(String::from_utf8(output).unwrap(),
+    ///            "hello hello hello\nhello hello hello\n");
+    /// # }
+    /// ```
+    pub width: usize,
+
+    /// Allow rendering of raw HTML and potentially dangerous links.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// let input = "<script>\nalert('xyz');\n</script>\n\n\
+    ///              Possibly <marquee>annoying</marquee>.\n\n\
+    ///              [Dangerous](javascript:alert(document.cookie)).\n\n\
+    ///              [Safe](http://commonmark.org).\n";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<!-- raw HTML omitted -->\n\
+    ///             <p>Possibly <!-- raw HTML omitted -->annoying<!-- raw HTML omitted -->.</p>\n\
+    ///             <p><a href=\"\">Dangerous</a>.</p>\n\
+    ///             <p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+    ///
+    /// options.render.unsafe_ = true;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<script>\nalert(\'xyz\');\n</script>\n\
+    ///             <p>Possibly <marquee>annoying</marquee>.</p>\n\
+    ///             <p><a href=\"javascript:alert(document.cookie)\">Dangerous</a>.</p>\n\
+    ///             <p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+    /// ```
+    pub unsafe_: bool,
+
+    /// Escape raw HTML instead of clobbering it.
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// let input = "<i>italic text</i>";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p><!-- raw HTML omitted -->italic text<!-- raw HTML omitted --></p>\n");
+    ///
+    /// options.render.escape = true;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>&lt;i&gt;italic text&lt;/i&gt;</p>\n");
+    /// ```
+    pub escape: bool,
+
+    /// Set the type of [bullet list marker](https://spec.commonmark.org/0.30/#bullet-list-marker) to use. Options are:
+    ///
+    /// * `ListStyleType::Dash` to use `-` (default)
+    /// * `ListStyleType::Plus` to use `+`
+    /// * `ListStyleType::Star` to use `*`
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark, Options, ListStyleType};
+    /// let mut options = Options::default();
+    /// let input = "- one\n- two\n- three";
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "- one\n- two\n- three\n"); // default is Dash
+    ///
+    /// options.render.list_style = ListStyleType::Plus;
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "+ one\n+ two\n+ three\n");
+    ///
+    /// options.render.list_style = ListStyleType::Star;
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "* one\n* two\n* three\n");
+    /// ```
+    pub list_style: ListStyleType,
+
+    /// The line ending written between lines of output by every formatter,
+    /// including line breaks within code blocks and raw HTML.  Hard breaks
+    /// are still written as `<br />` or a backslash, followed by the line
+    /// ending.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark, markdown_to_html, LineEnding, Options};
+    /// let mut options = Options::default();
+    /// options.render.line_ending = LineEnding::CrLf;
+    /// assert_eq!(markdown_to_html("# Hi\r\n\r\nOne\\\r\ntwo\r\n", &options),
+    ///            "<h1>Hi</h1>\r\n<p>One<br />\r\ntwo</p>\r\n");
+    /// assert_eq!(markdown_to_commonmark("- a\n- b\n", &options),
+    ///            "- a\r\n- b\r\n");
+    /// ```
+    pub line_ending: LineEnding,
+
+    /// Include source position attributes in XML output.
+    ///
+    /// Not yet compatible with extension.description_lists.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_commonmark_xml, Options};
+    /// let mut options = Options::default();
+    /// options.render.sourcepos = true;
+    /// let input = "Hello *world*!";
+    /// let xml = markdown_to_commonmark_xml(input, &options);
+    /// assert!(xml.contains("<emph sourcepos=\"1:7-1:13\">"));
+    /// ```
+    pub sourcepos: bool,
+
+    /// Wrap escaped characters in a `<span>` to allow any
+    /// post-processing to recognize them.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// let input = "Notify user \\@example";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>Notify user @example</p>\n");
+    ///
+    /// options.render.escaped_char_spans = true;
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>Notify user <span data-escaped-char>@</span>example</p>\n");
+    /// ```
+    pub escaped_char_spans: bool,
+
+    /// Inline HTML tags which are passed through verbatim even when
+    /// `unsafe_` is off.  Tag names are matched case-insensitively, and
+    /// only tags without attributes pass; all other inline HTML is still
+    /// omitted.  Has no effect on HTML blocks.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// let input = "H<sub>2</sub>O <script>x</script>";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>H<!-- raw HTML omitted -->2<!-- raw HTML omitted -->O \
+    ///             <!-- raw HTML omitted -->x<!-- raw HTML omitted --></p>\n");
+    ///
+    /// options.render.allowed_inline_html_tags = vec!["sub".to_string()];
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<p>H<sub>2</sub>O <!-- raw HTML omitted -->x<!-- raw HTML omitted --></p>\n");
+    /// ```
+    pub allowed_inline_html_tags: Vec<String>,
+
+    /// Render table cell alignment as the given CSS classes (left, center
+    /// and right respectively) instead of `align` attributes.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// let input = "| a |\n| :-: |\n| b |\n";
+    ///
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<table>\n<thead>\n<tr>\n<th align=\"center\">a</th>\n</tr>\n</thead>\n\
+    ///             <tbody>\n<tr>\n<td align=\"center\">b</td>\n</tr>\n</tbody>\n</table>\n");
+    ///
+    /// options.render.table_alignment_classes = Some([
+    ///     "text-left".to_string(),
+    ///     "text-center".to_string(),
+    ///     "text-right".to_string(),
+    /// ]);
+    /// assert_eq!(markdown_to_html(input, &options),
+    ///            "<table>\n<thead>\n<tr>\n<th class=\"text-center\">a</th>\n</tr>\n</thead>\n\
+    ///             <tbody>\n<tr>\n<td class=\"text-center\">b</td>\n</tr>\n</tbody>\n</table>\n");
+    /// ```
+    pub table_alignment_classes: Option<[String; 3]>,
+
+    /// Insert a `<wbr>` break opportunity after every N characters of any
+    /// run of non-whitespace in text longer than N characters.  Code is left
+    /// untouched.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.insert_wbr_in_long_words = Some(4);
+    ///
+    /// assert_eq!(markdown_to_html("abcdefghij abc `abcdefghij`", &options),
+    ///            "<p>abcd<wbr>efgh<wbr>ij abc <code>abcdefghij</code></p>\n");
+    /// ```
+    pub insert_wbr_in_long_words: Option<usize>,
+
+    /// Normalize the language of a code block's info string before it is
+    /// used for the `language-` class, the `lang` attribute, or passed to the
+    /// syntax highlighter.
+    ///
+    /// ```rust
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// fn normalize(lang: &str) -> String {
+    ///     match lang {
+    ///         "js" => "javascript".to_string(),
+    ///         "c++" => "cpp".to_string(),
+    ///         _ => lang.to_lowercase(),
+    ///     }
+    /// }
+    /// options.render.normalize_code_lang = Some(normalize);
+    ///
+    /// assert_eq!(markdown_to_html("``` js\nx\n```\n``` c++\ny\n```\n", &options),
+    ///            "<pre><code class=\"language-javascript\">x\n</code></pre>\n\
+    ///             <pre><code class=\"language-cpp\">y\n</code></pre>\n");
+    /// ```
+    pub normalize_code_lang: Option<fn(&str) -> String>,
+
+    #[cfg(feature = "shortcodes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "shortcodes")))]
+    /// Renders emoji shortcodes in HTML as images instead of Unicode.  The
+    /// function is given a shortcode without its colons and returns the
+    /// image's URL, or `None` to write the emoji itself.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.shortcodes = true;
+    /// options.render.emoji_image_url = Some(|shortcode| match shortcode {
+    ///     "smile" => Some(format!("/emoji/{}.png", shortcode)),
+    ///     _ => None,
+    /// });
+    ///
+    /// assert_eq!(markdown_to_html("Hi :smile: :rocket:", &options),
+    ///            "<p>Hi <img class=\"emoji\" src=\"/emoji/smile.png\" alt=\":smile:\" /> 🚀</p>\n");
+    /// ```
+    pub emoji_image_url: Option<fn(&str) -> Option<String>>,
+
+    /// Write the attributes of each HTML element in alphabetical order.
+    ///
+    /// Attributes are always written in a fixed order for a given element
+    /// and set of options; this instead makes the order independent of the
+    /// renderer, which can help when comparing output between versions.
+    /// Attributes given to a syntax highlighter plugin are not affected.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.sourcepos = true;
+    /// assert_eq!(markdown_to_html("[a](/u \"t\")", &options),
+    ///            "<p data-sourcepos=\"1:1-1:11\"><a data-sourcepos=\"1:1-1:11\" href=\"/u\" title=\"t\">a</a></p>\n");
+    ///
+    /// options.render.sorted_attributes = true;
+    /// assert_eq!(markdown_to_html("![a](/u \"t\")", &options),
+    ///            "<p data-sourcepos=\"1:1-1:12\"><img alt=\"a\" data-sourcepos=\"1:1-1:12\" src=\"/u\" title=\"t\" /></p>\n");
+    /// ```
+    pub sorted_attributes: bool,
+
+    /// Add ARIA `role` attributes to tables and lists, for assistive
+    /// technology that drops their implicit roles when CSS changes how they
+    /// are displayed.
+    ///
+    /// | Element                                    | Role           |
+    /// | ------------------------------------------ | -------------- |
+    /// | `<table>`                                  | `table`        |
+    /// | `<thead>`, `<tbody>`                       | `rowgroup`     |
+    /// | `<tr>`                                     | `row`          |
+    /// | `<th>`                                     | `columnheader` |
+    /// | `<td>`                                     | `cell`         |
+    /// | `<ul>`, `<ol>`                             | `list`         |
+    /// | `<li>`, including task list items          | `listitem`     |
+    ///
+    /// Footnote and description lists are left as they are.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.table = true;
+    /// options.render.aria_roles = true;
+    /// assert_eq!(markdown_to_html("| a |\n|---|\n| b |\n\n- c\n", &options),
+    ///            "<table role=\"table\">\n<thead role=\"rowgroup\">\n<tr role=\"row\">\n\
+    ///             <th role=\"columnheader\">a</th>\n</tr>\n</thead>\n<tbody role=\"rowgroup\">\n\
+    ///             <tr role=\"row\">\n<td role=\"cell\">b</td>\n</tr>\n</tbody>\n</table>\n\
+    ///             <ul role=\"list\">\n<li role=\"listitem\">c</li>\n</ul>\n");
+    /// ```
+    pub aria_roles: bool,
+
+    /// Write every non-ASCII character as a numeric character reference,
+    /// for destinations that mangle UTF-8, such as some email clients.
+    ///
+    /// This applies to all HTML output, including code blocks, attribute
+    /// values and raw HTML.  Only the HTML formatter is affected.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.ascii_only = true;
+    /// assert_eq!(markdown_to_html("Café &amp; 🎉", &options),
+    ///            "<p>Caf&#xE9; &amp; &#x1F389;</p>\n");
+    /// ```
+    pub ascii_only: bool,
+
+    /// Add a `data-line` attribute giving the first source line of each
+    /// block-level element.
+    ///
+    /// This is a lighter alternative to `sourcepos` for editors which only
+    /// need to match blocks to lines, such as to synchronise scrolling.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.data_line_start = true;
+    /// assert_eq!(markdown_to_html("# Hi\n\n> *a*\n> b\n", &options),
+    ///            "<h1 data-line=\"1\">Hi</h1>\n<blockquote data-line=\"3\">\n\
+    ///             <p data-line=\"3\"><em>a</em>\nb</p>\n</blockquote>\n");
+    /// ```
+    pub data_line_start: bool,
+
+    /// Keep the numbers of ordered list items as written, rather than
+    /// numbering them in sequence from the first.  A list whose second item
+    /// is numbered one less than its first is written `reversed`, so it
+    /// counts down, and items whose number differs from what a browser would
+    /// show are given a `value`.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("1. a\n5. b\n6. c\n", &options),
+    ///            "<ol>\n<li>a</li>\n<li>b</li>\n<li>c</li>\n</ol>\n");
+    ///
+    /// options.render.preserve_ordered_numbers = true;
+    /// assert_eq!(markdown_to_html("1. a\n5. b\n6. c\n", &options),
+    ///            "<ol>\n<li>a</li>\n<li value=\"5\">b</li>\n<li>c</li>\n</ol>\n");
+    /// assert_eq!(markdown_to_html("3. a\n2. b\n1. c\n", &options),
+    ///            "<ol reversed start=\"3\">\n<li>a</li>\n<li>b</li>\n<li>c</li>\n</ol>\n");
+    /// ```
+    pub preserve_ordered_numbers: bool,
+
+    /// Render math from the `math_dollars` and `math_code` extensions as
+    /// TeX between the delimiters MathJax finds by default: `\(...\)` for
+    /// inline math and `\[...\]` for display math and math code blocks.
+    /// The math is wrapped in a `<span>` in every case.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.math_dollars = true;
+    /// options.render.math_mathjax_delimiters = true;
+    /// assert_eq!(markdown_to_html("$x < y$ and $$z$$", &options),
+    ///            "<p><span data-math-style=\"inline\">\\(x &lt; y\\)</span> and \
+    ///             <span data-math-style=\"display\">\\[z\\]</span></p>\n");
+    /// ```
+    pub math_mathjax_delimiters: bool,
+
+    /// Remove spaces and tabs from the end of each line of a code block
+    /// when rendering it.  The parsed code block is left as it was.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.trim_code_trailing_whitespace = true;
+    /// assert_eq!(markdown_to_html("```\na  \nb\t\n```\n", &options),
+    ///            "<pre><code>a\nb\n</code></pre>\n");
+    /// ```
+    pub trim_code_trailing_whitespace: bool,
+
+    /// Write the address and text of email autolinks entirely as numeric
+    /// character references, to hide them from simple scrapers.  Browsers
+    /// show and follow them as usual.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.autolink = true;
+    /// options.render.obfuscate_email = true;
+    /// assert_eq!(markdown_to_html("a@b.c", &options),
+    ///            "<p><a href=\"&#109;&#97;&#105;&#108;&#116;&#111;&#58;&#97;&#64;&#98;&#46;&#99;\">\
+    ///             &#97;&#64;&#98;&#46;&#99;</a></p>\n");
+    /// ```
+    pub obfuscate_email: bool,
+
+    /// Take the language class of a fenced code block from the first word of
+    /// its info string exactly as written, without decoding character
+    /// references or backslash escapes.  Blocks whose info string comes from
+    /// `default_info_string` are unaffected.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// assert_eq!(markdown_to_html("```c\\+\\+\nint x;\n```\n", &options),
+    ///            "<pre><code class=\"language-c++\">int x;\n</code></pre>\n");
+    ///
+    /// options.render.raw_info_string_class = true;
+    /// assert_eq!(markdown_to_html("```c\\+\\+\nint x;\n```\n", &options),
+    ///            "<pre><code class=\"language-c\\+\\+\">int x;\n</code></pre>\n");
+    /// ```
+    pub raw_info_string_class: bool,
+
+    /// Wrap the details of every description list in paragraphs, as if all
+    /// lists were loose.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.description_lists = true;
+    /// assert_eq!(markdown_to_html("Term\n\n: Definition", &options),
+    ///            "<dl><dt>Term</dt>\n<dd>Definition</dd>\n</dl>\n");
+    ///
+    /// options.render.description_list_loose = true;
+    /// assert_eq!(markdown_to_html("Term\n\n: Definition", &options),
+    ///            "<dl><dt>Term</dt>\n<dd>\n<p>Definition</p>\n</dd>\n</dl>\n");
+    /// ```
+    pub description_list_loose: bool,
+
+    /// A class to put on every `<hr>` written for a thematic break.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.thematic_break_class = Some("divider".to_string());
+    /// assert_eq!(markdown_to_html("a\n\n***\n", &options),
+    ///            "<p>a</p>\n<hr class=\"divider\" />\n");
     /// ```
-    pub smart: bool,
+    pub thematic_break_class: Option<String>,
 
-    /// The default info string for fenced code blocks.
+    /// Write the backreference of a footnote referenced only once as older
+    /// versions of cmark-gfm did: without `data-footnote-backref-idx`, and
+    /// labelled by `footnote_backref_simple_label`.  Footnotes referenced
+    /// more than once are written as usual.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
-    ///            "<pre><code>fn hello();\n</code></pre>\n");
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_single_backref_simple = true;
+    /// assert!(markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options).contains(
+    ///     "<a href=\"#fnref-x\" class=\"footnote-backref\" data-footnote-backref \
+    ///      aria-label=\"Back to content\">↩</a>"
+    /// ));
+    /// ```
+    pub footnote_single_backref_simple: bool,
+
+    /// In CommonMark output, pad the markers of an ordered list to the width
+    /// of its widest marker, so that the items' content lines up.  Padding is
+    /// capped at four spaces, since any more would start an indented code
+    /// block.
     ///
-    /// options.parse.default_info_string = Some("rust".into());
-    /// assert_eq!(markdown_to_html("```\nfn hello();\n```\n", &options),
-    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
     /// ```
-    pub default_info_string: Option<String>,
+    /// # use comrak::{markdown_to_commonmark, Options};
+    /// let mut options = Options::default();
+    /// let input = "9. nine\n10. ten\n";
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "9.  nine\n10. ten\n");
+    ///
+    /// options.render.ordered_list_pad_markers = true;
+    /// let input = "99. ninety-nine\n100. one hundred\n";
+    /// assert_eq!(markdown_to_commonmark(input, &options),
+    ///            "99.  ninety-nine\n100. one hundred\n");
+    /// ```
+    pub ordered_list_pad_markers: bool,
 
-    /// Whether or not a simple `x` or `X` is used for tasklist or any other symbol is allowed.
-    pub relaxed_tasklist_matching: bool,
+    /// In CommonMark output, write two spaces after a bullet list marker
+    /// rather than one.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_commonmark, Options};
+    /// let mut options = Options::default();
+    /// options.render.bullet_list_double_space = true;
+    /// assert_eq!(markdown_to_commonmark("- one\n- two\n", &options),
+    ///            "-  one\n-  two\n");
+    /// ```
+    pub bullet_list_double_space: bool,
 
-    /// Relax parsing of autolinks, allow links to be detected inside brackets
-    /// and allow all url schemes
+    /// Write footnote references which directly follow one another inside a
+    /// single `<sup>`, separated by commas.  Each reference keeps its own
+    /// link and `id`.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// options.extension.autolink = true;
-    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
-    ///            "<p>[https://foo.com]</p>\n");
-    ///
-    /// options.parse.relaxed_autolinks = true;
-    /// assert_eq!(markdown_to_html("[https://foo.com]", &options),
-    ///            "<p>[<a href=\"https://foo.com\">https://foo.com</a>]</p>\n");
+    /// options.extension.footnotes = true;
+    /// options.render.group_footnote_refs = true;
+    /// assert!(markdown_to_html("x[^a][^b]\n\n[^a]: A\n[^b]: B\n", &options).starts_with(
+    ///     "<p>x<sup class=\"footnote-ref\">\
+    ///      <a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref>1</a>, \
+    ///      <a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>2</a></sup></p>\n"
+    /// ));
     /// ```
-    pub relaxed_autolinks: bool,
-}
+    pub group_footnote_refs: bool,
 
-#[non_exhaustive]
-#[derive(Default, Debug, Clone, Copy, Builder)]
-#[builder(default)]
-#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
-/// Options for formatter functions.
-pub struct RenderOptions {
-    /// [Soft line breaks](http://spec.commonmark.org/0.27/#soft-line-breaks) in the input
-    /// translate into hard line breaks in the output.
+    /// The prefix of footnote definitions' ids, `fn-` by default.
+    ///
+    /// Together with `footnote_ref_id_prefix`, this keeps ids from colliding
+    /// when several rendered documents share a page.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
-    ///            "<p>Hello.\nWorld.</p>\n");
-    ///
-    /// options.render.hardbreaks = true;
-    /// assert_eq!(markdown_to_html("Hello.\nWorld.\n", &options),
-    ///            "<p>Hello.<br />\nWorld.</p>\n");
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_id_prefix = Some("post-1-fn-".to_string());
+    /// options.render.footnote_ref_id_prefix = Some("post-1-fnref-".to_string());
+    /// let html = markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options);
+    /// assert!(html.contains("<a href=\"#post-1-fn-x\" id=\"post-1-fnref-x\" data-footnote-ref>1</a>"));
+    /// assert!(html.contains("<li id=\"post-1-fn-x\">"));
+    /// assert!(html.contains("<a href=\"#post-1-fnref-x\" class=\"footnote-backref\""));
     /// ```
-    pub hardbreaks: bool,
+    pub footnote_id_prefix: Option<String>,
 
-    /// GitHub-style `<pre lang="xyz">` is used for fenced code blocks with info tags.
+    /// The prefix of footnote references' ids, `fnref-` by default.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("``` rust\nfn hello();\n```\n", &options),
-    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
-    ///
-    /// options.render.github_pre_lang = true;
-    /// assert_eq!(markdown_to_html("``` rust\nfn hello();\n```\n", &options),
-    ///            "<pre lang=\"rust\"><code>fn hello();\n</code></pre>\n");
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_ref_id_prefix = Some("ref-".to_string());
+    /// assert!(markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options)
+    ///     .starts_with("<p>Hi<sup class=\"footnote-ref\"><a href=\"#fn-x\" id=\"ref-x\" data-footnote-ref>1</a></sup></p>\n"));
     /// ```
-    pub github_pre_lang: bool,
+    pub footnote_ref_id_prefix: Option<String>,
 
-    /// Enable full info strings for code blocks
+    /// The HTML of footnotes' backreference links, `↩` by default.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// assert_eq!(markdown_to_html("``` rust extra info\nfn hello();\n```\n", &options),
-    ///            "<pre><code class=\"language-rust\">fn hello();\n</code></pre>\n");
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_backref_symbol = Some("&#8617;&#xFE0E;".to_string());
+    /// assert!(markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options)
+    ///     .contains("aria-label=\"Back to reference 1\">&#8617;&#xFE0E;</a>"));
+    /// ```
+    pub footnote_backref_symbol: Option<String>,
+
+    /// The `aria-label` of footnotes' backreference links, in which `{}` is
+    /// replaced by the footnote's number and, for all but the first
+    /// reference, the reference's number (as in `2-3`).  Defaults to
+    /// `Back to reference {}`.  Backreferences written by
+    /// `footnote_single_backref_simple` take `footnote_backref_simple_label`
+    /// instead.
     ///
-    /// options.render.full_info_string = true;
-    /// let html = markdown_to_html("``` rust extra info\nfn hello();\n```\n", &options);
-    /// let re = regex::Regex::new(r#"data-meta="extra info""#).unwrap();
-    /// assert!(re.is_match(&html));
     /// ```
-    pub full_info_string: bool,
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_backref_label = Some("Zurück zum Verweis {}".to_string());
+    /// let html = markdown_to_html("Hi[^x] and[^x]\n\n[^x]: A greeting.\n", &options);
+    /// assert!(html.contains("aria-label=\"Zurück zum Verweis 1\""));
+    /// assert!(html.contains("aria-label=\"Zurück zum Verweis 1-2\""));
+    /// ```
+    pub footnote_backref_label: Option<String>,
 
-    /// The wrap column when outputting CommonMark.
+    /// The `aria-label` of the backreferences written by
+    /// `footnote_single_backref_simple`.  Defaults to `Back to content`.
     ///
     /// ```
-    /// # use comrak::{parse_document, Options, format_commonmark};
-    /// # fn main() {
-    /// # let arena = typed_arena::Arena::new();
+    /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let node = parse_document(&arena, "hello hello hello hello hello hello", &options);
-    /// let mut output = vec![];
-    /// format_commonmark(node, &options, &mut output).unwrap();
-    /// assert_eq!(String::from_utf8(output).unwrap(),
-    ///            "hello hello hello hello hello hello\n");
-    ///
-    /// options.render.width = 20;
-    /// let mut output = vec![];
-    /// format_commonmark(node, &options, &mut output).unwrap();
-    /// assert_eq!(String::from_utf8(output).unwrap(),
-    ///            "hello hello hello\nhello hello hello\n");
-    /// # }
+    /// options.extension.footnotes = true;
+    /// options.render.footnote_single_backref_simple = true;
+    /// options.render.footnote_backref_simple_label = Some("Zurück zum Text".to_string());
+    /// assert!(markdown_to_html("Hi[^x]\n\n[^x]: A greeting.\n", &options)
+    ///     .contains("aria-label=\"Zurück zum Text\""));
     /// ```
-    pub width: usize,
+    pub footnote_backref_simple_label: Option<String>,
 
-    /// Allow rendering of raw HTML and potentially dangerous links.
+    /// Put the language of a fenced code block in a `data-language`
+    /// attribute on the `<code>` element, for highlighters which look for
+    /// it there.  The `language-` class is still written, unless
+    /// `code_lang_data_attr_only` is also set.
     ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let input = "<script>\nalert('xyz');\n</script>\n\n\
-    ///              Possibly <marquee>annoying</marquee>.\n\n\
-    ///              [Dangerous](javascript:alert(document.cookie)).\n\n\
-    ///              [Safe](http://commonmark.org).\n";
-    ///
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<!-- raw HTML omitted -->\n\
-    ///             <p>Possibly <!-- raw HTML omitted -->annoying<!-- raw HTML omitted -->.</p>\n\
-    ///             <p><a href=\"\">Dangerous</a>.</p>\n\
-    ///             <p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+    /// options.render.code_lang_as_data_attr = true;
+    /// assert_eq!(markdown_to_html("```rust\nfn main() {}\n```\n", &options),
+    ///            "<pre><code class=\"language-rust\" data-language=\"rust\">fn main() {}\n</code></pre>\n");
     ///
-    /// options.render.unsafe_ = true;
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<script>\nalert(\'xyz\');\n</script>\n\
-    ///             <p>Possibly <marquee>annoying</marquee>.</p>\n\
-    ///             <p><a href=\"javascript:alert(document.cookie)\">Dangerous</a>.</p>\n\
-    ///             <p><a href=\"http://commonmark.org\">Safe</a>.</p>\n");
+    /// options.render.github_pre_lang = true;
+    /// assert_eq!(markdown_to_html("```rust\nfn main() {}\n```\n", &options),
+    ///            "<pre lang=\"rust\"><code data-language=\"rust\">fn main() {}\n</code></pre>\n");
     /// ```
-    pub unsafe_: bool,
+    pub code_lang_as_data_attr: bool,
 
-    /// Escape raw HTML instead of clobbering it.
+    /// With `code_lang_as_data_attr`, leave out the `language-` class, so the
+    /// language is only given by the `data-language` attribute.
+    ///
     /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let input = "<i>italic text</i>";
-    ///
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<p><!-- raw HTML omitted -->italic text<!-- raw HTML omitted --></p>\n");
-    ///
-    /// options.render.escape = true;
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<p>&lt;i&gt;italic text&lt;/i&gt;</p>\n");
+    /// options.render.code_lang_as_data_attr = true;
+    /// options.render.code_lang_data_attr_only = true;
+    /// assert_eq!(markdown_to_html("```rust\nfn main() {}\n```\n", &options),
+    ///            "<pre><code data-language=\"rust\">fn main() {}\n</code></pre>\n");
     /// ```
-    pub escape: bool,
+    pub code_lang_data_attr_only: bool,
 
-    /// Set the type of [bullet list marker](https://spec.commonmark.org/0.30/#bullet-list-marker) to use. Options are:
+    /// Give block elements an `id` made from this prefix, the element's tag
+    /// and its position among elements with that tag, such as `p-3` for the
+    /// third paragraph.  Renders of the same input always give the same ids.
     ///
-    /// * `ListStyleType::Dash` to use `-` (default)
-    /// * `ListStyleType::Plus` to use `+`
-    /// * `ListStyleType::Star` to use `*`
+    /// Paragraphs, block quotes, lists, list items, code blocks, tables,
+    /// description lists and thematic breaks get ids; headings are left to
+    /// `extension.header_ids`.  Block ids are counted apart from header
+    /// ids, which they never change: a block id that a heading already has
+    /// is given a suffix instead, as `p-2-1`.
     ///
-    /// ```rust
-    /// # use comrak::{markdown_to_commonmark, Options, ListStyleType};
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let input = "- one\n- two\n- three";
-    /// assert_eq!(markdown_to_commonmark(input, &options),
-    ///            "- one\n- two\n- three\n"); // default is Dash
+    /// options.render.block_ids = Some("b-".to_string());
+    /// assert_eq!(markdown_to_html("One\n\n> Two\n\n- three\n", &options),
+    ///            "<p id=\"b-p-1\">One</p>\n\
+    ///             <blockquote id=\"b-blockquote-1\">\n<p id=\"b-p-2\">Two</p>\n</blockquote>\n\
+    ///             <ul id=\"b-ul-1\">\n<li id=\"b-li-1\">three</li>\n</ul>\n");
+    /// ```
+    pub block_ids: Option<String>,
+
+    /// Follow each link's text with its URL in parentheses when formatting
+    /// plain text, unless the two are the same.
     ///
-    /// options.render.list_style = ListStyleType::Plus;
-    /// assert_eq!(markdown_to_commonmark(input, &options),
-    ///            "+ one\n+ two\n+ three\n");
+    /// ```
+    /// # use comrak::{markdown_to_plain, Options};
+    /// let mut options = Options::default();
+    /// let input = "See [the docs](https://example.com/docs) or <https://example.com>.";
+    /// assert_eq!(markdown_to_plain(input, &options),
+    ///            "See the docs or https://example.com.\n");
     ///
-    /// options.render.list_style = ListStyleType::Star;
-    /// assert_eq!(markdown_to_commonmark(input, &options),
-    ///            "* one\n* two\n* three\n");
+    /// options.render.plain_link_urls = true;
+    /// assert_eq!(markdown_to_plain(input, &options),
+    ///            "See the docs (https://example.com/docs) or https://example.com.\n");
     /// ```
-    pub list_style: ListStyleType,
+    pub plain_link_urls: bool,
 
-    /// Include source position attributes in XML output.
-    ///
-    /// Not yet compatible with extension.description_lists.
+    /// Demote every heading by this many levels in HTML output, for
+    /// documents embedded under headings of their own.  Levels are capped at
+    /// 6; the AST is left as it was.
     ///
-    /// ```rust
-    /// # use comrak::{markdown_to_commonmark_xml, Options};
+    /// ```
+    /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// options.render.sourcepos = true;
-    /// let input = "Hello *world*!";
-    /// let xml = markdown_to_commonmark_xml(input, &options);
-    /// assert!(xml.contains("<emph sourcepos=\"1:7-1:13\">"));
+    /// options.render.heading_offset = 2;
+    /// assert_eq!(markdown_to_html("# Title\n\n##### Deep\n", &options),
+    ///            "<h3>Title</h3>\n<h6>Deep</h6>\n");
     /// ```
-    pub sourcepos: bool,
+    pub heading_offset: u8,
 
-    /// Wrap escaped characters in a `<span>` to allow any
-    /// post-processing to recognize them.
+    /// Give links to other hosts `target="_blank" rel="noopener noreferrer"`
+    /// in HTML output, so they open in a new tab.  A link is to another host
+    /// when its URL has one, as in `https://example.com` or `//example.com`,
+    /// that isn't listed in `internal_link_hosts`.  Links made relative by
+    /// `extension.philomena_domains` are never treated as such.
     ///
-    /// ```rust
+    /// ```
     /// # use comrak::{markdown_to_html, Options};
     /// let mut options = Options::default();
-    /// let input = "Notify user \\@example";
-    ///
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<p>Notify user @example</p>\n");
+    /// options.render.external_links_new_tab = true;
+    /// assert_eq!(markdown_to_html("[a](https://example.com) [b](/about)", &options),
+    ///            "<p><a href=\"https://example.com\" target=\"_blank\" \
+    ///             rel=\"noopener noreferrer\">a</a> <a href=\"/about\">b</a></p>\n");
+    /// ```
+    pub external_links_new_tab: bool,
+
+    /// Hosts whose links `external_links_new_tab` leaves alone.  Each is
+    /// matched, ignoring case, against the start of the part of the URL
+    /// after `//`, so it may include a port or path, as in `example.com/wiki/`.
     ///
-    /// options.render.escaped_char_spans = true;
-    /// assert_eq!(markdown_to_html(input, &options),
-    ///            "<p>Notify user <span data-escaped-char>@</span>example</p>\n");
     /// ```
-    pub escaped_char_spans: bool,
+    /// # use comrak::{markdown_to_html, Options};
+    /// let mut options = Options::default();
+    /// options.render.external_links_new_tab = true;
+    /// options.render.internal_link_hosts = Some(vec!["example.com".to_string()]);
+    /// assert_eq!(markdown_to_html("[a](https://EXAMPLE.com/x) [b](https://example.com.org)", &options),
+    ///            "<p><a href=\"https://EXAMPLE.com/x\">a</a> <a href=\"https://example.com.org\" \
+    ///             target=\"_blank\" rel=\"noopener noreferrer\">b</a></p>\n");
+    /// ```
+    pub internal_link_hosts: Option<Vec<String>>,
 }
 
 #[non_exhaustive]
@@ -753,6 +2458,70 @@ pub struct Plugins<'p> {
     pub render: RenderPlugins<'p>,
 }
 
+impl<'p> Plugins<'p> {
+    /// Start building a [`Plugins`]; see [`RenderPlugins::builder`].
+    pub fn builder() -> PluginsBuilder<'p> {
+        PluginsBuilder::default()
+    }
+
+    /// Plugins with only a syntax highlighter for fenced code blocks.
+    ///
+    /// ```
+    /// # use comrak::{markdown_to_html_with_plugins, Options, Plugins};
+    /// # use comrak::adapters::SyntaxHighlighterAdapter;
+    /// # use std::collections::HashMap;
+    /// # use std::io::{self, Write};
+    /// struct Shouty;
+    /// impl SyntaxHighlighterAdapter for Shouty {
+    ///     fn write_highlighted(&self, output: &mut dyn Write, _lang: Option<&str>, code: &str) -> io::Result<()> {
+    ///         output.write_all(code.to_uppercase().as_bytes())
+    ///     }
+    ///     fn write_pre_tag(&self, output: &mut dyn Write, _attributes: HashMap<String, String>) -> io::Result<()> {
+    ///         output.write_all(b"<pre>")
+    ///     }
+    ///     fn write_code_tag(&self, output: &mut dyn Write, _attributes: HashMap<String, String>) -> io::Result<()> {
+    ///         output.write_all(b"<code>")
+    ///     }
+    /// }
+    ///
+    /// let plugins = Plugins::with_syntax_highlighter(&Shouty);
+    /// assert_eq!(markdown_to_html_with_plugins("```\nhi\n```", &Options::default(), &plugins),
+    ///            "<pre><code>HI\n</code></pre>\n");
+    /// ```
+    pub fn with_syntax_highlighter(adapter: &'p dyn SyntaxHighlighterAdapter) -> Self {
+        Plugins {
+            render: RenderPlugins {
+                codefence_syntax_highlighter: Some(adapter),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Plugins with only a heading adapter.
+    pub fn with_heading_adapter(adapter: &'p dyn HeadingAdapter) -> Self {
+        Plugins {
+            render: RenderPlugins {
+                heading_adapter: Some(adapter),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Plugins with both a syntax highlighter and a heading adapter attached.
+    pub fn with_adapters(
+        syntax_highlighter: &'p dyn SyntaxHighlighterAdapter,
+        heading_adapter: &'p dyn HeadingAdapter,
+    ) -> Self {
+        Plugins {
+            render: RenderPlugins {
+                codefence_syntax_highlighter: Some(syntax_highlighter),
+                heading_adapter: Some(heading_adapter),
+                ..Default::default()
+            },
+        }
+    }
+}
+
 #[non_exhaustive]
 #[derive(Default, Clone, Builder)]
 #[builder(default)]
@@ -797,6 +2566,41 @@ pub struct RenderPlugins<'p> {
 
     /// Optional heading adapter
     pub heading_adapter: Option<&'p dyn HeadingAdapter>,
+
+    /// Optional renderer given the first chance at writing each node.  See
+    /// [`NodeRenderer`] for an example.
+    pub node_renderer: Option<&'p dyn NodeRenderer>,
+}
+
+impl<'p> RenderPlugins<'p> {
+    /// Start building a [`RenderPlugins`], for when only some adapters are
+    /// set.
+    ///
+    /// ```
+    /// # use comrak::{Plugins, RenderPlugins};
+    /// # use comrak::adapters::{HeadingAdapter, HeadingMeta};
+    /// # use comrak::nodes::Sourcepos;
+    /// # use std::io::{self, Write};
+    /// struct Plain;
+    /// impl HeadingAdapter for Plain {
+    ///     fn enter(&self, output: &mut dyn Write, heading: &HeadingMeta, _sourcepos: Option<Sourcepos>) -> io::Result<()> {
+    ///         write!(output, "<h{}>", heading.level)
+    ///     }
+    ///     fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()> {
+    ///         write!(output, "</h{}>", heading.level)
+    ///     }
+    /// }
+    ///
+    /// let render = RenderPlugins::builder()
+    ///     .heading_adapter(Some(&Plain as &dyn HeadingAdapter))
+    ///     .build()
+    ///     .unwrap();
+    /// let plugins = Plugins::builder().render(render).build().unwrap();
+    /// assert!(plugins.render.heading_adapter.is_some());
+    /// ```
+    pub fn builder() -> RenderPluginsBuilder<'p> {
+        RenderPluginsBuilder::default()
+    }
 }
 
 impl Debug for RenderPlugins<'_> {
@@ -810,7 +2614,7 @@ impl Debug for RenderPlugins<'_> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Reference {
     pub url: String,
     pub title: String,
@@ -833,12 +2637,14 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         Parser {
             arena,
             refmap: RefMap::new(),
+            abbreviations: Default::default(),
             root,
             current: root,
             line_number: 0,
             offset: 0,
             column: 0,
             thematic_break_kill_pos: 0,
+            continued_table_row: None,
             first_nonspace: 0,
             first_nonspace_column: 0,
             indent: 0,
@@ -851,6 +2657,10 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             total_size: 0,
             options,
             callback,
+            diagnostics: None,
+            shared_refmap: None,
+            closing_fence: false,
+            missing_info: vec![],
         }
     }
 
@@ -914,7 +2724,9 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 }
             } else if eol < end && s[eol] == b'\0' {
                 linebuf.extend_from_slice(&s[buffer..eol]);
-                linebuf.extend_from_slice(&"\u{fffd}".to_string().into_bytes());
+                if self.options.parse.nul_handling != NulPolicy::Strip {
+                    linebuf.extend_from_slice(&"\u{fffd}".to_string().into_bytes());
+                }
             } else {
                 linebuf.extend_from_slice(&s[buffer..eol]);
             }
@@ -1106,8 +2918,14 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             self.find_first_nonspace(line);
 
             match ast.value {
-                NodeValue::BlockQuote => {
+                NodeValue::BlockQuote | NodeValue::Alert(..) => {
                     if !self.parse_block_quote_prefix(line) {
+                        if self.options.parse.lazy_blockquote_continuation {
+                            // A blank line continued the quote only for it to
+                            // end here; treat it as following the quote.
+                            ast.last_line_blank =
+                                nodes::block_quote_ends_with_blank_line(container);
+                        }
                         return (false, container, should_continue);
                     }
                 }
@@ -1137,11 +2955,21 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     }
                 }
                 NodeValue::Table(..) => {
-                    if !table::matches(&line[self.first_nonspace..]) {
+                    if !table::matches(&line[self.first_nonspace..])
+                        || (self.options.extension.table_captions
+                            && table::is_caption(&line[self.first_nonspace..]))
+                    {
                         return (false, container, should_continue);
                     }
                     continue;
                 }
+                NodeValue::TableRow(..)
+                    if self
+                        .continued_table_row
+                        .map_or(false, |row| row.same_node(container)) =>
+                {
+                    continue;
+                }
                 NodeValue::Heading(..) | NodeValue::TableRow(..) | NodeValue::TableCell => {
                     return (false, container, should_continue);
                 }
@@ -1150,7 +2978,9 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                         return (false, container, should_continue);
                     }
                 }
-                NodeValue::MultilineBlockQuote(..) => {
+                NodeValue::MultilineBlockQuote(..)
+                | NodeValue::Details(..)
+                | NodeValue::FencedDiv(..) => {
                     if !self.parse_multiline_block_quote_prefix(
                         line,
                         container,
@@ -1170,6 +3000,8 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
     fn open_new_blocks(&mut self, container: &mut &'a AstNode<'a>, line: &[u8], all_matched: bool) {
         let mut matched: usize = 0;
         let mut nl: NodeList = NodeList::default();
+        let mut nd: NodeDetails = NodeDetails::default();
+        let mut nfd: NodeFencedDiv = NodeFencedDiv::default();
         let mut sc: scanners::SetextChar = scanners::SetextChar::Equals;
         let mut maybe_lazy = node_matches!(self.current, NodeValue::Paragraph);
         let mut depth = 0;
@@ -1182,6 +3014,15 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             self.find_first_nonspace(line);
             let indented = self.indent >= CODE_INDENT;
 
+            if self.diagnostics.is_some() {
+                if depth == self.max_nesting_depth() {
+                    self.check_nesting_depth(container, line, indented);
+                }
+                if !indented {
+                    self.check_heading_depth(line);
+                }
+            }
+
             if !indented
                 && self.options.extension.multiline_block_quotes
                 && unwrap_into(
@@ -1202,8 +3043,44 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 );
                 self.advance_offset(line, first_nonspace + matched - offset, false);
             } else if !indented
-                && line[self.first_nonspace] == b'>'
-                && strings::is_space_or_tab(line[self.first_nonspace + 1])
+                && self.options.extension.details
+                && unwrap_into(
+                    details::scan_open_fence(&line[self.first_nonspace..]),
+                    &mut nd,
+                )
+            {
+                nd.fence_offset = self.first_nonspace - self.offset;
+                *container = self.add_child(
+                    container,
+                    NodeValue::Details(mem::take(&mut nd)),
+                    self.first_nonspace + 1,
+                );
+                let mut end = line.len();
+                while end > self.offset && strings::is_line_end_char(line[end - 1]) {
+                    end -= 1;
+                }
+                self.advance_offset(line, end - self.offset, false);
+            } else if !indented
+                && self.options.extension.fenced_divs
+                && unwrap_into(
+                    fenced_div::scan_open_fence(&line[self.first_nonspace..]),
+                    &mut nfd,
+                )
+            {
+                nfd.fence_offset = self.first_nonspace - self.offset;
+                *container = self.add_child(
+                    container,
+                    NodeValue::FencedDiv(mem::take(&mut nfd)),
+                    self.first_nonspace + 1,
+                );
+                let mut end = line.len();
+                while end > self.offset && strings::is_line_end_char(line[end - 1]) {
+                    end -= 1;
+                }
+                self.advance_offset(line, end - self.offset, false);
+            } else if !indented
+                && self.within_block_quote_depth(depth)
+                && self.is_block_quote_marker(line)
             {
                 let blockquote_startpos = self.first_nonspace;
 
@@ -1212,8 +3089,26 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 if strings::is_space_or_tab(line[self.offset]) {
                     self.advance_offset(line, 1, true);
                 }
-                *container =
-                    self.add_child(container, NodeValue::BlockQuote, blockquote_startpos + 1);
+
+                let alert = if self.options.extension.alerts {
+                    self.find_first_nonspace(line);
+                    alert::scan_alert_marker(&line[self.first_nonspace..])
+                } else {
+                    None
+                };
+                let value = match alert {
+                    Some((alert_type, title)) => {
+                        // The marker line holds nothing else.
+                        let mut end = line.len();
+                        while end > self.offset && strings::is_line_end_char(line[end - 1]) {
+                            end -= 1;
+                        }
+                        self.advance_offset(line, end - self.offset, false);
+                        NodeValue::Alert(NodeAlert { alert_type, title })
+                    }
+                    None => NodeValue::BlockQuote,
+                };
+                *container = self.add_child(container, value, blockquote_startpos + 1);
             } else if !indented
                 && unwrap_into(
                     scanners::atx_heading_start(&line[self.first_nonspace..]),
@@ -1260,6 +3155,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     fence_length: matched,
                     fence_offset: first_nonspace - offset,
                     info: String::with_capacity(10),
+                    raw_info: String::new(),
                     literal: String::new(),
                 };
                 *container = self.add_child(
@@ -1328,7 +3224,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 self.advance_offset(line, adv, false);
             } else if !indented
                 && self.options.extension.footnotes
-                && depth < MAX_LIST_DEPTH
+                && depth < self.max_nesting_depth()
                 && unwrap_into(
                     scanners::footnote_definition(&line[self.first_nonspace..]),
                     &mut matched,
@@ -1349,7 +3245,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 container.data.borrow_mut().internal_offset = matched;
             } else if !indented
                 && self.options.extension.description_lists
-                && line[self.first_nonspace] == b':'
+                && self.is_description_list_marker(line[self.first_nonspace])
                 && self.parse_desc_list_details(container)
             {
                 let offset = self.first_nonspace + 1 - self.offset;
@@ -1359,12 +3255,14 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 }
             } else if (!indented || node_matches!(container, NodeValue::List(..)))
                 && self.indent < 4
-                && depth < MAX_LIST_DEPTH
+                && depth < self.max_nesting_depth()
                 && unwrap_into_2(
                     parse_list_marker(
                         line,
                         self.first_nonspace,
                         node_matches!(container, NodeValue::Paragraph),
+                        self.options.extension.alpha_lists,
+                        continues_alpha_list(container),
                     ),
                     &mut matched,
                     &mut nl,
@@ -1395,6 +3293,11 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
 
                 nl.marker_offset = self.indent;
 
+                if let NodeValue::List(ref mnl) = container.data.borrow().value {
+                    let marker = &line[self.first_nonspace..self.first_nonspace + matched - 1];
+                    continue_numbering(mnl, &mut nl, marker);
+                }
+
                 if match container.data.borrow().value {
                     NodeValue::List(ref mnl) => !lists_match(&nl, mnl),
                     _ => true,
@@ -1413,6 +3316,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     fence_length: 0,
                     fence_offset: 0,
                     info: String::new(),
+                    raw_info: String::new(),
                     literal: String::new(),
                 };
                 *container = self.add_child(container, NodeValue::CodeBlock(ncb), self.offset + 1);
@@ -1448,6 +3352,89 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         }
     }
 
+    fn max_nesting_depth(&self) -> usize {
+        self.options
+            .parse
+            .max_nesting_depth
+            .unwrap_or(MAX_LIST_DEPTH)
+    }
+
+    // Block quotes are only limited when a depth has been set explicitly.
+    fn within_block_quote_depth(&self, depth: usize) -> bool {
+        self.options
+            .parse
+            .max_nesting_depth
+            .map_or(true, |max| depth < max)
+    }
+
+    // Report a list or block quote marker that open_new_blocks is about to
+    // ignore because blocks are nested too deeply.
+    fn check_nesting_depth(&mut self, container: &'a AstNode<'a>, line: &[u8], indented: bool) {
+        let depth = self.max_nesting_depth();
+        let message = if (!indented || node_matches!(container, NodeValue::List(..)))
+            && self.indent < 4
+            && parse_list_marker(
+                line,
+                self.first_nonspace,
+                node_matches!(container, NodeValue::Paragraph),
+                self.options.extension.alpha_lists,
+                continues_alpha_list(container),
+            )
+            .is_some()
+        {
+            format!(
+                "list marker ignored; lists are nested more than {} deep",
+                depth
+            )
+        } else if !indented
+            && !self.within_block_quote_depth(depth)
+            && self.is_block_quote_marker(line)
+        {
+            format!(
+                "block quote marker ignored; block quotes are nested more than {} deep",
+                depth
+            )
+        } else {
+            return;
+        };
+
+        let column = self.first_nonspace + 1;
+        if let Some(ref mut diagnostics) = self.diagnostics {
+            diagnostics.push(Diagnostic {
+                sourcepos: (self.line_number, column, self.line_number, column).into(),
+                kind: DiagnosticKind::NestingLimit,
+                message,
+            });
+        }
+    }
+
+    // Report a line that would be an ATX heading but for having more than
+    // six `#`.
+    fn check_heading_depth(&mut self, line: &[u8]) {
+        let hashes = line[self.first_nonspace..]
+            .iter()
+            .take_while(|&&c| c == b'#')
+            .count();
+        let after = self.first_nonspace + hashes;
+        if hashes > 6
+            && (after == line.len()
+                || strings::is_space_or_tab(line[after])
+                || strings::is_line_end_char(line[after]))
+        {
+            let start = self.first_nonspace + 1;
+            if let Some(ref mut diagnostics) = self.diagnostics {
+                diagnostics.push(Diagnostic {
+                    sourcepos: (self.line_number, start, self.line_number, after).into(),
+                    kind: DiagnosticKind::HeadingTooDeep,
+                    message: format!(
+                        "{} `#` can't start a heading; headings go at most 6 deep",
+                        hashes
+                    ),
+                });
+            }
+        }
+    }
+
     fn advance_offset(&mut self, line: &[u8], mut count: usize, columns: bool) {
         while count > 0 {
             match line[self.offset] {
@@ -1476,12 +3463,22 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         }
     }
 
+    // Whether the line has a `>` starting or continuing a block quote at
+    // `first_nonspace`.  It must be followed by a space or tab, unless
+    // `blockquote_no_space_required` is set, in which case only a line
+    // ending can't follow it.
+    fn is_block_quote_marker(&self, line: &[u8]) -> bool {
+        if line[self.first_nonspace] != b'>' {
+            return false;
+        }
+        let next = line[self.first_nonspace + 1];
+        strings::is_space_or_tab(next)
+            || (self.options.parse.blockquote_no_space_required && !strings::is_line_end_char(next))
+    }
+
     fn parse_block_quote_prefix(&mut self, line: &[u8]) -> bool {
         let indent = self.indent;
-        if indent <= 3
-            && line[self.first_nonspace] == b'>'
-            && strings::is_space_or_tab(line[self.first_nonspace + 1])
-        {
+        if indent <= 3 && self.is_block_quote_marker(line) {
             self.advance_offset(line, indent + 1, true);
 
             if strings::is_space_or_tab(line[self.offset]) {
@@ -1491,6 +3488,18 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             return true;
         }
 
+        if self.options.parse.lazy_blockquote_continuation {
+            if indent <= 3 && line[self.first_nonspace] == b'>' {
+                // Only a line ending can follow, or the marker would have
+                // matched above.
+                self.advance_offset(line, indent + 1, true);
+                return true;
+            }
+            if self.blank {
+                return true;
+            }
+        }
+
         false
     }
 
@@ -1521,6 +3530,15 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         }
     }
 
+    fn is_description_list_marker(&self, c: u8) -> bool {
+        let marker = self
+            .options
+            .extension
+            .description_list_marker
+            .unwrap_or(':');
+        marker.is_ascii() && c == marker as u8
+    }
+
     fn parse_description_item_prefix(
         &mut self,
         line: &[u8],
@@ -1577,6 +3595,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         if matched >= fence_length {
             *should_continue = false;
             self.advance_offset(line, matched, false);
+            self.closing_fence = true;
             self.current = self.finalize_borrowed(container, ast).unwrap();
             return false;
         }
@@ -1614,21 +3633,8 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             last_child.detach();
             let last_child_sourcepos = last_child.data.borrow().sourcepos;
 
-            // TODO: description list sourcepos has issues.
-            //
-            // DescriptionItem:
-            //   For all but the last, the end line/col is wrong.
-            //   Where it should be l:c, it gives (l+1):0.
-            //
-            // DescriptionTerm:
-            //   All are incorrect; they all give the start line/col of
-            //   the DescriptionDetails, and the end line/col is completely off.
-            //
-            // descriptionDetails:
-            //   Same as the DescriptionItem.  All but last, the end line/col
-            //   is (l+1):0.
-            //
-            // See crate::tests::description_lists::sourcepos.
+            // The term and item are opened on the details' line; their
+            // positions are put right when the list is finalized.
             let list = match container.last_child() {
                 Some(lc) if node_matches!(lc, NodeValue::DescriptionList) => {
                     reopen_ast_nodes(lc);
@@ -1648,6 +3654,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             let metadata = NodeDescriptionItem {
                 marker_offset: self.indent,
                 padding: 2,
+                tight: false,
             };
 
             let item = self.add_child(
@@ -1662,6 +3669,40 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
 
             term.append(last_child);
 
+            if self.options.extension.description_list_grouped_terms {
+                self.split_description_terms(term, last_child);
+            }
+
+            *container = details;
+
+            true
+        } else if self.options.extension.description_list_grouped_terms {
+            // Further details for the last item of the list before.  They get
+            // an item of their own for now, as the last item may be about to
+            // be closed; finalizing the list moves them into it.
+            let list = if node_matches!(container, NodeValue::DescriptionList) {
+                *container
+            } else if node_matches!(last_child, NodeValue::DescriptionList) {
+                reopen_ast_nodes(last_child);
+                last_child
+            } else {
+                return false;
+            };
+
+            let metadata = NodeDescriptionItem {
+                marker_offset: self.indent,
+                padding: 2,
+                tight: false,
+            };
+
+            let item = self.add_child(
+                list,
+                NodeValue::DescriptionItem(metadata),
+                self.first_nonspace + 1,
+            );
+            let details =
+                self.add_child(item, NodeValue::DescriptionDetails, self.first_nonspace + 1);
+
             *container = details;
 
             true
@@ -1670,6 +3711,65 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         }
     }
 
+    // Gives each line of a term paragraph a DescriptionTerm of its own,
+    // inserted after `term`.
+    fn split_description_terms(&mut self, term: &'a AstNode<'a>, paragraph: &'a AstNode<'a>) {
+        let (content, line_starts) = {
+            let ast = paragraph.data.borrow();
+            (ast.content.clone(), ast.line_starts.clone())
+        };
+
+        // Link reference definitions may have been taken from the start of
+        // the content, so its lines are the last of those added.
+        let lines = content
+            .trim_end_matches('\n')
+            .split('\n')
+            .collect::<Vec<_>>();
+        if lines.len() > line_starts.len() {
+            return;
+        }
+        let line_starts = &line_starts[line_starts.len() - lines.len()..];
+
+        // Where `line`, starting at `start`, has its first and last
+        // characters other than whitespace.
+        let span = |line: &str, start: LineColumn| -> Sourcepos {
+            let column = start.column + line.len() - line.trim_start().len();
+            (
+                start.line,
+                column,
+                start.line,
+                column + line.trim().len().max(1) - 1,
+            )
+                .into()
+        };
+
+        let mut last_term = term;
+        for (line, &start) in lines.iter().zip(line_starts).skip(1) {
+            let sourcepos = span(line, start);
+
+            let mut ast = Ast::new(NodeValue::Paragraph, sourcepos.start);
+            ast.content = format!("{}\n", line);
+            ast.sourcepos = sourcepos;
+            ast.open = paragraph.data.borrow().open;
+            let new_paragraph = self.arena.alloc(Node::new(RefCell::new(ast)));
+
+            let mut ast = Ast::new(NodeValue::DescriptionTerm, sourcepos.start);
+            ast.sourcepos = sourcepos;
+            let new_term = self.arena.alloc(Node::new(RefCell::new(ast)));
+            new_term.append(new_paragraph);
+
+            last_term.insert_after(new_term);
+            last_term = new_term;
+        }
+
+        let mut ast = paragraph.data.borrow_mut();
+        if lines.len() > 1 {
+            ast.sourcepos.end = span(lines[0], line_starts[0]).end;
+        }
+        ast.content = format!("{}\n", lines[0]);
+        ast.line_starts = line_starts[..1].to_vec();
+    }
+
     fn parse_multiline_block_quote_prefix(
         &mut self,
         line: &[u8],
@@ -1681,11 +3781,26 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             NodeValue::MultilineBlockQuote(ref node_value) => {
                 (node_value.fence_length, node_value.fence_offset)
             }
+            NodeValue::Details(ref nd) => (nd.fence_length, nd.fence_offset),
+            NodeValue::FencedDiv(ref nfd) => (nfd.fence_length, nfd.fence_offset),
             _ => unreachable!(),
         };
 
-        let matched = if self.indent <= 3 && line[self.first_nonspace] == b'>' {
-            scanners::close_multiline_block_quote_fence(&line[self.first_nonspace..]).unwrap_or(0)
+        let rest = &line[self.first_nonspace..];
+        let matched = if self.indent > 3 {
+            0
+        } else if let NodeValue::Details(..) = ast.value {
+            details::scan_close_fence(rest).unwrap_or(0)
+        } else if let NodeValue::FencedDiv(..) = ast.value {
+            // A div nested within and still open takes the fence, either as
+            // its own closing fence or, if too short, as content.
+            if has_open_fenced_div(container) {
+                0
+            } else {
+                details::scan_close_fence(rest).unwrap_or(0)
+            }
+        } else if rest[0] == b'>' {
+            scanners::close_multiline_block_quote_fence(rest).unwrap_or(0)
         } else {
             0
         };
@@ -1749,13 +3864,18 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
 
         container.data.borrow_mut().last_line_blank = self.blank
             && match container.data.borrow().value {
-                NodeValue::BlockQuote | NodeValue::Heading(..) | NodeValue::ThematicBreak => false,
+                NodeValue::BlockQuote
+                | NodeValue::Alert(..)
+                | NodeValue::Heading(..)
+                | NodeValue::ThematicBreak => false,
                 NodeValue::CodeBlock(ref ncb) => !ncb.fenced,
                 NodeValue::Item(..) => {
                     container.first_child().is_some()
                         || container.data.borrow().sourcepos.start.line != self.line_number
                 }
-                NodeValue::MultilineBlockQuote(..) => false,
+                NodeValue::MultilineBlockQuote(..)
+                | NodeValue::Details(..)
+                | NodeValue::FencedDiv(..) => false,
                 _ => true,
             };
 
@@ -1770,7 +3890,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             && !self.blank
             && !matches!(
                 container.data.borrow().value,
-                NodeValue::BlockQuote | NodeValue::Document
+                NodeValue::BlockQuote | NodeValue::Alert(..) | NodeValue::Document
             )
             && node_matches!(self.current, NodeValue::Paragraph)
         {
@@ -1867,6 +3987,10 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             ast.content
                 .push_str(str::from_utf8(&line[self.offset..]).unwrap());
         }
+        if let NodeValue::Paragraph = ast.value {
+            ast.line_starts
+                .push((self.line_number, self.offset + 1).into());
+        }
     }
 
     fn finish(&mut self, remaining: Vec<u8>) -> &'a AstNode<'a> {
@@ -1892,10 +4016,24 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             100000
         };
 
+        if self.options.extension.table_captions {
+            table::attach_captions(self.root);
+        }
+
         self.process_inlines();
         if self.options.extension.footnotes {
             self.process_footnotes();
         }
+
+        if let Some(default_info_string_fn) = self.options.parse.default_info_string_fn {
+            for node in mem::take(&mut self.missing_info) {
+                if let Some(info) = default_info_string_fn(node) {
+                    if let NodeValue::CodeBlock(ref mut ncb) = node.data.borrow_mut().value {
+                        ncb.info = info;
+                    }
+                }
+            }
+        }
     }
 
     fn finalize(&mut self, node: &'a AstNode<'a>) -> Option<&'a AstNode<'a>> {
@@ -1903,13 +4041,23 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
     }
 
     fn resolve_reference_link_definitions(&mut self, content: &mut String) -> bool {
+        let references = !self.options.parse.no_reference_definitions;
+        let abbreviations = self.options.extension.abbreviations;
+        if !references && !abbreviations {
+            return !strings::is_blank(content.as_bytes());
+        }
+
         let mut seeked = 0;
         {
             let mut pos = 0;
             let mut seek: &[u8] = content.as_bytes();
             while !seek.is_empty()
-                && seek[0] == b'['
-                && unwrap_into(self.parse_reference_inline(seek), &mut pos)
+                && ((references
+                    && seek[0] == b'['
+                    && unwrap_into(self.parse_reference_inline(seek), &mut pos))
+                    || (abbreviations
+                        && seek[0] == b'*'
+                        && unwrap_into(self.parse_abbreviation_definition(seek), &mut pos)))
             {
                 seek = &seek[pos..];
                 seeked += pos;
@@ -1939,7 +4087,9 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         } else if match ast.value {
             NodeValue::Document => true,
             NodeValue::CodeBlock(ref ncb) => ncb.fenced,
-            NodeValue::MultilineBlockQuote(..) => true,
+            NodeValue::MultilineBlockQuote(..)
+            | NodeValue::Details(..)
+            | NodeValue::FencedDiv(..) => true,
             _ => false,
         } {
             ast.sourcepos.end = (self.line_number, self.curline_end_col).into();
@@ -1959,6 +4109,16 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     strings::remove_trailing_blank_lines(content);
                     content.push('\n');
                 } else {
+                    if !mem::take(&mut self.closing_fence) {
+                        if let Some(ref mut diagnostics) = self.diagnostics {
+                            diagnostics.push(Diagnostic {
+                                sourcepos: ast.sourcepos,
+                                kind: DiagnosticKind::UnclosedCodeFence,
+                                message: "code fence is never closed".to_string(),
+                            });
+                        }
+                    }
+
                     let mut pos = 0;
                     while pos < content.len() {
                         if strings::is_line_end_char(content.as_bytes()[pos]) {
@@ -1968,10 +4128,15 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     }
                     assert!(pos < content.len());
 
+                    ncb.raw_info = content[..pos].to_string();
+
                     let mut tmp = entity::unescape_html(&content.as_bytes()[..pos]);
                     strings::trim(&mut tmp);
                     strings::unescape(&mut tmp);
                     if tmp.is_empty() {
+                        if self.options.parse.default_info_string_fn.is_some() {
+                            self.missing_info.push(node);
+                        }
                         ncb.info = self
                             .options
                             .parse
@@ -2023,6 +4188,55 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
 
                     ch = item.next_sibling();
                 }
+
+                if self.options.extension.reversed_lists && nl.list_type == ListType::Ordered {
+                    nl.reversed = counts_down(node);
+                }
+            }
+            NodeValue::DescriptionList => {
+                let mut ch = node.first_child();
+                while let Some(item) = ch {
+                    ch = item.next_sibling();
+                    let has_term = item
+                        .children()
+                        .any(|child| node_matches!(child, NodeValue::DescriptionTerm));
+                    if let (false, Some(prev)) = (has_term, item.previous_sibling()) {
+                        while let Some(details) = item.first_child() {
+                            prev.append(details);
+                        }
+                        prev.data.borrow_mut().sourcepos.end = item.data.borrow().sourcepos.end;
+                        item.detach();
+                    }
+                }
+
+                let tight = !node.children().any(|item| {
+                    item.children()
+                        .filter(|child| node_matches!(child, NodeValue::DescriptionDetails))
+                        .flat_map(|details| details.children())
+                        .any(|block| {
+                            block.next_sibling().is_some() && nodes::ends_with_blank_line(block)
+                        })
+                });
+
+                for item in node.children() {
+                    if let NodeValue::DescriptionItem(ref mut di) = item.data.borrow_mut().value {
+                        di.tight = tight;
+                    }
+                }
+
+                // Closing on the line after a blank one leaves items and
+                // details ending on that blank line, and terms span the
+                // details' line; take their extent from their contents.
+                for item in node.children() {
+                    for child in item.children() {
+                        let is_term = node_matches!(child, NodeValue::DescriptionTerm);
+                        fit_sourcepos_to_children(child, is_term);
+                    }
+                    fit_sourcepos_to_children(item, true);
+                }
+                if let Some(last) = node.last_child() {
+                    ast.sourcepos.end = last.data.borrow().sourcepos.end;
+                }
             }
             _ => (),
         }
@@ -2045,7 +4259,18 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
     fn parse_inlines(&mut self, node: &'a AstNode<'a>) {
         let delimiter_arena = Arena::new();
         let node_data = node.data.borrow();
-        let content = strings::rtrim_slice(node_data.content.as_bytes());
+        let mut content = strings::rtrim_slice(node_data.content.as_bytes());
+
+        let trailing_break = self.options.parse.list_item_trailing_hard_break
+            && nodes::ends_list_item(node)
+            && match trailing_hard_break(node_data.content.as_bytes()) {
+                Some(len) => {
+                    content = &content[..content.len() - len];
+                    true
+                }
+                None => false,
+            };
+
         let mut subj = inlines::Subject::new(
             self.arena,
             self.options,
@@ -2053,8 +4278,10 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             node_data.sourcepos.start.line,
             node_data.sourcepos.start.column - 1 + node_data.internal_offset,
             &mut self.refmap,
+            self.shared_refmap,
             &delimiter_arena,
             self.callback.as_mut(),
+            self.diagnostics.as_mut(),
         );
 
         while subj.parse_inline(node) {}
@@ -2062,14 +4289,37 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         subj.process_emphasis(0);
 
         while subj.pop_bracket() {}
+
+        subj.report_smart_punctuation();
+
+        if trailing_break {
+            let end = node_data.sourcepos.end;
+            node.append(inlines::make_inline(
+                self.arena,
+                NodeValue::LineBreak,
+                Sourcepos { start: end, end },
+            ));
+        }
     }
 
     fn process_footnotes(&mut self) {
         let mut map = HashMap::new();
-        Self::find_footnote_definitions(self.root, &mut map);
+        Self::find_footnote_definitions(self.root, &mut map, &mut self.diagnostics);
 
-        let mut ix = 0;
-        Self::find_footnote_references(self.root, &mut map, &mut ix);
+        let base = self.options.extension.footnote_base();
+        let mut ix = base;
+        let root = self.root;
+        Self::find_footnote_references(
+            root,
+            &mut map,
+            &mut ix,
+            &mut self.callback,
+            &mut self.diagnostics,
+        );
+
+        if self.options.extension.footnotes_render_unreferenced {
+            Self::number_unreferenced_footnotes(self.root, &mut map, &mut ix);
+        }
 
         if !map.is_empty() {
             // In order for references to be found inside footnote definitions,
@@ -2078,7 +4328,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             Self::cleanup_footnote_definitions(self.root);
         }
 
-        if ix > 0 {
+        if ix > base {
             let mut v = map.into_values().collect::<Vec<_>>();
             v.sort_unstable_by(|a, b| a.ix.cmp(&b.ix));
             for f in v {
@@ -2099,11 +4349,13 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
     fn find_footnote_definitions(
         node: &'a AstNode<'a>,
         map: &mut HashMap<String, FootnoteDefinition<'a>>,
+        diagnostics: &mut Option<Vec<Diagnostic>>,
     ) {
         match node.data.borrow().value {
             NodeValue::FootnoteDefinition(ref nfd) => {
-                map.insert(
-                    strings::normalize_label(&nfd.name, Case::DontPreserve),
+                let normalized = strings::normalize_label(&nfd.name, Case::DontPreserve);
+                let replaced = map.insert(
+                    normalized.clone(),
                     FootnoteDefinition {
                         ix: None,
                         node,
@@ -2111,10 +4363,39 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                         total_references: 0,
                     },
                 );
+                // The last definition of a label wins.
+                if let (Some(replaced), Some(ref mut diagnostics)) = (replaced, diagnostics) {
+                    diagnostics.push(Diagnostic {
+                        sourcepos: replaced.node.data.borrow().sourcepos,
+                        kind: DiagnosticKind::DuplicateFootnoteDefinition,
+                        message: format!(
+                            "footnote \"{}\" is defined again later; this definition is ignored",
+                            normalized
+                        ),
+                    });
+                }
             }
             _ => {
                 for n in node.children() {
-                    Self::find_footnote_definitions(n, map);
+                    Self::find_footnote_definitions(n, map, diagnostics);
+                }
+            }
+        }
+    }
+
+    fn number_unreferenced_footnotes(
+        root: &'a AstNode<'a>,
+        map: &mut HashMap<String, FootnoteDefinition<'a>>,
+        ixp: &mut u32,
+    ) {
+        for node in root.descendants() {
+            if let NodeValue::FootnoteDefinition(ref nfd) = node.data.borrow().value {
+                let normalized = strings::normalize_label(&nfd.name, Case::DontPreserve);
+                if let Some(footnote) = map.get_mut(&normalized) {
+                    if footnote.ix.is_none() && footnote.node.same_node(node) {
+                        *ixp = ixp.saturating_add(1);
+                        footnote.ix = Some(*ixp);
+                    }
                 }
             }
         }
@@ -2124,8 +4405,11 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         node: &'a AstNode<'a>,
         map: &mut HashMap<String, FootnoteDefinition>,
         ixp: &mut u32,
+        callback: &mut Option<Callback<'c>>,
+        diagnostics: &mut Option<Vec<Diagnostic>>,
     ) {
         let mut ast = node.data.borrow_mut();
+        let sourcepos = ast.sourcepos;
         let mut replace = None;
         match ast.value {
             NodeValue::FootnoteReference(ref mut nfr) => {
@@ -2134,7 +4418,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     let ix = match footnote.ix {
                         Some(ix) => ix,
                         None => {
-                            *ixp += 1;
+                            *ixp = ixp.saturating_add(1);
                             footnote.ix = Some(*ixp);
                             *ixp
                         }
@@ -2144,12 +4428,29 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     nfr.ix = ix;
                     nfr.name = strings::normalize_label(&footnote.name, Case::Preserve);
                 } else {
+                    if let Some(ref mut callback) = callback {
+                        callback(
+                            BrokenLinkReference {
+                                normalized: &normalized,
+                                original: &nfr.name,
+                                kind: ReferenceKind::Footnote,
+                            },
+                            false,
+                        );
+                    }
+                    if let Some(ref mut diagnostics) = diagnostics {
+                        diagnostics.push(Diagnostic {
+                            sourcepos,
+                            kind: DiagnosticKind::UnresolvedReference(ReferenceKind::Footnote),
+                            message: format!("unresolved footnote reference \"{}\"", normalized),
+                        });
+                    }
                     replace = Some(nfr.name.clone());
                 }
             }
             _ => {
                 for n in node.children() {
-                    Self::find_footnote_references(n, map, ixp);
+                    Self::find_footnote_references(n, map, ixp, callback, diagnostics);
                 }
             }
         }
@@ -2185,6 +4486,11 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                 let mut this_bracket = false;
                 let n_ast = &mut n.data.borrow_mut();
                 let mut sourcepos = n_ast.sourcepos;
+                let mut raw_backslashes = vec![];
+                if is_raw_backslash(n_ast) {
+                    raw_backslashes.push(0);
+                }
+                let mut next = n.next_sibling();
 
                 loop {
                     match n_ast.value {
@@ -2194,25 +4500,45 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                                 Some(ns) => ns,
                                 _ => {
                                     // Post-process once we are finished joining text nodes
-                                    self.postprocess_text_node(n, root, &mut sourcepos);
+                                    next = None;
+                                    self.postprocess_text_node(
+                                        n,
+                                        root,
+                                        &mut sourcepos,
+                                        raw_backslashes,
+                                    );
                                     break;
                                 }
                             };
 
-                            match ns.data.borrow().value {
+                            let ns_ast = ns.data.borrow();
+                            match ns_ast.value {
                                 NodeValue::Text(ref adj) => {
+                                    if is_raw_backslash(&ns_ast) {
+                                        raw_backslashes.push(root.len());
+                                    }
                                     root.push_str(adj);
-                                    sourcepos.end.column = ns.data.borrow().sourcepos.end.column;
+                                    sourcepos.end.column = ns_ast.sourcepos.end.column;
                                     ns.detach();
                                 }
                                 _ => {
-                                    // Post-process once we are finished joining text nodes
-                                    self.postprocess_text_node(n, root, &mut sourcepos);
+                                    // Post-process once we are finished joining text nodes;
+                                    // whatever it splits off lies before `ns`.
+                                    next = Some(ns);
+                                    self.postprocess_text_node(
+                                        n,
+                                        root,
+                                        &mut sourcepos,
+                                        raw_backslashes,
+                                    );
                                     break;
                                 }
                             }
                         }
-                        NodeValue::Link(..) | NodeValue::Image(..) | NodeValue::WikiLink(..) => {
+                        NodeValue::Link(..)
+                        | NodeValue::Image(..)
+                        | NodeValue::WikiLink(..)
+                        | NodeValue::Abbreviation(..) => {
                             this_bracket = true;
                             break;
                         }
@@ -2226,7 +4552,7 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
                     children.push(n);
                 }
 
-                nch = n.next_sibling();
+                nch = next;
             }
 
             // Push children onto work stack in reverse order so they are
@@ -2235,23 +4561,66 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
         }
     }
 
+    // Post-processes the joined text of `node`, splitting off autolinks and
+    // abbreviations into new siblings directly after it.  `raw_backslashes`
+    // are the offsets of backslashes in `text` which weren't escaped.
     fn postprocess_text_node(
         &mut self,
         node: &'a AstNode<'a>,
         text: &mut String,
         sourcepos: &mut Sourcepos,
+        mut raw_backslashes: Vec<usize>,
     ) {
         if self.options.extension.tasklist {
+            let len = text.len();
             self.process_tasklist(node, text, sourcepos);
+            let drained = len - text.len();
+            for ix in &mut raw_backslashes {
+                *ix -= drained;
+            }
         }
 
-        if self.options.extension.autolink {
+        // Whether each byte of the text is a column of the source, so that
+        // pieces split out of it can be given exact positions.
+        let literal = sourcepos.start.line == sourcepos.end.line
+            && sourcepos.end.column + 1 == sourcepos.start.column + text.len();
+
+        let pieces = if self.options.extension.autolink {
             autolink::process_autolinks(
                 self.arena,
                 node,
                 text,
-                self.options.parse.relaxed_autolinks,
+                &self.options.parse,
+                &raw_backslashes,
+            )
+        } else {
+            vec![]
+        };
+
+        if !self.abbreviations.is_empty() {
+            abbreviation::process_abbreviations(
+                self.arena,
+                node,
+                text,
+                sourcepos,
+                literal,
+                &self.abbreviations,
             );
+            for piece in pieces {
+                let mut ast = piece.data.borrow_mut();
+                let mut sourcepos = ast.sourcepos;
+                if let NodeValue::Text(ref mut text) = ast.value {
+                    abbreviation::process_abbreviations(
+                        self.arena,
+                        piece,
+                        text,
+                        &mut sourcepos,
+                        false,
+                        &self.abbreviations,
+                    );
+                }
+                ast.sourcepos = sourcepos;
+            }
         }
     }
 
@@ -2297,6 +4666,12 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             NodeValue::TaskItem(if symbol == ' ' { None } else { Some(symbol) });
     }
 
+    fn parse_abbreviation_definition(&mut self, content: &[u8]) -> Option<usize> {
+        let (label, title, len) = abbreviation::parse_definition(content)?;
+        self.abbreviations.insert(label, title);
+        Some(len)
+    }
+
     fn parse_reference_inline(&mut self, content: &[u8]) -> Option<usize> {
         // In this case reference inlines rarely have delimiters
         // so we often just need the minimal case
@@ -2308,8 +4683,10 @@ impl<'a, 'o, 'c> Parser<'a, 'o, 'c> {
             0, // XXX -1 in upstream; never used?
             0,
             &mut self.refmap,
+            None,
             &delimiter_arena,
             self.callback.as_mut(),
+            None,
         );
 
         let mut lab: String = match subj.link_label() {
@@ -2382,6 +4759,8 @@ fn parse_list_marker(
     line: &[u8],
     mut pos: usize,
     interrupts_paragraph: bool,
+    alpha_lists: bool,
+    continues_alpha: bool,
 ) -> Option<(usize, NodeList)> {
     let mut c = line[pos];
     let startpos = pos;
@@ -2410,8 +4789,10 @@ fn parse_list_marker(
                 padding: 0,
                 start: 1,
                 delimiter: ListDelimType::Period,
+                numbering: ListNumbering::Decimal,
                 bullet_char: c,
                 tight: false,
+                reversed: false,
             },
         ));
     } else if isdigit(c) {
@@ -2465,8 +4846,72 @@ fn parse_list_marker(
                 } else {
                     ListDelimType::Paren
                 },
+                numbering: ListNumbering::Decimal,
+                bullet_char: 0,
+                tight: false,
+                reversed: false,
+            },
+        ));
+    } else if alpha_lists && isalpha(c) {
+        // Too easily the start of a sentence to interrupt a paragraph.
+        if interrupts_paragraph {
+            return None;
+        }
+
+        let upper = c.is_ascii_uppercase();
+        while pos - startpos < 8 && isalpha(line[pos]) && line[pos].is_ascii_uppercase() == upper {
+            pos += 1;
+        }
+
+        let marker = line[startpos..pos].to_ascii_lowercase();
+        let (numbering, start) = if marker.len() == 1 && marker[0] != b'i' {
+            (ListNumbering::LowerAlpha, (marker[0] - b'a' + 1) as usize)
+        } else if marker.len() > 1 && continues_alpha {
+            (ListNumbering::LowerAlpha, nodes::parse_alpha(&marker)?)
+        } else {
+            (ListNumbering::LowerRoman, nodes::parse_roman(&marker)?)
+        };
+
+        c = line[pos];
+        if c != b'.' && c != b')' {
+            return None;
+        }
+
+        pos += 1;
+
+        if !isspace(line[pos]) {
+            return None;
+        }
+
+        // As in Pandoc, `A. Smith` is an initial rather than a list item: a
+        // capital letter and period need two spaces, or a tab, after them.
+        if marker.len() == 1 && upper && c == b'.' && line[pos] == b' ' {
+            match line.get(pos + 1) {
+                Some(&next) if !isspace(next) => return None,
+                _ => (),
+            }
+        }
+
+        return Some((
+            pos - startpos,
+            NodeList {
+                list_type: ListType::Ordered,
+                marker_offset: 0,
+                padding: 0,
+                start,
+                delimiter: if c == b'.' {
+                    ListDelimType::Period
+                } else {
+                    ListDelimType::Paren
+                },
+                numbering: match (numbering, upper) {
+                    (ListNumbering::LowerAlpha, true) => ListNumbering::UpperAlpha,
+                    (ListNumbering::LowerRoman, true) => ListNumbering::UpperRoman,
+                    _ => numbering,
+                },
                 bullet_char: 0,
                 tight: false,
+                reversed: false,
             },
         ));
     }
@@ -2474,6 +4919,58 @@ fn parse_list_marker(
     None
 }
 
+// Whether a list item marker found in `container` would continue an
+// alphabetical list, and so is read as letters even where it could be a roman
+// numeral.
+fn continues_alpha_list<'a>(container: &'a AstNode<'a>) -> bool {
+    match container.data.borrow().value {
+        NodeValue::List(ref nl) => matches!(
+            nl.numbering,
+            ListNumbering::LowerAlpha | ListNumbering::UpperAlpha
+        ),
+        _ => false,
+    }
+}
+
+// A one-letter marker such as `i.` or `v.` could be either a letter or a
+// roman numeral; take whichever continues the list it follows.
+fn continue_numbering(list: &NodeList, item: &mut NodeList, marker: &[u8]) {
+    if marker.len() != 1 {
+        return;
+    }
+
+    let lower = marker[0].to_ascii_lowercase();
+    let upper = marker[0].is_ascii_uppercase();
+
+    match (list.numbering, item.numbering) {
+        (
+            ListNumbering::LowerAlpha | ListNumbering::UpperAlpha,
+            ListNumbering::LowerRoman | ListNumbering::UpperRoman,
+        ) => {
+            item.numbering = if upper {
+                ListNumbering::UpperAlpha
+            } else {
+                ListNumbering::LowerAlpha
+            };
+            item.start = (lower - b'a' + 1) as usize;
+        }
+        (
+            ListNumbering::LowerRoman | ListNumbering::UpperRoman,
+            ListNumbering::LowerAlpha | ListNumbering::UpperAlpha,
+        ) => {
+            if let Some(n) = nodes::parse_roman(&[lower]) {
+                item.numbering = if upper {
+                    ListNumbering::UpperRoman
+                } else {
+                    ListNumbering::LowerRoman
+                };
+                item.start = n;
+            }
+        }
+        _ => (),
+    }
+}
+
 pub fn unwrap_into<T>(t: Option<T>, out: &mut T) -> bool {
     match t {
         Some(v) => {
@@ -2505,12 +5002,64 @@ fn unwrap_into_2<T, U>(tu: Option<(T, U)>, out_t: &mut T, out_u: &mut U) -> bool
     }
 }
 
+// Whether a list has at least two items, each numbered one less than the last.
+// Task items don't keep their number, so a list with any can't qualify.
+fn counts_down<'a>(list: &'a AstNode<'a>) -> bool {
+    let mut prev = None;
+    let mut count = 0;
+    for item in list.children() {
+        let start = match item.data.borrow().value {
+            NodeValue::Item(ref nl) => nl.start,
+            _ => return false,
+        };
+        if prev.map_or(false, |p| start + 1 != p) {
+            return false;
+        }
+        prev = Some(start);
+        count += 1;
+    }
+    count > 1
+}
+
+// Whether a fenced div is open anywhere within `container`.
+fn has_open_fenced_div<'a>(container: &'a AstNode<'a>) -> bool {
+    let mut node = container;
+    while nodes::last_child_is_open(node) {
+        node = node.last_child().unwrap();
+        if node_matches!(node, NodeValue::FencedDiv(..)) {
+            return true;
+        }
+    }
+    false
+}
+
 fn lists_match(list_data: &NodeList, item_data: &NodeList) -> bool {
     list_data.list_type == item_data.list_type
         && list_data.delimiter == item_data.delimiter
+        && list_data.numbering == item_data.numbering
         && list_data.bullet_char == item_data.bullet_char
 }
 
+// If the last line of a block's content ends in a hard line break, returns
+// how many bytes of the right-trimmed content make up the break: none for two
+// or more spaces, which trimming removes, or one for a backslash.
+fn trailing_hard_break(content: &[u8]) -> Option<usize> {
+    let line = content
+        .strip_suffix(b"\n")
+        .map(|l| l.strip_suffix(b"\r").unwrap_or(l))?;
+
+    if line.ends_with(b"  ") && !strings::is_blank(line) {
+        return Some(0);
+    }
+
+    let backslashes = line.iter().rev().take_while(|&&c| c == b'\\').count();
+    if backslashes % 2 == 1 {
+        Some(1)
+    } else {
+        None
+    }
+}
+
 fn reopen_ast_nodes<'a>(mut ast: &'a AstNode<'a>) {
     loop {
         ast.data.borrow_mut().open = true;
@@ -2521,10 +5070,112 @@ fn reopen_ast_nodes<'a>(mut ast: &'a AstNode<'a>) {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum AutolinkType {
-    Uri,
-    Email,
+// Sets the end of `node` to that of its last child, and its start to that of
+// its first if `start` is set.  Childless nodes are left as they are.
+fn fit_sourcepos_to_children<'a>(node: &'a AstNode<'a>, start: bool) {
+    let (first, last) = match (node.first_child(), node.last_child()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return,
+    };
+    let mut ast = node.data.borrow_mut();
+    if start {
+        ast.sourcepos.start = first.data.borrow().sourcepos.start;
+    }
+    ast.sourcepos.end = last.data.borrow().sourcepos.end;
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+/// Placement of heading IDs generated by `header_ids`. See `header_id_style` in
+/// [ExtensionOptions] for more details.
+pub enum HeaderIdStyle {
+    /// An empty `<a>` carrying the ID at the start of the heading.
+    #[default]
+    AnchorBefore,
+    /// The same `<a>` as `AnchorBefore`, but at the end of the heading.
+    AnchorAfter,
+    /// An `id` attribute on the heading element.
+    OnHeading,
+    /// No ID is emitted.
+    None,
+}
+
+/// A custom paired delimiter, for `inline_delimiters` in [ParseOptions].
+///
+/// Runs of `delim_char` are matched up as openers and closers by the same
+/// rules as `*`, and the inlines between a matched pair are wrapped in the
+/// node made by `builder`.  Strikethrough, superscript and highlight are
+/// delimiters of this kind.  A character the parser already gives a meaning
+/// to, such as `*`, `[` or the delimiter of an enabled extension, can't be
+/// used, nor can one that isn't ASCII punctuation.
+#[derive(Debug, Clone, Copy)]
+pub struct InlineDelimiter {
+    /// The delimiter's character.
+    pub delim_char: u8,
+
+    /// The shortest run of the character that can open or close.
+    pub min_length: usize,
+
+    /// The longest run of the character that can open or close, if any.
+    pub max_length: Option<usize>,
+
+    /// Whether an opener only pairs with a closer of the same length, both
+    /// used up entirely, as with `~~` for strikethrough.  Otherwise a pair
+    /// uses two characters of each run if both have two, or else one, and
+    /// any left over can pair again.
+    pub same_length: bool,
+
+    /// Builds the node for a matched pair, given how many characters of each
+    /// run were used.
+    pub builder: fn(usize) -> NodeValue,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+/// Handling of NUL bytes in the input. See `nul_handling` in [ParseOptions] for more details.
+pub enum NulPolicy {
+    /// Replace each NUL with U+FFFD REPLACEMENT CHARACTER.
+    #[default]
+    Replace,
+    /// Remove NULs from the input.
+    Strip,
+    /// Fail to parse input with a NUL in [`try_parse_document`].  Other entry
+    /// points replace NULs as with [`NulPolicy::Replace`].
+    Error,
+}
+
+/// A custom inline token, for `inline_tokens` in [ParseOptions].
+///
+/// Wherever `prefix` occurs in inline content, other than in code spans,
+/// autolinks, raw HTML and square brackets, which may turn out to be link
+/// text, or straight after an ASCII letter or digit,
+/// `matcher` is given the text from there to the end of the block and
+/// returns the length of the token starting there, if any.  `builder` then
+/// turns the token's text into the node to put in its place, or returns
+/// `None` to leave it as text.  A node that can contain inlines, such as a
+/// link, gets the token's text as its child.
+#[derive(Debug, Clone, Copy)]
+pub struct InlineToken {
+    /// The ASCII character every such token starts with.
+    pub prefix: u8,
+
+    /// Returns the length in bytes of the token at the start of its
+    /// argument, prefix included.
+    pub matcher: fn(&str) -> Option<usize>,
+
+    /// Builds the node for a token from its text.
+    pub builder: fn(&str) -> Option<NodeValue>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+/// Line endings in rendered output. See `line_ending` in [RenderOptions] for more details.
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`
+    CrLf,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -2539,3 +5190,11 @@ pub enum ListStyleType {
     /// The `*` character
     Star = 42,
 }
+
+// Whether `ast` is a backslash the inline parser left as text because what
+// follows it can't be escaped.  An escaped backslash also becomes a text
+// node of `\`, but spans both characters of its source.
+fn is_raw_backslash(ast: &Ast) -> bool {
+    matches!(ast.value, NodeValue::Text(ref text) if text == "\\")
+        && ast.sourcepos.start == ast.sourcepos.end
+}
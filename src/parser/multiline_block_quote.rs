@@ -1,5 +1,6 @@
 /// The metadata of a multiline blockquote.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeMultilineBlockQuote {
     /// The length of the fence.
     pub fence_length: usize,
@@ -0,0 +1,201 @@
+//! Experimental incremental reparsing of an edited document.
+
+use crate::arena_tree::Node;
+use crate::nodes::{Ast, AstNode, NodeValue};
+use crate::parser::{parse_document, Options, Parser};
+use std::cell::RefCell;
+use typed_arena::Arena;
+
+/// A change made to a document's source, given to [`reparse`].
+///
+/// All offsets are in bytes.  The text between `start` and `old_end` in the
+/// old source was replaced by the text between `start` and `new_end` in the
+/// new source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Where the edit begins, in both the old and new source.
+    pub start: usize,
+    /// Where the replaced text ended in the old source.
+    pub old_end: usize,
+    /// Where the inserted text ends in the new source.
+    pub new_end: usize,
+}
+
+/// **Experimental.**  Parse `new_source`, reusing top-level blocks of
+/// `old_root` that come before `edit`.
+///
+/// `old_root` must be the result of parsing `old_source` with the same
+/// `options`.  The returned document is the same as that from
+/// [`parse_document`] on `new_source`; reused blocks are moved out of
+/// `old_root`, which should not be used afterwards.
+///
+/// Parsing restarts at the block before the first one touched by the
+/// edit, since the edit may change how that block ends, and runs to the
+/// end of the document.  Where a partial parse can't be shown to match a
+/// full one, the whole of `new_source` is parsed instead: currently when
+/// footnotes, front matter, table captions or abbreviations are enabled, or
+/// when either part of the document may define link references.
+///
+/// ```
+/// # use comrak::{Arena, parse_document, reparse, format_html, Options, TextEdit};
+/// let arena = Arena::new();
+/// let options = Options::default();
+///
+/// let old_source = "# Title\n\nFirst.\n\nSecond.\n";
+/// let root = parse_document(&arena, old_source, &options);
+///
+/// let new_source = "# Title\n\nFirst.\n\nSecond, *edited*.\n";
+/// let edit = TextEdit { start: 23, old_end: 24, new_end: 33 };
+/// let root = reparse(&arena, root, old_source, edit, new_source, &options);
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<h1>Title</h1>\n<p>First.</p>\n<p>Second, <em>edited</em>.</p>\n"
+/// );
+/// ```
+pub fn reparse<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    old_root: &'a AstNode<'a>,
+    old_source: &str,
+    edit: TextEdit,
+    new_source: &str,
+    options: &Options,
+) -> &'a AstNode<'a> {
+    match first_reparsed_block(old_root, old_source, edit, new_source, options) {
+        Some(block) => reparse_from(arena, old_root, block, new_source, options)
+            .unwrap_or_else(|| parse_document(arena, new_source, options)),
+        None => parse_document(arena, new_source, options),
+    }
+}
+
+// Find the top-level block from which parsing must restart, if reusing the
+// blocks before it is safe.
+fn first_reparsed_block<'a>(
+    old_root: &'a AstNode<'a>,
+    old_source: &str,
+    edit: TextEdit,
+    new_source: &str,
+    options: &Options,
+) -> Option<&'a AstNode<'a>> {
+    if options.extension.footnotes
+        || options.extension.front_matter_delimiter.is_some()
+        || options.extension.table_captions
+        || options.extension.abbreviations
+    {
+        return None;
+    }
+
+    if edit.start > old_source.len()
+        || edit.start > new_source.len()
+        || old_source.as_bytes()[..edit.start] != new_source.as_bytes()[..edit.start]
+    {
+        return None;
+    }
+
+    let edit_line = line_of(old_source.as_bytes(), edit.start);
+    let touched = old_root
+        .children()
+        .find(|n| n.data.borrow().sourcepos.end.line >= edit_line);
+
+    let block = match touched {
+        Some(touched) => touched.previous_sibling()?,
+        None => old_root.last_child()?,
+    };
+    block.previous_sibling()?;
+
+    if !options.parse.no_reference_definitions {
+        let offset = line_offset(
+            new_source.as_bytes(),
+            block.data.borrow().sourcepos.start.line,
+        );
+        if new_source[..offset].contains("]:") {
+            return None;
+        }
+    }
+
+    Some(block)
+}
+
+fn reparse_from<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    old_root: &'a AstNode<'a>,
+    block: &'a AstNode<'a>,
+    new_source: &str,
+    options: &Options,
+) -> Option<&'a AstNode<'a>> {
+    let start_line = block.data.borrow().sourcepos.start.line;
+    let offset = line_offset(new_source.as_bytes(), start_line);
+
+    let root: &'a AstNode<'a> = arena.alloc(Node::new(RefCell::new(Ast {
+        value: NodeValue::Document,
+        content: String::new(),
+        sourcepos: (1, 1, 1, 1).into(),
+        internal_offset: 0,
+        line_starts: vec![],
+        open: true,
+        last_line_blank: false,
+        table_visited: false,
+    })));
+    let mut parser = Parser::new(arena, root, options, None);
+    let mut linebuf = Vec::with_capacity(new_source.len() - offset);
+    parser.feed(&mut linebuf, &new_source[offset..], true);
+    parser.finish(linebuf);
+
+    // Definitions in the reparsed part could change links in the reused one.
+    if !parser.refmap.map.is_empty() {
+        return None;
+    }
+
+    for node in root.descendants().skip(1) {
+        let sourcepos = &mut node.data.borrow_mut().sourcepos;
+        if sourcepos.start.line > 0 {
+            sourcepos.start.line += start_line - 1;
+            sourcepos.end.line += start_line - 1;
+        }
+    }
+    root.data.borrow_mut().sourcepos.end.line += start_line - 1;
+
+    let reused = old_root
+        .children()
+        .take_while(|n| !n.same_node(block))
+        .collect::<Vec<_>>();
+    for node in reused.into_iter().rev() {
+        node.detach();
+        root.prepend(node);
+    }
+
+    Some(root)
+}
+
+// The 1-based line on which byte `offset` falls.  Lines end with "\n",
+// "\r\n" or "\r", as in the parser.
+fn line_of(source: &[u8], offset: usize) -> usize {
+    let mut line = 1;
+    let mut i = 0;
+    while i < offset {
+        match source[i] {
+            b'\n' => line += 1,
+            b'\r' if source.get(i + 1) != Some(&b'\n') => line += 1,
+            _ => (),
+        }
+        i += 1;
+    }
+    line
+}
+
+// The byte offset at which the 1-based `line` begins.
+fn line_offset(source: &[u8], line: usize) -> usize {
+    let mut current = 1;
+    let mut i = 0;
+    while current < line && i < source.len() {
+        match source[i] {
+            b'\n' => current += 1,
+            b'\r' if source.get(i + 1) != Some(&b'\n') => current += 1,
+            _ => (),
+        }
+        i += 1;
+    }
+    i
+}
@@ -2,6 +2,7 @@ use std::{convert::TryFrom, str};
 
 /// The details of an inline emoji.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeShortCode(
     /// A short code that is translated into an emoji
     String,
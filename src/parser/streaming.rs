@@ -0,0 +1,204 @@
+//! Parsing a document fed in pieces.
+
+use crate::arena_tree::Node;
+use crate::nodes::{Ast, AstNode, NodeValue};
+use crate::parser::{Options, Parser};
+use crate::strings::{split_off_front_matter, trim_start_match};
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
+use std::io::{self, Read};
+use std::str;
+use typed_arena::Arena;
+
+/// A parser fed its input a piece at a time, for documents too large to
+/// hold in memory at once.
+///
+/// Pieces may split the input anywhere, even within a line or between the
+/// `\r` and `\n` of a line ending: the result is the same as that of
+/// [`parse_document`](crate::parse_document) on the whole input.  Only
+/// the line being read is held back between pieces, save for front matter,
+/// which is held until its closing delimiter has been seen.
+///
+/// ```
+/// # use comrak::{Arena, format_html, Options, StreamingParser};
+/// let arena = Arena::new();
+/// let options = Options::default();
+///
+/// let mut parser = StreamingParser::new(&arena, &options);
+/// for piece in ["# Ti", "tle\r", "\nSome *te", "xt*.\n"] {
+///     parser.push(piece);
+/// }
+/// let root = parser.finish();
+///
+/// let mut html = vec![];
+/// format_html(root, &options, &mut html).unwrap();
+/// assert_eq!(
+///     String::from_utf8(html).unwrap(),
+///     "<h1>Title</h1>\n<p>Some <em>text</em>.</p>\n"
+/// );
+/// ```
+pub struct StreamingParser<'a, 'o> {
+    parser: Parser<'a, 'o, 'o>,
+    linebuf: Vec<u8>,
+    // Input held back while it may yet turn out to begin with front matter.
+    pending: Option<String>,
+    pushed: bool,
+}
+
+impl<'a, 'o> StreamingParser<'a, 'o> {
+    /// Creates a parser allocating the document in `arena`.
+    pub fn new(arena: &'a Arena<AstNode<'a>>, options: &'o Options) -> Self {
+        let root: &'a AstNode<'a> = arena.alloc(Node::new(RefCell::new(Ast {
+            value: NodeValue::Document,
+            content: String::new(),
+            sourcepos: (1, 1, 1, 1).into(),
+            internal_offset: 0,
+            line_starts: vec![],
+            open: true,
+            last_line_blank: false,
+            table_visited: false,
+        })));
+        StreamingParser {
+            parser: Parser::new(arena, root, options, None),
+            linebuf: vec![],
+            pending: options
+                .extension
+                .front_matter_delimiter
+                .as_ref()
+                .map(|_| String::new()),
+            pushed: false,
+        }
+    }
+
+    /// Parses the next piece of the input.
+    ///
+    /// With [`strip_bom`](crate::ParseOptions::strip_bom), a byte order mark
+    /// starting the piece is skipped.
+    pub fn push(&mut self, mut s: &str) {
+        if self.pushed && self.parser.options.parse.strip_bom {
+            s = s.strip_prefix('\u{feff}').unwrap_or(s);
+        }
+        self.pushed = true;
+        self.push_piece(s);
+    }
+
+    // Parses the next piece of the input, wherever it was split from the
+    // rest.
+    fn push_piece(&mut self, s: &str) {
+        let pending = match self.pending {
+            Some(ref mut pending) => pending,
+            None => {
+                self.parser.feed(&mut self.linebuf, s, false);
+                return;
+            }
+        };
+
+        pending.push_str(s);
+        let delimiter = self
+            .parser
+            .options
+            .extension
+            .front_matter_delimiter
+            .as_ref();
+        if !front_matter_undecided(pending, delimiter.unwrap()) {
+            let pending = self.pending.take().unwrap();
+            self.parser.feed(&mut self.linebuf, &pending, false);
+        }
+    }
+
+    /// Parses what remains of the input, and returns the document.
+    pub fn finish(mut self) -> &'a AstNode<'a> {
+        if let Some(pending) = self.pending.take() {
+            self.parser.feed(&mut self.linebuf, &pending, true);
+        }
+        self.parser.finish(self.linebuf)
+    }
+}
+
+impl<'a, 'o> Debug for StreamingParser<'a, 'o> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingParser")
+            .field("linebuf", &String::from_utf8_lossy(&self.linebuf))
+            .field("pending", &self.pending)
+            .field("pushed", &self.pushed)
+            .finish_non_exhaustive()
+    }
+}
+
+// Whether more input is needed to tell if, and where, `s` begins with front
+// matter.
+fn front_matter_undecided(s: &str, delimiter: &str) -> bool {
+    let s = trim_start_match(s, "\u{feff}");
+    if s.len() <= delimiter.len() {
+        return delimiter.starts_with(s);
+    }
+    let rest = match s.strip_prefix(delimiter) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    if rest == "\r" {
+        return true;
+    }
+    if !rest.starts_with('\n') && !rest.starts_with("\r\n") {
+        return false;
+    }
+    match split_off_front_matter(s, delimiter) {
+        // A blank line after the closing delimiter belongs to the front
+        // matter too, so what follows it must be seen.
+        Some((_, rest)) => rest.is_empty() || rest == "\r",
+        None => true,
+    }
+}
+
+/// Parse a Markdown document read from `reader` to an AST, passing it to a
+/// [`StreamingParser`] as it is read.
+///
+/// Fails if reading fails or the input isn't valid UTF-8.
+///
+/// ```
+/// # use comrak::{Arena, format_html, parse_document_from_reader, Options};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let input = "Some *text*.\n".repeat(10000);
+///
+/// let root = parse_document_from_reader(&arena, input.as_bytes(), &options).unwrap();
+/// assert_eq!(root.children().count(), 1);
+/// ```
+pub fn parse_document_from_reader<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    mut reader: impl Read,
+    options: &Options,
+) -> io::Result<&'a AstNode<'a>> {
+    let mut parser = StreamingParser::new(arena, options);
+    let mut buf = vec![0; 64 * 1024];
+    // Bytes of a character split across reads.
+    let mut carry = 0;
+
+    loop {
+        let n = match reader.read(&mut buf[carry..]) {
+            Ok(0) if carry > 0 => return Err(invalid_utf8()),
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        let end = carry + n;
+        let valid = match str::from_utf8(&buf[..end]) {
+            Ok(_) => end,
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => return Err(invalid_utf8()),
+        };
+        parser.push_piece(str::from_utf8(&buf[..valid]).unwrap());
+        buf.copy_within(valid..end, 0);
+        carry = end - valid;
+    }
+
+    Ok(parser.finish())
+}
+
+fn invalid_utf8() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
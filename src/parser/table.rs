@@ -1,4 +1,5 @@
 use crate::arena_tree::Node;
+use crate::ctype::ispunct;
 use crate::nodes;
 use crate::nodes::{Ast, AstNode, NodeTable, NodeValue, TableAlignment};
 use crate::parser::Parser;
@@ -17,6 +18,11 @@ pub fn try_opening_block<'a>(
     container: &'a AstNode<'a>,
     line: &[u8],
 ) -> Option<(&'a AstNode<'a>, bool, bool)> {
+    let is_row = matches!(container.data.borrow().value, NodeValue::TableRow(false));
+    if is_row {
+        return try_continuing_row(parser, container, line);
+    }
+
     let aligns = match container.data.borrow().value {
         NodeValue::Paragraph => None,
         NodeValue::Table(NodeTable { ref alignments, .. }) => Some(alignments.clone()),
@@ -39,6 +45,11 @@ fn try_opening_header<'a>(
     }
 
     if scanners::table_start(&line[parser.first_nonspace..]).is_none() {
+        if parser.options.extension.tables_without_header {
+            if let Some(table) = try_opening_headerless(parser, container, line) {
+                return Some((table, true, false));
+            }
+        }
         return Some((container, false, false));
     }
 
@@ -126,6 +137,83 @@ fn try_opening_header<'a>(
     Some((table, true, false))
 }
 
+// A header-less table starts when the paragraph's last line and the current
+// line are both pipe-led rows with the same number of cells.  Requiring two
+// rows keeps a lone line with literal pipes a paragraph.
+fn try_opening_headerless<'a>(
+    parser: &mut Parser<'a, '_, '_>,
+    container: &'a AstNode<'a>,
+    line: &[u8],
+) -> Option<&'a AstNode<'a>> {
+    if line[parser.first_nonspace] != b'|' {
+        return None;
+    }
+
+    let this_row = row(&line[parser.first_nonspace..])?;
+    let first_row = row(container.data.borrow().content.as_bytes())?;
+
+    if first_row.cells.len() != this_row.cells.len() {
+        return None;
+    }
+
+    let starts_with_pipe = container.data.borrow().content.as_bytes()[first_row.paragraph_offset..]
+        .iter()
+        .find(|&&c| c != b' ' && c != b'\t')
+        == Some(&b'|');
+    if !starts_with_pipe {
+        return None;
+    }
+
+    if first_row.paragraph_offset > 0 {
+        try_inserting_table_header_paragraph(parser, container, first_row.paragraph_offset);
+    }
+
+    let alignments = vec![TableAlignment::None; first_row.cells.len()];
+
+    let start = container.data.borrow().sourcepos.start;
+    let child = Ast::new(
+        NodeValue::Table(NodeTable {
+            alignments: alignments.clone(),
+            num_columns: first_row.cells.len(),
+            num_rows: 0,
+            num_nonempty_cells: 0,
+        }),
+        start,
+    );
+    let table = parser.arena.alloc(Node::new(RefCell::new(child)));
+    container.append(table);
+
+    let first = parser.add_child(table, NodeValue::TableRow(false), start.column);
+    {
+        let first_ast = &mut first.data.borrow_mut();
+        first_ast.sourcepos.start.line = start.line;
+        first_ast.sourcepos.end = start.column_add(
+            (container.data.borrow().content.len() - 2 - first_row.paragraph_offset) as isize,
+        );
+    }
+
+    for cell in &first_row.cells {
+        let ast_cell = parser.add_child(
+            first,
+            NodeValue::TableCell,
+            start.column + cell.start_offset - first_row.paragraph_offset,
+        );
+        let ast = &mut ast_cell.data.borrow_mut();
+        ast.sourcepos.start.line = start.line;
+        ast.sourcepos.end =
+            start.column_add((cell.end_offset - first_row.paragraph_offset) as isize);
+        ast.internal_offset = cell.internal_offset;
+        ast.content.clone_from(&cell.content);
+    }
+
+    incr_table_row_count(table, first_row.cells.len());
+
+    let (second, _, _) = try_opening_row(parser, table, &alignments, line)?;
+    second.data.borrow_mut().sourcepos.end.column = parser.curline_end_col;
+
+    Some(table)
+}
+
 fn try_opening_row<'a>(
     parser: &mut Parser<'a, '_, '_>,
     container: &'a AstNode<'a>,
@@ -141,11 +229,14 @@ fn try_opening_row<'a>(
     }
 
     let sourcepos = container.data.borrow().sourcepos;
-    let this_row = match row(&line[parser.first_nonspace..]) {
+    let mut this_row = match row(&line[parser.first_nonspace..]) {
         Some(this_row) => this_row,
         None => return None,
     };
 
+    let continues =
+        parser.options.extension.table_cell_line_breaks && strip_row_continuation(&mut this_row);
+
     let new_row = parser.add_child(
         container,
         NodeValue::TableRow(false),
@@ -182,12 +273,104 @@ fn try_opening_row<'a>(
         i += 1;
     }
 
+    if continues {
+        parser.continued_table_row = Some(new_row);
+    }
+
     let offset = line.len() - 1 - parser.offset;
     parser.advance_offset(line, offset, false);
 
     Some((new_row, false, false))
 }
 
+fn try_continuing_row<'a>(
+    parser: &mut Parser<'a, '_, '_>,
+    container: &'a AstNode<'a>,
+    line: &[u8],
+) -> Option<(&'a AstNode<'a>, bool, bool)> {
+    if parser.blank
+        || !parser
+            .continued_table_row
+            .take()
+            .map_or(false, |row| row.same_node(container))
+    {
+        return None;
+    }
+
+    let start_column = container
+        .parent()
+        .unwrap()
+        .data
+        .borrow()
+        .sourcepos
+        .start
+        .column;
+    if let Some(mut this_row) = row(&line[parser.first_nonspace..]) {
+        if strip_row_continuation(&mut this_row) {
+            parser.continued_table_row = Some(container);
+        }
+        continue_row(parser, container, &this_row, start_column);
+    }
+
+    let offset = line.len() - 1 - parser.offset;
+    parser.advance_offset(line, offset, false);
+
+    Some((container, false, false))
+}
+
+// Removes a trailing backslash from a row, returning whether there was one
+// that wasn't itself escaped.  It may be inside the last cell, or follow the
+// closing pipe as a cell of its own.
+fn strip_row_continuation(row: &mut Row) -> bool {
+    let num_cells = row.cells.len();
+    let last = match row.cells.last_mut() {
+        Some(last) => last,
+        None => return false,
+    };
+
+    let content = last.content.as_bytes();
+    let backslashes = content.iter().rev().take_while(|&&c| c == b'\\').count();
+    if backslashes % 2 == 0 {
+        return false;
+    }
+
+    if content.len() == 1 && num_cells > 1 {
+        row.cells.pop();
+    } else {
+        last.content.pop();
+        let trimmed = last.content.trim_end().len();
+        last.content.truncate(trimmed);
+    }
+    true
+}
+
+// Joins each cell of `this_row` to the matching cell of `row` with a hard
+// line break, skipping empty cells.
+fn continue_row<'a>(
+    parser: &mut Parser<'a, '_, '_>,
+    row: &'a AstNode<'a>,
+    this_row: &Row,
+    start_column: usize,
+) {
+    for (cell, cell_node) in this_row.cells.iter().zip(row.children()) {
+        if cell.content.is_empty() {
+            continue;
+        }
+
+        let cell_ast = &mut cell_node.data.borrow_mut();
+        if !cell_ast.content.is_empty() {
+            cell_ast.content.push_str("\\\n");
+        }
+        cell_ast.content.push_str(&cell.content);
+        cell_ast.sourcepos.end.line = parser.line_number;
+        cell_ast.sourcepos.end.column = start_column + cell.end_offset;
+    }
+
+    let row_ast = &mut row.data.borrow_mut();
+    row_ast.sourcepos.end.line = parser.line_number;
+    row_ast.sourcepos.end.column = parser.curline_end_col;
+}
+
 struct Row {
     paragraph_offset: usize,
     cells: Vec<Cell>,
@@ -211,7 +394,7 @@ fn row(string: &[u8]) -> Option<Row> {
     let mut max_columns_abort = false;
 
     while offset < len && expect_more_cells {
-        let cell_matched = scanners::table_cell(&string[offset..]).unwrap_or(0);
+        let cell_matched = cell_length(&string[offset..]);
         let pipe_matched = scanners::table_cell_end(&string[offset + cell_matched..]).unwrap_or(0);
 
         if cell_matched > 0 || pipe_matched > 0 {
@@ -316,15 +499,43 @@ fn try_inserting_table_header_paragraph<'a>(
     container.insert_before(node);
 }
 
+// The length of the cell at the start of `string`, which runs up to the pipe
+// ending it.  A backslash escapes the punctuation character after it, so `\|`
+// stays in the cell while `\\|` is a backslash followed by the end of the
+// cell; a `||` spoiler doesn't end the cell either.
+fn cell_length(string: &[u8]) -> usize {
+    let mut i = 0;
+    while i < string.len() {
+        match string[i] {
+            b'\\' if string.get(i + 1).map_or(false, |&c| ispunct(c)) => i += 2,
+            b'|' if string.get(i + 1) == Some(&b'|') => i += 2,
+            b'|' | b'\r' | b'\n' | 0 => break,
+            _ => i += 1,
+        }
+    }
+    i
+}
+
 fn unescape_pipes(string: &[u8]) -> Vec<u8> {
     let len = string.len();
     let mut v = Vec::with_capacity(len);
 
-    for (i, &c) in string.iter().enumerate() {
-        if c == b'\\' && i + 1 < len && string[i + 1] == b'|' {
-            continue;
-        } else {
-            v.push(c);
+    let mut i = 0;
+    while i < len {
+        match (string[i], string.get(i + 1)) {
+            (b'\\', Some(&b'|')) => {
+                v.push(b'|');
+                i += 2;
+            }
+            // An escaped backslash can't escape a pipe after it.
+            (b'\\', Some(&b'\\')) => {
+                v.extend_from_slice(b"\\\\");
+                i += 2;
+            }
+            (c, _) => {
+                v.push(c);
+                i += 1;
+            }
         }
     }
 
@@ -368,3 +579,70 @@ fn get_num_autocompleted_cells<'a>(container: &'a AstNode<'a>) -> usize {
 pub fn matches(line: &[u8]) -> bool {
     row(line).is_some()
 }
+
+pub fn is_caption(line: &[u8]) -> bool {
+    line.starts_with(b"Table:")
+}
+
+// Turn `Table:` paragraphs adjacent to tables into captions.  A caption
+// directly above a table is preferred, so a caption paragraph sandwiched
+// between two tables goes to the one that follows it.
+pub fn attach_captions<'a>(root: &'a AstNode<'a>) {
+    let tables = root
+        .descendants()
+        .filter(|n| matches!(n.data.borrow().value, NodeValue::Table(..)))
+        .collect::<Vec<_>>();
+
+    for table in &tables {
+        if let Some(prev) = table.previous_sibling() {
+            let adjacent = prev.data.borrow().sourcepos.end.line + 1
+                == table.data.borrow().sourcepos.start.line;
+            if adjacent {
+                try_attaching_caption(table, prev);
+            }
+        }
+    }
+
+    for table in &tables {
+        if let Some(first) = table.first_child() {
+            if matches!(first.data.borrow().value, NodeValue::TableCaption) {
+                continue;
+            }
+        }
+        if let Some(next) = table.next_sibling() {
+            let adjacent = table.data.borrow().sourcepos.end.line + 1
+                == next.data.borrow().sourcepos.start.line;
+            if adjacent {
+                try_attaching_caption(table, next);
+            }
+        }
+    }
+}
+
+fn try_attaching_caption<'a>(table: &'a AstNode<'a>, paragraph: &'a AstNode<'a>) {
+    {
+        let mut ast = paragraph.data.borrow_mut();
+        if !matches!(ast.value, NodeValue::Paragraph) {
+            return;
+        }
+
+        let content = ast.content.trim_start();
+        if !is_caption(content.as_bytes()) {
+            return;
+        }
+
+        let caption = content["Table:".len()..].trim_start().to_string();
+
+        // Start the caption after the prefix, or at the end of its line if
+        // the text only begins on the next.
+        let prefix = &ast.content[..ast.content.len() - caption.len()];
+        let first_line = prefix.split('\n').next().unwrap().trim_end_matches('\r');
+        ast.sourcepos.start.column += first_line.len();
+
+        ast.content = caption;
+        ast.value = NodeValue::TableCaption;
+    }
+
+    paragraph.detach();
+    table.prepend(paragraph);
+}
@@ -0,0 +1,292 @@
+use crate::html::LineEndingWriter;
+use crate::nodes::{AstNode, NodeCode, NodeMath, NodeValue};
+use crate::parser::{Options, Plugins};
+use std::io::{self, Write};
+
+/// Formats an AST as plain text, modified by the given options.
+///
+/// Only the text content of the document is written, with its block
+/// structure kept by way of newlines:
+///
+/// * blocks are separated by a blank line, save for those in a tight list;
+/// * headings and paragraphs are written as their text, soft and hard line
+///   breaks alike becoming newlines;
+/// * list items of any kind are prefixed with `- `, or `- [ ] ` and `- [x] `
+///   for task items, their further lines and nested blocks being indented to
+///   match;
+/// * code blocks are written as their literal, and table rows as their cells
+///   separated by tabs;
+/// * links and images are reduced to their text, followed by the URL in
+///   parentheses for links with `render.plain_link_urls`;
+/// * raw HTML, front matter and thematic breaks are dropped.
+///
+/// ```
+/// # use comrak::{format_plain, parse_document, Arena, Options};
+/// let arena = Arena::new();
+/// let options = Options::default();
+/// let root = parse_document(
+///     &arena,
+///     "# Hello\n\nSee [the docs](/docs) and\n![a *map*](/map.png).\n\n- one\n- two\n",
+///     &options,
+/// );
+///
+/// let mut text = vec![];
+/// format_plain(root, &options, &mut text).unwrap();
+/// assert_eq!(
+///     String::from_utf8(text).unwrap(),
+///     "Hello\n\nSee the docs and\na map.\n\n- one\n- two\n"
+/// );
+/// ```
+pub fn format_document<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    format_document_with_plugins(root, options, output, &Plugins::default())
+}
+
+/// Formats an AST as plain text, modified by the given options. Accepts custom plugins,
+/// though none of them affect plain text.
+pub fn format_document_with_plugins<'a>(
+    root: &'a AstNode<'a>,
+    options: &Options,
+    output: &mut dyn Write,
+    _plugins: &Plugins,
+) -> io::Result<()> {
+    let mut f = PlainFormatter::new(options);
+    f.format_block(root);
+    LineEndingWriter::new(output, options.render.line_ending).write_all(f.out.as_bytes())
+}
+
+struct PlainFormatter<'o> {
+    options: &'o Options,
+    out: String,
+    tight: bool,
+    footnote_ix: u32,
+}
+
+impl<'o> PlainFormatter<'o> {
+    fn new(options: &'o Options) -> Self {
+        PlainFormatter {
+            options,
+            out: String::new(),
+            tight: false,
+            footnote_ix: 0,
+        }
+    }
+
+    // Separates the block about to be written from the one before it, unless
+    // both are in a tight list.
+    fn blank_line(&mut self) {
+        if !self.tight && !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn format_children<'a>(&mut self, node: &'a AstNode<'a>) {
+        for child in node.children() {
+            self.format_block(child);
+        }
+    }
+
+    fn format_block<'a>(&mut self, node: &'a AstNode<'a>) {
+        match node.data.borrow().value {
+            NodeValue::Document
+            | NodeValue::BlockQuote
+            | NodeValue::MultilineBlockQuote(..)
+            | NodeValue::DescriptionList
+            | NodeValue::DescriptionItem(..)
+            | NodeValue::FencedDiv(..) => self.format_children(node),
+            NodeValue::Alert(ref na) => {
+                self.blank_line();
+                let title = na.title.as_deref();
+                self.out
+                    .push_str(title.unwrap_or_else(|| na.alert_type.default_title()));
+                self.out.push('\n');
+                self.format_children(node);
+            }
+            NodeValue::Details(ref nd) => {
+                if !nd.summary.is_empty() {
+                    self.blank_line();
+                    self.out.push_str(&nd.summary);
+                    self.out.push('\n');
+                }
+                self.format_children(node);
+            }
+            NodeValue::Paragraph
+            | NodeValue::Heading(..)
+            | NodeValue::TableCaption
+            | NodeValue::DescriptionTerm => {
+                self.blank_line();
+                let text = self.inline_text(node);
+                self.out.push_str(&text);
+                self.out.push('\n');
+            }
+            NodeValue::DescriptionDetails => {
+                self.blank_line();
+                self.format_indented(node, "  ", "  ");
+            }
+            NodeValue::List(ref nl) => {
+                self.blank_line();
+                let tight = self.tight;
+                self.tight = nl.tight;
+                for item in node.children() {
+                    self.blank_line();
+                    self.format_item(item);
+                }
+                self.tight = tight;
+            }
+            NodeValue::Item(..) | NodeValue::TaskItem(..) => self.format_item(node),
+            NodeValue::CodeBlock(ref ncb) => {
+                self.blank_line();
+                self.out.push_str(&ncb.literal);
+                if !ncb.literal.is_empty() && !ncb.literal.ends_with('\n') {
+                    self.out.push('\n');
+                }
+            }
+            NodeValue::Table(..) => {
+                self.blank_line();
+                for child in node.children() {
+                    if let NodeValue::TableCaption = child.data.borrow().value {
+                        self.format_block(child);
+                        self.blank_line();
+                    }
+                }
+                for cells in table_rows(node, |cell| self.inline_text(cell)) {
+                    self.out.push_str(&cells.join("\t"));
+                    self.out.push('\n');
+                }
+            }
+            NodeValue::FootnoteDefinition(..) => {
+                self.footnote_ix += 1;
+                self.blank_line();
+                let number = self
+                    .options
+                    .extension
+                    .footnote_base()
+                    .saturating_add(self.footnote_ix);
+                let marker = format!("[{}] ", number);
+                let indent = " ".repeat(marker.len());
+                self.format_indented(node, &marker, &indent);
+            }
+            NodeValue::FrontMatter(..) | NodeValue::HtmlBlock(..) | NodeValue::ThematicBreak => {}
+            _ => {
+                let text = flatten_inline(node, '\n', &mut |url, label, image| {
+                    self.link_text(url, label, image)
+                });
+                self.out.push_str(&text);
+            }
+        }
+    }
+
+    fn format_item<'a>(&mut self, item: &'a AstNode<'a>) {
+        let marker = match item.data.borrow().value {
+            NodeValue::TaskItem(Some(_)) => "- [x] ",
+            NodeValue::TaskItem(None) => "- [ ] ",
+            _ => "- ",
+        };
+        self.format_indented(item, marker, "  ");
+    }
+
+    // Writes the children of `node`, prefixing the first line with `first`
+    // and indenting the rest with `rest`.
+    fn format_indented<'a>(&mut self, node: &'a AstNode<'a>, first: &str, rest: &str) {
+        let outer = std::mem::take(&mut self.out);
+        self.format_children(node);
+        let inner = std::mem::replace(&mut self.out, outer);
+
+        let mut prefix = first;
+        for line in inner.trim_end_matches('\n').split('\n') {
+            if line.is_empty() {
+                self.out.push_str(prefix.trim_end());
+            } else {
+                self.out.push_str(prefix);
+                self.out.push_str(line);
+            }
+            self.out.push('\n');
+            prefix = rest;
+        }
+    }
+
+    fn inline_text<'a>(&mut self, node: &'a AstNode<'a>) -> String {
+        flatten_inlines(node, '\n', &mut |url, label, image| {
+            self.link_text(url, label, image)
+        })
+    }
+
+    // Links are followed by their URL with `plain_link_urls`; images are
+    // reduced to their text.
+    fn link_text(&self, url: &str, label: String, image: bool) -> String {
+        if image || !self.options.render.plain_link_urls || url.is_empty() || url == label {
+            return label;
+        }
+        format!("{} ({})", label, url)
+    }
+}
+
+// Flattens the inlines under `node` to text, for the plain text and Gemtext
+// formatters.  Soft breaks become `soft_break` and hard breaks newlines.
+// Each link, image or wikilink is flattened in turn, then given to `link`
+// with its URL and whether it's an image, to say what to write in its place.
+pub(crate) fn flatten_inlines<'a>(
+    node: &'a AstNode<'a>,
+    soft_break: char,
+    link: &mut dyn FnMut(&str, String, bool) -> String,
+) -> String {
+    node.children()
+        .map(|child| flatten_inline(child, soft_break, link))
+        .collect()
+}
+
+// Flattens a single inline as `flatten_inlines` does.  Called directly, it
+// also serves for an inline outside of any block, as left by hand-built
+// trees.
+pub(crate) fn flatten_inline<'a>(
+    node: &'a AstNode<'a>,
+    soft_break: char,
+    link: &mut dyn FnMut(&str, String, bool) -> String,
+) -> String {
+    match node.data.borrow().value {
+        NodeValue::Text(ref literal)
+        | NodeValue::Code(NodeCode { ref literal, .. })
+        | NodeValue::Math(NodeMath { ref literal, .. })
+        | NodeValue::ImageMention(ref literal)
+        | NodeValue::EscapedTag(ref literal) => literal.clone(),
+        NodeValue::SoftBreak => soft_break.to_string(),
+        NodeValue::LineBreak => "\n".to_string(),
+        #[cfg(feature = "shortcodes")]
+        NodeValue::ShortCode(ref nsc) => nsc.emoji().to_string(),
+        NodeValue::FootnoteReference(ref nfr) => format!("[{}]", nfr.ix),
+        NodeValue::Link(ref nl) => {
+            let label = flatten_inlines(node, soft_break, link);
+            link(&nl.url, label, false)
+        }
+        NodeValue::Image(ref nl) => {
+            let label = flatten_inlines(node, soft_break, link);
+            link(&nl.url, label, true)
+        }
+        NodeValue::WikiLink(ref nwl) => {
+            let label = flatten_inlines(node, soft_break, link);
+            link(&nwl.url, label, false)
+        }
+        NodeValue::HtmlInline(..) => String::new(),
+        _ => flatten_inlines(node, soft_break, link),
+    }
+}
+
+// The text of each cell of each row of `table`, as given by `cell_text` with
+// any line breaks replaced by spaces.
+pub(crate) fn table_rows<'a>(
+    table: &'a AstNode<'a>,
+    mut cell_text: impl FnMut(&'a AstNode<'a>) -> String,
+) -> Vec<Vec<String>> {
+    table
+        .children()
+        .filter(|row| matches!(row.data.borrow().value, NodeValue::TableRow(..)))
+        .map(|row| {
+            row.children()
+                .map(|cell| cell_text(cell).replace('\n', " "))
+                .collect()
+        })
+        .collect()
+}
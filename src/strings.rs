@@ -1,6 +1,6 @@
 use crate::ctype::{ispunct, isspace};
 use crate::entity;
-use crate::parser::AutolinkType;
+use crate::nodes::AutolinkType;
 use std::ptr;
 use std::str;
 
@@ -4,23 +4,41 @@ use std::collections::HashMap;
 use std::io::{self, Write};
 use std::panic;
 
+mod abbreviations;
+mod alerts;
+mod alpha_lists;
 mod api;
 mod autolink;
+mod cjk_friendly_emphasis;
 mod commonmark;
 mod core;
 mod description_lists;
+mod details;
+mod diagnostics;
 mod escaped_char_spans;
+mod fenced_divs;
 mod footnotes;
 mod fuzz;
+mod gemtext;
 mod header_ids;
+mod highlight;
+mod image_srcset;
+mod inline_code_lang;
+mod inline_delimiters;
+mod inline_tokens;
+mod json;
 mod math;
 mod multiline_block_quotes;
 mod options;
 mod pathological;
 mod philomena;
+mod plain;
 mod plugins;
 mod regressions;
+mod reparse;
+mod reversed_lists;
 mod shortcodes;
+mod streaming;
 mod strikethrough;
 mod superscript;
 mod table;
@@ -153,40 +171,112 @@ macro_rules! html_opts {
                 strikethrough: true,
                 tagfilter: true,
                 table: true,
+                tables_without_header: true,
+                table_captions: true,
+                table_cell_line_breaks: true,
                 autolink: true,
                 tasklist: true,
                 superscript: true,
+                highlight: true,
                 philomena: true,
                 philomena_replacements: None,
                 philomena_domains: None,
                 header_ids: Some("user-content-".to_string()),
+                header_id_style: $crate::HeaderIdStyle::OnHeading,
+                header_anchor_content: Some("#".to_string()),
+                header_id_slugifier: None,
                 footnotes: true,
+                footnotes_render_unreferenced: true,
+                footnote_start_number: None,
                 description_lists: true,
+                description_list_marker: None,
+                description_list_grouped_terms: true,
+                alpha_lists: true,
+                reversed_lists: true,
                 multiline_block_quotes: true,
+                alerts: true,
+                details: true,
+                fenced_divs: true,
+                abbreviations: true,
                 math_dollars: true,
                 math_code: true,
                 front_matter_delimiter: Some("---".to_string()),
                 shortcodes: true,
                 wikilinks_title_after_pipe: true,
                 wikilinks_title_before_pipe: true,
+                wikilinks_slug_separator: Some('_'),
+                wikilinks_url_resolver: None,
                 camoifier: true,
+                image_srcset: true,
+                inline_code_lang: true,
             },
             parse: $crate::ParseOptions {
                 smart: true,
                 default_info_string: Some("rust".to_string()),
+                default_info_string_fn: None,
                 relaxed_tasklist_matching: true,
                 relaxed_autolinks: true,
+                autolink_extra_tlds: None,
+                autolink_extra_schemes: None,
+                autolink_allowed_tlds: None,
+                no_reference_definitions: true,
+                blockquote_no_space_required: true,
+                lazy_blockquote_continuation: true,
+                strip_bom: true,
+                cjk_friendly_emphasis: true,
+                list_item_trailing_hard_break: true,
+                smart_skip_links: true,
+                quote_elements: true,
+                nul_handling: $crate::NulPolicy::Strip,
+                max_nesting_depth: Some(10),
+                inline_tokens: vec![],
+                inline_delimiters: vec![],
             },
             render: $crate::RenderOptions {
                 hardbreaks: true,
+                collapse_soft_breaks: true,
                 github_pre_lang: true,
                 full_info_string: true,
+                code_block_title_from_info: true,
                 width: 80,
                 unsafe_: true,
                 escape: true,
                 list_style: $crate::ListStyleType::Star,
+                line_ending: $crate::LineEnding::CrLf,
                 sourcepos: true,
                 escaped_char_spans: true,
+                allowed_inline_html_tags: vec!["sub".to_string()],
+                table_alignment_classes: None,
+                insert_wbr_in_long_words: Some(30),
+                normalize_code_lang: None,
+                emoji_image_url: None,
+                sorted_attributes: true,
+                aria_roles: true,
+                ascii_only: true,
+                data_line_start: true,
+                preserve_ordered_numbers: true,
+                math_mathjax_delimiters: true,
+                trim_code_trailing_whitespace: true,
+                obfuscate_email: true,
+                raw_info_string_class: true,
+                description_list_loose: true,
+                thematic_break_class: Some("divider".to_string()),
+                footnote_single_backref_simple: true,
+                ordered_list_pad_markers: true,
+                bullet_list_double_space: true,
+                group_footnote_refs: true,
+                footnote_id_prefix: Some("n-".to_string()),
+                footnote_ref_id_prefix: Some("r-".to_string()),
+                footnote_backref_symbol: Some("^".to_string()),
+                footnote_backref_label: Some("Back {}".to_string()),
+                footnote_backref_simple_label: Some("Back".to_string()),
+                code_lang_as_data_attr: true,
+                code_lang_data_attr_only: true,
+                block_ids: Some("b-".to_string()),
+                plain_link_urls: true,
+                heading_offset: 1,
+                external_links_new_tab: true,
+                internal_link_hosts: None,
             },
         });
     }
@@ -271,6 +361,84 @@ where
     );
 }
 
+#[track_caller]
+fn gemtext(input: &str, expected: &str) {
+    gemtext_opts(input, expected, |_| ());
+}
+
+#[track_caller]
+fn gemtext_opts<F>(input: &str, expected: &str, opts: F)
+where
+    F: Fn(&mut Options),
+{
+    let arena = Arena::new();
+    let mut options = Options::default();
+    opts(&mut options);
+
+    let root = parse_document(&arena, input, &options);
+    let mut output = vec![];
+    crate::gemtext::format_document(root, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        expected,
+        "regular",
+        input,
+    );
+
+    let mut md = vec![];
+    cm::format_document(root, &options, &mut md).unwrap();
+
+    let md_string = &String::from_utf8(md).unwrap();
+    let root = parse_document(&arena, md_string, &options);
+    let mut output_from_rt = vec![];
+    crate::gemtext::format_document(root, &options, &mut output_from_rt).unwrap();
+    compare_strs(
+        &String::from_utf8(output_from_rt).unwrap(),
+        expected,
+        "roundtrip",
+        md_string,
+    );
+}
+
+#[track_caller]
+fn plain(input: &str, expected: &str) {
+    plain_opts(input, expected, |_| ());
+}
+
+#[track_caller]
+fn plain_opts<F>(input: &str, expected: &str, opts: F)
+where
+    F: Fn(&mut Options),
+{
+    let arena = Arena::new();
+    let mut options = Options::default();
+    opts(&mut options);
+
+    let root = parse_document(&arena, input, &options);
+    let mut output = vec![];
+    crate::plain::format_document(root, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        expected,
+        "regular",
+        input,
+    );
+
+    let mut md = vec![];
+    cm::format_document(root, &options, &mut md).unwrap();
+
+    let md_string = &String::from_utf8(md).unwrap();
+    let root = parse_document(&arena, md_string, &options);
+    let mut output_from_rt = vec![];
+    crate::plain::format_document(root, &options, &mut output_from_rt).unwrap();
+    compare_strs(
+        &String::from_utf8(output_from_rt).unwrap(),
+        expected,
+        "roundtrip",
+        md_string,
+    );
+}
+
 fn asssert_node_eq<'a>(node: &'a AstNode<'a>, location: &[usize], expected: &NodeValue) {
     let node = location
         .iter()
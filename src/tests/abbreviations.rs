@@ -0,0 +1,169 @@
+use super::*;
+
+#[test]
+fn abbreviations() {
+    html_opts!(
+        [extension.abbreviations],
+        concat!(
+            "*[HTML]: HyperText Markup Language\n",
+            "*[W3C]:  World Wide Web \"Consortium\"  \n",
+            "The HTML spec is maintained by the W3C.\n",
+            "\n",
+            "# HTML, html and XHTML\n",
+        ),
+        concat!(
+            "<p>The <abbr title=\"HyperText Markup Language\">HTML</abbr> spec is maintained by the ",
+            "<abbr title=\"World Wide Web &quot;Consortium&quot;\">W3C</abbr>.</p>\n",
+            "<h1><abbr title=\"HyperText Markup Language\">HTML</abbr>, html and XHTML</h1>\n",
+        ),
+    );
+}
+
+#[test]
+fn abbreviations_defined_later() {
+    html_opts!(
+        [extension.abbreviations],
+        concat!(
+            "An API.\n",
+            "\n",
+            "*[API]: Application Programming Interface\n"
+        ),
+        "<p>An <abbr title=\"Application Programming Interface\">API</abbr>.</p>\n",
+    );
+}
+
+#[test]
+fn abbreviations_whole_words() {
+    html_opts!(
+        [extension.abbreviations],
+        concat!(
+            "*[CSS]: Cascading Style Sheets\n",
+            "*[CSS 3]: Cascading Style Sheets, level 3\n",
+            "*[é]: E acute\n",
+            "\n",
+            "CSS 3, CSS3, CSS_x, _CSS_, écoleé é.\n",
+        ),
+        concat!(
+            "<p><abbr title=\"Cascading Style Sheets, level 3\">CSS 3</abbr>, CSS3, CSS_x, ",
+            "<em><abbr title=\"Cascading Style Sheets\">CSS</abbr></em>, écoleé ",
+            "<abbr title=\"E acute\">é</abbr>.</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn abbreviations_not_in_code_or_links() {
+    html_opts!(
+        [extension.abbreviations],
+        concat!(
+            "*[URL]: Uniform Resource Locator\n",
+            "\n",
+            "`URL` [URL](/url) URL\n",
+            "\n",
+            "    URL\n",
+        ),
+        concat!(
+            "<p><code>URL</code> <a href=\"/url\">URL</a> ",
+            "<abbr title=\"Uniform Resource Locator\">URL</abbr></p>\n",
+            "<pre><code>URL\n",
+            "</code></pre>\n",
+        ),
+    );
+}
+
+#[test]
+fn abbreviations_definitions() {
+    html_opts!(
+        [extension.abbreviations],
+        concat!(
+            "[ref]: /url\n",
+            "*[REF]:\n",
+            "Text [ref] REF\n",
+            "*[NOT]: a definition\n",
+            "\n",
+            "*[]: empty\n",
+            "*[X] : spaced\n",
+        ),
+        concat!(
+            "<p>Text <a href=\"/url\">ref</a> <abbr>REF</abbr>\n",
+            "*[NOT]: a definition</p>\n",
+            "<p>*[]: empty\n",
+            "*[X] : spaced</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn abbreviations_disabled() {
+    html(
+        concat!("*[HTML]: HyperText Markup Language\n", "\n", "HTML\n"),
+        concat!(
+            "<p>*[HTML]: HyperText Markup Language</p>\n",
+            "<p>HTML</p>\n"
+        ),
+    );
+}
+
+#[test]
+fn abbreviations_commonmark() {
+    let mut options = Options::default();
+    options.extension.abbreviations = true;
+
+    commonmark(
+        concat!(
+            "*[HTML]: HyperText Markup Language\n",
+            "*[UNUSED]: Never written\n",
+            "*[W3C]:\n",
+            "\n",
+            "HTML and W3C and HTML.\n",
+        ),
+        concat!(
+            "HTML and W3C and HTML.\n",
+            "\n",
+            "*[HTML]: HyperText Markup Language\n",
+            "*[W3C]:\n",
+        ),
+        Some(&options),
+    );
+}
+
+#[test]
+fn sourcepos() {
+    assert_ast_match!(
+        [extension.abbreviations],
+        "*[API]: Application Programming Interface\n"
+        "\n"
+        "An API or API.\n",
+        (document (1:1-3:14) [
+            (paragraph (3:1-3:14) [
+                (text (3:1-3:3) "An ")
+                (abbreviation (3:4-3:6) [
+                    (text (3:4-3:6) "API")
+                ])
+                (text (3:7-3:10) " or ")
+                (abbreviation (3:11-3:13) [
+                    (text (3:11-3:13) "API")
+                ])
+                (text (3:14-3:14) ".")
+            ])
+        ])
+    );
+}
+
+#[test]
+fn sourcepos_at_start() {
+    assert_ast_match!(
+        [extension.abbreviations],
+        "*[API]: Application Programming Interface\n"
+        "\n"
+        "API docs\n",
+        (document (1:1-3:8) [
+            (paragraph (3:1-3:8) [
+                (abbreviation (3:1-3:3) [
+                    (text (3:1-3:3) "API")
+                ])
+                (text (3:4-3:8) " docs")
+            ])
+        ])
+    );
+}
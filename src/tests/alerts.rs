@@ -0,0 +1,135 @@
+use super::*;
+
+#[test]
+fn alerts() {
+    for (marker, class, title) in [
+        ("NOTE", "note", "Note"),
+        ("TIP", "tip", "Tip"),
+        ("IMPORTANT", "important", "Important"),
+        ("WARNING", "warning", "Warning"),
+        ("CAUTION", "caution", "Caution"),
+    ] {
+        html_opts!(
+            [extension.alerts],
+            &format!("> [!{}]\n> Be *careful*.\n", marker),
+            &format!(
+                concat!(
+                    "<div class=\"markdown-alert markdown-alert-{}\">\n",
+                    "<p class=\"markdown-alert-title\">{}</p>\n",
+                    "<p>Be <em>careful</em>.</p>\n",
+                    "</div>\n"
+                ),
+                class, title
+            ),
+        );
+    }
+}
+
+#[test]
+fn alerts_case_insensitive_with_trailing_whitespace() {
+    html_opts!(
+        [extension.alerts],
+        concat!("> [!wArNiNg]  \t\n", "> Hot.\n"),
+        concat!(
+            "<div class=\"markdown-alert markdown-alert-warning\">\n",
+            "<p class=\"markdown-alert-title\">Warning</p>\n",
+            "<p>Hot.</p>\n",
+            "</div>\n"
+        ),
+    );
+}
+
+#[test]
+fn alerts_custom_title() {
+    html_opts!(
+        [extension.alerts],
+        concat!("> [!TIP] Pro <tip>\n", "> Use it.\n", "> \n", "> - a\n"),
+        concat!(
+            "<div class=\"markdown-alert markdown-alert-tip\">\n",
+            "<p class=\"markdown-alert-title\">Pro &lt;tip&gt;</p>\n",
+            "<p>Use it.</p>\n",
+            "<ul>\n",
+            "<li>a</li>\n",
+            "</ul>\n",
+            "</div>\n"
+        ),
+    );
+}
+
+#[test]
+fn alerts_not_matched() {
+    html_opts!(
+        [extension.alerts],
+        concat!(
+            "> [!UNKNOWN]\n",
+            "> a\n",
+            "\n",
+            "> [!NOTE]b\n",
+            "\n",
+            "> text\n",
+            "> [!NOTE]\n",
+        ),
+        concat!(
+            "<blockquote>\n",
+            "<p>[!UNKNOWN]\n",
+            "a</p>\n",
+            "</blockquote>\n",
+            "<blockquote>\n",
+            "<p>[!NOTE]b</p>\n",
+            "</blockquote>\n",
+            "<blockquote>\n",
+            "<p>text\n",
+            "[!NOTE]</p>\n",
+            "</blockquote>\n",
+        ),
+    );
+
+    html(
+        concat!("> [!NOTE]\n", "> a\n"),
+        concat!(
+            "<blockquote>\n",
+            "<p>[!NOTE]\n",
+            "a</p>\n",
+            "</blockquote>\n"
+        ),
+    );
+}
+
+#[test]
+fn alerts_commonmark() {
+    let mut options = Options::default();
+    options.extension.alerts = true;
+    commonmark(
+        concat!(
+            "> [!caution]   Custom  title \n",
+            "> Body\n",
+            "> > nested\n"
+        ),
+        concat!(
+            "> [!CAUTION] Custom  title\n",
+            "> Body\n",
+            "> \n",
+            "> > nested\n"
+        ),
+        Some(&options),
+    );
+}
+
+#[test]
+fn alerts_xml() {
+    xml_opts(
+        concat!("> [!NOTE] Hi\n", "> a\n"),
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <alert type=\"note\" title=\"Hi\">\n",
+            "    <paragraph>\n",
+            "      <text xml:space=\"preserve\">a</text>\n",
+            "    </paragraph>\n",
+            "  </alert>\n",
+            "</document>\n"
+        ),
+        |opts| opts.extension.alerts = true,
+    );
+}
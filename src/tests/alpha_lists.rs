@@ -0,0 +1,181 @@
+use super::*;
+
+#[test]
+fn alpha_lists() {
+    html_opts_no_roundtrip(
+        concat!(
+            "a. one\n",
+            "b. two\n",
+            "\n",
+            "- x\n",
+            "\n",
+            "C) three\n",
+            "D) four\n",
+        ),
+        concat!(
+            "<ol type=\"a\">\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "</ol>\n",
+            "<ul>\n",
+            "<li>x</li>\n",
+            "</ul>\n",
+            "<ol type=\"A\" start=\"3\">\n",
+            "<li>three</li>\n",
+            "<li>four</li>\n",
+            "</ol>\n",
+        ),
+        |opts| opts.extension.alpha_lists = true,
+    );
+}
+
+#[test]
+fn roman_lists() {
+    html_opts_no_roundtrip(
+        concat!(
+            "iv. one\n",
+            "v. two\n",
+            "vi. three\n",
+            "\n",
+            "- x\n",
+            "\n",
+            "XL. four\n"
+        ),
+        concat!(
+            "<ol type=\"i\" start=\"4\">\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "<li>three</li>\n",
+            "</ol>\n",
+            "<ul>\n",
+            "<li>x</li>\n",
+            "</ul>\n",
+            "<ol type=\"I\" start=\"40\">\n",
+            "<li>four</li>\n",
+            "</ol>\n",
+        ),
+        |opts| opts.extension.alpha_lists = true,
+    );
+}
+
+#[test]
+fn alpha_list_continues_with_i() {
+    html_opts!(
+        [extension.alpha_lists],
+        concat!("h. one\n", "i. two\n", "j. three\n"),
+        concat!(
+            "<ol type=\"a\" start=\"8\">\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "<li>three</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn alpha_lists_past_z() {
+    html_opts!(
+        [extension.alpha_lists],
+        concat!("y. one\n", "z. two\n", "aa. three\n", "ab. four\n"),
+        concat!(
+            "<ol type=\"a\" start=\"25\">\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "<li>three</li>\n",
+            "<li>four</li>\n",
+            "</ol>\n",
+        ),
+    );
+    html_opts!(
+        [extension.alpha_lists],
+        concat!("aa. not a list\n"),
+        "<p>aa. not a list</p>\n",
+    );
+}
+
+#[test]
+fn alpha_lists_initials() {
+    html_opts!(
+        [extension.alpha_lists],
+        concat!("A. Smith\n", "\n", "B.  one\n", "C.\ttwo\n"),
+        concat!(
+            "<p>A. Smith</p>\n",
+            "<ol type=\"A\" start=\"2\">\n",
+            "<li>one</li>\n",
+            "<li>two</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn alpha_lists_mixed_styles() {
+    html_opts_no_roundtrip(
+        concat!("a. one\n", "1. two\n", "A.  three\n", "b) four\n"),
+        concat!(
+            "<ol type=\"a\">\n",
+            "<li>one</li>\n",
+            "</ol>\n",
+            "<ol>\n",
+            "<li>two</li>\n",
+            "</ol>\n",
+            "<ol type=\"A\">\n",
+            "<li>three</li>\n",
+            "</ol>\n",
+            "<ol type=\"a\" start=\"2\">\n",
+            "<li>four</li>\n",
+            "</ol>\n",
+        ),
+        |opts| opts.extension.alpha_lists = true,
+    );
+}
+
+#[test]
+fn alpha_lists_dont_interrupt_paragraphs() {
+    html_opts!(
+        [extension.alpha_lists],
+        concat!("Some text\n", "a. not a list\n"),
+        "<p>Some text\na. not a list</p>\n",
+    );
+}
+
+#[test]
+fn alpha_lists_invalid_markers() {
+    html_opts!(
+        [extension.alpha_lists],
+        concat!(
+            "iiii. no\n",
+            "\n",
+            "aB. no\n",
+            "\n",
+            "abcdefghi. no\n",
+            "\n",
+            "a.no\n",
+        ),
+        concat!(
+            "<p>iiii. no</p>\n",
+            "<p>aB. no</p>\n",
+            "<p>abcdefghi. no</p>\n",
+            "<p>a.no</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn alpha_lists_disabled() {
+    html("a. one\nb. two\n", "<p>a. one\nb. two</p>\n");
+}
+
+#[test]
+fn alpha_lists_commonmark() {
+    let mut options = Options::default();
+    options.extension.alpha_lists = true;
+
+    commonmark("b. one\nc. two\n", "b.  one\nc.  two\n", Some(&options));
+    commonmark("ix) one\nx) two\n", "ix) one\nx)  two\n", Some(&options));
+    commonmark("a\\. b\n", "a\\. b\n", Some(&options));
+
+    options.render.ordered_list_pad_markers = true;
+    commonmark("A.  one\nB.  two\n", "A.  one\nB.  two\n", Some(&options));
+}
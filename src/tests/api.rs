@@ -1,5 +1,7 @@
 use crate::{
-    adapters::{HeadingAdapter, HeadingMeta, SyntaxHighlighterAdapter},
+    adapters::{
+        HeadingAdapter, HeadingMeta, NodeRenderer, RenderDecision, SyntaxHighlighterAdapter,
+    },
     nodes::Sourcepos,
 };
 
@@ -25,6 +27,11 @@ fn exercise_full_api() {
 
     let _: String = Anchorizer::new().anchorize("header".to_string());
 
+    let toc: Vec<TocEntry> = table_of_contents(node, &default_options);
+    let _: Option<(u8, &str, &str)> = toc
+        .first()
+        .map(|e| (e.level, e.text.as_str(), e.id.as_str()));
+
     let _: &AstNode = parse_document(&arena, "document", &default_options);
 
     let _: &AstNode = parse_document_with_broken_link_callback(
@@ -34,20 +41,116 @@ fn exercise_full_api() {
         Some(&mut |_: &str| Some(("abc".to_string(), "xyz".to_string()))),
     );
 
+    let mut closure = |_: &str| None;
+    let callback: &mut dyn FnMut(&str) -> Option<(String, String)> = &mut closure;
+    let _: &AstNode = parse_document_with_broken_link_callback(
+        &arena,
+        "document",
+        &default_options,
+        Some(callback),
+    );
+
+    struct Resolver;
+
+    impl BrokenLinkCallback for Resolver {
+        fn resolve(&mut self, reference: BrokenLinkReference) -> BrokenLinkResolution {
+            let _: &str = reference.normalized;
+            let _: &str = reference.original;
+            match reference.kind {
+                ReferenceKind::Link => BrokenLinkResolution::Link {
+                    url: "abc".to_string(),
+                    title: "xyz".to_string(),
+                    attrs: vec![("rel".to_string(), "nofollow".to_string())],
+                },
+                ReferenceKind::Image => BrokenLinkResolution::Text,
+                ReferenceKind::Footnote => BrokenLinkResolution::Default,
+            }
+        }
+    }
+
+    let _: &AstNode = parse_document_with_broken_link_callback(
+        &arena,
+        "document",
+        &default_options,
+        Some(&mut Resolver),
+    );
+
+    let _: &AstNode = parse_document_with_unresolved_reference_callback(
+        &arena,
+        "document",
+        &default_options,
+        Some(&mut |_: &str, kind: ReferenceKind| match kind {
+            ReferenceKind::Link | ReferenceKind::Image => {
+                Some(("abc".to_string(), "xyz".to_string()))
+            }
+            ReferenceKind::Footnote => None,
+        }),
+    );
+
+    let refmap: RefMap = RefMap::from_document("[a]: /a", &default_options);
+    let _: RefMap = RefMap::new();
+    let _: &AstNode = parse_document_with_refmap(&arena, "document", &default_options, &refmap);
+
+    let mut streaming: StreamingParser = StreamingParser::new(&arena, &default_options);
+    streaming.push("docu");
+    streaming.push("ment");
+    let _: &AstNode = streaming.finish();
+    let _: std::io::Result<&AstNode> =
+        parse_document_from_reader(&arena, "document".as_bytes(), &default_options);
+
+    let (_, diagnostics): (&AstNode, Vec<Diagnostic>) =
+        parse_document_with_diagnostics(&arena, "document", &default_options);
+    for diagnostic in diagnostics {
+        let _: Sourcepos = diagnostic.sourcepos;
+        let _: String = diagnostic.message;
+        match diagnostic.kind {
+            DiagnosticKind::UnresolvedReference(_) => {}
+            DiagnosticKind::UnclosedCodeFence => {}
+            DiagnosticKind::NestingLimit => {}
+            DiagnosticKind::DuplicateFootnoteDefinition => {}
+            DiagnosticKind::HeadingTooDeep => {}
+            DiagnosticKind::SmartPunctuation => {}
+        }
+    }
+
+    match try_parse_document(&arena, "document", &default_options) {
+        Ok(_) => {}
+        Err(ParseError::Nul(lc)) => {
+            let _: nodes::LineColumn = lc;
+        }
+    }
+
     let mut extension = ExtensionOptionsBuilder::default();
     extension.strikethrough(false);
     extension.tagfilter(false);
     extension.table(false);
+    extension.tables_without_header(false);
+    extension.table_captions(false);
+    extension.table_cell_line_breaks(false);
     extension.autolink(false);
     extension.tasklist(false);
     extension.superscript(false);
+    extension.highlight(false);
     extension.philomena(false);
     extension.philomena_replacements(None);
     extension.philomena_domains(None);
     extension.header_ids(Some("abc".to_string()));
+    extension.header_id_style(HeaderIdStyle::AnchorBefore);
+    extension.header_anchor_content(Some("#".to_string()));
+    extension.header_id_slugifier(Some(|text: &str| text.to_string()));
     extension.footnotes(false);
+    extension.footnotes_render_unreferenced(false);
+    extension.footnote_start_number(None);
     extension.description_lists(false);
+    extension.description_list_marker(None);
+    extension.description_list_grouped_terms(false);
+    extension.alpha_lists(false);
+    extension.reversed_lists(false);
     extension.multiline_block_quotes(false);
+    extension.alerts(false);
+    extension.details(false);
+    extension.fenced_divs(false);
+    extension.abbreviations(false);
     extension.math_dollars(false);
     extension.math_code(false);
     extension.front_matter_delimiter(None);
@@ -56,23 +159,93 @@ fn exercise_full_api() {
     extension.shortcodes(true);
     extension.wikilinks_title_after_pipe(true);
     extension.wikilinks_title_before_pipe(true);
+    extension.wikilinks_slug_separator(Some('_'));
+    extension.wikilinks_url_resolver(Some(|target: &str| format!("/wiki/{}", target)));
+    extension.image_srcset(true);
+    extension.inline_code_lang(true);
 
     let mut parse = ParseOptionsBuilder::default();
     parse.smart(false);
     parse.default_info_string(Some("abc".to_string()));
+    parse.default_info_string_fn(Some(|_| None));
     parse.relaxed_tasklist_matching(false);
     parse.relaxed_autolinks(false);
+    parse.autolink_extra_tlds(Some(vec!["internal".to_string()]));
+    parse.autolink_extra_schemes(Some(vec!["tel".to_string()]));
+    parse.autolink_allowed_tlds(Some(vec!["com".to_string()]));
+    parse.no_reference_definitions(false);
+    parse.blockquote_no_space_required(false);
+    parse.lazy_blockquote_continuation(false);
+    parse.strip_bom(false);
+    parse.cjk_friendly_emphasis(false);
+    parse.list_item_trailing_hard_break(false);
+    parse.smart_skip_links(false);
+    parse.quote_elements(true);
+    parse.nul_handling(NulPolicy::Strip);
+    parse.max_nesting_depth(Some(10));
+    parse.inline_tokens(vec![InlineToken {
+        prefix: b'#',
+        matcher: |_| None,
+        builder: |_| None,
+    }]);
+    parse.inline_delimiters(vec![InlineDelimiter {
+        delim_char: b'+',
+        min_length: 1,
+        max_length: None,
+        same_length: false,
+        builder: |_| NodeValue::Emph,
+    }]);
 
     let mut render = RenderOptionsBuilder::default();
     render.hardbreaks(false);
+    render.collapse_soft_breaks(false);
     render.github_pre_lang(false);
     render.full_info_string(false);
+    render.code_block_title_from_info(false);
     render.width(123456);
     render.unsafe_(false);
     render.escape(false);
     render.list_style(ListStyleType::Dash);
+    render.line_ending(LineEnding::CrLf);
     render.sourcepos(false);
     render.escaped_char_spans(false);
+    render.allowed_inline_html_tags(vec!["sub".to_string()]);
+    render.insert_wbr_in_long_words(Some(30));
+    render.normalize_code_lang(Some(|lang: &str| lang.to_lowercase()));
+    #[cfg(feature = "shortcodes")]
+    render.emoji_image_url(Some(|shortcode: &str| Some(format!("/{}.png", shortcode))));
+    render.sorted_attributes(true);
+    render.aria_roles(true);
+    render.ascii_only(true);
+    render.data_line_start(true);
+    render.preserve_ordered_numbers(true);
+    render.math_mathjax_delimiters(true);
+    render.trim_code_trailing_whitespace(true);
+    render.obfuscate_email(true);
+    render.raw_info_string_class(true);
+    render.description_list_loose(true);
+    render.thematic_break_class(Some("divider".to_string()));
+    render.footnote_single_backref_simple(true);
+    render.ordered_list_pad_markers(true);
+    render.bullet_list_double_space(true);
+    render.group_footnote_refs(true);
+    render.footnote_id_prefix(Some("fn-".to_string()));
+    render.footnote_ref_id_prefix(Some("fnref-".to_string()));
+    render.footnote_backref_symbol(Some("^".to_string()));
+    render.footnote_backref_label(Some("Back {}".to_string()));
+    render.footnote_backref_simple_label(Some("Back".to_string()));
+    render.code_lang_as_data_attr(true);
+    render.code_lang_data_attr_only(true);
+    render.block_ids(Some("b-".to_string()));
+    render.plain_link_urls(true);
+    render.heading_offset(1);
+    render.external_links_new_tab(true);
+    render.internal_link_hosts(Some(vec!["example.com".to_string()]));
+    render.table_alignment_classes(Some([
+        "left".to_string(),
+        "center".to_string(),
+        "right".to_string(),
+    ]));
 
     pub struct MockAdapter {}
     impl SyntaxHighlighterAdapter for MockAdapter {
@@ -117,16 +290,44 @@ fn exercise_full_api() {
         }
     }
 
+    impl NodeRenderer for MockAdapter {
+        fn render<'a>(
+            &self,
+            _output: &mut dyn Write,
+            _node: &'a AstNode<'a>,
+        ) -> io::Result<RenderDecision> {
+            unreachable!()
+        }
+    }
+
     let mock_adapter = MockAdapter {};
 
     let mut render_plugins = RenderPluginsBuilder::default();
     render_plugins.codefence_syntax_highlighter(Some(&mock_adapter));
     render_plugins.heading_adapter(Some(&mock_adapter));
+    render_plugins.node_renderer(Some(&mock_adapter));
 
     let mut plugins = PluginsBuilder::default();
     plugins.render(render_plugins.build().unwrap());
 
+    let _: RenderPluginsBuilder = RenderPlugins::builder();
+    let _: PluginsBuilder = Plugins::builder();
+    let _: Plugins = Plugins::with_syntax_highlighter(&mock_adapter);
+    let _: Plugins = Plugins::with_heading_adapter(&mock_adapter);
+    let _: Plugins = Plugins::with_adapters(&mock_adapter, &mock_adapter);
+
     let _: String = markdown_to_html("# Yes", &default_options);
+    let _: String = canonicalize("# Yes", &default_options);
+    let _: String = wrap_text("Yes", 80);
+
+    let _: std::io::Result<()> = markdown_to_writer("# Yes", &default_options, &mut buffer);
+    let _: std::io::Result<()> =
+        markdown_to_commonmark_writer("# Yes", &default_options, &mut buffer);
+    let _: std::io::Result<()> =
+        markdown_to_commonmark_xml_writer("# Yes", &default_options, &mut buffer);
+
+    let _: bool = nodes::contains_raw_html(node);
+    let _: Option<String> = nodes::front_matter(node);
 
     //
 
@@ -153,7 +354,11 @@ fn exercise_full_api() {
             let _: bool = nl.tight;
         }
         nodes::NodeValue::DescriptionList => {}
-        nodes::NodeValue::DescriptionItem(_ndi) => {}
+        nodes::NodeValue::DescriptionItem(ndi) => {
+            let _: usize = ndi.marker_offset;
+            let _: usize = ndi.padding;
+            let _: bool = ndi.tight;
+        }
         nodes::NodeValue::DescriptionTerm => {}
         nodes::NodeValue::DescriptionDetails => {}
         nodes::NodeValue::CodeBlock(ncb) => {
@@ -161,6 +366,7 @@ fn exercise_full_api() {
             let _: u8 = ncb.fence_char;
             let _: usize = ncb.fence_length;
             let _: String = ncb.info;
+            let _: String = ncb.raw_info;
             let _: String = ncb.literal;
         }
         nodes::NodeValue::HtmlBlock(nhb) => {
@@ -191,6 +397,7 @@ fn exercise_full_api() {
             let _: &bool = header;
         }
         nodes::NodeValue::TableCell => {}
+        nodes::NodeValue::TableCaption => {}
         nodes::NodeValue::Text(text) => {
             let _: &String = text;
         }
@@ -213,9 +420,16 @@ fn exercise_full_api() {
         nodes::NodeValue::Subscript => {}
         nodes::NodeValue::SpoileredText => {}
         nodes::NodeValue::Underline => {}
+        nodes::NodeValue::Highlight => {}
+        nodes::NodeValue::Quoted => {}
         nodes::NodeValue::Link(nl) | nodes::NodeValue::Image(nl) => {
             let _: String = nl.url;
             let _: String = nl.title;
+            let _: Vec<(String, String)> = nl.attrs;
+            for candidate in &nl.srcset {
+                let _: &String = &candidate.url;
+                let _: &String = &candidate.descriptor;
+            }
         }
         #[cfg(feature = "shortcodes")]
         nodes::NodeValue::ShortCode(ne) => {
@@ -229,6 +443,27 @@ fn exercise_full_api() {
             let _: usize = mbc.fence_length;
             let _: usize = mbc.fence_offset;
         }
+        nodes::NodeValue::Alert(na) => {
+            let _: nodes::AlertType = na.alert_type;
+            let _: &'static str = na.alert_type.default_title();
+            let _: &'static str = na.alert_type.css_class();
+            let _: &Option<String> = &na.title;
+        }
+        nodes::NodeValue::Details(nd) => {
+            let _: &String = &nd.summary;
+            let _: bool = nd.open;
+            let _: usize = nd.fence_length;
+            let _: usize = nd.fence_offset;
+        }
+        nodes::NodeValue::Abbreviation(na) => {
+            let _: &String = &na.title;
+        }
+        nodes::NodeValue::FencedDiv(nfd) => {
+            let _: &String = &nfd.class;
+            let _: &Vec<(String, String)> = &nfd.attrs;
+            let _: usize = nfd.fence_length;
+            let _: usize = nfd.fence_offset;
+        }
         nodes::NodeValue::Escaped => {}
         nodes::NodeValue::Math(math) => {
             let _: bool = math.display_math;
@@ -1,4 +1,5 @@
 use super::*;
+use ntest::test_case;
 
 #[test]
 fn autolink_www() {
@@ -264,3 +265,286 @@ fn sourcepos_correctly_restores_context() {
         ])
     );
 }
+
+#[test]
+fn autolink_backslash_suppresses() {
+    html_opts_no_roundtrip(
+        concat!(
+            "\\www.autolink.com www.autolink.com\n",
+            "\\https://google.com and \\john@smith.com and \\mailto:john@smith.com\n",
+            "a\\www.autolink.com C:\\temp\n"
+        ),
+        concat!(
+            "<p>www.autolink.com <a href=\"http://www.autolink.com\">www.autolink.com</a>\n",
+            "https://google.com and john@smith.com and mailto:john@smith.com\n",
+            "a\\www.autolink.com C:\\temp</p>\n"
+        ),
+        |opts| opts.extension.autolink = true,
+    );
+}
+
+#[test]
+fn autolink_escaped_backslash_does_not_suppress() {
+    html_opts_i(
+        concat!(
+            "\\\\www.autolink.com \\\\https://google.com\n",
+            "\\\\john@smith.com a\\\\\\www.autolink.com\n"
+        ),
+        concat!(
+            "<p>\\www.autolink.com \\<a href=\"https://google.com\">https://google.com</a>\n",
+            "\\<a href=\"mailto:john@smith.com\">john@smith.com</a> a\\\\www.autolink.com</p>\n"
+        ),
+        |opts| opts.extension.autolink = true,
+    );
+}
+
+#[test]
+fn autolink_backslash_escaped_char_spans() {
+    html_opts_no_roundtrip(
+        "\\www.autolink.com \\*www.autolink.com\n",
+        concat!(
+            "<p>www.autolink.com <span data-escaped-char>*</span>",
+            "<a href=\"http://www.autolink.com\">www.autolink.com</a></p>\n"
+        ),
+        |opts| {
+            opts.extension.autolink = true;
+            opts.render.escaped_char_spans = true;
+        },
+    );
+}
+
+#[test]
+fn autolink_type_on_link_node() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.autolink = true;
+
+    let root = parse_document(
+        &arena,
+        "www.example.com john@smith.com <https://a.com> <me@a.com> [x](https://b.com)\n",
+        &options,
+    );
+
+    let kinds = root
+        .descendants()
+        .filter_map(|n| match n.data.borrow().value {
+            NodeValue::Link(ref nl) => Some(nl.autolink),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        kinds,
+        vec![
+            Some(nodes::AutolinkType::Uri),
+            Some(nodes::AutolinkType::Email),
+            Some(nodes::AutolinkType::Uri),
+            Some(nodes::AutolinkType::Email),
+            None,
+        ]
+    );
+}
+
+#[test]
+fn obfuscate_email() {
+    let mut options = Options::default();
+    options.extension.autolink = true;
+    options.render.obfuscate_email = true;
+
+    let html = markdown_to_html(
+        "Write to <a.b@example.com> or c@example.org, [not](mailto:d@example.net).\n",
+        &options,
+    );
+
+    let decoded = html
+        .split("&#")
+        .enumerate()
+        .map(|(i, part)| {
+            if i == 0 {
+                return part.to_string();
+            }
+            let end = part.find(';').unwrap();
+            let c = char::from_u32(part[..end].parse().unwrap()).unwrap();
+            format!("{}{}", c, &part[end + 1..])
+        })
+        .collect::<String>();
+
+    assert_eq!(html.matches('@').count(), 1);
+    assert!(html.contains("mailto:d@example.net"));
+    assert!(!html.contains("example.com"));
+    assert!(!html.contains("example.org"));
+    assert_eq!(
+        decoded,
+        concat!(
+            "<p>Write to <a href=\"mailto:a.b@example.com\">a.b@example.com</a> ",
+            "or <a href=\"mailto:c@example.org\">c@example.org</a>, ",
+            "<a href=\"mailto:d@example.net\">not</a>.</p>\n"
+        )
+    );
+}
+
+#[test]
+fn autolink_extra_tlds() {
+    html_opts_i(
+        concat!(
+            "Docs at wiki.corp.internal/start, build.INTERNAL and ",
+            "(ci.internal). Not file.txt, me@host.internal or http://x.internal.\n"
+        ),
+        concat!(
+            "<p>Docs at <a href=\"http://wiki.corp.internal/start\">wiki.corp.internal/start</a>, ",
+            "<a href=\"http://build.INTERNAL\">build.INTERNAL</a> and ",
+            "(<a href=\"http://ci.internal\">ci.internal</a>). Not file.txt, ",
+            "<a href=\"mailto:me@host.internal\">me@host.internal</a> or ",
+            "<a href=\"http://x.internal\">http://x.internal</a>.</p>\n"
+        ),
+        |opts| {
+            opts.extension.autolink = true;
+            opts.parse.autolink_extra_tlds = Some(vec![".internal".to_string()]);
+        },
+    );
+}
+
+#[test]
+fn autolink_extra_schemes() {
+    for relaxed in [false, true] {
+        html_opts_i(
+            concat!(
+                "Call tel:+1-555-0100, chat at MATRIX:u/alice:example.org or ",
+                "matrix://example.org/room and www.example.com.\n",
+                "Not xtel:+1, 3tel:+1, tel: 1, tel:. or sftp:host\n"
+            ),
+            concat!(
+                "<p>Call <a href=\"tel:+1-555-0100\">tel:+1-555-0100</a>, chat at ",
+                "<a href=\"MATRIX:u/alice:example.org\">MATRIX:u/alice:example.org</a> or ",
+                "<a href=\"matrix://example.org/room\">matrix://example.org/room</a> and ",
+                "<a href=\"http://www.example.com\">www.example.com</a>.\n",
+                "Not xtel:+1, 3tel:+1, tel: 1, tel:. or sftp:host</p>\n"
+            ),
+            |opts| {
+                opts.extension.autolink = true;
+                opts.parse.relaxed_autolinks = relaxed;
+                opts.parse.autolink_extra_schemes =
+                    Some(vec!["tel".to_string(), "matrix".to_string()]);
+            },
+        );
+    }
+
+    // Other schemes still need `//`, and relaxed_autolinks to be accepted.
+    html_opts_i(
+        "gopher://example.org and gopher:example\n",
+        "<p>gopher://example.org and gopher:example</p>\n",
+        |opts| {
+            opts.extension.autolink = true;
+            opts.parse.autolink_extra_schemes = Some(vec!["tel".to_string()]);
+        },
+    );
+    html_opts_i(
+        "gopher://example.org and gopher:example\n",
+        "<p><a href=\"gopher://example.org\">gopher://example.org</a> and gopher:example</p>\n",
+        |opts| {
+            opts.extension.autolink = true;
+            opts.parse.relaxed_autolinks = true;
+            opts.parse.autolink_extra_schemes = Some(vec!["tel".to_string()]);
+        },
+    );
+}
+
+#[test]
+fn autolink_allowed_tlds() {
+    html_opts_i(
+        concat!(
+            "www.example.com, www.file.txt, a@example.com, b@example.txt, ",
+            "c@host.internal and https://example.txt\n"
+        ),
+        concat!(
+            "<p><a href=\"http://www.example.com\">www.example.com</a>, www.file.txt, ",
+            "<a href=\"mailto:a@example.com\">a@example.com</a>, b@example.txt, ",
+            "<a href=\"mailto:c@host.internal\">c@host.internal</a> and ",
+            "<a href=\"https://example.txt\">https://example.txt</a></p>\n"
+        ),
+        |opts| {
+            opts.extension.autolink = true;
+            opts.parse.autolink_allowed_tlds = Some(vec!["com".to_string()]);
+            opts.parse.autolink_extra_tlds = Some(vec!["internal".to_string()]);
+        },
+    );
+}
+
+// Examples from the GFM spec's extended autolink section, and the left
+// boundaries a `www.` autolink needs.
+#[test_case(
+    "www.commonmark.org",
+    "<p><a href=\"http://www.commonmark.org\">www.commonmark.org</a></p>\n"
+)]
+#[test_case("Visit www.commonmark.org/help for more information.", "<p>Visit <a href=\"http://www.commonmark.org/help\">www.commonmark.org/help</a> for more information.</p>\n")]
+#[test_case(
+    "Visit www.commonmark.org.",
+    "<p>Visit <a href=\"http://www.commonmark.org\">www.commonmark.org</a>.</p>\n"
+)]
+#[test_case(
+    "Visit www.commonmark.org/a.b.",
+    "<p>Visit <a href=\"http://www.commonmark.org/a.b\">www.commonmark.org/a.b</a>.</p>\n"
+)]
+#[test_case("www.google.com/search?q=Markup+(business)))", "<p><a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a>))</p>\n")]
+#[test_case("(www.google.com/search?q=Markup+(business))", "<p>(<a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a>)</p>\n")]
+#[test_case("(www.google.com/search?q=Markup+(business)", "<p>(<a href=\"http://www.google.com/search?q=Markup+(business)\">www.google.com/search?q=Markup+(business)</a></p>\n")]
+#[test_case("www.google.com/search?q=(business))+ok", "<p><a href=\"http://www.google.com/search?q=(business))+ok\">www.google.com/search?q=(business))+ok</a></p>\n")]
+#[test_case("www.google.com/search?q=commonmark&hl;", "<p><a href=\"http://www.google.com/search?q=commonmark\">www.google.com/search?q=commonmark</a>&amp;hl;</p>\n")]
+#[test_case(
+    "www.commonmark.org/he<lp",
+    "<p><a href=\"http://www.commonmark.org/he\">www.commonmark.org/he</a>&lt;lp</p>\n"
+)]
+#[test_case("(Visit https://encrypted.google.com/search?q=Markup+(business))", "<p>(Visit <a href=\"https://encrypted.google.com/search?q=Markup+(business)\">https://encrypted.google.com/search?q=Markup+(business)</a>)</p>\n")]
+#[test_case("hello@mail+xyz.example isn't valid, but hello+xyz@mail.example is.", "<p>hello@mail+xyz.example isn't valid, but <a href=\"mailto:hello+xyz@mail.example\">hello+xyz@mail.example</a> is.</p>\n")]
+#[test_case(
+    "a.b-c_d@a.b.",
+    "<p><a href=\"mailto:a.b-c_d@a.b\">a.b-c_d@a.b</a>.</p>\n"
+)]
+#[test_case("a.b-c_d@a.b-", "<p>a.b-c_d@a.b-</p>\n")]
+#[test_case(
+    "*www.example.com*",
+    "<p><em><a href=\"http://www.example.com\">www.example.com</a></em></p>\n"
+)]
+#[test_case(
+    "_www.example.com_",
+    "<p><em><a href=\"http://www.example.com\">www.example.com</a></em></p>\n"
+)]
+#[test_case(
+    "~~www.example.com~~",
+    "<p><del><a href=\"http://www.example.com\">www.example.com</a></del></p>\n"
+)]
+#[test_case(
+    "**a**www.example.com",
+    "<p><strong>a</strong><a href=\"http://www.example.com\">www.example.com</a></p>\n"
+)]
+#[test_case(
+    "a*www.example.com",
+    "<p>a*<a href=\"http://www.example.com\">www.example.com</a></p>\n"
+)]
+#[test_case(
+    "x\nwww.example.com",
+    "<p>x\n<a href=\"http://www.example.com\">www.example.com</a></p>\n"
+)]
+#[test_case("xwww.example.com", "<p>xwww.example.com</p>\n")]
+#[test_case("\"www.example.com\"", "<p>&quot;www.example.com&quot;</p>\n")]
+#[test_case("`c`www.example.com", "<p><code>c</code>www.example.com</p>\n")]
+#[test_case("[a](b)www.example.com", "<p><a href=\"b\">a</a>www.example.com</p>\n")]
+#[test_case(
+    "`c` www.example.com",
+    "<p><code>c</code> <a href=\"http://www.example.com\">www.example.com</a></p>\n"
+)]
+#[test_case(
+    "`c`http://example.com",
+    "<p><code>c</code><a href=\"http://example.com\">http://example.com</a></p>\n"
+)]
+#[test_case(
+    "`c`foo@bar.baz",
+    "<p><code>c</code><a href=\"mailto:foo@bar.baz\">foo@bar.baz</a></p>\n"
+)]
+fn autolink_gfm_boundaries(markdown: &str, html: &str) {
+    html_opts!(
+        [extension.autolink, extension.strikethrough],
+        markdown,
+        html
+    );
+}
@@ -0,0 +1,32 @@
+use super::*;
+use ntest::test_case;
+
+#[test_case("**「強調」**です", "<p><strong>「強調」</strong>です</p>\n")]
+#[test_case(
+    "これは**「強調」**です",
+    "<p>これは<strong>「強調」</strong>です</p>\n"
+)]
+#[test_case("*（注）*を参照", "<p><em>（注）</em>を参照</p>\n")]
+#[test_case("**中文，**测试", "<p><strong>中文，</strong>测试</p>\n")]
+#[test_case("**中文**、测试", "<p><strong>中文</strong>、测试</p>\n")]
+#[test_case(
+    "한국어**“강조”**입니다",
+    "<p>한국어<strong>“강조”</strong>입니다</p>\n"
+)]
+fn cjk_friendly_emphasis(input: &str, expected: &str) {
+    html_opts!([parse.cjk_friendly_emphasis], input, expected);
+}
+
+#[test_case("**「強調」**です", "<p>**「強調」**です</p>\n")]
+#[test_case("*（注）*を参照", "<p>*（注）*を参照</p>\n")]
+fn cjk_emphasis_default(input: &str, expected: &str) {
+    html(input, expected);
+}
+
+#[test_case("a**\"b\"**c", "<p>a**&quot;b&quot;**c</p>\n")]
+#[test_case("**foo.**bar", "<p>**foo.**bar</p>\n")]
+// Underscores still can't emphasize within a word.
+#[test_case("__「強調」__です", "<p>__「強調」__です</p>\n")]
+fn cjk_friendly_emphasis_leaves_other_text(input: &str, expected: &str) {
+    html_opts!([parse.cjk_friendly_emphasis], input, expected);
+}
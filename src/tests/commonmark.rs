@@ -64,3 +64,132 @@ fn wikilinks(markdown: &str, cm: &str) {
 
     commonmark(markdown, cm, Some(&options));
 }
+
+#[test]
+fn width_keeps_links_intact() {
+    let mut options = Options::default();
+    options.render.width = 40;
+    options.extension.autolink = true;
+
+    let input = concat!(
+        "See [the installation guide](https://example.com/docs/getting-started/installation) ",
+        "for details, and ![a small diagram](/img/diagram.png \"How it fits together\") too, ",
+        "or write to <someone@example.com> or visit https://example.com/a/rather/long/path/here ",
+        "today.\n",
+    );
+    let output = concat!(
+        "See\n",
+        "[the installation guide](https://example.com/docs/getting-started/installation)\n",
+        "for details, and\n",
+        "![a small diagram](/img/diagram.png \"How it fits together\")\n",
+        "too, or write to <someone@example.com>\n",
+        "or visit\n",
+        "<https://example.com/a/rather/long/path/here>\n",
+        "today.\n",
+    );
+
+    commonmark(input, output, Some(&options));
+}
+
+#[test_case(
+    20,
+    "Run\n`cargo   build --release --workspace`\nfirst, then `make`.\n"
+)]
+#[test_case(
+    30,
+    "Run\n`cargo   build --release --workspace`\nfirst, then `make`.\n"
+)]
+#[test_case(45, "Run `cargo   build --release --workspace`\nfirst, then `make`.\n")]
+#[test_case(80, "Run `cargo   build --release --workspace` first, then `make`.\n")]
+fn width_keeps_code_spans_intact(width: usize, output: &str) {
+    let mut options = Options::default();
+    options.render.width = width;
+
+    commonmark(
+        "Run `cargo   build --release --workspace` first, then `make`.\n",
+        output,
+        Some(&options),
+    );
+}
+
+#[test_case(
+    10,
+    "A\n`` `tick` ``\nand\n<https://example.com/some/long/path>\nend.\n"
+)]
+#[test_case(25, "A `` `tick` `` and\n<https://example.com/some/long/path>\nend.\n")]
+#[test_case(50, "A `` `tick` `` and\n<https://example.com/some/long/path> end.\n")]
+fn width_keeps_code_spans_and_urls_intact(width: usize, output: &str) {
+    let mut options = Options::default();
+    options.render.width = width;
+    options.extension.autolink = true;
+
+    commonmark(
+        "A `` `tick` `` and https://example.com/some/long/path end.\n",
+        output,
+        Some(&options),
+    );
+}
+
+#[test]
+fn ordered_list_pad_markers() {
+    let mut options = Options::default();
+    options.render.ordered_list_pad_markers = true;
+
+    let input = concat!("98. a\n", "99. b\n", "100. c\n", "     continued\n",);
+    let output = concat!("98.  a\n", "99.  b\n", "100. c\n", "     continued\n",);
+
+    commonmark(input, output, Some(&options));
+}
+
+#[test]
+fn bullet_list_double_space() {
+    let mut options = Options::default();
+    options.render.bullet_list_double_space = true;
+
+    let input = "- a\n  - b\n    c\n";
+    let output = "-  a\n   -  b\n      c\n";
+
+    commonmark(input, output, Some(&options));
+}
+
+#[test_case("Hello _world_ and __there__.\n")]
+#[test_case("* one\n* two\n\n\n\n+ three\n")]
+#[test_case("3) three\n4) four\n")]
+#[test_case("[a][b] and [b]\n\n[b]: /url \"title\"\n")]
+#[test_case("Setext\n===\n\n    indented code\n")]
+#[test_case("> quote\ncontinued\n>\n> - item\n")]
+#[test_case("a  \nb\\\nc\n")]
+#[test_case("*foo`*`* <http://a.b> \\* &amp;\n")]
+#[test_case("| a | b |\n|---|:-:|\n| c | d |\n\n~~gone~~ www.example.com\n")]
+fn canonicalize_is_idempotent(input: &str) {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.autolink = true;
+
+    let once = canonicalize(input, &options);
+    let twice = canonicalize(&once, &options);
+    compare_strs(&twice, &once, "canonicalize", input);
+}
+
+#[test]
+fn wrap_text_matches_formatter() {
+    let text = concat!(
+        "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod ",
+        "tempor incididunt ut labore https://example.com/a/rather/long/path/that/will/not/fit ",
+        "et dolore magna aliqua, in 2024 or 1999 and   extra   spaces too."
+    );
+
+    for width in [1, 10, 20, 33, 72, 500] {
+        let mut options = Options::default();
+        options.render.width = width;
+        let formatted = markdown_to_commonmark(text, &options);
+        assert_eq!(wrap_text(text, width) + "\n", formatted, "width {}", width);
+    }
+}
+
+#[test]
+fn wrap_text_keeps_newlines() {
+    assert_eq!(wrap_text("one two\nthree four", 8), "one two\nthree\nfour");
+    assert_eq!(wrap_text("one two three", 0), "one two three");
+}
@@ -287,12 +287,14 @@ fn backticks_num() {
     let code1 = NodeValue::Code(NodeCode {
         num_backticks: 1,
         literal: "code1".to_string(),
+        lang: None,
     });
     asssert_node_eq(root, &[0, 1], &code1);
 
     let code2 = NodeValue::Code(NodeCode {
         num_backticks: 3,
         literal: "code2".to_string(),
+        lang: None,
     });
     asssert_node_eq(root, &[0, 3], &code2);
 }
@@ -409,6 +411,43 @@ fn reference_links() {
     );
 }
 
+#[test]
+fn reference_links_angle_brackets() {
+    html(
+        concat!(
+            "[a] [b] [c] [d] [e]\n",
+            "\n",
+            "[a]: <url with spaces> \"title here\"\n",
+            "[b]: <url with spaces>\n",
+            "  'next line'\n",
+            "[c]:\n",
+            "<my url>\n",
+            "[d]: <a\\>b> (t)\n",
+            "[e]: <>\n"
+        ),
+        concat!(
+            "<p><a href=\"url%20with%20spaces\" title=\"title here\">a</a> ",
+            "<a href=\"url%20with%20spaces\" title=\"next line\">b</a> ",
+            "<a href=\"my%20url\">c</a> ",
+            "<a href=\"a%3Eb\" title=\"t\">d</a> ",
+            "<a href=\"\">e</a></p>\n"
+        ),
+    );
+}
+
+#[test]
+fn reference_links_angle_brackets_invalid() {
+    html(
+        concat!("[a]: <1 2\n3>\n", "\n", "[b]: <1>(t)\n", "\n", "[a] [b]\n"),
+        concat!(
+            "<p>[a]: &lt;1 2\n",
+            "3&gt;</p>\n",
+            "<p>[b]: &lt;1&gt;(t)</p>\n",
+            "<p>[a] [b]</p>\n"
+        ),
+    );
+}
+
 #[test]
 fn safety() {
     html(
@@ -466,6 +505,29 @@ fn nul_replacement_5() {
     html("a\r\n\0b", "<p>a\n\u{fffd}b</p>\n");
 }
 
+#[test]
+fn nul_strip() {
+    html_opts_i("a\0b\0\0c\0\n\0d\r\n\0", "<p>abc\nd</p>\n", |opts| {
+        opts.parse.nul_handling = NulPolicy::Strip
+    });
+}
+
+#[test]
+fn nul_error() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.parse.nul_handling = NulPolicy::Error;
+
+    let err = try_parse_document(&arena, "a\r\nb\rc\nde\0f", &options).unwrap_err();
+    assert_eq!(err, ParseError::Nul((4, 3).into()));
+    assert_eq!(err.to_string(), "NUL byte at 4:3");
+
+    // The infallible entry points fall back to replacing.
+    html_opts_i("a\0b", "<p>a\u{fffd}b</p>\n", |opts| {
+        opts.parse.nul_handling = NulPolicy::Error
+    });
+}
+
 #[test]
 fn case_insensitive_safety() {
     html(
@@ -473,3 +535,573 @@ fn case_insensitive_safety() {
         "<p><a href=\"\">a</a> <a href=\"\">b</a> <a href=\"\">c</a> <a href=\"\">d</a> <a href=\"\">e</a> <a href=\"\">f</a> <a href=\"\">g</a></p>\n",
     );
 }
+
+#[test]
+fn unresolved_reference_callback() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+
+    let mut seen = vec![];
+    let root = parse_document_with_unresolved_reference_callback(
+        &arena,
+        concat!(
+            "[Known] [Unknown] ![Pic] [^known] [^gone]\n",
+            "\n",
+            "[known]: /url\n",
+            "\n",
+            "[^known]: Note.\n"
+        ),
+        &options,
+        Some(&mut |label: &str, kind: ReferenceKind| {
+            seen.push((label.to_string(), kind));
+            match kind {
+                ReferenceKind::Image => Some(("/pic.png".to_string(), String::new())),
+                _ => None,
+            }
+        }),
+    );
+
+    let mut output = vec![];
+    html::format_document(root, &options, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!(
+        seen,
+        vec![
+            ("unknown".to_string(), ReferenceKind::Link),
+            ("pic".to_string(), ReferenceKind::Image),
+            ("gone".to_string(), ReferenceKind::Footnote),
+        ]
+    );
+    assert!(output.starts_with(
+        "<p><a href=\"/url\">Known</a> [Unknown] <img src=\"/pic.png\" alt=\"Pic\" /> "
+    ));
+    assert!(output.contains(" [^gone]</p>\n"));
+}
+
+#[test]
+fn broken_link_callback_resolution() {
+    struct Resolver(Vec<(String, String, ReferenceKind)>);
+
+    impl BrokenLinkCallback for Resolver {
+        fn resolve(&mut self, reference: BrokenLinkReference) -> BrokenLinkResolution {
+            self.0.push((
+                reference.normalized.to_string(),
+                reference.original.to_string(),
+                reference.kind,
+            ));
+            match reference.original {
+                "Some  Page" | "logo" => BrokenLinkResolution::Link {
+                    url: format!("/{}", reference.normalized.replace(' ', "-")),
+                    title: String::new(),
+                    attrs: vec![("data-x".to_string(), "a\"b".to_string())],
+                },
+                "^note" => BrokenLinkResolution::Text,
+                _ => BrokenLinkResolution::Default,
+            }
+        }
+    }
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+
+    let mut resolver = Resolver(vec![]);
+    let root = parse_document_with_broken_link_callback(
+        &arena,
+        concat!(
+            "[Some  Page] ![Logo][logo] [other] [^note] [^kept]\n",
+            "\n",
+            "[^note]: Note.\n",
+            "\n",
+            "[^kept]: Kept.\n"
+        ),
+        &options,
+        Some(&mut resolver),
+    );
+
+    assert_eq!(
+        resolver.0,
+        vec![
+            (
+                "some page".to_string(),
+                "Some  Page".to_string(),
+                ReferenceKind::Link
+            ),
+            ("logo".to_string(), "logo".to_string(), ReferenceKind::Image),
+            (
+                "other".to_string(),
+                "other".to_string(),
+                ReferenceKind::Link
+            ),
+            (
+                "^note".to_string(),
+                "^note".to_string(),
+                ReferenceKind::Link
+            ),
+            (
+                "^kept".to_string(),
+                "^kept".to_string(),
+                ReferenceKind::Link
+            ),
+        ]
+    );
+
+    let mut output = vec![];
+    html::format_document(root, &options, &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(concat!(
+        "<p><a href=\"/some-page\" data-x=\"a&quot;b\">Some  Page</a> ",
+        "<img src=\"/logo\" alt=\"Logo\" data-x=\"a&quot;b\" /> ",
+        "[other] [^note] <sup class=\"footnote-ref\">"
+    )));
+}
+
+#[test]
+fn broken_link_callback_attribute_names() {
+    struct Attrs;
+
+    impl BrokenLinkCallback for Attrs {
+        fn resolve(&mut self, _: BrokenLinkReference) -> BrokenLinkResolution {
+            BrokenLinkResolution::Link {
+                url: "/a".to_string(),
+                title: String::new(),
+                attrs: [
+                    "data-ok", "xml:lang", "a b", "x=\"y", "a>", "", "-x", "onclick",
+                ]
+                .iter()
+                .map(|name| (name.to_string(), "v".to_string()))
+                .collect(),
+            }
+        }
+    }
+
+    let arena = Arena::new();
+    let mut options = Options::default();
+    let root =
+        parse_document_with_broken_link_callback(&arena, "[a]\n", &options, Some(&mut Attrs));
+
+    let mut output = vec![];
+    html::format_document(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<p><a href=\"/a\" data-ok=\"v\" xml:lang=\"v\">a</a></p>\n"
+    );
+
+    options.render.unsafe_ = true;
+    let mut output = vec![];
+    html::format_document(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<p><a href=\"/a\" data-ok=\"v\" xml:lang=\"v\" onclick=\"v\">a</a></p>\n"
+    );
+}
+
+#[test]
+fn broken_link_callback_closure() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let root = parse_document_with_broken_link_callback(
+        &arena,
+        "[a] [b]\n",
+        &options,
+        Some(&mut |label: &str| {
+            if label == "a" {
+                Some(("/a".to_string(), String::new()))
+            } else {
+                None
+            }
+        }),
+    );
+
+    let mut output = vec![];
+    html::format_document(root, &options, &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "<p><a href=\"/a\">a</a> [b]</p>\n"
+    );
+}
+
+#[test]
+fn format_html_fragment_subtree() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.footnotes = true;
+    options.extension.header_ids = Some("".to_string());
+
+    let root = parse_document(
+        &arena,
+        concat!(
+            "# Title\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| [x] | y[^1] |\n",
+            "\n",
+            "# Title\n",
+            "\n",
+            "[x]: /url\n",
+            "\n",
+            "[^1]: Note.\n"
+        ),
+        &options,
+    );
+
+    let mut children = root.children();
+    children.next();
+    let table = children.next().unwrap();
+    let heading = children.next().unwrap();
+
+    let mut output = vec![];
+    format_html_fragment(table, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td><a href=\"/url\">x</a></td>\n",
+            "<td>y<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>1</a></sup></td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+        "html",
+        "",
+    );
+
+    // Header IDs are only unique within what's being rendered.
+    let mut output = vec![];
+    format_html_fragment(heading, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        "<h1><a href=\"#title\" aria-hidden=\"true\" class=\"anchor\" id=\"title\"></a>Title</h1>\n",
+        "html",
+        "",
+    );
+
+    let mut output = vec![];
+    format_commonmark(table, &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        "| a         | b     |\n| --------- | ----- |\n| [x](/url) | y[^1] |\n",
+        "cm",
+        "",
+    );
+}
+
+#[test]
+fn text_content() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.math_dollars = true;
+    options.extension.strikethrough = true;
+
+    let root = parse_document(
+        &arena,
+        concat!(
+            "# A *b* ~~c~~ $x^2$\n",
+            "\n",
+            "d  \n",
+            "e\n",
+            "f ![g *h*](/i.png) [j](/k) <span>l</span>\n"
+        ),
+        &options,
+    );
+
+    let mut children = root.children();
+    let heading = children.next().unwrap();
+    let paragraph = children.next().unwrap();
+
+    assert_eq!(heading.text_content(), "A b c x^2");
+    assert_eq!(paragraph.text_content(), "d e f g h j l");
+}
+
+#[test]
+fn dump_ast_literals() {
+    let arena = Arena::new();
+    let root = parse_document(
+        &arena,
+        "```\nfn main() {}\n```\n\na  \nb\n",
+        &Options::default(),
+    );
+
+    assert_eq!(
+        crate::nodes::dump_ast(root, 100),
+        concat!(
+            "(document (1:1-6:1) [\n",
+            "    (code_block (1:1-3:3) \"fn main() {}\\n\")\n",
+            "    (paragraph (5:1-6:1) [\n",
+            "        (text (5:1-5:3) \"a\")\n",
+            "        (linebreak (5:4-5:4))\n",
+            "        (text (6:1-6:1) \"b\")\n",
+            "    ])\n",
+            "])\n",
+        )
+    );
+}
+
+#[test]
+fn contains_raw_html() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.math_dollars = true;
+
+    let safe = parse_document(
+        &arena,
+        "`<b>`\n\n```\n<div>\n```\n\n$<i>$ &lt;b&gt;\n",
+        &options,
+    );
+    assert!(!crate::nodes::contains_raw_html(safe));
+
+    let block = parse_document(&arena, "> <div>\n> hi\n", &options);
+    assert!(crate::nodes::contains_raw_html(block));
+
+    let inline = parse_document(&arena, "- *a <span>b</span>*\n", &options);
+    assert!(crate::nodes::contains_raw_html(inline));
+}
+
+#[test]
+fn front_matter() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.front_matter_delimiter = Some("+++".to_string());
+
+    let root = parse_document(
+        &arena,
+        "\u{feff}+++\r\ntitle = \"a\"\r\n++ = 1\r\n+++\r\n\r\nText\n",
+        &options,
+    );
+    assert_eq!(
+        crate::nodes::front_matter(root).as_deref(),
+        Some("title = \"a\"\r\n++ = 1\r\n")
+    );
+    assert_eq!(crate::nodes::front_matter(root.last_child().unwrap()), None);
+
+    let root = parse_document(&arena, "+++\nx\n+++\n+++\n", &options);
+    assert_eq!(crate::nodes::front_matter(root).as_deref(), Some("x\n"));
+
+    let root = parse_document(&arena, "Text\n\n+++\nx\n+++\n", &options);
+    assert_eq!(crate::nodes::front_matter(root), None);
+
+    options.extension.front_matter_delimiter = None;
+    let root = parse_document(&arena, "+++\nx\n+++\n", &options);
+    assert_eq!(crate::nodes::front_matter(root), None);
+}
+
+#[test]
+fn prune_empty() {
+    let arena = Arena::new();
+    let options = Options::default();
+    let input = "Intro\n\n*a* **b** c\n\n> *d*\n> e\n";
+
+    fn empty_texts<'a>(root: &'a AstNode<'a>, blank: &str) {
+        for node in root.descendants() {
+            if let NodeValue::Text(ref mut text) = node.data.borrow_mut().value {
+                if text != "Intro" {
+                    *text = blank.to_string();
+                }
+            }
+        }
+    }
+    let render = |root| {
+        let mut html = vec![];
+        format_html(root, &options, &mut html).unwrap();
+        String::from_utf8(html).unwrap()
+    };
+
+    let root = parse_document(&arena, input, &options);
+    empty_texts(root, "");
+    crate::nodes::prune_empty(root, false);
+    assert_eq!(render(root), "<p>Intro</p>\n<blockquote>\n</blockquote>\n");
+
+    let root = parse_document(&arena, input, &options);
+    empty_texts(root, " \t");
+    crate::nodes::prune_empty(root, false);
+    assert_eq!(
+        render(root),
+        concat!(
+            "<p>Intro</p>\n",
+            "<p><em> \t</em> \t<strong> \t</strong> \t</p>\n",
+            "<blockquote>\n",
+            "<p><em> \t</em>\n \t</p>\n",
+            "</blockquote>\n"
+        )
+    );
+    crate::nodes::prune_empty(root, true);
+    assert_eq!(render(root), "<p>Intro</p>\n<blockquote>\n</blockquote>\n");
+}
+
+#[test]
+fn hard_breaks_survive_text_joining() {
+    html_opts!(
+        [extension.autolink],
+        concat!("- a\\*b  \n", "  c www.example.com\\\n", "  d\n"),
+        concat!(
+            "<ul>\n",
+            "<li>a*b<br />\n",
+            "c <a href=\"http://www.example.com\">www.example.com</a><br />\n",
+            "d</li>\n",
+            "</ul>\n",
+        ),
+    );
+}
+
+#[test]
+fn markdown_to_writers() {
+    let input = "# Title\n\nSome *text* and a [link](/url).\n";
+    let options = Options::default();
+
+    let mut html = vec![];
+    markdown_to_writer(input, &options, &mut html).unwrap();
+    assert_eq!(
+        String::from_utf8(html).unwrap(),
+        markdown_to_html(input, &options)
+    );
+
+    let mut cm = vec![];
+    markdown_to_commonmark_writer(input, &options, &mut cm).unwrap();
+    assert_eq!(
+        String::from_utf8(cm).unwrap(),
+        markdown_to_commonmark(input, &options)
+    );
+
+    let mut xml = vec![];
+    markdown_to_commonmark_xml_writer(input, &options, &mut xml).unwrap();
+    assert_eq!(
+        String::from_utf8(xml).unwrap(),
+        markdown_to_commonmark_xml(input, &options)
+    );
+}
+
+#[test]
+fn shared_refmap() {
+    let options = Options::default();
+    let shared = RefMap::from_document(
+        "Preamble text is dropped.\n\n[a]: /shared-a \"A\"\n[b]: /shared-b\n[Pic]: /pic.png\n",
+        &options,
+    );
+
+    let render = |input: &str| {
+        let arena = Arena::new();
+        let root = parse_document_with_refmap(&arena, input, &options, &shared);
+        let mut html = vec![];
+        format_html(root, &options, &mut html).unwrap();
+        String::from_utf8(html).unwrap()
+    };
+
+    assert_eq!(
+        render("[a] [B][] ![x][pic] [c]\n"),
+        concat!(
+            "<p><a href=\"/shared-a\" title=\"A\">a</a> <a href=\"/shared-b\">B</a> ",
+            "<img src=\"/pic.png\" alt=\"x\" /> [c]</p>\n"
+        ),
+    );
+
+    // The document's definitions win, the first of them as usual.
+    assert_eq!(
+        render("[a]\n\n[a]: /doc-1\n[a]: /doc-2\n"),
+        "<p><a href=\"/doc-1\">a</a></p>\n",
+    );
+
+    // The shared map is left as it was.
+    assert_eq!(
+        render("[a]\n"),
+        "<p><a href=\"/shared-a\" title=\"A\">a</a></p>\n"
+    );
+}
+
+#[test]
+fn max_nesting_depth() {
+    let render = |input: &str, depth: Option<usize>| {
+        let mut options = Options::default();
+        options.parse.max_nesting_depth = depth;
+        markdown_to_html(input, &options)
+    };
+
+    let lists = |n: usize| "<ul>\n<li>\n".repeat(n - 1);
+    let lists_end = |n: usize| "</li>\n</ul>\n".repeat(n - 1);
+
+    // By default lists stop nesting at 100, block quotes never do.
+    let input = format!("{}a\n", "- ".repeat(100));
+    assert_eq!(
+        render(&input, None),
+        format!("{}<ul>\n<li>- a</li>\n</ul>\n{}", lists(99), lists_end(99)),
+    );
+    let input = format!("{}a\n", "> ".repeat(150));
+    assert_eq!(
+        render(&input, None),
+        format!(
+            "{}<p>a</p>\n{}",
+            "<blockquote>\n".repeat(150),
+            "</blockquote>\n".repeat(150)
+        ),
+    );
+
+    // Either side of a lowered limit.
+    assert_eq!(
+        render("- - - a\n", Some(4)),
+        format!("{}<ul>\n<li>a</li>\n</ul>\n{}", lists(3), lists_end(3)),
+    );
+    assert_eq!(
+        render("- - - a\n", Some(3)),
+        format!("{}<ul>\n<li>- a</li>\n</ul>\n{}", lists(2), lists_end(2)),
+    );
+    assert_eq!(
+        render("> > > a\n", Some(3)),
+        "<blockquote>\n<blockquote>\n<p>&gt; a</p>\n</blockquote>\n</blockquote>\n",
+    );
+    assert_eq!(
+        render("> - > - a\n", Some(3)),
+        "<blockquote>\n<ul>\n<li>&gt; - a</li>\n</ul>\n</blockquote>\n",
+    );
+
+    // And a raised one.
+    let input = format!("{}a\n", "- ".repeat(150));
+    assert_eq!(
+        render(&input, Some(200)),
+        format!("{}<ul>\n<li>a</li>\n</ul>\n{}", lists(150), lists_end(150)),
+    );
+}
+
+#[test]
+fn heading_offset() {
+    let input = "# One\n\nTwo\n---\n\n#### Four\n\n###### Six\n";
+    html_opts_i(
+        input,
+        concat!(
+            "<h3 data-sourcepos=\"1:1-1:5\">One</h3>\n",
+            "<h4 data-sourcepos=\"3:1-4:3\">Two</h4>\n",
+            "<h6 data-sourcepos=\"6:1-6:9\">Four</h6>\n",
+            "<h6 data-sourcepos=\"8:1-8:10\">Six</h6>\n",
+        ),
+        |opts| {
+            opts.render.heading_offset = 2;
+            opts.render.sourcepos = true;
+        },
+    );
+
+    let mut options = Options::default();
+    options.render.heading_offset = u8::MAX;
+    assert_eq!(markdown_to_html("# One\n", &options), "<h6>One</h6>\n");
+
+    // The AST keeps the levels written.
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, &options);
+    let levels = root
+        .children()
+        .map(|node| match node.data.borrow().value {
+            NodeValue::Heading(ref nh) => nh.level,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(levels, vec![1, 2, 4, 6]);
+}
@@ -16,13 +16,9 @@ fn description_lists() {
         concat!(
             "<dl>",
             "<dt>Term 1</dt>\n",
-            "<dd>\n",
-            "<p>Definition 1</p>\n",
-            "</dd>\n",
+            "<dd>Definition 1</dd>\n",
             "<dt>Term 2 with <em>inline markup</em></dt>\n",
-            "<dd>\n",
-            "<p>Definition 2</p>\n",
-            "</dd>\n",
+            "<dd>Definition 2</dd>\n",
             "</dl>\n",
         ),
     );
@@ -41,27 +37,275 @@ fn description_lists() {
             "<ul>\n",
             "<li>\n",
             "<p>Nested</p>\n",
+            "<dl>",
+            "<dt>Term 1</dt>\n",
+            "<dd>Definition 1</dd>\n",
+            "<dt>Term 2 with <em>inline markup</em></dt>\n",
+            "<dd>Definition 2</dd>\n",
+            "</dl>\n",
+            "</li>\n",
+            "</ul>\n",
+        ),
+    );
+}
+
+#[test]
+fn description_lists_loose() {
+    html_opts_no_roundtrip(
+        concat!(
+            "Term 1\n",
+            "\n",
+            ": Definition 1\n",
+            "\n",
+            "Term 2\n",
+            "\n",
+            ": Definition 2\n",
+            "\n",
+            "    More of definition 2.\n",
+            "\n",
+            "    - and\n",
+            "    - a list\n",
+        ),
+        concat!(
             "<dl>",
             "<dt>Term 1</dt>\n",
             "<dd>\n",
             "<p>Definition 1</p>\n",
             "</dd>\n",
-            "<dt>Term 2 with <em>inline markup</em></dt>\n",
+            "<dt>Term 2</dt>\n",
             "<dd>\n",
             "<p>Definition 2</p>\n",
+            "<p>More of definition 2.</p>\n",
+            "<ul>\n",
+            "<li>and</li>\n",
+            "<li>a list</li>\n",
+            "</ul>\n",
             "</dd>\n",
             "</dl>\n",
-            "</li>\n",
+        ),
+        |opts| opts.extension.description_lists = true,
+    );
+}
+
+#[test]
+fn description_lists_tight_multiple_blocks() {
+    html_opts_no_roundtrip(
+        concat!(
+            "Term\n",
+            "\n",
+            ": Definition\n",
+            "    - with\n",
+            "    - a list\n",
+        ),
+        concat!(
+            "<dl>",
+            "<dt>Term</dt>\n",
+            "<dd>Definition\n",
+            "<ul>\n",
+            "<li>with</li>\n",
+            "<li>a list</li>\n",
             "</ul>\n",
+            "</dd>\n",
+            "</dl>\n",
+        ),
+        |opts| opts.extension.description_lists = true,
+    );
+}
+
+#[test]
+fn description_list_loose_override() {
+    html_opts_i(
+        concat!(
+            "Term 1\n",
+            "\n",
+            ": Definition 1\n",
+            "\n",
+            "Term 2\n",
+            "\n",
+            ": Definition 2\n"
+        ),
+        concat!(
+            "<dl>",
+            "<dt>Term 1</dt>\n",
+            "<dd>\n",
+            "<p>Definition 1</p>\n",
+            "</dd>\n",
+            "<dt>Term 2</dt>\n",
+            "<dd>\n",
+            "<p>Definition 2</p>\n",
+            "</dd>\n",
+            "</dl>\n",
+        ),
+        |opts| {
+            opts.extension.description_lists = true;
+            opts.render.description_list_loose = true;
+        },
+    );
+}
+
+#[test]
+fn description_list_marker() {
+    html_opts_i(
+        concat!(
+            "Term 1\n",
+            "\n",
+            "~ Definition 1\n",
+            "\n",
+            "Term 2\n",
+            "\n",
+            ": Not a definition\n"
+        ),
+        concat!(
+            "<dl>",
+            "<dt>Term 1</dt>\n",
+            "<dd>Definition 1</dd>\n",
+            "</dl>\n",
+            "<p>Term 2</p>\n",
+            "<p>: Not a definition</p>\n",
+        ),
+        |opts| {
+            opts.extension.description_lists = true;
+            opts.extension.description_list_marker = Some('~');
+        },
+    );
+}
+
+#[test]
+fn description_list_grouped_terms() {
+    html_opts!(
+        [
+            extension.description_lists,
+            extension.description_list_grouped_terms
+        ],
+        concat!(
+            "Colour\n",
+            "Color\n",
+            "\n",
+            ": The *hue* of a thing\n",
+            "\n",
+            "Shade\n",
+            "\n",
+            ": A darker colour\n",
+            "\n",
+            ": Somewhere out of the sun\n",
+        ),
+        concat!(
+            "<dl>",
+            "<dt>Colour</dt>\n",
+            "<dt>Color</dt>\n",
+            "<dd>The <em>hue</em> of a thing</dd>\n",
+            "<dt>Shade</dt>\n",
+            "<dd>A darker colour</dd>\n",
+            "<dd>Somewhere out of the sun</dd>\n",
+            "</dl>\n",
+        ),
+    );
+}
+
+#[test]
+fn description_list_grouped_terms_ast() {
+    assert_ast_match!(
+        [
+            extension.description_lists,
+            extension.description_list_grouped_terms
+        ],
+        "ta\n"
+        "tb\n"
+        "\n"
+        ": da\n"
+        "\n"
+        ": db\n",
+        (document (1:1-6:4) [
+            (description_list (1:1-6:4) [
+                (description_item (1:1-6:4) [
+                    (description_term (1:1-1:2) [
+                        (paragraph (1:1-1:2) [
+                            (text (1:1-1:2) "ta")
+                        ])
+                    ])
+                    (description_term (2:1-2:2) [
+                        (paragraph (2:1-2:2) [
+                            (text (2:1-2:2) "tb")
+                        ])
+                    ])
+                    (description_details (4:1-4:4) [
+                        (paragraph (4:3-4:4) [
+                            (text (4:3-4:4) "da")
+                        ])
+                    ])
+                    (description_details (6:1-6:4) [
+                        (paragraph (6:3-6:4) [
+                            (text (6:3-6:4) "db")
+                        ])
+                    ])
+                ])
+            ])
+        ])
+    );
+}
+
+#[test]
+fn description_list_grouped_terms_indented_ast() {
+    assert_ast_match!(
+        [
+            extension.description_lists,
+            extension.description_list_grouped_terms
+        ],
+        "- ta\n"
+        "   tb \n"
+        "tc\n"
+        "\n"
+        "  : da\n",
+        (document (1:1-5:6) [
+            (list (1:1-5:6) [
+                (item (1:1-5:6) [
+                    (description_list (1:3-5:6) [
+                        (description_item (1:3-5:6) [
+                            (description_term (1:3-1:4) [
+                                (paragraph (1:3-1:4) [
+                                    (text (1:3-1:4) "ta")
+                                ])
+                            ])
+                            (description_term (2:4-2:5) [
+                                (paragraph (2:4-2:5) [
+                                    (text (2:4-2:5) "tb")
+                                ])
+                            ])
+                            (description_term (3:1-3:2) [
+                                (paragraph (3:1-3:2) [
+                                    (text (3:1-3:2) "tc")
+                                ])
+                            ])
+                            (description_details (5:3-5:6) [
+                                (paragraph (5:5-5:6) [
+                                    (text (5:5-5:6) "da")
+                                ])
+                            ])
+                        ])
+                    ])
+                ])
+            ])
+        ])
+    );
+}
+
+#[test]
+fn description_list_grouped_terms_disabled() {
+    html_opts!(
+        [extension.description_lists],
+        concat!("Colour\n", "Color\n", "\n", ": A hue\n", "\n", ": A tint\n"),
+        concat!(
+            "<dl>",
+            "<dt>Colour\nColor</dt>\n",
+            "<dd>A hue</dd>\n",
+            "</dl>\n",
+            "<p>: A tint</p>\n",
         ),
     );
 }
 
 #[test]
 fn sourcepos() {
-    // TODO There's plenty of work to do here still.  The test currently represents
-    // how things *are* -- see comments for what should be different.
-    // See partner comment in crate::parser::Parser::parse_desc_list_details.
     assert_ast_match!(
         [extension.description_lists],
         "ta\n"
@@ -72,25 +316,29 @@ fn sourcepos() {
         "\n"
         ": d*b*\n"
         "\n"
+        "  more\n"
+        "\n"
         "tc\n"
         "\n"
-        ": dc\n",
-        (document (1:1-11:4) [
-            (description_list (1:1-11:4) [
-                (description_item (1:1-4:0) [      // (description_item (1:1-3:4) [
-                    (description_term (3:1-3:0) [      // (description_term (1:1-1:2) [
+        ": dc\n"
+        "\n"
+        "after\n",
+        (document (1:1-15:5) [
+            (description_list (1:1-13:4) [
+                (description_item (1:1-3:4) [
+                    (description_term (1:1-1:2) [
                         (paragraph (1:1-1:2) [
                             (text (1:1-1:2) "ta")
                         ])
                     ])
-                    (description_details (3:1-4:0) [   // (description_details (3:1-3:4) [
+                    (description_details (3:1-3:4) [
                         (paragraph (3:3-3:4) [
                             (text (3:3-3:4) "da")
                         ])
                     ])
                 ])
-                (description_item (5:1-8:0) [      // (description_item (5:1-7:6) [
-                    (description_term (7:1-7:0) [      // (description_term (5:1-5:4) [
+                (description_item (5:1-9:6) [
+                    (description_term (5:1-5:4) [
                         (paragraph (5:1-5:4) [
                             (text (5:1-5:1) "t")
                             (emph (5:2-5:4) [
@@ -98,28 +346,34 @@ fn sourcepos() {
                             ])
                         ])
                     ])
-                    (description_details (7:1-8:0) [   // (description_details (7:1-7:6) [
+                    (description_details (7:1-9:6) [
                         (paragraph (7:3-7:6) [
                             (text (7:3-7:3) "d")
                             (emph (7:4-7:6) [
                                 (text (7:5-7:5) "b")
                             ])
                         ])
+                        (paragraph (9:3-9:6) [
+                            (text (9:3-9:6) "more")
+                        ])
                     ])
                 ])
-                (description_item (9:1-11:4) [
-                    (description_term (11:1-11:0) [    // (description_term (9:1-11:4) [
-                        (paragraph (9:1-9:2) [
-                            (text (9:1-9:2) "tc")
+                (description_item (11:1-13:4) [
+                    (description_term (11:1-11:2) [
+                        (paragraph (11:1-11:2) [
+                            (text (11:1-11:2) "tc")
                         ])
                     ])
-                    (description_details (11:1-11:4) [
-                        (paragraph (11:3-11:4) [
-                            (text (11:3-11:4) "dc")
+                    (description_details (13:1-13:4) [
+                        (paragraph (13:3-13:4) [
+                            (text (13:3-13:4) "dc")
                         ])
                     ])
                 ])
             ])
+            (paragraph (15:1-15:5) [
+                (text (15:1-15:5) "after")
+            ])
         ])
     );
 }
@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+
+use self::nodes::{Ast, LineColumn, NodeDetails};
+
+use super::*;
+
+#[test]
+fn details() {
+    html_opts!(
+        [extension.details],
+        concat!(
+            "::: details Why <this>?\n",
+            "Paragraph 1\n",
+            "\n",
+            "- a\n",
+            "- b\n",
+            ":::\n",
+            "\n",
+            ":::+ DETAILS\n",
+            "Shown.\n",
+            "::::\n",
+        ),
+        concat!(
+            "<details>\n",
+            "<summary>Why &lt;this&gt;?</summary>\n",
+            "<p>Paragraph 1</p>\n",
+            "<ul>\n",
+            "<li>a</li>\n",
+            "<li>b</li>\n",
+            "</ul>\n",
+            "</details>\n",
+            "<details open>\n",
+            "<p>Shown.</p>\n",
+            "</details>\n",
+        ),
+    );
+}
+
+#[test]
+fn details_nested() {
+    html_opts!(
+        [extension.details],
+        concat!(
+            "- item\n",
+            "\n",
+            "  :::: details Outer\n",
+            "  Text\n",
+            "  ::: details Inner\n",
+            "  Hidden\n",
+            "  ::::\n",
+            "  After\n",
+        ),
+        concat!(
+            "<ul>\n",
+            "<li>\n",
+            "<p>item</p>\n",
+            "<details>\n",
+            "<summary>Outer</summary>\n",
+            "<p>Text</p>\n",
+            "<details>\n",
+            "<summary>Inner</summary>\n",
+            "<p>Hidden</p>\n",
+            "</details>\n",
+            "</details>\n",
+            "<p>After</p>\n",
+            "</li>\n",
+            "</ul>\n",
+        ),
+    );
+}
+
+#[test]
+fn details_fences() {
+    html_opts!(
+        [extension.details],
+        concat!(
+            "Paragraph\n",
+            "::: details\n",
+            "Inside\n",
+            ":: not closed\n",
+            "::: closed? no\n",
+        ),
+        concat!(
+            "<p>Paragraph</p>\n",
+            "<details>\n",
+            "<p>Inside\n",
+            ":: not closed\n",
+            "::: closed? no</p>\n",
+            "</details>\n",
+        ),
+    );
+
+    html_opts!(
+        [extension.details],
+        concat!(
+            ":: details\n",
+            "\n",
+            "::: detailed\n",
+            "\n",
+            "    ::: details\n"
+        ),
+        concat!(
+            "<p>:: details</p>\n",
+            "<p>::: detailed</p>\n",
+            "<pre><code>::: details\n",
+            "</code></pre>\n",
+        ),
+    );
+}
+
+#[test]
+fn details_commonmark() {
+    let mut options = Options::default();
+    options.extension.details = true;
+
+    commonmark(
+        concat!(
+            ":::: details Outer *summary*\n",
+            "Text\n",
+            "\n",
+            ":::+ details\n",
+            "Inner\n",
+            ":::\n",
+            "::::\n",
+        ),
+        concat!(
+            ":::: details Outer *summary*\n",
+            "Text\n",
+            "\n",
+            ":::+ details\n",
+            "Inner\n",
+            "\n",
+            ":::\n",
+            "\n",
+            "::::\n",
+        ),
+        Some(&options),
+    );
+}
+
+#[test]
+fn sourcepos() {
+    assert_ast_match!(
+        [extension.details],
+        "::: details Title\n"
+        "Hidden\n"
+        ":::\n",
+        (document (1:1-3:3) [
+            (details (1:1-3:3) [
+                (paragraph (2:1-2:6) [
+                    (text (2:1-2:6) "Hidden")
+                ])
+            ])
+        ])
+    );
+}
+
+#[test]
+fn details_commonmark_lengthens_fences() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.details = true;
+    let empty = LineColumn { line: 0, column: 0 };
+
+    // Equal fences, as in a hand-built tree, would let the inner block's
+    // closing fence close the outer one.
+    let ast = |val: NodeValue| arena.alloc(AstNode::new(RefCell::new(Ast::new(val, empty))));
+    let details = |summary: &str| {
+        ast(NodeValue::Details(NodeDetails {
+            summary: summary.to_string(),
+            open: false,
+            fence_length: 3,
+            fence_offset: 0,
+        }))
+    };
+    let root = ast(NodeValue::Document);
+    let outer = details("Outer");
+    let inner = details("Inner");
+    root.append(outer);
+    outer.append(inner);
+
+    let mut md = vec![];
+    format_commonmark(root, &options, &mut md).unwrap();
+    let md = String::from_utf8(md).unwrap();
+    compare_strs(
+        &md,
+        ":::: details Outer\n::: details Inner\n:::\n\n::::\n",
+        "commonmark",
+        "",
+    );
+
+    let root = parse_document(&arena, &md, &options);
+    let outer = root.first_child().unwrap();
+    assert!(outer.next_sibling().is_none());
+    let inner = outer.first_child().unwrap();
+    assert!(
+        matches!(inner.data.borrow().value, NodeValue::Details(ref nd) if nd.summary == "Inner")
+    );
+}
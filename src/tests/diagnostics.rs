@@ -0,0 +1,226 @@
+use super::*;
+
+fn diagnostics(input: &str, opts: impl Fn(&mut Options)) -> Vec<Diagnostic> {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    opts(&mut options);
+    parse_document_with_diagnostics(&arena, input, &options).1
+}
+
+#[test]
+fn unresolved_references() {
+    let found = diagnostics(
+        concat!(
+            "A [full][nowhere], a [collapsed][] and\n",
+            "a [shortcut] link, an ![image][pic] and [fine][ok].\n",
+            "\n",
+            "[ok]: /ok\n",
+        ),
+        |_| (),
+    );
+
+    assert_eq!(
+        found,
+        vec![
+            Diagnostic {
+                sourcepos: (1, 3, 1, 17).into(),
+                kind: DiagnosticKind::UnresolvedReference(ReferenceKind::Link),
+                message: "unresolved link reference \"nowhere\"".to_string(),
+            },
+            Diagnostic {
+                sourcepos: (1, 22, 1, 34).into(),
+                kind: DiagnosticKind::UnresolvedReference(ReferenceKind::Link),
+                message: "unresolved link reference \"collapsed\"".to_string(),
+            },
+            Diagnostic {
+                sourcepos: (2, 3, 2, 12).into(),
+                kind: DiagnosticKind::UnresolvedReference(ReferenceKind::Link),
+                message: "unresolved link reference \"shortcut\"".to_string(),
+            },
+            Diagnostic {
+                sourcepos: (2, 23, 2, 35).into(),
+                kind: DiagnosticKind::UnresolvedReference(ReferenceKind::Image),
+                message: "unresolved image reference \"pic\"".to_string(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn unresolved_footnotes() {
+    let found = diagnostics(
+        concat!("Noted.[^a] Not noted.[^b]\n", "\n", "[^a]: A note.\n"),
+        |opts| opts.extension.footnotes = true,
+    );
+
+    assert_eq!(
+        found,
+        vec![Diagnostic {
+            sourcepos: (1, 22, 1, 25).into(),
+            kind: DiagnosticKind::UnresolvedReference(ReferenceKind::Footnote),
+            message: "unresolved footnote reference \"b\"".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn unclosed_code_fences() {
+    let found = diagnostics(
+        concat!(
+            "```\n",
+            "closed\n",
+            "```\n",
+            "\n",
+            "- ```\n",
+            "  in a list\n",
+            "\n",
+            "~~~\n",
+            "at the end\n",
+        ),
+        |_| (),
+    );
+
+    assert_eq!(
+        found
+            .iter()
+            .map(|d| (d.kind, d.sourcepos.start.line))
+            .collect::<Vec<_>>(),
+        vec![
+            (DiagnosticKind::UnclosedCodeFence, 5),
+            (DiagnosticKind::UnclosedCodeFence, 8),
+        ]
+    );
+}
+
+#[test]
+fn nesting_limit() {
+    let found = diagnostics(&format!("{}x\n", "1. ".repeat(120)), |_| ());
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].kind, DiagnosticKind::NestingLimit);
+    assert_eq!(found[0].sourcepos, (1, 298, 1, 298).into());
+
+    assert!(diagnostics(&format!("{}x\n", "1. ".repeat(50)), |_| ()).is_empty());
+
+    let found = diagnostics("> > > x\n", |o| o.parse.max_nesting_depth = Some(3));
+    assert_eq!(
+        found,
+        vec![Diagnostic {
+            sourcepos: (1, 5, 1, 5).into(),
+            kind: DiagnosticKind::NestingLimit,
+            message: "block quote marker ignored; block quotes are nested more than 3 deep"
+                .to_string(),
+        }]
+    );
+}
+
+#[test]
+fn duplicate_footnote_definitions() {
+    let input = concat!(
+        "Text[^a][^b].\n",
+        "\n",
+        "[^a]: First.\n",
+        "[^b]: Only.\n",
+        "[^A]: Second.\n",
+    );
+    let found = diagnostics(input, |o| o.extension.footnotes = true);
+
+    assert_eq!(
+        found,
+        vec![Diagnostic {
+            sourcepos: (3, 1, 3, 12).into(),
+            kind: DiagnosticKind::DuplicateFootnoteDefinition,
+            message: "footnote \"a\" is defined again later; this definition is ignored"
+                .to_string(),
+        }]
+    );
+}
+
+#[test]
+fn heading_too_deep() {
+    let input = concat!(
+        "####### Seven\n",
+        "> ######## Eight\n",
+        "#######\n",
+        "###### Six\n",
+        "#######nope\n",
+        "\n",
+        "```\n",
+        "####### Code\n",
+        "```\n",
+    );
+    let found = diagnostics(input, |_| ());
+
+    assert_eq!(
+        found
+            .iter()
+            .map(|d| (d.kind, d.sourcepos))
+            .collect::<Vec<_>>(),
+        vec![
+            (DiagnosticKind::HeadingTooDeep, (1, 1, 1, 7).into()),
+            (DiagnosticKind::HeadingTooDeep, (2, 3, 2, 10).into()),
+            (DiagnosticKind::HeadingTooDeep, (3, 1, 3, 7).into()),
+        ]
+    );
+    assert_eq!(
+        found[0].message,
+        "7 `#` can't start a heading; headings go at most 6 deep"
+    );
+}
+
+#[test]
+fn ast_unchanged() {
+    let input = "####### x\n\n[^a]: 1\n[^a]: 2\n\nx[^a] [y]\n\n```\n";
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+
+    let arena = Arena::new();
+    let (with, found) = parse_document_with_diagnostics(&arena, input, &options);
+    let without = parse_document(&arena, input, &options);
+    assert_eq!(found.len(), 4);
+
+    let mut a = vec![];
+    let mut b = vec![];
+    format_xml(with, &options, &mut a).unwrap();
+    format_xml(without, &options, &mut b).unwrap();
+    assert_eq!(String::from_utf8(a).unwrap(), String::from_utf8(b).unwrap());
+}
+
+#[test]
+fn smart_punctuation() {
+    let input = concat!(
+        "\"Well -- it's *done*...\" she said.\n",
+        "  Not [it's](/url) or `it's`.\n",
+    );
+    let smart = |options: &mut Options| {
+        options.parse.smart = true;
+        options.parse.smart_skip_links = true;
+    };
+
+    let found = diagnostics(input, smart);
+    let replacement = |sourcepos: (usize, usize, usize, usize), from: &str, to: &str| Diagnostic {
+        sourcepos: sourcepos.into(),
+        kind: DiagnosticKind::SmartPunctuation,
+        message: format!("smart punctuation replaced {:?} with {:?}", from, to),
+    };
+    assert_eq!(
+        found,
+        vec![
+            replacement((1, 1, 1, 1), "\"", "“"),
+            replacement((1, 7, 1, 8), "--", "–"),
+            replacement((1, 12, 1, 12), "'", "’"),
+            replacement((1, 21, 1, 23), "...", "…"),
+            replacement((1, 24, 1, 24), "\"", "”"),
+        ]
+    );
+
+    // Each position covers the original text in the source.
+    let lines = input.lines().collect::<Vec<_>>();
+    for d in &found {
+        let line = lines[d.sourcepos.start.line - 1];
+        let original = &line[d.sourcepos.start.column - 1..d.sourcepos.end.column];
+        assert!(d.message.contains(&format!("{:?}", original)));
+    }
+
+    assert!(diagnostics(input, |_| ()).is_empty());
+}
@@ -0,0 +1,301 @@
+use super::*;
+
+#[test]
+fn fenced_divs() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!(
+            "::: warning\n",
+            "Wet *paint*.\n",
+            "\n",
+            "- a\n",
+            "- b\n",
+            ":::\n",
+            "\n",
+            "::: .note :::\n",
+            "Dotted.\n",
+            ":::::\n",
+        ),
+        concat!(
+            "<div class=\"warning\">\n",
+            "<p>Wet <em>paint</em>.</p>\n",
+            "<ul>\n",
+            "<li>a</li>\n",
+            "<li>b</li>\n",
+            "</ul>\n",
+            "</div>\n",
+            "<div class=\"note\">\n",
+            "<p>Dotted.</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_attributes() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!(
+            ":::: {.a .b #top data-x=\"1 < 2\" lang=en}\n",
+            "Text\n",
+            "::::\n",
+        ),
+        concat!(
+            "<div class=\"a b\" id=\"top\" data-x=\"1 &lt; 2\">\n",
+            "<p>Text</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_attributes_unsafe() {
+    html_opts!(
+        [extension.fenced_divs, render.unsafe_],
+        concat!(
+            "::: {onmouseover=\"alert(1)\" style=x .a class=\"b c\" data-q=\"say \\\"hi\\\"\"}\n",
+            "Text\n",
+            ":::\n",
+        ),
+        concat!(
+            "<div class=\"a b c\" onmouseover=\"alert(1)\" style=\"x\" data-q=\"say &quot;hi&quot;\">\n",
+            "<p>Text</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_attributes_safe() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!(
+            "::: {onmouseover=\"alert(1)\" style=x #i DATA-a=1}\n",
+            "Text\n",
+            ":::\n",
+        ),
+        concat!("<div id=\"i\" DATA-a=\"1\">\n", "<p>Text</p>\n", "</div>\n",),
+    );
+}
+
+#[test]
+fn fenced_divs_not_opened() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!(
+            "::: two words\n",
+            ":::\n",
+            ":: short\n",
+            "::: {.a b}\n",
+            "::: {.a\n",
+            "    ::: indented\n",
+        ),
+        concat!(
+            "<p>::: two words\n",
+            ":::\n",
+            ":: short\n",
+            "::: {.a b}\n",
+            "::: {.a\n",
+            "::: indented</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_nested() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!(
+            "::: outer\n",
+            "::: inner\n",
+            "Inside\n",
+            ":::\n",
+            "Between\n",
+            ":::\n",
+            "After\n",
+        ),
+        concat!(
+            "<div class=\"outer\">\n",
+            "<div class=\"inner\">\n",
+            "<p>Inside</p>\n",
+            "</div>\n",
+            "<p>Between</p>\n",
+            "</div>\n",
+            "<p>After</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_nested_by_length() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!(
+            "::: outer\n",
+            ":::: inner\n",
+            "Inside\n",
+            ":::\n",
+            "Still inside\n",
+            "::::\n",
+            "Between\n",
+            "::::::\n",
+        ),
+        concat!(
+            "<div class=\"outer\">\n",
+            "<div class=\"inner\">\n",
+            "<p>Inside\n",
+            ":::\n",
+            "Still inside</p>\n",
+            "</div>\n",
+            "<p>Between</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_closing_fence_with_attributes() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!("::: a\n", "One\n", "::: {.b}\n", "Two\n", ":::\n", ":::\n",),
+        concat!(
+            "<div class=\"a\">\n",
+            "<p>One</p>\n",
+            "<div class=\"b\">\n",
+            "<p>Two</p>\n",
+            "</div>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_unclosed() {
+    html_opts!(
+        [extension.fenced_divs],
+        concat!("> ::: a\n", "> Quoted\n", "\n", "Outside\n"),
+        concat!(
+            "<blockquote>\n",
+            "<div class=\"a\">\n",
+            "<p>Quoted</p>\n",
+            "</div>\n",
+            "</blockquote>\n",
+            "<p>Outside</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_with_details() {
+    html_opts!(
+        [extension.fenced_divs, extension.details],
+        concat!(
+            ":::: box\n",
+            "::: details More\n",
+            "Hidden\n",
+            ":::\n",
+            "::::\n",
+        ),
+        concat!(
+            "<div class=\"box\">\n",
+            "<details>\n",
+            "<summary>More</summary>\n",
+            "<p>Hidden</p>\n",
+            "</details>\n",
+            "</div>\n",
+        ),
+    );
+
+    html_opts!(
+        [extension.fenced_divs],
+        concat!("::: details\n", "Plain div\n", ":::\n"),
+        concat!(
+            "<div class=\"details\">\n",
+            "<p>Plain div</p>\n",
+            "</div>\n",
+        ),
+    );
+}
+
+#[test]
+fn fenced_divs_commonmark() {
+    let mut options = Options::default();
+    options.extension.fenced_divs = true;
+
+    commonmark(
+        concat!(
+            "::: {.a .b #top lang=en}\n",
+            "::: c\n",
+            "Text\n",
+            ":::\n",
+            ":::\n",
+        ),
+        concat!(
+            ":::: {.a .b #top lang=\"en\"}\n",
+            "::: c\n",
+            "Text\n",
+            "\n",
+            ":::\n",
+            "\n",
+            "::::\n",
+        ),
+        Some(&options),
+    );
+}
+
+#[test]
+fn fenced_divs_commonmark_attributes() {
+    let mut options = Options::default();
+    options.extension.fenced_divs = true;
+    options.extension.details = true;
+
+    commonmark(
+        concat!(
+            "::: {a=x\"y b=\"p\\\\q\"}\n",
+            "One\n",
+            ":::\n",
+            "\n",
+            "::: {.details}\n",
+            "Two\n",
+            ":::\n",
+            "\n",
+            "::: {.a class=b}\n",
+            "Three\n",
+            ":::\n",
+        ),
+        concat!(
+            "::: {a=\"x\\\"y\" b=\"p\\\\q\"}\n",
+            "One\n",
+            "\n",
+            ":::\n",
+            "\n",
+            "::: {.details}\n",
+            "Two\n",
+            "\n",
+            ":::\n",
+            "\n",
+            "::: {.a .b}\n",
+            "Three\n",
+            "\n",
+            ":::\n",
+        ),
+        Some(&options),
+    );
+}
+
+#[test]
+fn sourcepos() {
+    assert_ast_match!(
+        [extension.fenced_divs],
+        "::: a\n"
+        "Text\n"
+        ":::\n",
+        (document (1:1-3:3) [
+            (fenced_div (1:1-3:3) [
+                (paragraph (2:1-2:4) [
+                    (text (2:1-2:4) "Text")
+                ])
+            ])
+        ])
+    );
+}
@@ -281,3 +281,206 @@ fn sourcepos() {
         ])
     );
 }
+
+#[test]
+fn footnotes_render_unreferenced() {
+    html_opts!(
+        [extension.footnotes, extension.footnotes_render_unreferenced],
+        concat!(
+            "[^orphan]: Nobody references me.\n",
+            "\n",
+            "Here is a reference.[^used]\n",
+            "\n",
+            "[^used]: Referenced.\n"
+        ),
+        concat!(
+            "<p>Here is a reference.<sup class=\"footnote-ref\"><a href=\"#fn-used\" \
+             id=\"fnref-used\" data-footnote-ref>1</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-used\">\n",
+            "<p>Referenced. <a href=\"#fnref-used\" \
+             class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-orphan\">\n",
+            "<p>Nobody references me.</p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n"
+        ),
+    );
+}
+
+#[test]
+fn footnote_single_backref_simple() {
+    html_opts_i(
+        concat!(
+            "Once.[^a] Twice.[^b][^b]\n",
+            "\n",
+            "[^a]: A.\n",
+            "[^b]: B.\n"
+        ),
+        concat!(
+            "<p>Once.<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref>1</a></sup> \
+             Twice.<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>2</a></sup>\
+             <sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b-2\" data-footnote-ref>2</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-a\">\n",
+            "<p>A. <a href=\"#fnref-a\" class=\"footnote-backref\" data-footnote-backref \
+             aria-label=\"Back to content\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-b\">\n",
+            "<p>B. <a href=\"#fnref-b\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a> \
+             <a href=\"#fnref-b-2\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"2-2\" aria-label=\"Back to reference 2-2\">↩<sup \
+             class=\"footnote-ref\">2</sup></a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n"
+        ),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.footnote_single_backref_simple = true;
+        },
+    );
+}
+
+#[test]
+fn group_footnote_refs() {
+    html_opts_i(
+        concat!("x[^a][^b] y[^a]\n", "\n", "[^a]: A.\n", "[^b]: B.\n"),
+        concat!(
+            "<p>x<sup class=\"footnote-ref\">",
+            "<a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref>1</a>, ",
+            "<a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>2</a></sup> ",
+            "y<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a-2\" data-footnote-ref>1</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-a\">\n",
+            "<p>A. <a href=\"#fnref-a\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a> \
+             <a href=\"#fnref-a-2\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"1-2\" aria-label=\"Back to reference 1-2\">↩<sup \
+             class=\"footnote-ref\">2</sup></a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-b\">\n",
+            "<p>B. <a href=\"#fnref-b\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"2\" aria-label=\"Back to reference 2\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.group_footnote_refs = true;
+        },
+    );
+}
+
+#[test]
+fn footnote_start_number() {
+    html_opts_i(
+        concat!(
+            "First.[^a] Second.[^b]\n",
+            "\n",
+            "[^a]: A.\n",
+            "[^b]: B.\n",
+            "[^c]: C.\n"
+        ),
+        concat!(
+            "<p>First.<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref>10</a></sup> \
+             Second.<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b\" data-footnote-ref>11</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol start=\"10\">\n",
+            "<li id=\"fn-a\">\n",
+            "<p>A. <a href=\"#fnref-a\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"10\" aria-label=\"Back to reference 10\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-b\">\n",
+            "<p>B. <a href=\"#fnref-b\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"11\" aria-label=\"Back to reference 11\">↩</a></p>\n",
+            "</li>\n",
+            "<li id=\"fn-c\">\n",
+            "<p>C.</p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n"
+        ),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.extension.footnotes_render_unreferenced = true;
+            opts.extension.footnote_start_number = Some(10);
+        },
+    );
+}
+
+#[test]
+fn footnote_start_number_max() {
+    let mut options = Options::default();
+    options.extension.footnotes = true;
+    options.extension.footnotes_render_unreferenced = true;
+    options.extension.footnote_start_number = Some(u32::MAX);
+
+    let html = markdown_to_html(
+        concat!(
+            "A.[^a] B.[^b]\n",
+            "\n",
+            "[^a]: A.\n",
+            "[^b]: B.\n",
+            "[^c]: C.\n"
+        ),
+        &options,
+    );
+    assert!(html.contains("<ol start=\"4294967295\">\n"));
+    assert!(html.contains(">4294967295</a></sup></p>\n"));
+}
+
+#[test]
+fn footnote_ids_and_backref_labels() {
+    html_opts_i(
+        concat!("One.[^a] Two.[^a]\n", "\n", "[^a]: A.\n"),
+        concat!(
+            "<p>One.<sup class=\"footnote-ref\"><a href=\"#doc%221-a\" id=\"ref%221-a\" data-footnote-ref>1</a></sup> \
+             Two.<sup class=\"footnote-ref\"><a href=\"#doc%221-a\" id=\"ref%221-a-2\" data-footnote-ref>1</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"doc%221-a\">\n",
+            "<p>A. <a href=\"#ref%221-a\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"1\" aria-label=\"&lt;1&gt; ↑\"><b>up</b></a> \
+             <a href=\"#ref%221-a-2\" class=\"footnote-backref\" data-footnote-backref \
+             data-footnote-backref-idx=\"1-2\" aria-label=\"&lt;1-2&gt; ↑\"><b>up</b><sup class=\"footnote-ref\">2</sup></a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n"
+        ),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.footnote_id_prefix = Some("doc\"1-".to_string());
+            opts.render.footnote_ref_id_prefix = Some("ref\"1-".to_string());
+            opts.render.footnote_backref_symbol = Some("<b>up</b>".to_string());
+            opts.render.footnote_backref_label = Some("<{}> ↑".to_string());
+        },
+    );
+
+    html_opts_i(
+        concat!("One.[^a]\n", "\n", "[^a]: A.\n"),
+        concat!(
+            "<p>One.<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref>1</a></sup></p>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-a\">\n",
+            "<p>A. <a href=\"#fnref-a\" class=\"footnote-backref\" data-footnote-backref \
+             aria-label=\"&lt;Zurück&gt;\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n"
+        ),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.render.footnote_single_backref_simple = true;
+            opts.render.footnote_backref_simple_label = Some("<Zurück>".to_string());
+        },
+    );
+}
@@ -0,0 +1,172 @@
+use super::*;
+
+#[test]
+fn headings() {
+    gemtext(
+        concat!("# One\n", "## Two\n", "### Three\n", "#### Four *emph*\n"),
+        concat!(
+            "# One\n",
+            "\n",
+            "## Two\n",
+            "\n",
+            "### Three\n",
+            "\n",
+            "### Four emph\n"
+        ),
+    );
+}
+
+#[test]
+fn links_follow_their_paragraph() {
+    gemtext(
+        concat!(
+            "Read [the guide](gemini://example.org/guide) or\n",
+            "![a map](/map.png), then <https://example.com>.\n",
+            "\n",
+            "Next.\n"
+        ),
+        concat!(
+            "Read the guide or a map, then https://example.com.\n",
+            "=> gemini://example.org/guide the guide\n",
+            "=> /map.png a map\n",
+            "=> https://example.com\n",
+            "\n",
+            "Next.\n"
+        ),
+    );
+}
+
+#[test]
+fn link_only_paragraphs() {
+    gemtext(
+        concat!("[One](/one)\n", "[Two](/two)\n"),
+        concat!("=> /one One\n", "=> /two Two\n"),
+    );
+}
+
+#[test]
+fn lists() {
+    gemtext_opts(
+        concat!(
+            "1. First [link](/a)\n",
+            "2. Second\n",
+            "   - nested\n",
+            "   - [x] done\n",
+            "\n",
+            "After.\n"
+        ),
+        concat!(
+            "* First link\n",
+            "* Second\n",
+            "* nested\n",
+            "* [x] done\n",
+            "=> /a link\n",
+            "\n",
+            "After.\n"
+        ),
+        |opts| opts.extension.tasklist = true,
+    );
+}
+
+#[test]
+fn block_quotes_and_code() {
+    gemtext(
+        concat!(
+            "> Quoted [text](/q).\n",
+            "> \n",
+            "> More.\n",
+            "\n",
+            "```rust\n",
+            "fn main() {}\n",
+            "```\n",
+            "\n",
+            "---\n",
+            "\n",
+            "<div>dropped</div>\n"
+        ),
+        concat!(
+            "> Quoted text.\n",
+            ">\n",
+            "> More.\n",
+            "=> /q text\n",
+            "\n",
+            "```rust\n",
+            "fn main() {}\n",
+            "```\n"
+        ),
+    );
+}
+
+#[test]
+fn tables_and_footnotes() {
+    gemtext_opts(
+        concat!(
+            "| a | b |\n",
+            "|---|---|\n",
+            "| 1 | [2](/two) |\n",
+            "\n",
+            "Noted.[^n]\n",
+            "\n",
+            "[^n]: The note.\n"
+        ),
+        concat!(
+            "```\n",
+            "a | b\n",
+            "1 | 2\n",
+            "```\n",
+            "=> /two 2\n",
+            "\n",
+            "Noted.[1]\n",
+            "\n",
+            "[1] The note.\n"
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.footnotes = true;
+        },
+    );
+}
+
+#[test]
+fn footnote_start_number() {
+    gemtext_opts(
+        concat!("Hi[^a]\n", "\n", "[^a]: A greeting.\n"),
+        concat!("Hi[5]\n", "\n", "[5] A greeting.\n"),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.extension.footnote_start_number = Some(5);
+        },
+    );
+}
+
+#[test]
+fn text_lines_not_taken_as_other_lines() {
+    gemtext(
+        concat!(
+            "\\# not a heading\n",
+            "\n",
+            "\\=> not a link\\\n",
+            "\\* not an item\\\n",
+            "\\> not a quote\\\n",
+            "\\`\\`\\` not a toggle\n",
+            "\n",
+            "~~~~\n",
+            "```\n",
+            "# inside\n",
+            "~~~~\n",
+        ),
+        concat!(
+            " # not a heading\n",
+            "\n",
+            " => not a link\n",
+            " * not an item\n",
+            " > not a quote\n",
+            " ``` not a toggle\n",
+            "\n",
+            "```\n",
+            " ```\n",
+            "# inside\n",
+            "```\n",
+        ),
+    );
+}
@@ -24,3 +24,218 @@ fn header_ids() {
         |opts| opts.extension.header_ids = Some("user-content-".to_owned()),
     );
 }
+
+#[test]
+fn header_id_style_on_heading() {
+    html_opts_i(
+        concat!("# Hi.\n", "## Hi.\n"),
+        concat!(
+            "<h1 id=\"user-content-hi\">Hi.</h1>\n",
+            "<h2 id=\"user-content-hi-1\">Hi.</h2>\n"
+        ),
+        |opts| {
+            opts.extension.header_ids = Some("user-content-".to_owned());
+            opts.extension.header_id_style = HeaderIdStyle::OnHeading;
+        },
+    );
+}
+
+#[test]
+fn header_id_style_anchor_after() {
+    let input = concat!("# Hi *there*\n", "## Hi there\n");
+    let render = |style: HeaderIdStyle| {
+        let mut options = Options::default();
+        options.extension.header_ids = Some("user-content-".to_owned());
+        options.extension.header_anchor_content = Some("#".to_owned());
+        options.extension.header_id_style = style;
+        markdown_to_html(input, &options)
+    };
+
+    let after = render(HeaderIdStyle::AnchorAfter);
+    assert_eq!(
+        after,
+        concat!(
+            "<h1>Hi <em>there</em><a href=\"#hi-there\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-hi-there\">#</a></h1>\n",
+            "<h2>Hi there<a href=\"#hi-there-1\" aria-hidden=\"true\" class=\"anchor\" id=\"user-content-hi-there-1\">#</a></h2>\n",
+        )
+    );
+
+    // Moving each anchor from the front of its heading to the back gives the
+    // AnchorBefore output exactly.
+    let before = render(HeaderIdStyle::AnchorBefore);
+    let moved = after
+        .lines()
+        .map(|line| {
+            let start = line.find("<a ").unwrap();
+            let end = line.find("</a>").unwrap() + 4;
+            let open = line.find('>').unwrap() + 1;
+            format!(
+                "{}{}{}{}\n",
+                &line[..open],
+                &line[start..end],
+                &line[open..start],
+                &line[end..]
+            )
+        })
+        .collect::<String>();
+    assert_eq!(moved, before);
+}
+
+#[test]
+fn header_id_style_none() {
+    html_opts_i(
+        concat!("# Hi.\n", "## Hi.\n"),
+        concat!("<h1>Hi.</h1>\n", "<h2>Hi.</h2>\n"),
+        |opts| {
+            opts.extension.header_ids = Some("user-content-".to_owned());
+            opts.extension.header_id_style = HeaderIdStyle::None;
+        },
+    );
+}
+
+#[test]
+fn header_id_style_ids_match_anchor_ids() {
+    let input = concat!("# Hi.\n", "## Hi.\n", "### Isn't it grand?\n", "# Hi.\n");
+    let ids = |style: HeaderIdStyle| {
+        let mut options = Options::default();
+        options.extension.header_ids = Some("p-".to_owned());
+        options.extension.header_id_style = style;
+        let html = markdown_to_html(input, &options);
+        html.match_indices(" id=\"")
+            .map(|(i, _)| {
+                let rest = &html[i + 5..];
+                rest[..rest.find('"').unwrap()].to_string()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let expected = vec!["p-hi", "p-hi-1", "p-isnt-it-grand", "p-hi-2"];
+    assert_eq!(ids(HeaderIdStyle::AnchorBefore), expected);
+    assert_eq!(ids(HeaderIdStyle::AnchorAfter), expected);
+    assert_eq!(ids(HeaderIdStyle::OnHeading), expected);
+}
+
+#[test]
+fn header_id_style_on_heading_sourcepos() {
+    html_opts_i(
+        "## Hi.\n",
+        "<h2 data-sourcepos=\"1:1-1:6\" id=\"hi\">Hi.</h2>\n",
+        |opts| {
+            opts.extension.header_ids = Some(String::new());
+            opts.extension.header_id_style = HeaderIdStyle::OnHeading;
+            opts.render.sourcepos = true;
+        },
+    );
+}
+
+#[test]
+fn header_anchor_content() {
+    html_opts_i(
+        "# Hi\n\n## Hi\n",
+        concat!(
+            "<h1><a href=\"#hi\" aria-hidden=\"true\" class=\"anchor\" id=\"hi\">",
+            "<svg class=\"icon\"></svg></a>Hi</h1>\n",
+            "<h2><a href=\"#hi-1\" aria-hidden=\"true\" class=\"anchor\" id=\"hi-1\">",
+            "<svg class=\"icon\"></svg></a>Hi</h2>\n",
+        ),
+        |opts| {
+            opts.extension.header_ids = Some(String::new());
+            opts.extension.header_anchor_content = Some("<svg class=\"icon\"></svg>".to_string());
+        },
+    );
+
+    html_opts_i("# Hi\n", "<h1 id=\"hi\">Hi</h1>\n", |opts| {
+        opts.extension.header_ids = Some(String::new());
+        opts.extension.header_id_style = HeaderIdStyle::OnHeading;
+        opts.extension.header_anchor_content = Some("#".to_string());
+    });
+}
+
+#[test]
+fn header_id_slugifier() {
+    html_opts_i(
+        concat!("# Hi there\n", "## Hi there\n", "### Say \"hi\"\n"),
+        concat!(
+            "<h1 id=\"user-content-HI_THERE\">Hi there</h1>\n",
+            "<h2 id=\"user-content-HI_THERE-1\">Hi there</h2>\n",
+            "<h3 id=\"user-content-SAY_&quot;HI&quot;\">Say &quot;hi&quot;</h3>\n"
+        ),
+        |opts| {
+            opts.extension.header_ids = Some("user-content-".to_owned());
+            opts.extension.header_id_style = HeaderIdStyle::OnHeading;
+            opts.extension.header_id_slugifier = Some(|text| text.to_uppercase().replace(' ', "_"));
+        },
+    );
+}
+
+#[test]
+fn table_of_contents_matches_anchors() {
+    let input = concat!(
+        "# Hi.\n",
+        "\n",
+        "Text.\n",
+        "\n",
+        "- ## Hi.\n",
+        "\n",
+        "> ### Isn't *it* grand?\n",
+        "\n",
+        "p-1\n",
+        "===\n",
+    );
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_ids = Some("h-".to_owned());
+    options.extension.header_id_style = HeaderIdStyle::OnHeading;
+    options.render.block_ids = Some(String::new());
+    options.render.heading_offset = 1;
+    let root = parse_document(&arena, input, &options);
+
+    let toc = table_of_contents(root, &options);
+    assert_eq!(
+        toc,
+        vec![
+            TocEntry {
+                level: 2,
+                text: "Hi.".to_string(),
+                id: "hi".to_string(),
+            },
+            TocEntry {
+                level: 3,
+                text: "Hi.".to_string(),
+                id: "hi-1".to_string(),
+            },
+            TocEntry {
+                level: 4,
+                text: "Isn't it grand?".to_string(),
+                id: "isnt-it-grand".to_string(),
+            },
+            TocEntry {
+                level: 2,
+                text: "p-1".to_string(),
+                id: "p-1".to_string(),
+            },
+        ]
+    );
+
+    let mut html = vec![];
+    format_html(root, &options, &mut html).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    for entry in &toc {
+        assert!(html.contains(&format!("<h{} id=\"h-{}\">", entry.level, entry.id)));
+    }
+}
+
+#[test]
+fn table_of_contents_without_header_ids() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    options.extension.header_id_slugifier = Some(|text| text.to_lowercase().replace(' ', "_"));
+    let root = parse_document(&arena, "# A B\n\n## A B\n", &options);
+
+    let ids = table_of_contents(root, &options)
+        .into_iter()
+        .map(|e| e.id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["a_b", "a_b-1"]);
+    assert_eq!(markdown_to_html("# A B\n", &options), "<h1>A B</h1>\n",);
+}
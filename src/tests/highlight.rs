@@ -0,0 +1,74 @@
+use super::*;
+
+#[test]
+fn highlight() {
+    html_opts!(
+        [extension.highlight],
+        concat!("Some ==highlighted== text.\n"),
+        concat!("<p>Some <mark>highlighted</mark> text.</p>\n"),
+    );
+}
+
+#[test]
+fn highlight_nests_with_emphasis() {
+    html_opts!(
+        [extension.highlight],
+        concat!("==*a* and **b**==\n", "\n", "*==c==* and **x ==y== z**\n",),
+        concat!(
+            "<p><mark><em>a</em> and <strong>b</strong></mark></p>\n",
+            "<p><em><mark>c</mark></em> and <strong>x <mark>y</mark> z</strong></p>\n",
+        ),
+    );
+}
+
+#[test]
+fn highlight_needs_two_equals() {
+    html_opts!(
+        [extension.highlight],
+        concat!(
+            "===three===, =one=, ====four====\n",
+            "\n",
+            "a == b == c, and ==unclosed\n",
+            "\n",
+            "====\n",
+        ),
+        concat!(
+            "<p>===three===, =one=, ====four====</p>\n",
+            "<p>a == b == c, and ==unclosed</p>\n",
+            "<p>====</p>\n",
+        ),
+    );
+}
+
+#[test]
+fn highlight_setext_heading() {
+    html_opts!(
+        [extension.highlight],
+        concat!("==Title==\n", "==\n", "\n", "Some ==text==\n", "===\n"),
+        concat!(
+            "<h1><mark>Title</mark></h1>\n",
+            "<h1>Some <mark>text</mark></h1>\n",
+        ),
+    );
+}
+
+#[test]
+fn highlight_commonmark() {
+    let mut options = Options::default();
+    options.extension.highlight = true;
+
+    commonmark(
+        "Some ==*marked*== text\n",
+        "Some ==*marked*== text\n",
+        Some(&options),
+    );
+}
+
+#[test]
+fn highlight_sourcepos() {
+    html_opts!(
+        [extension.highlight, render.sourcepos],
+        "a ==b==\n",
+        "<p data-sourcepos=\"1:1-1:7\">a <mark data-sourcepos=\"1:3-1:7\">b</mark></p>\n",
+    );
+}
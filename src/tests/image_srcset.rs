@@ -0,0 +1,94 @@
+use super::*;
+use ntest::test_case;
+
+#[test]
+fn image_srcset() {
+    html_opts!(
+        [extension.image_srcset],
+        "![a cat](cat-480.jpg 480w,cat-800.jpg   800w , cat\\(big\\).jpg 1.5x)\n",
+        concat!(
+            "<p><img src=\"cat-480.jpg\" ",
+            "srcset=\"cat-480.jpg 480w, cat-800.jpg 800w, cat(big).jpg 1.5x\" ",
+            "alt=\"a cat\" /></p>\n",
+        ),
+    );
+}
+
+#[test]
+fn image_srcset_single_candidate() {
+    html_opts!(
+        [extension.image_srcset],
+        "![](cat.jpg 2x) and ![](cat.jpg \"A cat\")\n",
+        concat!(
+            "<p><img src=\"cat.jpg\" srcset=\"cat.jpg 2x\" alt=\"\" /> and ",
+            "<img src=\"cat.jpg\" alt=\"\" title=\"A cat\" /></p>\n",
+        ),
+    );
+}
+
+#[test]
+fn image_srcset_links_unaffected() {
+    html_opts!(
+        [extension.image_srcset],
+        "[a](b.jpg 1x, c.jpg 2x)\n",
+        "<p>[a](b.jpg 1x, c.jpg 2x)</p>\n",
+    );
+}
+
+#[test]
+fn image_srcset_drops_dangerous_candidates() {
+    html_opts!(
+        [extension.image_srcset],
+        "![a](javascript:void 1x, b.jpg 2x)\n",
+        "<p><img src=\"\" srcset=\"b.jpg 2x\" alt=\"a\" /></p>\n",
+    );
+    html_opts!(
+        [extension.image_srcset],
+        "![a](b.jpg 1x, javascript:void 2x)\n",
+        "<p><img src=\"b.jpg\" srcset=\"b.jpg 1x\" alt=\"a\" /></p>\n",
+    );
+}
+
+#[test_case("![a](b.jpg 1x c.jpg 2x)")]
+#[test_case("![a](b.jpg 1x, c.jpg)")]
+#[test_case("![a](b.jpg 1q, c.jpg 2x)")]
+#[test_case("![a](b.jpg 1.x, c.jpg 2x)")]
+#[test_case("![a](b.jpg 1.5w, c.jpg 2x)")]
+#[test_case("![a](b.jpg x, c.jpg 2x)")]
+#[test_case("![a](b.jpg, 1x)")]
+#[test_case("![a](b.jpg 1x,, c.jpg 2x)")]
+#[test_case("![a](b.jpg 1x, c.jpg 2x")]
+fn image_srcset_malformed(input: &str) {
+    html_opts!(
+        [extension.image_srcset],
+        input,
+        &format!("<p>{}</p>\n", input),
+    );
+}
+
+#[test]
+fn image_srcset_disabled() {
+    html(
+        "![a](b.jpg 1x, c.jpg 2x)\n",
+        "<p>![a](b.jpg 1x, c.jpg 2x)</p>\n",
+    );
+}
+
+#[test]
+fn image_srcset_xml() {
+    xml_opts(
+        "![a](b.jpg 1x, c.jpg 2x)\n",
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <paragraph>\n",
+            "    <image destination=\"b.jpg\" title=\"\" srcset=\"b.jpg 1x, c.jpg 2x\">\n",
+            "      <text xml:space=\"preserve\">a</text>\n",
+            "    </image>\n",
+            "  </paragraph>\n",
+            "</document>\n",
+        ),
+        |opts| opts.extension.image_srcset = true,
+    );
+}
@@ -0,0 +1,61 @@
+use super::*;
+use ntest::test_case;
+
+#[test]
+fn inline_code_lang() {
+    html_opts!(
+        [extension.inline_code_lang],
+        "Use `Vec::new()`{.rust}, ``a ` b``{.c++} or `x`{.objective-c}.\n",
+        concat!(
+            "<p>Use <code class=\"language-rust\">Vec::new()</code>, ",
+            "<code class=\"language-c++\">a ` b</code> or ",
+            "<code class=\"language-objective-c\">x</code>.</p>\n",
+        ),
+    );
+}
+
+#[test_case("`x` {.rust}", "<p><code>x</code> {.rust}</p>\n")]
+#[test_case("`x`{rust}", "<p><code>x</code>{rust}</p>\n")]
+#[test_case("`x`{.}", "<p><code>x</code>{.}</p>\n")]
+#[test_case("`x`{.ru st}", "<p><code>x</code>{.ru st}</p>\n")]
+#[test_case("`x`{.rust", "<p><code>x</code>{.rust</p>\n")]
+#[test_case("`x{.rust}", "<p>`x{.rust}</p>\n")]
+fn inline_code_lang_not_annotations(markdown: &str, html: &str) {
+    html_opts!([extension.inline_code_lang], markdown, html);
+}
+
+#[test]
+fn inline_code_lang_disabled() {
+    html(
+        "`fn main()`{.rust}\n",
+        "<p><code>fn main()</code>{.rust}</p>\n",
+    );
+}
+
+#[test]
+fn inline_code_lang_commonmark() {
+    let mut options = Options::default();
+    options.extension.inline_code_lang = true;
+    commonmark(
+        "`a`{.rust} and `b`\n",
+        "`a`{.rust} and `b`\n",
+        Some(&options),
+    );
+}
+
+#[test]
+fn inline_code_lang_xml() {
+    xml_opts(
+        "`a`{.rust}\n",
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n",
+            "<document xmlns=\"http://commonmark.org/xml/1.0\">\n",
+            "  <paragraph>\n",
+            "    <code lang=\"rust\" xml:space=\"preserve\">a</code>\n",
+            "  </paragraph>\n",
+            "</document>\n",
+        ),
+        |opts| opts.extension.inline_code_lang = true,
+    );
+}
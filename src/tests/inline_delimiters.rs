@@ -0,0 +1,105 @@
+use super::*;
+
+// Highlights are written back to CommonMark as `==`, which must parse
+// again for the roundtrip.
+fn marks(options: &mut Options) {
+    options.extension.highlight = true;
+    options.parse.inline_delimiters = vec![InlineDelimiter {
+        delim_char: b'+',
+        min_length: 2,
+        max_length: Some(2),
+        same_length: true,
+        builder: |_| NodeValue::Highlight,
+    }];
+}
+
+#[test]
+fn inline_delimiters() {
+    html_opts_i(
+        "++one++ and ++*two*++ a++b++c ++++four++++ + ++d+ +++e+++\n",
+        concat!(
+            "<p><mark>one</mark> and <mark><em>two</em></mark> a<mark>b</mark>c ",
+            "++++four++++ + ++d+ +++e+++</p>\n",
+        ),
+        marks,
+    );
+}
+
+#[test]
+fn inline_delimiters_nest_with_emphasis() {
+    html_opts_i(
+        "**++a++** ++**b**++ ++*c++*\n",
+        concat!(
+            "<p><strong><mark>a</mark></strong> <mark><strong>b</strong></mark> ",
+            "<mark>*c</mark>*</p>\n",
+        ),
+        marks,
+    );
+}
+
+#[test]
+fn inline_delimiters_by_run_length() {
+    html_opts_i(
+        "@a@ @@b@@ @@@c@@@ @@d@\n",
+        concat!(
+            "<p><em>a</em> <strong>b</strong> <em><strong>c</strong></em> ",
+            "@<em>d</em></p>\n",
+        ),
+        |opts| {
+            opts.parse.inline_delimiters = vec![InlineDelimiter {
+                delim_char: b'@',
+                min_length: 1,
+                max_length: None,
+                same_length: false,
+                builder: |used| {
+                    if used == 2 {
+                        NodeValue::Strong
+                    } else {
+                        NodeValue::Emph
+                    }
+                },
+            }];
+        },
+    );
+}
+
+#[test]
+fn inline_delimiters_reserved_characters() {
+    let delimiter = |delim_char| InlineDelimiter {
+        delim_char,
+        min_length: 1,
+        max_length: None,
+        same_length: false,
+        builder: |_| NodeValue::Highlight,
+    };
+
+    html_opts_i(
+        "*a* ~b~ [c] xdx\n",
+        "<p><em>a</em> <del>b</del> [c] xdx</p>\n",
+        |opts| {
+            opts.extension.strikethrough = true;
+            opts.parse.inline_delimiters = vec![
+                delimiter(b'*'),
+                delimiter(b'~'),
+                delimiter(b'['),
+                delimiter(b'x'),
+            ];
+        },
+    );
+}
+
+#[test]
+fn sourcepos() {
+    assert_ast_match_i(
+        "a ++b++\n",
+        ast!((document (1:1-1:7) [
+            (paragraph (1:1-1:7) [
+                (text (1:1-1:2) "a ")
+                (highlight (1:3-1:7) [
+                    (text (1:5-1:5) "b")
+                ])
+            ])
+        ])),
+        marks,
+    );
+}
@@ -0,0 +1,130 @@
+use crate::nodes::NodeLink;
+
+use super::*;
+
+fn issue_number(s: &str) -> Option<usize> {
+    let len = s[1..].bytes().take_while(u8::is_ascii_digit).count();
+    if len > 0 {
+        Some(1 + len)
+    } else {
+        None
+    }
+}
+
+fn issue_link(token: &str) -> Option<NodeValue> {
+    Some(NodeValue::Link(NodeLink {
+        url: format!("/issues/{}", &token[1..]),
+        title: String::new(),
+        autolink: None,
+        srcset: vec![],
+        attrs: vec![],
+    }))
+}
+
+fn emoji_name(s: &str) -> Option<usize> {
+    let len = s[1..].bytes().take_while(u8::is_ascii_lowercase).count();
+    if s[1 + len..].starts_with(':') {
+        Some(len + 2)
+    } else {
+        None
+    }
+}
+
+fn emoji(token: &str) -> Option<NodeValue> {
+    match token {
+        ":wave:" => Some(NodeValue::Text("👋".to_string())),
+        _ => None,
+    }
+}
+
+fn ping(s: &str) -> Option<usize> {
+    if s.starts_with("!ping") {
+        Some(5)
+    } else {
+        None
+    }
+}
+
+fn pong(_: &str) -> Option<NodeValue> {
+    Some(NodeValue::HtmlInline("<b>pong</b>".to_string()))
+}
+
+fn issues(options: &mut Options) {
+    options.parse.inline_tokens = vec![InlineToken {
+        prefix: b'#',
+        matcher: issue_number,
+        builder: issue_link,
+    }];
+}
+
+#[test]
+fn inline_tokens() {
+    html_opts_i(
+        "#1 and *#22* ends #333\n",
+        concat!(
+            "<p><a href=\"/issues/1\">#1</a> and <em><a href=\"/issues/22\">#22</a></em> ",
+            "ends <a href=\"/issues/333\">#333</a></p>\n",
+        ),
+        issues,
+    );
+}
+
+#[test]
+fn inline_tokens_unmatched() {
+    html_opts_i(
+        concat!(
+            "# heading #1\n",
+            "C#1, #x, `#1`, [#1](/x), <a title=\"#1\">, # and #\n",
+        ),
+        concat!(
+            "<h1>heading <a href=\"/issues/1\">#1</a></h1>\n",
+            "<p>C#1, #x, <code>#1</code>, <a href=\"/x\">#1</a>, <a title=\"#1\">, # and #</p>\n",
+        ),
+        |opts| {
+            issues(opts);
+            opts.render.unsafe_ = true;
+        },
+    );
+}
+
+#[test]
+fn inline_tokens_in_order() {
+    html_opts_i(
+        ":wave: :nope: !ping ![img](/i.png) !pingpong\n",
+        concat!(
+            "<p>👋 :nope: <b>pong</b> <img src=\"/i.png\" alt=\"img\" /> ",
+            "<b>pong</b>pong</p>\n",
+        ),
+        |opts| {
+            opts.render.unsafe_ = true;
+            opts.parse.inline_tokens = vec![
+                InlineToken {
+                    prefix: b':',
+                    matcher: emoji_name,
+                    builder: emoji,
+                },
+                InlineToken {
+                    prefix: b'!',
+                    matcher: ping,
+                    builder: pong,
+                },
+            ];
+        },
+    );
+}
+
+#[test]
+fn sourcepos() {
+    let arena = Arena::new();
+    let mut options = Options::default();
+    issues(&mut options);
+    let root = parse_document(&arena, "See #12.\n", &options);
+
+    let link = root.first_child().unwrap().children().nth(1).unwrap();
+    assert!(matches!(link.data.borrow().value, NodeValue::Link(..)));
+    assert_eq!(link.data.borrow().sourcepos, (1, 5, 1, 7).into());
+    assert_eq!(
+        link.first_child().unwrap().data.borrow().sourcepos,
+        (1, 5, 1, 7).into()
+    );
+}
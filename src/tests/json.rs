@@ -0,0 +1,118 @@
+#![cfg(feature = "json")]
+
+use super::*;
+
+#[track_caller]
+fn roundtrip(input: &str, options: &Options) {
+    let arena = Arena::new();
+    let root = parse_document(&arena, input, options);
+    let json = to_json(root);
+
+    let restored = from_json(&arena, &json).unwrap();
+    assert_eq!(JsonNode::from_ast(restored), JsonNode::from_ast(root));
+    assert_eq!(to_json(restored), json);
+
+    let mut expected = vec![];
+    format_html(root, options, &mut expected).unwrap();
+    let mut actual = vec![];
+    format_html(restored, options, &mut actual).unwrap();
+    compare_strs(
+        &String::from_utf8(actual).unwrap(),
+        &String::from_utf8(expected).unwrap(),
+        "json",
+        input,
+    );
+}
+
+#[test]
+fn roundtrip_preserves_the_tree() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.tasklist = true;
+    options.extension.footnotes = true;
+    options.extension.strikethrough = true;
+    options.extension.description_lists = true;
+    options.extension.math_dollars = true;
+    options.extension.alerts = true;
+    options.render.sourcepos = true;
+
+    roundtrip(
+        concat!(
+            "# Title *emph*\n",
+            "\n",
+            "``` rust title=\"x\"\n",
+            "fn main() {}\n",
+            "```\n",
+            "\n",
+            "3) three\n",
+            "4) [x] four\n",
+            "\n",
+            "- loose\n",
+            "\n",
+            "- list\n",
+            "\n",
+            "| a | b |\n",
+            "|:--|--:|\n",
+            "| [l](/u \"t\") | $x$ |\n",
+            "\n",
+            "Term\n",
+            "\n",
+            ": Details.[^1]\n",
+            "\n",
+            "> [!warning] Careful\n",
+            "> ~~Now~~.\n",
+            "\n",
+            "[^1]: Note <b>html</b>.\n"
+        ),
+        &options,
+    );
+}
+
+#[test]
+fn json_shape() {
+    let arena = Arena::new();
+    let root = parse_document(&arena, "*a*\n", &Options::default());
+    assert_eq!(
+        to_json(root),
+        concat!(
+            r#"[{"value":"Document","sourcepos":{"start":{"line":1,"column":1},"end":{"line":1,"column":3}}},"#,
+            r#"{"parent":0,"value":"Paragraph","sourcepos":{"start":{"line":1,"column":1},"end":{"line":1,"column":3}}},"#,
+            r#"{"parent":1,"value":"Emph","sourcepos":{"start":{"line":1,"column":1},"end":{"line":1,"column":3}}},"#,
+            r#"{"parent":2,"value":{"Text":"a"},"sourcepos":{"start":{"line":1,"column":2},"end":{"line":1,"column":2}}}]"#
+        ),
+    );
+}
+
+#[test]
+fn deep_nesting() {
+    roundtrip(&">".repeat(500), &Options::default());
+}
+
+#[test]
+fn invalid_json() {
+    let arena = Arena::new();
+    assert!(matches!(
+        from_json(&arena, "[{\"value\":\"Nonsense\"}]"),
+        Err(JsonError::Syntax(..))
+    ));
+    assert!(matches!(from_json(&arena, "["), Err(JsonError::Syntax(..))));
+
+    let sourcepos = r#""sourcepos":{"start":{"line":1,"column":1},"end":{"line":1,"column":1}}"#;
+    for (json, ix) in [
+        "[]".to_string(),
+        format!(
+            r#"[{{"value":"Document",{}}},{{"value":"Paragraph",{}}}]"#,
+            sourcepos, sourcepos
+        ),
+        format!(
+            r#"[{{"value":"Document",{}}},{{"parent":1,"value":"Paragraph",{}}}]"#,
+            sourcepos, sourcepos
+        ),
+        format!(r#"[{{"parent":0,"value":"Document",{}}}]"#, sourcepos),
+    ]
+    .iter()
+    .zip([0, 1, 1, 0])
+    {
+        assert_eq!(from_json(&arena, json).err(), Some(JsonError::Tree(ix)));
+    }
+}
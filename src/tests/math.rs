@@ -130,6 +130,29 @@ fn math_unrecognized_syntax_non_roundtrip(markdown: &str, html: &str) {
     );
 }
 
+#[test_case("$x$", "<p><span data-math-style=\"inline\">\\(x\\)</span></p>\n")]
+#[test_case("$$x$$", "<p><span data-math-style=\"display\">\\[x\\]</span></p>\n")]
+#[test_case("$`x`$", "<p><span data-math-style=\"inline\">\\(x\\)</span></p>\n")]
+#[test_case(
+    "$a < b$",
+    "<p><span data-math-style=\"inline\">\\(a &lt; b\\)</span></p>\n"
+)]
+#[test_case(
+    "```math\nx^2\n```\n",
+    "<span data-math-style=\"display\">\\[x^2\n\\]</span>\n"
+)]
+fn math_mathjax_delimiters(markdown: &str, html: &str) {
+    html_opts!(
+        [
+            extension.math_dollars,
+            extension.math_code,
+            render.math_mathjax_delimiters
+        ],
+        markdown,
+        html
+    );
+}
+
 #[test]
 fn sourcepos() {
     assert_ast_match!(
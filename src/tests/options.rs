@@ -62,3 +62,886 @@ fn smart_chars() {
         "<p>Hm. Hm.. hm… yes- indeed– quite—!</p>\n",
     );
 }
+
+#[test]
+fn collapse_soft_breaks() {
+    html_opts!(
+        [render.collapse_soft_breaks],
+        "one\ntwo\nthree `a\nb`\n\n```\nx\ny\n```\n",
+        "<p>one two three <code>a b</code></p>\n<pre><code>x\ny\n</code></pre>\n",
+    );
+}
+
+#[test]
+fn insert_wbr_in_long_words() {
+    html_opts_no_roundtrip(
+        "aaaaaa a&b&c&d ééééé [link](http://x.y/aaaaaaaaaa) `codecode`\n",
+        concat!(
+            "<p>aaa<wbr>aaa a&amp;b<wbr>&amp;c&amp;<wbr>d ééé<wbr>éé ",
+            "<a href=\"http://x.y/aaaaaaaaaa\">lin<wbr>k</a> <code>codecode</code></p>\n"
+        ),
+        |opts| opts.render.insert_wbr_in_long_words = Some(3),
+    );
+}
+
+#[test]
+fn no_reference_definitions() {
+    html_opts!(
+        [parse.no_reference_definitions],
+        concat!(
+            "[x]: y\n",
+            "\n",
+            "[x] [inline](/url) <https://auto.link>\n",
+        ),
+        concat!(
+            "<p>[x]: y</p>\n",
+            "<p>[x] <a href=\"/url\">inline</a> <a href=\"https://auto.link\">https://auto.link</a></p>\n"
+        ),
+    );
+}
+
+#[test]
+fn code_block_title_from_info() {
+    html_opts!(
+        [render.full_info_string, render.code_block_title_from_info],
+        concat!(
+            "``` rust title=\"my file.rs\"\n",
+            "fn main();\n",
+            "```\n",
+            "\n",
+            "``` rust  title='a \"quoted\" <name>'  \n",
+            "x\n",
+            "```\n",
+            "\n",
+            "``` title=bare\n",
+            "y\n",
+            "```\n",
+            "\n",
+        ),
+        concat!(
+            "<figure class=\"code-block\">\n",
+            "<figcaption>my file.rs</figcaption>\n",
+            "<pre><code class=\"language-rust\">fn main();\n</code></pre>\n",
+            "</figure>\n",
+            "<figure class=\"code-block\">\n",
+            "<figcaption>a &quot;quoted&quot; &lt;name&gt;</figcaption>\n",
+            "<pre><code class=\"language-rust\">x\n</code></pre>\n",
+            "</figure>\n",
+            "<pre><code class=\"language-title=bare\">y\n</code></pre>\n",
+        ),
+    );
+}
+
+#[test]
+fn code_block_title_from_info_keeps_meta() {
+    html_opts!(
+        [render.full_info_string, render.code_block_title_from_info],
+        concat!(
+            "``` rust hl=1 title=\"a b\" wrap\n",
+            "x\n",
+            "```\n",
+            "\n",
+            "``` rust subtitle=x\n",
+            "y\n",
+            "```\n",
+        ),
+        concat!(
+            "<figure class=\"code-block\">\n",
+            "<figcaption>a b</figcaption>\n",
+            "<pre><code class=\"language-rust\" data-meta=\"hl=1 wrap\">x\n</code></pre>\n",
+            "</figure>\n",
+            "<pre><code class=\"language-rust\" data-meta=\"subtitle=x\">y\n</code></pre>\n",
+        ),
+    );
+}
+
+#[test]
+fn code_block_title_from_info_quoted_meta() {
+    html_opts!(
+        [render.full_info_string, render.code_block_title_from_info],
+        concat!(
+            "``` rust alt=\"see title=x\" title='a b' hl=\"1 2\"\n",
+            "x\n",
+            "```\n",
+        ),
+        concat!(
+            "<figure class=\"code-block\">\n",
+            "<figcaption>a b</figcaption>\n",
+            "<pre><code class=\"language-rust\" ",
+            "data-meta=\"alt=&quot;see title=x&quot; hl=&quot;1 2&quot;\">x\n</code></pre>\n",
+            "</figure>\n",
+        ),
+    );
+}
+
+#[test]
+fn raw_info_string_class() {
+    html_opts_no_roundtrip(
+        concat!(
+            "```  f&#35;  extra\n",
+            "a\n",
+            "```\n",
+            "\n",
+            "```objective\\-c\n",
+            "b\n",
+            "```\n",
+            "\n",
+            "```\n",
+            "c\n",
+            "```\n",
+        ),
+        concat!(
+            "<pre><code class=\"language-f&amp;#35;\">a\n</code></pre>\n",
+            "<pre><code class=\"language-objective\\-c\">b\n</code></pre>\n",
+            "<pre><code class=\"language-text\">c\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.parse.default_info_string = Some("text".to_string());
+            opts.render.raw_info_string_class = true;
+        },
+    );
+}
+
+#[test]
+fn sorted_attributes() {
+    html_opts_no_roundtrip(
+        concat!(
+            "# Hi\n",
+            "\n",
+            "3. [a](/u \"t\") ![b](/i \"t\")[^1] [[w]]\n",
+            "\n",
+            "| x |\n",
+            "|--:|\n",
+            "| \\* |\n",
+            "\n",
+            "- [x] done\n",
+            "\n",
+            "``` rust meta\n",
+            "```\n",
+            "\n",
+            "[^1]: Note.\n",
+        ),
+        concat!(
+            "<h1><a aria-hidden=\"true\" class=\"anchor\" href=\"#hi\" id=\"hi\"></a>Hi</h1>\n",
+            "<ol start=\"3\">\n",
+            "<li><a href=\"/u\" title=\"t\">a</a> <img alt=\"b\" src=\"/i\" title=\"t\" />",
+            "<sup class=\"footnote-ref\"><a data-footnote-ref href=\"#fn-1\" id=\"fnref-1\">1</a></sup> ",
+            "<a data-wikilink=\"true\" href=\"w\">w</a></li>\n",
+            "</ol>\n",
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th align=\"right\">x</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td align=\"right\"><span data-escaped-char>*</span></td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+            "<ul>\n",
+            "<li><input checked=\"\" disabled=\"\" type=\"checkbox\" /> done</li>\n",
+            "</ul>\n",
+            "<pre><code class=\"language-rust\" data-meta=\"meta\"></code></pre>\n",
+            "<section class=\"footnotes\" data-footnotes>\n",
+            "<ol>\n",
+            "<li id=\"fn-1\">\n",
+            "<p>Note. <a aria-label=\"Back to reference 1\" class=\"footnote-backref\" ",
+            "data-footnote-backref data-footnote-backref-idx=\"1\" href=\"#fnref-1\">↩</a></p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "</section>\n",
+        ),
+        |opts| {
+            opts.extension.header_ids = Some("".to_string());
+            opts.extension.footnotes = true;
+            opts.extension.table = true;
+            opts.extension.tasklist = true;
+            opts.extension.wikilinks_title_after_pipe = true;
+            opts.render.escaped_char_spans = true;
+            opts.render.full_info_string = true;
+            opts.render.sorted_attributes = true;
+        },
+    );
+}
+
+#[test]
+fn sorted_attributes_sourcepos() {
+    html_opts_i(
+        "![b](/i \"t\") `c`\n",
+        concat!(
+            "<p data-sourcepos=\"1:1-1:16\"><img alt=\"b\" data-sourcepos=\"1:1-1:12\" src=\"/i\" title=\"t\" /> ",
+            "<code data-sourcepos=\"1:15-1:15\">c</code></p>\n"
+        ),
+        |opts| {
+            opts.render.sourcepos = true;
+            opts.render.sorted_attributes = true;
+        },
+    );
+}
+
+#[test]
+fn aria_roles() {
+    html_opts_no_roundtrip(
+        concat!(
+            "| a | b |\n",
+            "|---|--:|\n",
+            "| c | d |\n",
+            "\n",
+            "2. x\n",
+            "\n",
+            "- [ ] y\n",
+        ),
+        concat!(
+            "<table data-sourcepos=\"1:1-3:9\" role=\"table\">\n",
+            "<thead role=\"rowgroup\">\n",
+            "<tr data-sourcepos=\"1:1-1:9\" role=\"row\">\n",
+            "<th data-sourcepos=\"1:2-1:4\" role=\"columnheader\">a</th>\n",
+            "<th data-sourcepos=\"1:6-1:8\" role=\"columnheader\" align=\"right\">b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody role=\"rowgroup\">\n",
+            "<tr data-sourcepos=\"3:1-3:9\" role=\"row\">\n",
+            "<td data-sourcepos=\"3:2-3:4\" role=\"cell\">c</td>\n",
+            "<td data-sourcepos=\"3:6-3:8\" role=\"cell\" align=\"right\">d</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+            "<ol data-sourcepos=\"5:1-6:0\" role=\"list\" start=\"2\">\n",
+            "<li data-sourcepos=\"5:1-6:0\" role=\"listitem\">x</li>\n",
+            "</ol>\n",
+            "<ul data-sourcepos=\"7:1-7:7\" role=\"list\">\n",
+            "<li data-sourcepos=\"7:1-7:7\" role=\"listitem\"><input type=\"checkbox\" disabled=\"\" /> y</li>\n",
+            "</ul>\n",
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.tasklist = true;
+            opts.render.sourcepos = true;
+            opts.render.aria_roles = true;
+        },
+    );
+}
+
+#[test]
+fn ascii_only() {
+    html_opts_i(
+        concat!(
+            "# Résumé 🎉\n",
+            "\n",
+            "[naïve](/ü \"título\") ![😀](/x.png) &amp; &eacute;\n",
+            "\n",
+            "```\n",
+            "ß → ✓\n",
+            "```\n",
+        ),
+        concat!(
+            "<h1>R&#xE9;sum&#xE9; &#x1F389;</h1>\n",
+            "<p><a href=\"/%C3%BC\" title=\"t&#xED;tulo\">na&#xEF;ve</a> ",
+            "<img src=\"/x.png\" alt=\"&#x1F600;\" /> &amp; &#xE9;</p>\n",
+            "<pre><code>&#xDF; &#x2192; &#x2713;\n",
+            "</code></pre>\n",
+        ),
+        |opts| opts.render.ascii_only = true,
+    );
+}
+
+#[test]
+fn data_line_start() {
+    html_opts_no_roundtrip(
+        concat!(
+            "- a\n",
+            "\n",
+            "  > b\n",
+            "  > ```\n",
+            "  > c\n",
+            "  > ```\n",
+            "- *d*\n",
+            "\n",
+            "---\n",
+        ),
+        concat!(
+            "<ul data-line=\"1\">\n",
+            "<li data-line=\"1\">\n",
+            "<p data-line=\"1\">a</p>\n",
+            "<blockquote data-line=\"3\">\n",
+            "<p data-line=\"3\">b</p>\n",
+            "<pre data-line=\"4\"><code>c\n",
+            "</code></pre>\n",
+            "</blockquote>\n",
+            "</li>\n",
+            "<li data-line=\"7\">\n",
+            "<p data-line=\"7\"><em>d</em></p>\n",
+            "</li>\n",
+            "</ul>\n",
+            "<hr data-line=\"9\" />\n",
+        ),
+        |opts| opts.render.data_line_start = true,
+    );
+}
+
+#[test]
+fn preserve_ordered_numbers() {
+    html_opts_no_roundtrip(
+        concat!("2. a\n", "5. b\n", "9. c\n", "10. d\n", "\n", "- e\n", "- f\n"),
+        concat!(
+            "<ol start=\"2\">\n",
+            "<li>a</li>\n",
+            "<li value=\"5\">b</li>\n",
+            "<li value=\"9\">c</li>\n",
+            "<li>d</li>\n",
+            "</ol>\n",
+            "<ul>\n",
+            "<li>e</li>\n",
+            "<li>f</li>\n",
+            "</ul>\n",
+        ),
+        |opts| opts.render.preserve_ordered_numbers = true,
+    );
+}
+
+#[test]
+fn preserve_ordered_numbers_descending() {
+    html_opts_no_roundtrip(
+        concat!("5. a\n", "4. b\n", "9. c\n", "8. d\n", "\n", "1) e\n", "1) f\n"),
+        concat!(
+            "<ol reversed start=\"5\">\n",
+            "<li>a</li>\n",
+            "<li>b</li>\n",
+            "<li value=\"9\">c</li>\n",
+            "<li>d</li>\n",
+            "</ol>\n",
+            "<ol>\n",
+            "<li>e</li>\n",
+            "<li value=\"1\">f</li>\n",
+            "</ol>\n",
+        ),
+        |opts| opts.render.preserve_ordered_numbers = true,
+    );
+}
+
+#[test]
+fn preserve_ordered_numbers_task_items() {
+    html_opts_no_roundtrip(
+        concat!("1. a\n", "4. [x] b\n", "5. c\n", "7. d\n"),
+        concat!(
+            "<ol>\n",
+            "<li>a</li>\n",
+            "<li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> b</li>\n",
+            "<li value=\"5\">c</li>\n",
+            "<li value=\"7\">d</li>\n",
+            "</ol>\n",
+        ),
+        |opts| {
+            opts.extension.tasklist = true;
+            opts.render.preserve_ordered_numbers = true;
+        },
+    );
+}
+
+#[test]
+fn trim_code_trailing_whitespace() {
+    html_opts_no_roundtrip(
+        concat!(
+            "```rust\n",
+            "fn main() {   \n",
+            "\tlet x = 1;\t \n",
+            "  \n",
+            "}\n",
+            "```\n",
+            "\n",
+            "    indented  \n",
+            "    code\n",
+        ),
+        concat!(
+            "<pre><code class=\"language-rust\">fn main() {\n",
+            "\tlet x = 1;\n",
+            "\n",
+            "}\n",
+            "</code></pre>\n",
+            "<pre><code>indented\n",
+            "code\n",
+            "</code></pre>\n",
+        ),
+        |opts| opts.render.trim_code_trailing_whitespace = true,
+    );
+}
+
+#[test]
+fn thematic_break_class() {
+    html_opts_i(
+        concat!("a\n", "\n", "---\n", "\n", "> ___\n"),
+        concat!(
+            "<p>a</p>\n",
+            "<hr class=\"a &quot;b&quot;\" />\n",
+            "<blockquote>\n",
+            "<hr class=\"a &quot;b&quot;\" />\n",
+            "</blockquote>\n",
+        ),
+        |opts| opts.render.thematic_break_class = Some("a \"b\"".to_string()),
+    );
+}
+
+#[test]
+fn default_info_string_fn() {
+    // Take the language from a heading directly before the code block.
+    fn from_heading<'a>(node: &'a AstNode<'a>) -> Option<String> {
+        let prev = node.previous_sibling()?;
+        let is_heading = matches!(prev.data.borrow().value, NodeValue::Heading(..));
+        if is_heading {
+            Some(prev.text_content().to_lowercase())
+        } else {
+            None
+        }
+    }
+
+    html_opts_no_roundtrip(
+        concat!(
+            "# Python\n",
+            "\n",
+            "```\n",
+            "print(1)\n",
+            "```\n",
+            "\n",
+            "```\n",
+            "x\n",
+            "```\n",
+            "\n",
+            "# Shell\n",
+            "\n",
+            "``` bash\n",
+            "ls\n",
+            "```\n",
+        ),
+        concat!(
+            "<h1>Python</h1>\n",
+            "<pre><code class=\"language-python\">print(1)\n</code></pre>\n",
+            "<pre><code class=\"language-text\">x\n</code></pre>\n",
+            "<h1>Shell</h1>\n",
+            "<pre><code class=\"language-bash\">ls\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.parse.default_info_string = Some("text".to_string());
+            opts.parse.default_info_string_fn = Some(from_heading);
+        },
+    );
+}
+
+#[test]
+fn strip_bom() {
+    let pieces = ["\u{feff}# A\n", "\u{feff}```\n\u{feff}b\n", "\u{feff}```\n"];
+    let mut options = Options::default();
+    options.parse.strip_bom = true;
+
+    html_opts_no_roundtrip(
+        &pieces.concat(),
+        concat!(
+            "<h1>A</h1>\n",
+            "<p>\u{feff}<code> \u{feff}b \u{feff}</code></p>\n",
+        ),
+        |opts| opts.parse.strip_bom = true,
+    );
+
+    let arena = Arena::new();
+    let mut parser = StreamingParser::new(&arena, &options);
+    for piece in pieces {
+        parser.push(piece);
+    }
+    let mut output = vec![];
+    format_html(parser.finish(), &options, &mut output).unwrap();
+    compare_strs(
+        &String::from_utf8(output).unwrap(),
+        concat!("<h1>A</h1>\n", "<pre><code>\u{feff}b\n", "</code></pre>\n",),
+        "streaming",
+        &pieces.concat(),
+    );
+}
+
+#[test]
+fn quote_elements() {
+    html_opts_i(
+        concat!(
+            "\"Hello *\"world\"*,\" she said.\n",
+            "\n",
+            "It's 5\" tall, \"or so.\n",
+        ),
+        concat!(
+            "<p><q>Hello <em><q>world</q></em>,</q> she said.</p>\n",
+            "<p>It’s 5” tall, “or so.</p>\n",
+        ),
+        |opts| {
+            opts.parse.smart = true;
+            opts.parse.quote_elements = true;
+        },
+    );
+}
+
+#[test]
+fn quote_elements_requires_smart() {
+    html_opts_i("\"Hello\"\n", "<p>&quot;Hello&quot;</p>\n", |opts| {
+        opts.parse.quote_elements = true
+    });
+}
+
+#[test]
+fn quote_elements_emphasis_across_quotes() {
+    html_opts_i("*a \"b* c\"\n", "<p><em>a “b</em> c”</p>\n", |opts| {
+        opts.parse.smart = true;
+        opts.parse.quote_elements = true;
+    });
+}
+
+#[test]
+fn list_item_trailing_hard_break() {
+    html_opts_i(
+        concat!(
+            "- one\\\n",
+            "- two  \n",
+            "- three\\\\\n",
+            "- [ ] four\\\n",
+            "\n",
+            "seven\\\n",
+        ),
+        concat!(
+            "<ul>\n",
+            "<li>one<br />\n",
+            "</li>\n",
+            "<li>two<br />\n",
+            "</li>\n",
+            "<li>three\\</li>\n",
+            "<li><input type=\"checkbox\" disabled=\"\" /> four<br />\n",
+            "</li>\n",
+            "</ul>\n",
+            "<p>seven\\</p>\n",
+        ),
+        |opts| {
+            opts.extension.tasklist = true;
+            opts.parse.list_item_trailing_hard_break = true;
+        },
+    );
+
+    // Only the item's last paragraph keeps its break.
+    html_opts_i(
+        concat!("1. five\\\n", "\n", "   six\\\n"),
+        concat!(
+            "<ol>\n",
+            "<li>\n",
+            "<p>five\\</p>\n",
+            "<p>six<br />\n",
+            "</p>\n",
+            "</li>\n",
+            "</ol>\n",
+        ),
+        |opts| opts.parse.list_item_trailing_hard_break = true,
+    );
+}
+
+#[test]
+fn smart_skip_links() {
+    html_opts_i(
+        concat!(
+            "[\"It's\" -- a [![l'image...](i.png)](u)](v) and 'this'...\n",
+            "\n",
+            "[don't][ref], [don't] -- [\"not a link\"]\n",
+            "\n",
+            "[ref]: /r\n",
+            "[don't]: /d\n",
+        ),
+        concat!(
+            "<p>[“It’s” – a <a href=\"u\"><img src=\"i.png\" alt=\"l'image...\" /></a>](v) and ‘this’…</p>\n",
+            "<p><a href=\"/r\">don't</a>, <a href=\"/d\">don't</a> – [“not a link”]</p>\n",
+        ),
+        |opts| {
+            opts.parse.smart = true;
+            opts.parse.smart_skip_links = true;
+        },
+    );
+}
+
+#[test]
+fn code_lang_as_data_attr() {
+    let input = concat!(
+        "```rust extra\n",
+        "fn main() {}\n",
+        "```\n",
+        "\n",
+        "```\n",
+        "plain\n",
+        "```\n"
+    );
+
+    html_opts_i(
+        input,
+        concat!(
+            "<pre><code class=\"language-rust\" data-meta=\"extra\" data-language=\"rust\">fn main() {}\n</code></pre>\n",
+            "<pre><code>plain\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.render.full_info_string = true;
+            opts.render.code_lang_as_data_attr = true;
+        },
+    );
+
+    html_opts_i(
+        input,
+        concat!(
+            "<pre><code data-language=\"rust\">fn main() {}\n</code></pre>\n",
+            "<pre><code>plain\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.render.code_lang_as_data_attr = true;
+            opts.render.code_lang_data_attr_only = true;
+        },
+    );
+
+    // Without code_lang_as_data_attr there's nothing to replace the class.
+    html_opts_i(
+        input,
+        concat!(
+            "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n",
+            "<pre><code>plain\n</code></pre>\n",
+        ),
+        |opts| opts.render.code_lang_data_attr_only = true,
+    );
+}
+
+#[test]
+fn code_info_attribute_injection() {
+    let input = concat!(
+        "```x\"onerror=alert(1)// a\"b<c>\n",
+        "1\n",
+        "```\n",
+        "\n",
+        "```y&quot;onmouseover=&quot;alert(2)\n",
+        "2\n",
+        "```\n"
+    );
+
+    html_opts_i(
+        input,
+        concat!(
+            "<pre><code class=\"language-x&quot;onerror=alert(1)//\" data-meta=\"a&quot;b&lt;c&gt;\" \
+             data-language=\"x&quot;onerror=alert(1)//\">1\n</code></pre>\n",
+            "<pre><code class=\"language-y&quot;onmouseover=&quot;alert(2)\" \
+             data-language=\"y&quot;onmouseover=&quot;alert(2)\">2\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.render.full_info_string = true;
+            opts.render.code_lang_as_data_attr = true;
+        },
+    );
+
+    html_opts_i(
+        input,
+        concat!(
+            "<pre lang=\"x&quot;onerror=alert(1)//\" data-meta=\"a&quot;b&lt;c&gt;\"><code>1\n</code></pre>\n",
+            "<pre lang=\"y&quot;onmouseover=&quot;alert(2)\"><code>2\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.render.full_info_string = true;
+            opts.render.github_pre_lang = true;
+        },
+    );
+}
+
+#[test]
+fn block_ids() {
+    html_opts_i(
+        concat!(
+            "First\n",
+            "\n",
+            "- tight\n",
+            "\n",
+            "Second\n",
+            "\n",
+            "# P 2\n",
+            "\n",
+            "3. loose\n",
+            "\n",
+            "   item\n",
+            "\n",
+            "***\n",
+            "\n",
+            "```\n",
+            "code\n",
+            "```\n"
+        ),
+        concat!(
+            "<p id=\"b-p-1\">First</p>\n",
+            "<ul id=\"b-ul-1\">\n",
+            "<li id=\"b-li-1\">tight</li>\n",
+            "</ul>\n",
+            "<p id=\"b-p-2\">Second</p>\n",
+            "<h1><a href=\"#p-2\" aria-hidden=\"true\" class=\"anchor\" id=\"p-2\"></a>P 2</h1>\n",
+            "<ol id=\"b-ol-1\" start=\"3\">\n",
+            "<li id=\"b-li-2\">\n",
+            "<p id=\"b-p-3\">loose</p>\n",
+            "<p id=\"b-p-4\">item</p>\n",
+            "</li>\n",
+            "</ol>\n",
+            "<hr id=\"b-hr-1\" />\n",
+            "<pre id=\"b-pre-1\"><code>code\n</code></pre>\n",
+        ),
+        |opts| {
+            opts.extension.header_ids = Some(String::new());
+            opts.render.block_ids = Some("b-".to_string());
+        },
+    );
+
+    html_opts_i(
+        "> Quote\n",
+        concat!(
+            "<blockquote id=\"doc-&quot;-blockquote-1\">\n",
+            "<p id=\"doc-&quot;-p-1\">Quote</p>\n",
+            "</blockquote>\n",
+        ),
+        |opts| opts.render.block_ids = Some("doc-\"-".to_string()),
+    );
+
+    html_opts_i(
+        concat!("One\n", "\n", "Two\n", "\n", "# P 2\n", "\n", "# P 2\n"),
+        concat!(
+            "<p id=\"p-1\">One</p>\n",
+            "<p id=\"p-2-2\">Two</p>\n",
+            "<h1 id=\"p-2\">P 2</h1>\n",
+            "<h1 id=\"p-2-1\">P 2</h1>\n",
+        ),
+        |opts| {
+            opts.extension.header_ids = Some(String::new());
+            opts.extension.header_id_style = HeaderIdStyle::OnHeading;
+            opts.render.block_ids = Some(String::new());
+        },
+    );
+}
+
+#[test]
+fn external_links_new_tab() {
+    let new_tab = "target=\"_blank\" rel=\"noopener noreferrer\"";
+    html_opts_i(
+        concat!(
+            "[a](https://example.com/x) [b](//cdn.example.org) [c](/about) [d](#top) ",
+            "[e](mailto:me@example.com) [f](https://wiki.corp:8080/page) ",
+            "[g](https://wiki.corp.evil.org) [h](HTTPS://Docs.Corp/x)\n"
+        ),
+        &format!(
+            concat!(
+                "<p><a href=\"https://example.com/x\" {0}>a</a> ",
+                "<a href=\"//cdn.example.org\" {0}>b</a> <a href=\"/about\">c</a> ",
+                "<a href=\"#top\">d</a> <a href=\"mailto:me@example.com\">e</a> ",
+                "<a href=\"https://wiki.corp:8080/page\">f</a> ",
+                "<a href=\"https://wiki.corp.evil.org\" {0}>g</a> ",
+                "<a href=\"HTTPS://Docs.Corp/x\">h</a></p>\n"
+            ),
+            new_tab
+        ),
+        |opts| {
+            opts.render.external_links_new_tab = true;
+            opts.render.internal_link_hosts =
+                Some(vec!["wiki.corp".to_string(), "docs.corp/".to_string()]);
+        },
+    );
+
+    // Links relativized by philomena_domains are internal.
+    html_opts_no_roundtrip(
+        "[a](https://example.com/path) [b](https://example.org/path)",
+        &format!(
+            concat!(
+                "<div class=\"paragraph\"><a href=\"/path\">a</a> ",
+                "<a href=\"https://example.org/path\" {}>b</a></div>\n"
+            ),
+            new_tab
+        ),
+        |opts| {
+            opts.extension.philomena = true;
+            opts.extension.philomena_domains = Some(vec!["example.com".to_string()]);
+            opts.render.external_links_new_tab = true;
+        },
+    );
+}
+
+#[test]
+fn crlf_line_endings() {
+    let input = concat!(
+        "# Title\r\n",
+        "\r\n",
+        "One\\\r\n",
+        "two\r\n",
+        "\r\n",
+        "```\r\n",
+        "code\r\n",
+        "```\r\n",
+        "\r\n",
+        "<div>\r\n",
+        "raw\r\n",
+        "</div>\r\n",
+        "\r\n",
+        "- a\r\n",
+        "- b\r\n",
+    );
+    let mut options = Options::default();
+    options.render.unsafe_ = true;
+    options.render.line_ending = LineEnding::CrLf;
+
+    assert_eq!(
+        markdown_to_html(input, &options),
+        concat!(
+            "<h1>Title</h1>\r\n",
+            "<p>One<br />\r\n",
+            "two</p>\r\n",
+            "<pre><code>code\r\n",
+            "</code></pre>\r\n",
+            "<div>\r\n",
+            "raw\r\n",
+            "</div>\r\n",
+            "<ul>\r\n",
+            "<li>a</li>\r\n",
+            "<li>b</li>\r\n",
+            "</ul>\r\n",
+        ),
+    );
+
+    let cm = markdown_to_commonmark(input, &options);
+    assert_eq!(
+        cm,
+        concat!(
+            "# Title\r\n",
+            "\r\n",
+            "One\\\r\n",
+            "two\r\n",
+            "\r\n",
+            "    code\r\n",
+            "\r\n",
+            "<div>\r\n",
+            "raw\r\n",
+            "</div>\r\n",
+            "\r\n",
+            "- a\r\n",
+            "- b\r\n",
+        ),
+    );
+    assert_eq!(markdown_to_commonmark(&cm, &options), cm);
+
+    let xml = markdown_to_commonmark_xml(input, &options);
+    assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\r\n"));
+    assert!(!xml.replace("\r\n", "").contains('\n'));
+
+    options.render.line_ending = LineEnding::Lf;
+    assert!(!markdown_to_html(input, &options).contains('\r'));
+}
+
+#[test]
+fn quote_elements_smart_skip_links() {
+    html_opts_i(
+        "\"[\"Hi\"](/a)\" and ![\"Hi\"](a.png)\n",
+        concat!(
+            "<p><q><a href=\"/a\">&quot;Hi&quot;</a></q> and ",
+            "<img src=\"a.png\" alt=\"&quot;Hi&quot;\" /></p>\n",
+        ),
+        |opts| {
+            opts.parse.smart = true;
+            opts.parse.smart_skip_links = true;
+            opts.parse.quote_elements = true;
+        },
+    );
+}
@@ -67,3 +67,57 @@ fn pathological_footnotes() {
 
     html_opts!([extension.footnotes], &input, &exp);
 }
+
+// input: python3 -c 'n = 5000; print("".join("*[A%d]: x\n" % i for i in range(n)) + "\n" + " ".join("A%d" % i for i in range(n)))'
+#[test]
+#[timeout(4000)]
+fn pathological_abbreviations() {
+    let n = 5_000;
+    let definitions: String = (0..n).map(|i| format!("*[A{}]: x\n", i)).collect();
+    let uses: Vec<String> = (0..n).map(|i| format!("A{}", i)).collect();
+    let input = format!("{}\n{}\n", definitions, uses.join(" "));
+
+    let mut options = Options::default();
+    options.extension.abbreviations = true;
+
+    // Not interested in the actual html, just that we don't timeout
+    markdown_to_html(&input, &options);
+}
+
+#[test]
+#[timeout(4000)]
+fn pathological_padded_list_markers() {
+    let n = 50_000;
+    let input: String = (1..=n).map(|i| format!("{}. x\n", i)).collect();
+
+    let mut options = Options::default();
+    options.render.ordered_list_pad_markers = true;
+
+    // Not interested in the actual output, just that we don't timeout
+    markdown_to_commonmark(&input, &options);
+}
+
+// input: python3 -c 'print("x " + "# " * 500000)'
+#[test]
+#[timeout(4000)]
+fn pathological_inline_tokens() {
+    let n = 500_000;
+    let input = format!("x {}\n", "# ".repeat(n));
+
+    let mut options = Options::default();
+    options.parse.inline_tokens = vec![InlineToken {
+        prefix: b'#',
+        matcher: |s| {
+            let digits = s[1..].bytes().take_while(u8::is_ascii_digit).count();
+            if digits > 0 {
+                Some(digits + 1)
+            } else {
+                None
+            }
+        },
+        builder: |s| Some(NodeValue::Text(s.to_string())),
+    }];
+
+    // Not interested in the actual html, just that we don't timeout
+    markdown_to_html(&input, &options);
+}
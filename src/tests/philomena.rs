@@ -119,6 +119,82 @@ fn unnest_quotes_on_line_end_commonmark() {
     );
 }
 
+#[test]
+fn separate_quotes_on_line_end_lazy_continuation() {
+    html_opts_i(
+        "> 1\n>\n> 2",
+        "<blockquote>\n<p>1</p>\n<p>2</p>\n</blockquote>\n",
+        |opts| opts.parse.lazy_blockquote_continuation = true,
+    );
+}
+
+#[test]
+fn separate_quotes_on_blank_line_lazy_continuation() {
+    html_opts_i(
+        "> 1\n\n> 2\n\n3",
+        "<blockquote>\n<p>1</p>\n<p>2</p>\n</blockquote>\n<p>3</p>\n",
+        |opts| opts.parse.lazy_blockquote_continuation = true,
+    );
+}
+
+#[test]
+fn unnest_quotes_on_line_end_lazy_continuation() {
+    html_opts_i(
+        "> 1\n> > 2\n> 1",
+        "<blockquote>\n<p>1</p>\n<blockquote>\n<p>2</p>\n</blockquote>\n<p>1</p>\n</blockquote>\n",
+        |opts| opts.parse.lazy_blockquote_continuation = true,
+    );
+
+    html_opts_i(
+        "> 1\n> > 2\n>\n> 1",
+        "<blockquote>\n<p>1</p>\n<blockquote>\n<p>2</p>\n</blockquote>\n<p>1</p>\n</blockquote>\n",
+        |opts| opts.parse.lazy_blockquote_continuation = true,
+    );
+}
+
+#[test]
+fn lazy_blockquote_continuation_in_list() {
+    html_opts_i(
+        concat!("- > a\n", "\n", "  b\n", "- c\n"),
+        concat!(
+            "<ul>\n",
+            "<li>\n",
+            "<blockquote>\n",
+            "<p>a</p>\n",
+            "</blockquote>\n",
+            "<p>b</p>\n",
+            "</li>\n",
+            "<li>\n",
+            "<p>c</p>\n",
+            "</li>\n",
+            "</ul>\n",
+        ),
+        |opts| opts.parse.lazy_blockquote_continuation = true,
+    );
+}
+
+#[test]
+fn blockquote_no_space_required() {
+    html_opts_i(
+        concat!(">1\n", ">1\n", "> >2\n", ">>2\n", ">\n", ">3\n"),
+        concat!(
+            "<blockquote>\n",
+            "<p>1\n",
+            "1</p>\n",
+            "<blockquote>\n",
+            "<p>2\n",
+            "2</p>\n",
+            "</blockquote>\n",
+            "</blockquote>\n",
+            "<p>&gt;</p>\n",
+            "<blockquote>\n",
+            "<p>3</p>\n",
+            "</blockquote>\n",
+        ),
+        |opts| opts.parse.blockquote_no_space_required = true,
+    );
+}
+
 #[test]
 fn philomena_images() {
     html_opts!(
@@ -172,6 +248,25 @@ fn image_mention() {
     );
 }
 
+#[test]
+fn image_mention_overlapping_keys() {
+    for _ in 0..16 {
+        html_opts_no_roundtrip(
+            ">>1234p >>12 >>123",
+            "<div class=\"paragraph\"><b>long</b> <b>short</b> &gt;&gt;123</div>\n",
+            |opts| {
+                let mut replacements = HashMap::new();
+                replacements.insert("12".to_string(), "<b>short</b>".to_string());
+                replacements.insert("1234".to_string(), "<b>mid</b>".to_string());
+                replacements.insert("1234p".to_string(), "<b>long</b>".to_string());
+
+                opts.extension.philomena = true;
+                opts.extension.philomena_replacements = Some(replacements);
+            },
+        );
+    }
+}
+
 #[test]
 fn auto_relative_links() {
     html_opts_no_roundtrip(
@@ -0,0 +1,118 @@
+use super::*;
+
+#[test]
+fn blocks() {
+    plain(
+        concat!(
+            "# One *emph*\n",
+            "\n",
+            "Some `code` and\n",
+            "a **strong** line.  \n",
+            "Broken.\n",
+            "\n",
+            "> Quoted\n",
+            "\n",
+            "---\n",
+            "\n",
+            "```rust\n",
+            "fn main() {}\n",
+            "```\n",
+            "\n",
+            "<div>raw</div>\n",
+            "\n",
+            "Last <b>one</b>.\n"
+        ),
+        concat!(
+            "One emph\n",
+            "\n",
+            "Some code and\n",
+            "a strong line.\n",
+            "Broken.\n",
+            "\n",
+            "Quoted\n",
+            "\n",
+            "fn main() {}\n",
+            "\n",
+            "Last one.\n"
+        ),
+    );
+}
+
+#[test]
+fn lists() {
+    plain_opts(
+        concat!(
+            "1. First\n",
+            "2. Second\n",
+            "   continued\n",
+            "   - nested\n",
+            "   - [x] done\n"
+        ),
+        concat!(
+            "- First\n",
+            "- Second\n",
+            "  continued\n",
+            "  - nested\n",
+            "  - [x] done\n"
+        ),
+        |opts| opts.extension.tasklist = true,
+    );
+
+    plain(
+        concat!("- One\n", "\n", "  More.\n", "- Two\n"),
+        concat!("- One\n", "\n", "  More.\n", "\n", "- Two\n"),
+    );
+}
+
+#[test]
+fn links_and_images() {
+    plain(
+        "A [link](/a), an ![*alt* text](/i.png), and <https://example.com>.\n",
+        "A link, an alt text, and https://example.com.\n",
+    );
+
+    plain_opts(
+        "A [link](/a \"title\"), an ![image](/i.png), and <https://example.com>.\n",
+        "A link (/a), an image, and https://example.com.\n",
+        |opts| opts.render.plain_link_urls = true,
+    );
+}
+
+#[test]
+fn tables() {
+    plain_opts(
+        concat!("| a | b |\n", "|---|---|\n", "| *1* | 2 |\n"),
+        concat!("a\tb\n", "1\t2\n"),
+        |opts| opts.extension.table = true,
+    );
+}
+
+#[test]
+fn footnotes() {
+    plain_opts(
+        concat!("Text.[^n]\n", "\n", "[^n]: The note\n", "    over lines.\n"),
+        concat!("Text.[1]\n", "\n", "[1] The note\n", "    over lines.\n"),
+        |opts| opts.extension.footnotes = true,
+    );
+}
+
+#[test]
+fn footnote_start_number() {
+    plain_opts(
+        concat!("Hi[^a]\n", "\n", "[^a]: A greeting.\n"),
+        concat!("Hi[5]\n", "\n", "[5] A greeting.\n"),
+        |opts| {
+            opts.extension.footnotes = true;
+            opts.extension.footnote_start_number = Some(5);
+        },
+    );
+}
+
+#[test]
+fn markdown_to_plain_matches() {
+    let options = Options::default();
+    assert_eq!(
+        markdown_to_plain("*Hello*, [world](/)!\n", &options),
+        "Hello, world!\n"
+    );
+}
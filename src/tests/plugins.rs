@@ -1,5 +1,7 @@
 use crate::{
-    adapters::{HeadingAdapter, HeadingMeta, SyntaxHighlighterAdapter},
+    adapters::{
+        HeadingAdapter, HeadingMeta, NodeRenderer, RenderDecision, SyntaxHighlighterAdapter,
+    },
     nodes::Sourcepos,
 };
 
@@ -49,6 +51,135 @@ fn syntax_highlighter_plugin() {
     html_plugins(input, expected, &plugins);
 }
 
+#[test]
+fn syntax_highlighter_plugin_normalized_lang() {
+    struct MockAdapter;
+
+    impl SyntaxHighlighterAdapter for MockAdapter {
+        fn write_highlighted(
+            &self,
+            output: &mut dyn Write,
+            lang: Option<&str>,
+            code: &str,
+        ) -> io::Result<()> {
+            write!(output, "<!--{}-->{}", lang.unwrap(), code)
+        }
+
+        fn write_pre_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "pre", attributes)
+        }
+
+        fn write_code_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "code", attributes)
+        }
+    }
+
+    let mut options = Options::default();
+    options.render.normalize_code_lang = Some(|lang| match lang {
+        "js" => "javascript".to_string(),
+        _ => lang.to_string(),
+    });
+    let mut plugins = Plugins::default();
+    let adapter = MockAdapter;
+    plugins.render.codefence_syntax_highlighter = Some(&adapter);
+
+    assert_eq!(
+        markdown_to_html_with_plugins("``` js\nx\n```\n", &options, &plugins),
+        "<pre><code class=\"language-javascript\"><!--javascript-->x\n</code></pre>\n"
+    );
+
+    options.render.github_pre_lang = true;
+    assert_eq!(
+        markdown_to_html("``` js\nx\n```\n```\ny\n```\n", &options),
+        "<pre lang=\"javascript\"><code>x\n</code></pre>\n<pre><code>y\n</code></pre>\n"
+    );
+}
+
+#[test]
+fn syntax_highlighter_plugin_with_info() {
+    struct MockAdapter;
+
+    impl SyntaxHighlighterAdapter for MockAdapter {
+        fn write_highlighted(
+            &self,
+            _output: &mut dyn Write,
+            _lang: Option<&str>,
+            _code: &str,
+        ) -> io::Result<()> {
+            unreachable!()
+        }
+
+        fn write_highlighted_with_info(
+            &self,
+            output: &mut dyn Write,
+            info: Option<&str>,
+            code: &str,
+        ) -> io::Result<()> {
+            write!(output, "<!--{}-->{}", info.unwrap(), code)
+        }
+
+        fn write_pre_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "pre", attributes)
+        }
+
+        fn write_code_tag(
+            &self,
+            output: &mut dyn Write,
+            _attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            output.write_all(b"<code>")
+        }
+    }
+
+    let mut options = Options::default();
+    options.render.normalize_code_lang = Some(|lang| match lang {
+        "rs" => "rust".to_string(),
+        _ => lang.to_string(),
+    });
+    let mut plugins = Plugins::default();
+    let adapter = MockAdapter;
+    plugins.render.codefence_syntax_highlighter = Some(&adapter);
+
+    assert_eq!(
+        markdown_to_html_with_plugins(
+            "``` rs  hl_lines=1-3 {linenos}\nx\n```\n```\ny\n```\n",
+            &options,
+            &plugins
+        ),
+        concat!(
+            "<pre><code><!--rust hl_lines=1-3 {linenos}-->x\n</code></pre>\n",
+            "<pre><code><!---->y\n</code></pre>\n",
+        ),
+    );
+
+    options.render.full_info_string = true;
+    options.render.code_block_title_from_info = true;
+    assert_eq!(
+        markdown_to_html_with_plugins(
+            "```rust title=\"main.rs\" hl_lines=2\nx\n```\n",
+            &options,
+            &plugins
+        ),
+        concat!(
+            "<figure class=\"code-block\">\n<figcaption>main.rs</figcaption>\n",
+            "<pre><code><!--rust hl_lines=2-->x\n",
+            "</code></pre>\n</figure>\n",
+        ),
+    );
+}
+
 #[test]
 fn heading_adapter_plugin() {
     struct MockAdapter;
@@ -86,6 +217,183 @@ fn heading_adapter_plugin() {
     }
 }
 
+#[test]
+fn heading_adapter_plugin_ids() {
+    struct IdAdapter;
+
+    impl HeadingAdapter for IdAdapter {
+        fn enter(
+            &self,
+            output: &mut dyn Write,
+            heading: &HeadingMeta,
+            _sourcepos: Option<Sourcepos>,
+        ) -> io::Result<()> {
+            match heading.id {
+                Some(ref id) => write!(output, "<h{} id=\"{}\">", heading.level, id),
+                None => write!(output, "<h{}>", heading.level),
+            }
+        }
+
+        fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()> {
+            write!(output, "</h{}><!-- {:?} -->\n", heading.level, heading.id)
+        }
+    }
+
+    let adapter = IdAdapter;
+    let mut plugins = Plugins::default();
+    plugins.render.heading_adapter = Some(&adapter);
+    let mut options = Options::default();
+    options.extension.header_ids = Some("h-".to_string());
+
+    let arena = Arena::new();
+    let root = parse_document(&arena, "# Hi\n## Hi\n# Hi 1\n", &options);
+    let mut output = vec![];
+    format_html_with_plugins(root, &options, &mut output, &plugins).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        concat!(
+            "<h1 id=\"h-hi\">Hi</h1><!-- Some(\"h-hi\") -->\n",
+            "<h2 id=\"h-hi-1\">Hi</h2><!-- Some(\"h-hi-1\") -->\n",
+            "<h1 id=\"h-hi-1-1\">Hi 1</h1><!-- Some(\"h-hi-1-1\") -->\n",
+        ),
+    );
+
+    html_plugins("# Hi\n", "<h1>Hi</h1><!-- None -->\n", &plugins);
+
+    // The adapter is given the level after any offset.
+    options.render.heading_offset = 4;
+    let root = parse_document(&arena, "# Hi\n### Ho\n", &options);
+    let mut output = vec![];
+    format_html_with_plugins(root, &options, &mut output, &plugins).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        concat!(
+            "<h5 id=\"h-hi\">Hi</h5><!-- Some(\"h-hi\") -->\n",
+            "<h6 id=\"h-ho\">Ho</h6><!-- Some(\"h-ho\") -->\n",
+        ),
+    );
+}
+
+#[test]
+fn node_renderer_plugin() {
+    struct MockRenderer;
+
+    impl NodeRenderer for MockRenderer {
+        fn render<'a>(
+            &self,
+            output: &mut dyn Write,
+            node: &'a AstNode<'a>,
+        ) -> io::Result<RenderDecision> {
+            match node.data.borrow().value {
+                NodeValue::Image(ref nl) => {
+                    write!(output, "<object data=\"{}\"></object>", nl.url)?;
+                    Ok(RenderDecision::Handled)
+                }
+                NodeValue::BlockQuote => {
+                    output.write_all(b"<aside>...</aside>\n")?;
+                    Ok(RenderDecision::Handled)
+                }
+                NodeValue::Strong => {
+                    // Seen, but left alone.
+                    output.write_all(b"!")?;
+                    Ok(RenderDecision::Default)
+                }
+                _ => Ok(RenderDecision::Default),
+            }
+        }
+    }
+
+    let mut plugins = Plugins::default();
+    plugins.render.node_renderer = Some(&MockRenderer);
+
+    html_plugins(
+        concat!(
+            "A **b** ![c](/c.png)\n",
+            "\n",
+            "> Quoted **text**\n",
+            "\n",
+            "[![**alt**](/d.png)](/e)\n",
+        ),
+        concat!(
+            "<p>A !<strong>b</strong> <object data=\"/c.png\"></object></p>\n",
+            "<aside>...</aside>\n",
+            "<p><a href=\"/e\"><object data=\"/d.png\"></object></a></p>\n",
+        ),
+        &plugins,
+    );
+}
+
+#[test]
+fn plugins_with_adapters() {
+    struct MockAdapter;
+
+    impl SyntaxHighlighterAdapter for MockAdapter {
+        fn write_highlighted(
+            &self,
+            output: &mut dyn Write,
+            _lang: Option<&str>,
+            code: &str,
+        ) -> io::Result<()> {
+            write!(output, "<!--hl-->{}", code)
+        }
+
+        fn write_pre_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "pre", attributes)
+        }
+
+        fn write_code_tag(
+            &self,
+            output: &mut dyn Write,
+            attributes: HashMap<String, String>,
+        ) -> io::Result<()> {
+            html::write_opening_tag(output, "code", attributes)
+        }
+    }
+
+    impl HeadingAdapter for MockAdapter {
+        fn enter(
+            &self,
+            output: &mut dyn Write,
+            heading: &HeadingMeta,
+            _sourcepos: Option<Sourcepos>,
+        ) -> io::Result<()> {
+            write!(output, "<h{} class=\"mock\">", heading.level)
+        }
+
+        fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()> {
+            write!(output, "</h{}>", heading.level)
+        }
+    }
+
+    let input = concat!("# Title\n", "\n", "```\n", "x\n", "```\n");
+
+    html_plugins(
+        input,
+        concat!("<h1>Title</h1>\n", "<pre><code><!--hl-->x\n</code></pre>\n"),
+        &Plugins::with_syntax_highlighter(&MockAdapter),
+    );
+    html_plugins(
+        input,
+        concat!(
+            "<h1 class=\"mock\">Title</h1>\n",
+            "<pre><code>x\n</code></pre>\n"
+        ),
+        &Plugins::with_heading_adapter(&MockAdapter),
+    );
+    html_plugins(
+        input,
+        concat!(
+            "<h1 class=\"mock\">Title</h1>\n",
+            "<pre><code><!--hl-->x\n</code></pre>\n"
+        ),
+        &Plugins::with_adapters(&MockAdapter, &MockAdapter),
+    );
+}
+
 #[test]
 #[cfg(feature = "syntect")]
 fn syntect_plugin_with_base16_ocean_dark_theme() {
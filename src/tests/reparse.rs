@@ -0,0 +1,154 @@
+use super::*;
+
+fn xml<'a>(root: &'a AstNode<'a>, options: &Options) -> String {
+    let mut output = vec![];
+    format_xml(root, options, &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+// Reparse `old` edited to `new` and check the result matches a full parse
+// of `new`.  Returns whether the first block of `old` was reused.
+fn check_reparse(old: &str, new: &str, options: &Options) -> bool {
+    let start = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[start..]
+        .bytes()
+        .rev()
+        .zip(new[start..].bytes().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let edit = TextEdit {
+        start,
+        old_end: old.len() - suffix,
+        new_end: new.len() - suffix,
+    };
+
+    let arena = Arena::new();
+    let old_root = parse_document(&arena, old, options);
+    let first = old_root.first_child();
+    let root = reparse(&arena, old_root, old, edit, new, options);
+
+    let mut options = options.clone();
+    options.render.sourcepos = true;
+    let expected = xml(parse_document(&arena, new, &options), &options);
+    compare_strs(&xml(root, &options), &expected, "reparse", new);
+
+    first.map_or(false, |first| {
+        root.first_child().map_or(false, |n| n.same_node(first))
+    })
+}
+
+#[test]
+fn reparse_reuses_earlier_blocks() {
+    let options = Options::default();
+    let old = "# Title\n\nOne *two*.\n\n- a\n- b\n\nThree.\n";
+
+    assert!(check_reparse(
+        old,
+        "# Title\n\nOne *two*.\n\n- a\n- b\n\nThree, *four*.\n",
+        &options
+    ));
+    assert!(check_reparse(
+        old,
+        "# Title\n\nOne *two*.\n\n- a\n- b\n- c\n\nThree.\n",
+        &options
+    ));
+    assert!(check_reparse(
+        old,
+        "# Title\n\nOne *two*.\n\n- a\n- b\n",
+        &options
+    ));
+    assert!(check_reparse(old, &format!("{}More.\n", old), &options));
+}
+
+#[test]
+fn reparse_edit_changes_previous_block() {
+    let options = Options::default();
+    let old = "# Title\n\nPara\n\ntext\n";
+
+    // Joining the last two blocks, then turning the paragraph into a heading.
+    assert!(check_reparse(old, "# Title\n\nPara\ntext\n", &options));
+    assert!(check_reparse(old, "# Title\n\nPara\n---\n", &options));
+    // A lazy continuation line.
+    assert!(check_reparse(
+        "# Title\n\n> quote\n\ntext\n",
+        "# Title\n\n> quote\ntext\n",
+        &options
+    ));
+    // Opening a fence swallows the rest of the document.
+    assert!(check_reparse(
+        "# Title\n\nPara\n\nx\n\n# End\n",
+        "# Title\n\nPara\n\n```\nx\n\n# End\n",
+        &options
+    ));
+}
+
+#[test]
+fn reparse_line_endings() {
+    let options = Options::default();
+    assert!(check_reparse(
+        "a\r\n\r\nb\r\rc\r\n\r\nd\r\n",
+        "a\r\n\r\nb\r\rc\r\n\r\nd *e*\r\n",
+        &options
+    ));
+}
+
+#[test]
+fn reparse_falls_back_to_full_parse() {
+    let mut options = Options::default();
+
+    // Reference definitions before or after the edit.
+    assert!(!check_reparse(
+        "[a]: /u\n\n[a]\n\nx\n\ny\n",
+        "[a]: /u\n\n[a]\n\nx\n\ny [a]\n",
+        &options
+    ));
+    assert!(!check_reparse(
+        "[a]\n\nx\n\ny\n",
+        "[a]\n\nx\n\ny\n\n[a]: /u\n",
+        &options
+    ));
+
+    // Edits at the start of the document, or that don't match the sources.
+    assert!(!check_reparse("a\n\nb\n\nc\n", "z\n\nb\n\nc\n", &options));
+
+    options.extension.footnotes = true;
+    assert!(!check_reparse(
+        "a[^1]\n\nb\n\nc\n\n[^1]: x\n",
+        "a[^1]\n\nb\n\nc[^1]\n\n[^1]: x\n",
+        &options
+    ));
+
+    // An abbreviation defined after the edit applies before it too.
+    let mut options = Options::default();
+    options.extension.abbreviations = true;
+    assert!(!check_reparse(
+        "a X\n\nb X\n\nc\n",
+        "a X\n\nb X\n\nc\n\n*[X]: Ex\n",
+        &options
+    ));
+}
+
+#[test]
+fn reparse_with_extensions() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.autolink = true;
+    options.extension.tasklist = true;
+    options.extension.strikethrough = true;
+
+    let old = "# T\n\n| a |\n|---|\n| b |\n\n- [ ] www.example.com\n\n~x~\n";
+    assert!(check_reparse(
+        old,
+        "# T\n\n| a |\n|---|\n| b |\n| c |\n\n- [ ] www.example.com\n\n~x~\n",
+        &options
+    ));
+    assert!(check_reparse(
+        old,
+        "# T\n\n| a |\n|---|\n| b |\n\n- [x] www.example.com\n\n~x~ y\n",
+        &options
+    ));
+}
@@ -0,0 +1,118 @@
+use super::*;
+
+#[test]
+fn reversed_lists() {
+    html_opts!(
+        [extension.reversed_lists],
+        concat!("3. three\n", "2. two\n", "1. one\n"),
+        concat!(
+            "<ol reversed start=\"3\">\n",
+            "<li>three</li>\n",
+            "<li>two</li>\n",
+            "<li>one</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn reversed_lists_start_is_always_written() {
+    html_opts!(
+        [extension.reversed_lists],
+        concat!("1) one\n", "0) none\n"),
+        concat!(
+            "<ol reversed start=\"1\">\n",
+            "<li>one</li>\n",
+            "<li>none</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn reversed_lists_ascending() {
+    html_opts!(
+        [extension.reversed_lists],
+        concat!("5. five\n", "6. six\n"),
+        concat!(
+            "<ol start=\"5\">\n",
+            "<li>five</li>\n",
+            "<li>six</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn reversed_lists_need_a_steady_count() {
+    html_opts!(
+        [extension.reversed_lists],
+        concat!("3. a\n", "2. b\n", "2. c\n"),
+        concat!(
+            "<ol start=\"3\">\n",
+            "<li>a</li>\n",
+            "<li>b</li>\n",
+            "<li>c</li>\n",
+            "</ol>\n",
+        ),
+    );
+    html_opts!(
+        [extension.reversed_lists],
+        concat!("4. d\n", "2. e\n"),
+        concat!(
+            "<ol start=\"4\">\n",
+            "<li>d</li>\n",
+            "<li>e</li>\n",
+            "</ol>\n"
+        ),
+    );
+    html_opts!(
+        [extension.reversed_lists],
+        "9. f\n",
+        concat!("<ol start=\"9\">\n", "<li>f</li>\n", "</ol>\n"),
+    );
+}
+
+#[test]
+fn reversed_lists_disabled() {
+    html(
+        concat!("3. three\n", "2. two\n", "1. one\n"),
+        concat!(
+            "<ol start=\"3\">\n",
+            "<li>three</li>\n",
+            "<li>two</li>\n",
+            "<li>one</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn reversed_lists_preserve_ordered_numbers() {
+    html_opts!(
+        [
+            extension.reversed_lists,
+            extension.alpha_lists,
+            render.preserve_ordered_numbers
+        ],
+        concat!("c. three\n", "b. two\n", "a. one\n"),
+        concat!(
+            "<ol type=\"a\" reversed start=\"3\">\n",
+            "<li>three</li>\n",
+            "<li>two</li>\n",
+            "<li>one</li>\n",
+            "</ol>\n",
+        ),
+    );
+}
+
+#[test]
+fn reversed_lists_commonmark() {
+    let mut options = Options::default();
+    options.extension.reversed_lists = true;
+    commonmark(
+        "3. three\n2. two\n1. one\n",
+        "3.  three\n2.  two\n1.  one\n",
+        Some(&options),
+    );
+}
@@ -32,3 +32,42 @@ fn emojis() {
         concat!("<p>Hello, happy days! :diego:</p>\n"),
     );
 }
+
+#[test]
+fn emoji_images() {
+    html_opts_i(
+        "Hi :smile: and :rocket::smile:\n",
+        concat!(
+            "<p>Hi <img class=\"emoji\" src=\"https://cdn.example/emoji/smile.png?v=1&amp;s=2\" ",
+            "alt=\":smile:\" /> and 🚀",
+            "<img class=\"emoji\" src=\"https://cdn.example/emoji/smile.png?v=1&amp;s=2\" ",
+            "alt=\":smile:\" /></p>\n",
+        ),
+        |opts| {
+            opts.extension.shortcodes = true;
+            opts.render.emoji_image_url = Some(|shortcode| match shortcode {
+                "smile" => Some(format!(
+                    "https://cdn.example/emoji/{}.png?v=1&s=2",
+                    shortcode
+                )),
+                _ => None,
+            });
+        },
+    );
+}
+
+#[test]
+fn emoji_images_sourcepos() {
+    html_opts_i(
+        ":smile:\n",
+        concat!(
+            "<p data-sourcepos=\"1:1-1:7\"><img data-sourcepos=\"1:1-1:7\" class=\"emoji\" ",
+            "src=\"/smile.png\" alt=\":smile:\" /></p>\n",
+        ),
+        |opts| {
+            opts.extension.shortcodes = true;
+            opts.render.sourcepos = true;
+            opts.render.emoji_image_url = Some(|shortcode| Some(format!("/{}.png", shortcode)));
+        },
+    );
+}
@@ -0,0 +1,109 @@
+use super::*;
+
+fn to_xml<'a>(root: &'a AstNode<'a>, options: &Options) -> String {
+    let mut xml = vec![];
+    format_xml(root, options, &mut xml).unwrap();
+    String::from_utf8(xml).unwrap()
+}
+
+// Checks that `input` parses the same however it is split into pieces.
+#[track_caller]
+fn streams_like_whole(input: &str, options: &Options) {
+    let arena = Arena::new();
+    let expected = to_xml(parse_document(&arena, input, options), options);
+
+    let boundaries = (0..=input.len())
+        .filter(|&i| input.is_char_boundary(i))
+        .collect::<Vec<_>>();
+    for &i in &boundaries {
+        let mut parser = StreamingParser::new(&arena, options);
+        parser.push(&input[..i]);
+        parser.push(&input[i..]);
+        compare_strs(
+            &to_xml(parser.finish(), options),
+            &expected,
+            &format!("split at {}", i),
+            input,
+        );
+    }
+
+    let mut parser = StreamingParser::new(&arena, options);
+    for window in boundaries.windows(2) {
+        parser.push(&input[window[0]..window[1]]);
+    }
+    compare_strs(
+        &to_xml(parser.finish(), options),
+        &expected,
+        "by character",
+        input,
+    );
+}
+
+#[test]
+fn split_anywhere() {
+    let mut options = Options::default();
+    options.render.sourcepos = true;
+    options.extension.table = true;
+
+    streams_like_whole(
+        concat!(
+            "# Héading\r\n",
+            "\r\n",
+            "Some *text*\r",
+            "over lines.\n",
+            "\n",
+            "| a | b |\n",
+            "|---|---|\n",
+            "| ü | \0 |\n",
+            "\n",
+            "```\n",
+            "code\r\n",
+            "```\n",
+            "last line"
+        ),
+        &options,
+    );
+}
+
+#[test]
+fn split_front_matter() {
+    let mut options = Options::default();
+    options.render.sourcepos = true;
+    options.extension.front_matter_delimiter = Some("---".to_string());
+
+    streams_like_whole("---\ntitle: x\n---\n\nBody.\n", &options);
+    streams_like_whole("\u{feff}---\r\ntitle: x\r\n---\r\n\r\nBody.\r\n", &options);
+    streams_like_whole("---\ntitle: x\n---\nBody.\n", &options);
+    streams_like_whole("---\nnot closed\n\nBody.\n", &options);
+    streams_like_whole("--- not front matter\n---\n", &options);
+    streams_like_whole("-", &options);
+}
+
+#[test]
+fn from_reader() {
+    struct Trickle<'s>(&'s [u8]);
+
+    // Hands out three bytes at a time, splitting multibyte characters.
+    impl<'s> io::Read for Trickle<'s> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(3).min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let options = Options::default();
+    let input = "# Grüße\n\nÜber *alles* — 日本語.\n";
+    let arena = Arena::new();
+    let root = parse_document_from_reader(&arena, Trickle(input.as_bytes()), &options).unwrap();
+    assert_eq!(
+        to_xml(root, &options),
+        to_xml(parse_document(&arena, input, &options), &options)
+    );
+
+    let err = parse_document_from_reader(&arena, Trickle(b"ok \xff"), &options).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = parse_document_from_reader(&arena, Trickle(b"cut \xe6\x97"), &options).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
@@ -15,3 +15,12 @@ fn strikethrough() {
         ),
     );
 }
+
+#[test]
+fn strikethrough_unmatched_runs() {
+    html_opts!(
+        [extension.strikethrough],
+        "x ~a~~ *b* ~c~ ~~~d~~~ **e**\n",
+        "<p>x ~a~~ <em>b</em> <del>c</del> ~~~d~~~ <strong>e</strong></p>\n",
+    );
+}
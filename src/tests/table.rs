@@ -1,4 +1,5 @@
 use super::*;
+use ntest::test_case;
 
 #[test]
 fn table() {
@@ -222,3 +223,538 @@ fn sourcepos_with_preceding_para_offset() {
         ])
     );
 }
+
+#[test]
+fn table_alignment_classes() {
+    html_opts_i(
+        concat!(
+            "| a | b | c | d |\n",
+            "|:--|:-:|--:|---|\n",
+            "| e | f | g | h |\n"
+        ),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th class=\"text-left\">a</th>\n",
+            "<th class=\"text-center\">b</th>\n",
+            "<th class=\"text-right\">c</th>\n",
+            "<th>d</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td class=\"text-left\">e</td>\n",
+            "<td class=\"text-center\">f</td>\n",
+            "<td class=\"text-right\">g</td>\n",
+            "<td>h</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.render.table_alignment_classes = Some([
+                "text-left".to_string(),
+                "text-center".to_string(),
+                "text-right".to_string(),
+            ]);
+        },
+    );
+}
+
+#[test]
+fn tables_without_header() {
+    html_opts!(
+        [extension.table, extension.tables_without_header],
+        concat!("| a | b |\n", "| c | d |\n", "| e | f |\n"),
+        concat!(
+            "<table>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>a</td>\n",
+            "<td>b</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td>d</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>e</td>\n",
+            "<td>f</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn tables_without_header_after_paragraph() {
+    html_opts!(
+        [extension.table, extension.tables_without_header],
+        concat!("Some text\n", "| a | b |\n", "| c | d |\n"),
+        concat!(
+            "<p>Some text</p>\n",
+            "<table>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>a</td>\n",
+            "<td>b</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td>d</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn tables_without_header_need_two_rows() {
+    html_opts!(
+        [extension.table, extension.tables_without_header],
+        concat!(
+            "| a | b |\n",
+            "\n",
+            "a | b\n",
+            "c | d\n",
+            "\n",
+            "| a | b |\n",
+            "| c |\n"
+        ),
+        concat!(
+            "<p>| a | b |</p>\n",
+            "<p>a | b\n",
+            "c | d</p>\n",
+            "<p>| a | b |\n",
+            "| c |</p>\n"
+        ),
+    );
+}
+
+#[test]
+fn tables_without_header_keeps_header_tables() {
+    html_opts!(
+        [extension.table, extension.tables_without_header],
+        concat!("| a | b |\n", "|---|---|\n", "| c | d |\n"),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td>d</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn table_caption_before() {
+    html_opts!(
+        [extension.table, extension.table_captions],
+        concat!("Table: Some *data*\n", "| a |\n", "|---|\n", "| b |\n"),
+        concat!(
+            "<table>\n",
+            "<caption>Some <em>data</em></caption>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>b</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn table_caption_after() {
+    html_opts!(
+        [extension.table, extension.table_captions],
+        concat!("| a |\n", "|---|\n", "| b |\n", "Table: Some *data*\n"),
+        concat!(
+            "<table>\n",
+            "<caption>Some <em>data</em></caption>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>b</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn table_caption_sourcepos() {
+    assert_ast_match!(
+        [extension.table, extension.table_captions],
+        "| a |\n"
+        "|---|\n"
+        "Table:  Some *data*\n"
+        ,
+        (document (1:1-3:19) [
+            (table (1:1-2:5) [
+                (table_caption (3:9-3:19) [
+                    (text (3:9-3:13) "Some ")
+                    (emph (3:14-3:19) [
+                        (text (3:15-3:18) "data")
+                    ])
+                ])
+                (table_row (1:1-1:5) [
+                    (table_cell (1:2-1:4) [
+                        (text (1:3-1:3) "a")
+                    ])
+                ])
+            ])
+        ])
+    );
+}
+
+#[test]
+fn table_caption_between_tables() {
+    html_opts!(
+        [extension.table, extension.table_captions],
+        concat!(
+            "| a |\n",
+            "|---|\n",
+            "Table: second\n",
+            "| b |\n",
+            "|---|\n"
+        ),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n",
+            "<table>\n",
+            "<caption>second</caption>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn table_caption_not_adjacent() {
+    html_opts!(
+        [extension.table, extension.table_captions],
+        concat!(
+            "Table: lonely\n",
+            "\n",
+            "| a |\n",
+            "|---|\n",
+            "\n",
+            "Table: also lonely\n"
+        ),
+        concat!(
+            "<p>Table: lonely</p>\n",
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "</table>\n",
+            "<p>Table: also lonely</p>\n"
+        ),
+    );
+}
+
+#[test]
+fn table_caption_without_header() {
+    html_opts!(
+        [
+            extension.table,
+            extension.tables_without_header,
+            extension.table_captions
+        ],
+        concat!("Table: cap\n", "| a |\n", "| b |\n"),
+        concat!(
+            "<table>\n",
+            "<caption>cap</caption>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>a</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>b</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+    );
+}
+
+#[test]
+fn table_cell_line_breaks() {
+    html_opts_i(
+        concat!("| a | b |\n", "|---|---|\n", "| c | d |\\\n", "|   | e |\n"),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "<td>d<br />\n",
+            "e</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_cell_line_breaks = true;
+        },
+    );
+}
+
+#[test]
+fn table_cell_line_breaks_chained() {
+    html_opts_i(
+        concat!(
+            "| a | b |\n",
+            "|---|---|\n",
+            "| c | d \\\n",
+            "| e | f \\\n",
+            "| g |\n",
+            "| h | i |\n"
+        ),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "<th>b</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>c<br />\n",
+            "e<br />\n",
+            "g</td>\n",
+            "<td>d<br />\n",
+            "f</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>h</td>\n",
+            "<td>i</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_cell_line_breaks = true;
+        },
+    );
+}
+
+#[test]
+fn table_cell_line_breaks_escaped_backslash() {
+    html_opts_i(
+        concat!("| a |\n", "|---|\n", "| b \\\\\n", "| c |\n"),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>b \\</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td>c</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_cell_line_breaks = true;
+        },
+    );
+}
+
+#[test]
+fn table_cell_line_breaks_after_escaped_backslash() {
+    html_opts_i(
+        concat!("| a |\n", "|---|\n", "| b \\\\\\\n", "| c |\n"),
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th>a</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td>b \\<br />\n",
+            "c</td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n"
+        ),
+        |opts| {
+            opts.extension.table = true;
+            opts.extension.table_cell_line_breaks = true;
+        },
+    );
+}
+
+#[test]
+fn table_cell_line_breaks_commonmark() {
+    let mut options = Options::default();
+    options.extension.table = true;
+    options.extension.table_cell_line_breaks = true;
+
+    commonmark(
+        concat!(
+            "| a | b |\n",
+            "|---|---|\n",
+            "| c | *d* \\\n",
+            "| e | f\\\\ \\\n",
+            "|   | g |\n",
+            "| h | i |\n"
+        ),
+        concat!(
+            "| a   | b   |\n",
+            "| --- | --- |\n",
+            "| c   | *d* |\\\n",
+            "| e   | f\\\\ |\\\n",
+            "|     | g   |\n",
+            "| h   | i   |\n"
+        ),
+        Some(&options),
+    );
+}
+
+#[test_case("| \\|x | y\\| |", "<td>|x</td>\n<td>y|</td>\n")]
+#[test_case("| \\|| x |", "<td>|</td>\n<td>x</td>\n")]
+#[test_case("| x \\\\| y |", "<td>x \\</td>\n<td>y</td>\n")]
+#[test_case("| \\\\\\| | z |", "<td>\\|</td>\n<td>z</td>\n")]
+#[test_case("| b `\\|` az | c |", "<td>b <code>|</code> az</td>\n<td>c</td>\n")]
+#[test_case(
+    "| b **\\|** im | c |",
+    "<td>b <strong>|</strong> im</td>\n<td>c</td>\n"
+)]
+#[test_case("| a\\\\ | \\\\b |", "<td>a\\</td>\n<td>\\b</td>\n")]
+fn table_escaped_pipes(row: &str, cells: &str) {
+    html_opts!(
+        [extension.table],
+        &format!("| h\\|1 | h2 |\n|---|---|\n{}\n", row),
+        &format!(
+            concat!(
+                "<table>\n",
+                "<thead>\n",
+                "<tr>\n",
+                "<th>h|1</th>\n",
+                "<th>h2</th>\n",
+                "</tr>\n",
+                "</thead>\n",
+                "<tbody>\n",
+                "<tr>\n",
+                "{}",
+                "</tr>\n",
+                "</tbody>\n",
+                "</table>\n",
+            ),
+            cells
+        ),
+    );
+}
+
+#[test]
+fn commonmark_alignment_and_padding() {
+    let mut options = Options::default();
+    options.extension.table = true;
+
+    let input = concat!(
+        "| Left | Centre | Right | None |\n",
+        "|:-----|:------:|------:|------|\n",
+        "| a much longer cell | b | c | d \\| e |\n",
+        "| ü | *emph* |\n",
+    );
+    let output = concat!(
+        "| Left               | Centre | Right | None   |\n",
+        "| :----------------- | :----: | ----: | ------ |\n",
+        "| a much longer cell | b      | c     | d \\| e |\n",
+        "| ü                  | *emph* |       |        |\n",
+    );
+    commonmark(input, output, Some(&options));
+    // The padded table reads back the same.
+    commonmark(output, output, Some(&options));
+
+    html_opts!(
+        [extension.table],
+        output,
+        concat!(
+            "<table>\n",
+            "<thead>\n",
+            "<tr>\n",
+            "<th align=\"left\">Left</th>\n",
+            "<th align=\"center\">Centre</th>\n",
+            "<th align=\"right\">Right</th>\n",
+            "<th>None</th>\n",
+            "</tr>\n",
+            "</thead>\n",
+            "<tbody>\n",
+            "<tr>\n",
+            "<td align=\"left\">a much longer cell</td>\n",
+            "<td align=\"center\">b</td>\n",
+            "<td align=\"right\">c</td>\n",
+            "<td>d | e</td>\n",
+            "</tr>\n",
+            "<tr>\n",
+            "<td align=\"left\">ü</td>\n",
+            "<td align=\"center\"><em>emph</em></td>\n",
+            "<td align=\"right\"></td>\n",
+            "<td></td>\n",
+            "</tr>\n",
+            "</tbody>\n",
+            "</table>\n",
+        ),
+    );
+
+    // Tables aren't padded when wrapping.
+    options.render.width = 80;
+    commonmark(
+        concat!("| a | b |\n", "|--:|---|\n", "| long | x |\n"),
+        concat!("| a | b |\n", "| --: | --- |\n", "| long | x |\n"),
+        Some(&options),
+    );
+}
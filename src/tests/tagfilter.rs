@@ -8,3 +8,36 @@ fn tagfilter() {
         concat!("<p>hi &lt;xmp> ok</p>\n", "&lt;xmp>\n"),
     );
 }
+
+#[test]
+fn allowed_inline_html_tags() {
+    html_opts_i(
+        "H<sub>2</sub>O<BR> <sup>x</sup> <script>alert(1)</script>\n",
+        concat!(
+            "<p>H<sub>2</sub>O<BR> <!-- raw HTML omitted -->x<!-- raw HTML omitted --> ",
+            "<!-- raw HTML omitted -->alert(1)<!-- raw HTML omitted --></p>\n"
+        ),
+        |opts| opts.render.allowed_inline_html_tags = vec!["sub".to_string(), "br".to_string()],
+    );
+}
+
+#[test]
+fn allowed_inline_html_tags_not_blocks() {
+    html_opts_i(
+        "<sub>\nblock\n</sub>\n",
+        "<!-- raw HTML omitted -->\n",
+        |opts| opts.render.allowed_inline_html_tags = vec!["sub".to_string()],
+    );
+}
+
+#[test]
+fn allowed_inline_html_tags_without_attributes() {
+    html_opts_i(
+        "<sub onmouseover=\"alert(1)\">x</sub> <sub >y</sub > <br/> <br class=a/>\n",
+        concat!(
+            "<p><!-- raw HTML omitted -->x</sub> <sub >y</sub > <br/> ",
+            "<!-- raw HTML omitted --></p>\n"
+        ),
+        |opts| opts.render.allowed_inline_html_tags = vec!["sub".to_string(), "br".to_string()],
+    );
+}
@@ -230,3 +230,63 @@ fn sourcepos() {
         ])
     );
 }
+
+#[test]
+fn wikilinks_pipe_in_code_span() {
+    html_opts!(
+        [extension.wikilinks_title_after_pipe, render.sourcepos],
+        "[[`a|b`|label]]",
+        "<p data-sourcepos=\"1:1-1:15\"><a data-sourcepos=\"1:1-1:15\" href=\"%60a%7Cb%60\" data-wikilink=\"true\">label</a></p>\n",
+    );
+
+    html_opts!(
+        [extension.wikilinks_title_before_pipe, render.sourcepos],
+        "[[`x]]` here|target]]",
+        "<p data-sourcepos=\"1:1-1:21\"><a data-sourcepos=\"1:1-1:21\" href=\"target\" data-wikilink=\"true\">`x]]` here</a></p>\n",
+    );
+
+    html_opts!(
+        [extension.wikilinks_title_after_pipe],
+        "[[`a|b]]",
+        "<p><a href=\"%60a\" data-wikilink=\"true\">b</a></p>\n",
+    );
+}
+
+#[test]
+fn wikilinks_escaped_brackets_in_label() {
+    html_opts!(
+        [extension.wikilinks_title_after_pipe, render.sourcepos],
+        "[[Page|a \\]\\] b]]",
+        "<p data-sourcepos=\"1:1-1:17\"><a data-sourcepos=\"1:1-1:17\" href=\"Page\" data-wikilink=\"true\">a ]] b</a></p>\n",
+    );
+}
+
+#[test]
+fn wikilinks_slug_separator_and_resolver() {
+    // The commonmark output percent-encodes the target, so no roundtrip here.
+    html_opts_no_roundtrip(
+        "[[Main Page]] and [[ Other\tPage |there]]",
+        "<p><a href=\"Main_Page\" data-wikilink=\"true\">Main Page</a> and <a href=\"Other_Page\" data-wikilink=\"true\">there</a></p>\n",
+        |opts| {
+            opts.extension.wikilinks_title_after_pipe = true;
+            opts.extension.wikilinks_slug_separator = Some('_');
+        },
+    );
+
+    // Resolved URLs are still checked.
+    html_opts_no_roundtrip(
+        "[[Page One]] [[alert(1)]]",
+        "<p><a href=\"/wiki/Page-One\" data-wikilink=\"true\">Page One</a> <a href=\"\" data-wikilink=\"true\">alert(1)</a></p>\n",
+        |opts| {
+            opts.extension.wikilinks_title_after_pipe = true;
+            opts.extension.wikilinks_slug_separator = Some('-');
+            opts.extension.wikilinks_url_resolver = Some(|target| {
+                if target.contains('(') {
+                    format!("javascript:{}", target)
+                } else {
+                    format!("/wiki/{}", target)
+                }
+            });
+        },
+    );
+}
@@ -1,3 +1,4 @@
+use crate::html::LineEndingWriter;
 use crate::nodes::{AstNode, ListType, NodeCode, NodeMath, NodeTable, NodeValue};
 use crate::parser::{Options, Plugins};
 use once_cell::sync::Lazy;
@@ -24,6 +25,8 @@ pub fn format_document_with_plugins<'a>(
     output: &mut dyn Write,
     plugins: &Plugins,
 ) -> io::Result<()> {
+    let mut output = LineEndingWriter::new(output, options.render.line_ending);
+    let output: &mut dyn Write = &mut output;
     output.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
     output.write_all(b"<!DOCTYPE document SYSTEM \"CommonMark.dtd\">\n")?;
 
@@ -151,6 +154,18 @@ impl<'o> XmlFormatter<'o> {
                 NodeValue::Document => self
                     .output
                     .write_all(b" xmlns=\"http://commonmark.org/xml/1.0\"")?,
+                NodeValue::Code(NodeCode {
+                    ref literal,
+                    lang: Some(ref lang),
+                    ..
+                }) => {
+                    self.output.write_all(b" lang=\"")?;
+                    self.escape(lang.as_bytes())?;
+                    self.output.write_all(b"\" xml:space=\"preserve\">")?;
+                    self.escape(literal.as_bytes())?;
+                    write!(self.output, "</{}", ast.value.xml_node_name())?;
+                    was_literal = true;
+                }
                 NodeValue::Text(ref literal)
                 | NodeValue::Code(NodeCode { ref literal, .. })
                 | NodeValue::HtmlBlock(NodeHtmlBlock { ref literal, .. })
@@ -175,7 +190,32 @@ impl<'o> XmlFormatter<'o> {
                 }
                 NodeValue::FrontMatter(_) => (),
                 NodeValue::BlockQuote => {}
+                NodeValue::Alert(ref na) => {
+                    write!(self.output, " type=\"{}\"", na.alert_type.css_class())?;
+                    if let Some(ref title) = na.title {
+                        self.output.write_all(b" title=\"")?;
+                        self.escape(title.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                }
                 NodeValue::MultilineBlockQuote(..) => {}
+                NodeValue::Details(ref nd) => {
+                    self.output.write_all(b" summary=\"")?;
+                    self.escape(nd.summary.as_bytes())?;
+                    write!(self.output, "\" open=\"{}\"", nd.open)?;
+                }
+                NodeValue::FencedDiv(ref nfd) => {
+                    if !nfd.class.is_empty() {
+                        self.output.write_all(b" class=\"")?;
+                        self.escape(nfd.class.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                    for (key, value) in &nfd.attrs {
+                        write!(self.output, " {}=\"", key)?;
+                        self.escape(value.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                }
                 NodeValue::Item(..) => {}
                 NodeValue::DescriptionList => {}
                 NodeValue::DescriptionItem(..) => (),
@@ -207,14 +247,32 @@ impl<'o> XmlFormatter<'o> {
                 NodeValue::Emph => {}
                 NodeValue::Strikethrough => {}
                 NodeValue::Superscript => {}
+                NodeValue::Highlight => {}
+                NodeValue::Abbreviation(ref na) => {
+                    self.output.write_all(b" title=\"")?;
+                    self.escape(na.title.as_bytes())?;
+                    self.output.write_all(b"\"")?;
+                }
                 NodeValue::Subscript => {}
                 NodeValue::Underline => {}
+                NodeValue::Quoted => {}
                 NodeValue::Link(ref nl) | NodeValue::Image(ref nl) => {
                     self.output.write_all(b" destination=\"")?;
                     self.escape(nl.url.as_bytes())?;
                     self.output.write_all(b"\" title=\"")?;
                     self.escape(nl.title.as_bytes())?;
                     self.output.write_all(b"\"")?;
+                    if !nl.srcset.is_empty() {
+                        self.output.write_all(b" srcset=\"")?;
+                        for (i, candidate) in nl.srcset.iter().enumerate() {
+                            if i > 0 {
+                                self.output.write_all(b", ")?;
+                            }
+                            self.escape(candidate.url.as_bytes())?;
+                            write!(self.output, " {}", candidate.descriptor)?;
+                        }
+                        self.output.write_all(b"\"")?;
+                    }
                 }
                 NodeValue::Table(..) => {
                     // noop
@@ -222,6 +280,9 @@ impl<'o> XmlFormatter<'o> {
                 NodeValue::TableRow(..) => {
                     // noop
                 }
+                NodeValue::TableCaption => {
+                    // noop
+                }
                 NodeValue::TableCell => {
                     let mut ancestors = node.ancestors().skip(1);
 